@@ -0,0 +1,125 @@
+use std::env;
+use std::path::PathBuf;
+
+use riscv_cli::{parse_u64, Arg, CliError, CliParser, ErrorKind};
+use riscv_core::config::MachineConfig;
+
+use crate::trace::TraceFormat;
+
+const USAGE: &str = "\
+Usage: riscv-run [OPTIONS] <.elf> [ARGS]...
+
+Options:
+    --max-insns <N>       Stop after N retired instructions (default: no limit)
+    --stdin               UART receive from host's stdin
+    --user                Linux user-mode syscall emulation, ARGS are passed to program
+    --semihost <DIR>      Semihosting, guest can open files under DIR
+    --gdb <ADDR>          Wait GDB on host:port or unix:path before running
+    --resume <FILE>       Resume from snapshot after loading the file
+    --checkpoint <FILE>   Save snapshot when the run stops
+    --trace <FILE>        Write instruction trace into FILE
+    --trace-format <FMT>  Trace format, spike or binary (default: spike)
+    --cosim <FILE>        Compare with reference commit trace, stop at first divergence";
+
+/// Input file, run limit and machine configuration from CLI
+#[derive(Debug)]
+pub struct CliArgs {
+    pub file: String,
+    /// `None` means run until guest exit
+    pub max_insns: Option<u64>,
+    pub stdin: bool,
+    /// Address to wait GDB connection
+    pub gdb: Option<String>,
+    /// Snapshot to resume from
+    pub resume: Option<PathBuf>,
+    /// Snapshot to save when the run stops
+    pub checkpoint: Option<PathBuf>,
+    /// File to write instruction trace
+    pub trace: Option<PathBuf>,
+    pub trace_format: TraceFormat,
+    /// Reference commit trace to compare with
+    pub cosim: Option<PathBuf>,
+    pub config: MachineConfig,
+}
+
+/// Load CLI argument from `env::args().skip(1)`. Only accept one binary file.
+/// With `--user`, arguments after the file are program's arguments.
+/// ## Example
+/// ```bash
+/// # Here is bash
+/// riscv-run --max-insns 1000000 --ram-size 128M binary_file
+/// riscv-run --user binary_file --guest-option
+/// riscv-run --gdb 127.0.0.1:1234 binary_file
+/// riscv-run --max-insns 100000000 --checkpoint boot.snap binary_file
+/// riscv-run --resume boot.snap binary_file
+/// riscv-run --max-insns 1000 --trace run.log binary_file
+/// riscv-run --cosim spike.log binary_file
+/// ```
+pub fn load_arg() -> Result<CliArgs, CliError> {
+    let mut parser = CliParser::new(env::args().skip(1), USAGE);
+    let mut file = None;
+    let mut max_insns = None;
+    let mut stdin = false;
+    let mut gdb = None;
+    let mut resume = None;
+    let mut checkpoint = None;
+    let mut trace = None;
+    let mut trace_format = TraceFormat::default();
+    let mut cosim = None;
+    let mut user = false;
+    let mut user_mode = None;
+    let mut semihosting = None;
+
+    while let Some(arg) = parser.next_arg()? {
+        let option = match arg {
+            Arg::Positional(arg) => {
+                if file.is_some() {
+                    return Err(parser.error(ErrorKind::TooManyArgument));
+                }
+                if user {
+                    // argv[0] is the file, the rest belong to program
+                    user_mode = Some(std::iter::once(arg.clone()).chain(parser.rest()).collect());
+                }
+                file = Some(arg);
+                continue;
+            },
+            Arg::Option(option) => option,
+        };
+
+        match option.as_str() {
+            "--stdin"        => stdin = true,
+            "--user"         => user = true,
+            "--max-insns"    => max_insns = Some(parser.value_with(&option, parse_u64)?),
+            "--semihost"     => semihosting = Some(parser.value(&option)?.into()),
+            "--gdb"          => gdb = Some(parser.value(&option)?),
+            "--resume"       => resume = Some(parser.value(&option)?.into()),
+            "--checkpoint"   => checkpoint = Some(parser.value(&option)?.into()),
+            "--trace"        => trace = Some(parser.value(&option)?.into()),
+            "--trace-format" => trace_format = parser.value_with(&option, TraceFormat::parse)?,
+            "--cosim"        => cosim = Some(parser.value(&option)?.into()),
+            _                => return Err(parser.error(ErrorKind::UnknownOption(option))),
+        }
+    }
+
+    let file = file.ok_or_else(|| parser.error(ErrorKind::NoInputFile))?;
+    if user {
+        // `--user` is after the file
+        user_mode.get_or_insert_with(|| vec![file.clone()]);
+    }
+    let mut config = parser.finish();
+    config.user_mode = user_mode;
+    config.semihosting = semihosting;
+
+    Ok(CliArgs {
+        file,
+        max_insns,
+        stdin,
+        gdb,
+        resume,
+        checkpoint,
+        trace,
+        trace_format,
+        cosim,
+        config
+    })
+}
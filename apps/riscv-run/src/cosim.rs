@@ -0,0 +1,199 @@
+//! Lockstep co-simulation against a reference commit trace, like `spike --log-commits`.
+//! Each retired instruction's PC, raw instruction, written registers and memory writes are compared
+
+use std::fmt::Display;
+use std::io::{BufRead, Lines};
+use std::sync::{Arc, Mutex};
+
+use riscv_core::Xlen;
+use riscv_core::debug::{Commit, Tracer};
+use riscv_core::mmio::AccessType;
+
+use crate::trace::XLEN_HEX;
+
+/// Retired instruction of reference or emulator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retired {
+    pub pc: Xlen,
+    pub raw: u32,
+    /// Written registers, `x0` is not included
+    pub regs: Vec<(u8, Xlen)>,
+    /// Written FP registers, whole 64 bits
+    pub fregs: Vec<(u8, u64)>,
+    /// Memory writes as `(addr, data)`
+    pub stores: Vec<(Xlen, u64)>,
+}
+
+impl Retired {
+    /// Parse a commit line `core   0: 3 0x80000000 (0x00000297) x5  0x80000000 mem 0x80001000 0x01`.
+    /// Other lines like disassembly and exception are `None`
+    pub fn parse(line: &str) -> Option<Self> {
+        let (_, line) = line.strip_prefix("core")?.split_once(':')?;
+        let mut tokens = line.split_whitespace();
+
+        // Privilege mode, disassembly line does not have it
+        tokens.next()?.parse::<u8>().ok()?;
+        let pc = parse_hex(tokens.next()?)?;
+        let raw = parse_hex_u64(tokens.next()?.strip_prefix('(')?.strip_suffix(')')?)? as u32;
+
+        let mut retired = Retired { pc, raw, regs: Vec::new(), fregs: Vec::new(), stores: Vec::new() };
+        let mut tokens = tokens.peekable();
+        while let Some(token) = tokens.next() {
+            if token == "mem" {
+                let addr = parse_hex(tokens.next()?)?;
+                // Store has data after the address
+                if let Some(data) = tokens.next_if(|token| token.starts_with("0x")) {
+                    retired.stores.push((addr, parse_hex_u64(data)?));
+                }
+            } else if let Some(id) = token.strip_prefix('x').and_then(|id| id.parse::<u8>().ok()) {
+                let data = parse_hex(tokens.next()?)?;
+                if id != 0 {
+                    retired.regs.push((id, data));
+                }
+            } else if let Some(id) = token.strip_prefix('f').and_then(|id| id.parse::<u8>().ok()) {
+                retired.fregs.push((id, parse_hex_u64(tokens.next()?)?));
+            } else {
+                // CSR or vector register write is not compared
+                tokens.next();
+            }
+        }
+        Some(retired)
+    }
+}
+
+impl From<&Commit> for Retired {
+    fn from(commit: &Commit) -> Self {
+        Retired {
+            pc: commit.pc,
+            raw: commit.raw.unwrap_or(0),
+            regs: commit.regs.clone(),
+            fregs: commit.fregs.clone(),
+            stores: commit.mem.iter()
+                .filter(|mem| mem.kind == AccessType::Store)
+                .map(|mem| (mem.addr, mem.data))
+                .collect(),
+        }
+    }
+}
+
+/// First different retired instruction. `reference` is `None` if reference trace ended
+#[derive(Debug)]
+pub struct Divergence {
+    /// Number of instructions retired before the different one
+    pub count: u64,
+    pub reference: Option<Retired>,
+    pub emulator: Retired,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(reference) = &self.reference else {
+            return write!(f, "Reference trace ended after {} instructions, emulator retired pc {:#0XLEN_HEX$x}",
+                self.count, self.emulator.pc);
+        };
+        let emulator = &self.emulator;
+
+        let mut rows = vec![
+            ("pc".to_string(), format!("{:#0XLEN_HEX$x}", reference.pc), format!("{:#0XLEN_HEX$x}", emulator.pc)),
+            ("instruction".to_string(), format!("{:#010x}", reference.raw), format!("{:#010x}", emulator.raw)),
+        ];
+
+        let mut ids: Vec<u8> = Vec::new();
+        for (id, _) in reference.regs.iter().chain(emulator.regs.iter()) {
+            if !ids.contains(id) {
+                ids.push(*id);
+            }
+        }
+        let reg = |regs: &[(u8, Xlen)], id: u8| regs.iter()
+            .find(|(reg, _)| *reg == id)
+            .map_or("-".to_string(), |(_, data)| format!("{data:#0XLEN_HEX$x}"));
+        for id in ids {
+            rows.push((format!("x{id}"), reg(&reference.regs, id), reg(&emulator.regs, id)));
+        }
+
+        let mut ids: Vec<u8> = Vec::new();
+        for (id, _) in reference.fregs.iter().chain(emulator.fregs.iter()) {
+            if !ids.contains(id) {
+                ids.push(*id);
+            }
+        }
+        let freg = |fregs: &[(u8, u64)], id: u8| fregs.iter()
+            .find(|(reg, _)| *reg == id)
+            .map_or("-".to_string(), |(_, data)| format!("{data:#018x}"));
+        for id in ids {
+            rows.push((format!("f{id}"), freg(&reference.fregs, id), freg(&emulator.fregs, id)));
+        }
+
+        let store = |stores: &[(Xlen, u64)], i: usize| stores.get(i)
+            .map_or("-".to_string(), |(addr, data)| format!("{addr:#0XLEN_HEX$x} {data:#x}"));
+        for i in 0..reference.stores.len().max(emulator.stores.len()) {
+            rows.push(("mem".to_string(), store(&reference.stores, i), store(&emulator.stores, i)));
+        }
+
+        writeln!(f, "Diverged after {} instructions", self.count)?;
+        write!(f, "  {:<12} {:<24} Emulator", "", "Reference")?;
+        // Different rows are marked by `>`
+        for (name, reference, emulator) in rows {
+            let mark = if reference != emulator { ">" } else { "" };
+            write!(f, "\n{mark:<2}{name:<12} {reference:<24} {emulator}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `Tracer` that compares the emulator's commits with reference trace.
+/// Reference lines before the first PC of emulator are skipped, like Spike's boot ROM
+pub struct Cosim<R> {
+    reference: Lines<R>,
+    count: u64,
+    started: bool,
+    divergence: Arc<Mutex<Option<Divergence>>>,
+}
+
+impl<R: BufRead> Cosim<R> {
+    /// Return the checker and where the divergence is put
+    pub fn new(reference: R) -> (Self, Arc<Mutex<Option<Divergence>>>) {
+        let divergence = Arc::new(Mutex::new(None));
+        let cosim = Cosim { reference: reference.lines(), count: 0, started: false, divergence: divergence.clone() };
+        (cosim, divergence)
+    }
+
+    /// Next retired instruction of reference. Unreadable file is the same as ended
+    fn next_reference(&mut self, pc: Xlen) -> Option<Retired> {
+        loop {
+            let line = self.reference.next()?.ok()?;
+            if let Some(retired) = Retired::parse(&line)
+                && (self.started || retired.pc == pc)
+            {
+                self.started = true;
+                return Some(retired);
+            }
+        }
+    }
+}
+
+impl<R: BufRead + Send> Tracer for Cosim<R> {
+    fn commit(&mut self, commit: &Commit) {
+        // Trapped instruction is not retired
+        if commit.trap.is_some() || self.divergence.lock().unwrap().is_some() {
+            return;
+        }
+
+        let emulator = Retired::from(commit);
+        let reference = self.next_reference(emulator.pc);
+        if reference.as_ref() != Some(&emulator) {
+            *self.divergence.lock().unwrap() = Some(Divergence { count: self.count, reference, emulator });
+            return;
+        }
+        self.count += 1;
+    }
+}
+
+fn parse_hex(value: &str) -> Option<Xlen> {
+    // 64 bits value of Spike is truncated on RV32
+    parse_hex_u64(value).map(|value| value as Xlen)
+}
+
+fn parse_hex_u64(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.strip_prefix("0x")?, 16).ok()
+}
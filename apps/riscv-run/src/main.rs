@@ -0,0 +1,116 @@
+mod cli;
+mod cosim;
+mod runner;
+mod trace;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use anyhow::Result;
+
+use riscv_core::Halt;
+use riscv_core::debug::{TraceSink, Tracer};
+use riscv_gdbstub::{Disconnect, GdbStub};
+use riscv_loader::load;
+
+use crate::cosim::Cosim;
+use crate::runner::{Runner, Stop};
+use crate::trace::{BinaryTracer, SpikeTracer, Tee, TraceFormat};
+
+/// Exit code when instruction limit is reached, same as `timeout` command
+const EXIT_TIMEOUT: u8 = 124;
+/// Exit code when emulator itself failed, like invalid argument or load error
+const EXIT_ERROR: u8 = 125;
+/// Exit code when emulator is different from reference trace
+const EXIT_DIVERGED: u8 = 126;
+/// Exit code when GDB kill the program, same as killed by `SIGKILL`
+const EXIT_KILLED: u8 = 137;
+
+// Headless entry for Risc-V emulator. Exit with guest's exit code.
+fn main() -> ExitCode {
+    let code = match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("riscv-run: {err}");
+            EXIT_ERROR
+        },
+    };
+    let _ = io::stdout().flush();
+    ExitCode::from(code)
+}
+
+fn run() -> Result<u8> {
+    let args = cli::load_arg()?;
+    let info = load(&args.file)?;
+
+    let mut config = args.config;
+    let mut tracers: Vec<Box<dyn Tracer>> = Vec::new();
+    if let Some(path) = &args.trace {
+        let out = BufWriter::new(File::create(path)?);
+        tracers.push(match args.trace_format {
+            TraceFormat::Spike  => Box::new(SpikeTracer::new(out, config.hart_id)),
+            TraceFormat::Binary => Box::new(BinaryTracer::new(out)?),
+        });
+    }
+    let divergence = match &args.cosim {
+        Some(path) => {
+            let (cosim, divergence) = Cosim::new(BufReader::new(File::open(path)?));
+            tracers.push(Box::new(cosim));
+            Some(divergence)
+        },
+        None => None,
+    };
+    if !tracers.is_empty() {
+        config = config.with_tracer(TraceSink::new(Tee(tracers)));
+    }
+
+    // UART output go to stdout by default sink
+    let mut mach = config.build()?;
+    mach.load_info(&info)?;
+    mach.uart_stdin(args.stdin);
+    if let Some(path) = &args.resume {
+        mach.load_snapshot(path)?;
+    }
+
+    // Keep running after GDB detach
+    if let Some(addr) = &args.gdb {
+        eprintln!("riscv-run: Waiting for GDB on {addr}");
+        let conn = riscv_gdbstub::listen(addr)?;
+        if GdbStub::new(conn).serve(&mut mach)? == Disconnect::Kill {
+            return Ok(EXIT_KILLED);
+        }
+    }
+
+    let mut runner = Runner::new(mach, args.max_insns);
+    if let Some(divergence) = divergence.clone() {
+        runner = runner.with_cosim(divergence);
+    }
+    let (stop, count) = runner.run()?;
+    if let Some(path) = &args.checkpoint {
+        runner.machine().save_snapshot(path)?;
+        eprintln!("riscv-run: Snapshot saved to {}", path.display());
+    }
+
+    let code = match stop {
+        // Code larger than u8 still should be a failure
+        Stop::Halt(Halt::Exit(code)) => u8::try_from(code).unwrap_or(u8::MAX),
+        Stop::Timeout => {
+            eprintln!("riscv-run: Instruction limit {count} reached");
+            EXIT_TIMEOUT
+        },
+        Stop::Diverged => {
+            // Safe: Runner only stop by divergence when it is set
+            let divergence = divergence.unwrap().lock().unwrap().take().unwrap();
+            eprintln!("riscv-run: {divergence}");
+            EXIT_DIVERGED
+        },
+        #[cfg(not(feature = "zicsr"))]
+        Stop::Trap(trap) => {
+            eprintln!("riscv-run: Unhandled trap `{trap}` after {count} instructions");
+            EXIT_ERROR
+        },
+    };
+
+    Ok(code)
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+
+use riscv_core::{Halt, RiscV, RiscVError, StepEvent};
+#[cfg(not(feature = "zicsr"))]
+use riscv_core::Trap;
+
+use crate::cosim::Divergence;
+
+/// Why the run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Machine halted by guest
+    Halt(Halt),
+    /// Reached instruction limit
+    Timeout,
+    /// Co-simulation found a different instruction
+    Diverged,
+    /// Trap can not be handled without Zicsr
+    #[cfg(not(feature = "zicsr"))]
+    Trap(Trap),
+}
+
+/// Run machine until it halts or `max_insns` instructions are retired
+#[derive(Debug)]
+pub struct Runner {
+    mach: RiscV,
+    max_insns: Option<u64>,
+    /// Set by co-simulation checker
+    divergence: Option<Arc<Mutex<Option<Divergence>>>>,
+}
+
+impl Runner {
+    pub fn new(mach: RiscV, max_insns: Option<u64>) -> Self {
+        Runner { mach, max_insns, divergence: None }
+    }
+
+    /// Also stop when `divergence` is set
+    pub fn with_cosim(mut self, divergence: Arc<Mutex<Option<Divergence>>>) -> Self {
+        self.divergence = Some(divergence);
+        self
+    }
+
+    pub fn machine(&self) -> &RiscV {
+        &self.mach
+    }
+
+    /// Return stop reason and retired instruction count. Trap steps, interrupt entry
+    /// as well as faulted instruction, are not counted like `minstret`
+    pub fn run(&mut self) -> Result<(Stop, u64), RiscVError> {
+        let mut count = 0;
+
+        loop {
+            if self.max_insns.is_some_and(|max| count >= max) {
+                return Ok((Stop::Timeout, count));
+            }
+
+            let event = self.mach.step()?;
+            if self.divergence.as_ref().is_some_and(|divergence| divergence.lock().unwrap().is_some()) {
+                return Ok((Stop::Diverged, count));
+            }
+            match event {
+                Some(StepEvent::Halt(halt)) => return Ok((Stop::Halt(halt), count)),
+                #[cfg(not(feature = "zicsr"))]
+                Some(StepEvent::Trap(trap)) => return Ok((Stop::Trap(trap), count)),
+                #[cfg(feature = "zicsr")]
+                Some(StepEvent::Trap(_)) => continue,
+                _ => {},
+            }
+            count += 1;
+        }
+    }
+}
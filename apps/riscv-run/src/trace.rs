@@ -0,0 +1,209 @@
+//! Instruction trace writers. Text format is the same as `spike -l --log-commits`,
+//! binary format is a compact record of each step
+//!
+//! Binary format is started by `MAGIC`, then records in little endian:
+//!
+//! | Field | Content |
+//! | --- | --- |
+//! | Header | PC (xlen), raw instruction (u32), privilege mode (u8), flags (u8), number of registers, FP registers, CSRs and memory accesses (4 u8) |
+//! | Trap | `mcause` (xlen) and `mtval` (xlen), only if flags bit 1 is set |
+//! | Registers | (id (u8), value (xlen)) |
+//! | FP registers | (id (u8), value (u64)) |
+//! | CSRs | (address (u16), value (xlen)) |
+//! | Memory | (is store (u8), length (u8), address (xlen), data (u64)) |
+//!
+//! Flags bit 0 means raw instruction is valid. xlen is u32, or u64 with `rv64` feature.
+
+use std::io::{self, Write};
+
+use riscv_core::{Trap, XLEN, Xlen};
+use riscv_core::debug::{Commit, Tracer};
+use riscv_core::mmio::AccessType;
+use riscv_disasm::disasm;
+
+pub const MAGIC: &[u8; 8] = b"RVTRACE2";
+
+const FLAG_RAW: u8 = 1 << 0;
+const FLAG_TRAP: u8 = 1 << 1;
+
+/// Hex width of XLEN value with `0x`
+pub(crate) const XLEN_HEX: usize = XLEN as usize / 4 + 2;
+
+/// Output format of `--trace`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    #[default]
+    Spike,
+    Binary,
+}
+
+impl TraceFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "spike"  => Some(TraceFormat::Spike),
+            "binary" => Some(TraceFormat::Binary),
+            _        => None,
+        }
+    }
+}
+
+/// Write trace like `spike -l --log-commits`. A line of disassembly, then a line of commit
+#[derive(Debug)]
+pub struct SpikeTracer<W> {
+    out: W,
+    hart_id: u32,
+}
+
+impl<W: Write> SpikeTracer<W> {
+    pub fn new(out: W, hart_id: u32) -> Self {
+        SpikeTracer { out, hart_id }
+    }
+
+    fn write(&mut self, commit: &Commit) -> io::Result<()> {
+        let core = format!("core {:>3}:", self.hart_id);
+
+        if let Some(raw) = commit.raw {
+            let ins = ins_string(raw);
+            writeln!(self.out, "{core} {:#0XLEN_HEX$x} {ins} {}", commit.pc, disasm::disassemble(raw, commit.pc))?;
+
+            if commit.trap.is_none() {
+                write!(self.out, "{core} {} {:#0XLEN_HEX$x} {ins}", commit.mode, commit.pc)?;
+                for (id, data) in commit.regs.iter() {
+                    write!(self.out, " x{id:<2} {data:#0XLEN_HEX$x}")?;
+                }
+                for (id, data) in commit.fregs.iter() {
+                    write!(self.out, " f{id:<2} {data:#018x}")?;
+                }
+                #[cfg(feature = "zicsr")]
+                for (addr, data) in commit.csrs.iter() {
+                    write!(self.out, " c{addr}_{} {data:#0XLEN_HEX$x}", disasm::csr_name(*addr))?;
+                }
+                for mem in commit.mem.iter() {
+                    match mem.kind {
+                        AccessType::Store => write!(self.out, " mem {:#0XLEN_HEX$x} {:#0width$x}",
+                            mem.addr, mem.data, width = 2 + 2 * mem.len as usize)?,
+                        _                 => write!(self.out, " mem {:#0XLEN_HEX$x}", mem.addr)?,
+                    }
+                }
+                writeln!(self.out)?;
+            }
+        }
+
+        if let Some(trap) = commit.trap {
+            writeln!(self.out, "{core} exception {}, epc {:#0XLEN_HEX$x}", trap_name(trap), commit.pc)?;
+            if !trap.is_interrupt() {
+                writeln!(self.out, "{core}           tval {:#0XLEN_HEX$x}", trap.tval())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> Tracer for SpikeTracer<W> {
+    fn commit(&mut self, commit: &Commit) {
+        // Trace lost should not stop the guest, so ignore error
+        let _ = self.write(commit);
+    }
+}
+
+/// Write trace in binary format, see module document
+#[derive(Debug)]
+pub struct BinaryTracer<W> {
+    out: W,
+}
+
+impl<W: Write> BinaryTracer<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        Ok(BinaryTracer { out })
+    }
+
+    fn write(&mut self, commit: &Commit) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(64);
+        let flags = if commit.raw.is_some() { FLAG_RAW } else { 0 }
+            | if commit.trap.is_some() { FLAG_TRAP } else { 0 };
+
+        buf.extend_from_slice(&commit.pc.to_le_bytes());
+        buf.extend_from_slice(&commit.raw.unwrap_or(0).to_le_bytes());
+        buf.extend_from_slice(&[
+            commit.mode,
+            flags,
+            commit.regs.len() as u8,
+            commit.fregs.len() as u8,
+            commit.csrs.len() as u8,
+            commit.mem.len() as u8,
+        ]);
+        if let Some(trap) = commit.trap {
+            buf.extend_from_slice(&Xlen::from(trap).to_le_bytes());
+            buf.extend_from_slice(&trap.tval().to_le_bytes());
+        }
+        for (id, data) in commit.regs.iter() {
+            buf.push(*id);
+            buf.extend_from_slice(&data.to_le_bytes());
+        }
+        for (id, data) in commit.fregs.iter() {
+            buf.push(*id);
+            buf.extend_from_slice(&data.to_le_bytes());
+        }
+        for (addr, data) in commit.csrs.iter() {
+            buf.extend_from_slice(&addr.to_le_bytes());
+            buf.extend_from_slice(&data.to_le_bytes());
+        }
+        for mem in commit.mem.iter() {
+            buf.push((mem.kind == AccessType::Store) as u8);
+            buf.push(mem.len);
+            buf.extend_from_slice(&mem.addr.to_le_bytes());
+            buf.extend_from_slice(&mem.data.to_le_bytes());
+        }
+        self.out.write_all(&buf)
+    }
+}
+
+impl<W: Write + Send> Tracer for BinaryTracer<W> {
+    fn commit(&mut self, commit: &Commit) {
+        // Trace lost should not stop the guest, so ignore error
+        let _ = self.write(commit);
+    }
+}
+
+/// Give commit to all tracers, like `--trace` with `--cosim`
+pub struct Tee(pub Vec<Box<dyn Tracer>>);
+
+impl Tracer for Tee {
+    fn commit(&mut self, commit: &Commit) {
+        self.0.iter_mut().for_each(|tracer| tracer.commit(commit));
+    }
+}
+
+/// Compressed instruction is shown in 4 hex digits like Spike
+fn ins_string(raw: u32) -> String {
+    if raw & 0b11 != 0b11 {
+        format!("({raw:#06x})")
+    } else {
+        format!("({raw:#010x})")
+    }
+}
+
+/// Name of trap in Spike's log
+fn trap_name(trap: Trap) -> String {
+    if trap.is_interrupt() {
+        return format!("interrupt #{}", trap.code());
+    }
+    match trap.code() {
+        0  => "trap_instruction_address_misaligned",
+        1  => "trap_instruction_access_fault",
+        2  => "trap_illegal_instruction",
+        3  => "trap_breakpoint",
+        4  => "trap_load_address_misaligned",
+        5  => "trap_load_access_fault",
+        6  => "trap_store_address_misaligned",
+        7  => "trap_store_access_fault",
+        8  => "trap_user_ecall",
+        9  => "trap_supervisor_ecall",
+        11 => "trap_machine_ecall",
+        12 => "trap_instruction_page_fault",
+        13 => "trap_load_page_fault",
+        15 => "trap_store_page_fault",
+        code => return format!("trap #{code}"),
+    }.to_string()
+}
@@ -1,34 +1,45 @@
 mod key;
 
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{self, Receiver};
 
 use anyhow::Result;
 
-use riscv_core::RiscV;
+use riscv_core::{RiscV, StepEvent, UartSink};
+use riscv_core::config::MachineConfig;
 #[cfg(not(feature = "zicsr"))]
 use riscv_core::RiscVError;
-use riscv_core::debug::DebugInterface;
+use riscv_core::debug::{Breakpoint, DebugControl, DebugInterface};
 use riscv_disasm::disasm;
 use riscv_loader::LoadInfo;
 
 use crate::event::{self, EmuEvent};
 use crate::event::key::KeyControl;
+use crate::input::EditTarget;
 use crate::state::{EmuMode, EmuState};
 use crate::ui;
 use crate::ui::terminal::EmuTerminal;
 
+/// Number of steps can be undone
+const HISTORY_LEN: usize = 4096;
+
 #[derive(Debug)]
 pub struct EmuApp {
     mach: RiscV,
     info: LoadInfo,
+    snapshot: PathBuf,
+    console: Arc<Mutex<Vec<u8>>>,
     state: EmuState,
     should_quit: bool,
     event_rx: Receiver<EmuEvent>,
 }
 
 impl EmuApp {
-    pub fn new(info: LoadInfo) -> Result<Self> {
-        let mut mach = RiscV::default();
+    pub fn new(info: LoadInfo, config: MachineConfig, snapshot: PathBuf) -> Result<Self> {
+        // Capture UART output that stdout would break the screen
+        let (sink, console) = UartSink::buffer();
+        let mut mach = config.with_uart_sink(sink).with_history(HISTORY_LEN).build()?;
         mach.load_info(&info)?;
 
         let ins_list = disasm::disassembler(&info);
@@ -39,7 +50,7 @@ impl EmuApp {
         event::spawn_event_thread(event_tx);
 
         Ok(EmuApp { 
-            mach, info, state, 
+            mach, info, snapshot, console, state, 
             should_quit: false, event_rx 
         })
     }
@@ -56,15 +67,64 @@ impl EmuApp {
     }
 
     fn step(&mut self) -> Result<()> {
-        if let Some(except) = self.mach.step()? {
-            self.state.mach_snap.update_exception(except);
-            #[cfg(not(feature = "zicsr"))]
-            return Err(anyhow::Error::new(RiscVError::Exception));
+        // Replay undone steps first
+        if self.mach.step_forward() {
+            self.state.mach_snap.update_snapshot(&self.mach);
+            return Ok(());
+        }
+
+        let result = self.mach.step();
+        self.update_console();
+        if let Some(event) = result? {
+            self.state.mach_snap.update_exception(event);
+            match event {
+                // Guest exit or breakpoint, stop running
+                StepEvent::Halt(_) | StepEvent::Break(_) => self.state.mode = EmuMode::Stay,
+                #[cfg(not(feature = "zicsr"))]
+                StepEvent::Trap(_) => return Err(anyhow::Error::new(RiscVError::Exception)),
+                #[cfg(feature = "zicsr")]
+                StepEvent::Trap(_) => {},
+            }
         }
         self.state.mach_snap.update_snapshot(&self.mach);
         Ok(())
     }
 
+    /// Undo the last step. Device state and console output are kept
+    fn step_back(&mut self) {
+        if self.mach.step_back() {
+            self.state.mach_snap.reset_exception();
+            self.state.mach_snap.update_snapshot(&self.mach);
+        }
+    }
+
+    /// Save machine into the snapshot file, result is shown in exception panel
+    fn save_snapshot(&mut self) {
+        let message = match self.mach.save_snapshot(&self.snapshot) {
+            Ok(())   => format!("Snapshot saved to {}", self.snapshot.display()),
+            Err(err) => format!("Save snapshot failed: {err}"),
+        };
+        self.state.mach_snap.update_message(message);
+    }
+
+    /// Restore machine from the snapshot file. Machine is not changed if it failed
+    fn load_snapshot(&mut self) {
+        let message = match self.mach.load_snapshot(&self.snapshot) {
+            Ok(())   => format!("Snapshot loaded from {}", self.snapshot.display()),
+            Err(err) => format!("Load snapshot failed: {err}"),
+        };
+        self.state.mach_snap.update_snapshot(&self.mach);
+        self.state.mach_snap.update_message(message);
+    }
+
+    fn update_console(&mut self) {
+        let mut output = self.console.lock().unwrap();
+        if !output.is_empty() {
+            self.state.mach_snap.update_console(&output);
+            output.clear();
+        }
+    }
+
     fn event(&mut self) -> Result<()> {
         match self.event_rx.recv()? {
             EmuEvent::Key(key) => {
@@ -82,17 +142,12 @@ impl EmuApp {
             EmuEvent::Resize(_, _) => {},
             EmuEvent::Tick => {
                 if self.state.mode == EmuMode::Running {
-                    if self.state.breakpoint_set
-                        .contains(&self.state.mach_snap.ins.current_select) {
+                    #[cfg(not(feature = "zicsr"))]
+                    if self.step().is_err() {
                         self.state.mode = EmuMode::Stay 
-                    } else {
-                        #[cfg(not(feature = "zicsr"))]
-                        if self.step().is_err() {
-                            self.state.mode = EmuMode::Stay 
-                        }
-                        #[cfg(feature = "zicsr")]
-                        self.step()?;
                     }
+                    #[cfg(feature = "zicsr")]
+                    self.step()?;
                 }
             }
         }
@@ -107,4 +162,29 @@ impl EmuApp {
         self.state.mode.popup();
         self.state.show_bus_popup = true;
     }
+
+    /// Set or remove breakpoint at the selected instruction
+    pub fn toggle_breakpoint(&mut self) {
+        let select = self.state.mach_snap.ins.current_select;
+        if let Some(id) = self.state.breakpoint_set.remove(&select) {
+            self.mach.remove_breakpoint(id);
+        } else if let Some((addr, _)) = self.state.mach_snap.ins.list.get(select) {
+            let id = self.mach.add_breakpoint(Breakpoint::exec(*addr));
+            self.state.breakpoint_set.insert(select, id);
+        }
+    }
+
+    /// Write register or bus byte in place. Invalid input is ignored
+    pub fn receive_edit(&mut self) {
+        match self.state.input.target {
+            EditTarget::Register(id) => if let Some(data) = self.state.input.submit() {
+                self.mach.write_reg(id, data);
+            },
+            EditTarget::BusByte => if let Some((addr, byte)) = self.state.input.submit_byte() {
+                self.mach.write_bus(addr, &[byte]);
+            },
+            EditTarget::BusSearch => {},
+        }
+        self.state.mach_snap.update_snapshot(&self.mach);
+    }
 }
\ No newline at end of file
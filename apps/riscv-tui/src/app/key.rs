@@ -3,7 +3,10 @@ use anyhow::Result;
 
 use crate::EmuApp;
 use crate::event::key::{NormalKeyControl, EditKeyControl};
+use crate::input::EditTarget;
 use crate::state::Selected; 
+#[cfg(feature = "zicsr")]
+use crate::state::Mid;
 
 impl EmuApp {
     pub(super) fn key_observation(&mut self, key: NormalKeyControl) {
@@ -20,13 +23,8 @@ impl EmuApp {
             #[cfg(feature = "zicsr")]
             ChangeMid   => self.state.change_mid(),
             ChangeView  => self.state.change_view(),
-            BreakPoint  => if self.state.selected == Selected::Ins {
-                self.state.breakpoint()
-            },
-            SearchBus   => {
-                self.state.show_search_popup = true;
-                self.state.input.mode.edit();
-            },
+            BreakPoint if self.state.selected == Selected::Ins => self.toggle_breakpoint(),
+            SearchBus   => self.open_input(EditTarget::BusSearch),
             ShowInfo    => self.state.show_info_popup = !self.state.show_info_popup,
             _ => {},
         }
@@ -42,6 +40,8 @@ impl EmuApp {
             GoNext     => self.state.next(),
             GoPrev     => self.state.prev(),
             ShowInfo   => self.state.show_info_popup = !self.state.show_info_popup,
+            EditReg    => self.edit_register(),
+            WriteBus   => self.open_input(EditTarget::BusByte),
             ChangeMode => {
                 self.state.observation_mode_selected();
                 self.state.mode.observation();
@@ -51,6 +51,7 @@ impl EmuApp {
                 self.mach.load_info(&self.info)?;
                 self.state.mach_snap.update_snapshot(&self.mach);
                 self.state.mach_snap.reset_exception();
+                self.state.mach_snap.reset_console();
             },
             Step => {
                 #[cfg(not(feature = "zicsr"))]
@@ -59,6 +60,9 @@ impl EmuApp {
                 #[cfg(feature = "zicsr")]
                 self.step()?
             }
+            StepBack => self.step_back(),
+            SaveSnapshot => self.save_snapshot(),
+            LoadSnapshot => self.load_snapshot(),
             RunToEnd => {
                 #[cfg(not(feature = "zicsr"))]
                 if self.step().is_err() {
@@ -77,6 +81,14 @@ impl EmuApp {
         match key {
             Quit => self.should_quit = true,
             RunToEnd => self.state.mode.stay(),
+            EditReg  => {
+                self.state.mode.stay();
+                self.edit_register();
+            },
+            WriteBus => {
+                self.state.mode.stay();
+                self.open_input(EditTarget::BusByte);
+            },
             ChangeMode => {
                 self.state.observation_mode_selected();
                 self.state.mode.observation();
@@ -98,11 +110,30 @@ impl EmuApp {
         }
     }
 
+    fn open_input(&mut self, target: EditTarget) {
+        self.state.input.target = target;
+        self.state.input.mode.edit();
+        self.state.show_search_popup = true;
+    }
+
+    /// Edit the selected register, CSR panel is switched to register panel
+    fn edit_register(&mut self) {
+        #[cfg(feature = "zicsr")]
+        if self.state.mid_selected == Mid::Csr {
+            self.state.change_mid();
+        }
+        let id = self.state.mach_snap.reg.current_select as u8;
+        self.open_input(EditTarget::Register(id));
+    }
+
     pub(super) fn key_editting(&mut self, key: EditKeyControl) {
         use EditKeyControl::*;
         match key {
             Enter => {
-                self.receive_bus_address();
+                match self.state.input.target {
+                    EditTarget::BusSearch => self.receive_bus_address(),
+                    EditTarget::Register(_) | EditTarget::BusByte => self.receive_edit(),
+                }
                 self.state.input.mode.normal();
                 self.state.show_search_popup = false;
             },
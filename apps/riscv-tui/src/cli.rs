@@ -1,38 +1,49 @@
 use std::env;
+use std::path::PathBuf;
 
-use thiserror::Error;
+use riscv_cli::{Arg, CliError, CliParser, ErrorKind};
+use riscv_core::config::MachineConfig;
 
-const USAGE: &str = "Usage: cargo run <.elf>";
+const USAGE: &str = "\
+Usage: cargo run [OPTIONS] <.elf>
 
-#[derive(Error, Debug)]
-pub enum CliError {
-    #[error("No input file\n{}", USAGE)]
-    NoInputFile,
+Options:
+    --snapshot <FILE>     Snapshot file of (K) save and (L) load (default: riscv.snap)";
 
-    #[error("Too many input file\n{}", USAGE)]
-    TooManyArgument,
+/// Input file and machine configuration from CLI
+#[derive(Debug)]
+pub struct CliArgs {
+    pub file: String,
+    pub config: MachineConfig,
+    pub snapshot: PathBuf,
 }
 
 /// Load CLI argument from `env::args().skip(1)`. Only accept one binary file for now.
 /// ## Example
 /// ```bash
 /// # Here is bash
-/// cargo run binary_file
+/// cargo run -- --ram-base 0x40000000 --ram-size 128M --plic none binary_file
 /// ```
-/// ```rust,no_run
-/// // Rust
-/// # use risc_v_emulator::riscv::loader;
-/// if let Ok(binary_file_name) = loader::load_arg() {
-///     assert_eq!(binary_file_name, String::from("binary_file"));
-/// }
-/// ```
-pub fn load_arg() -> Result<String, CliError>{
-    let mut args = env::args().skip(1);
+pub fn load_arg() -> Result<CliArgs, CliError> {
+    let mut parser = CliParser::new(env::args().skip(1), USAGE);
+    let mut file = None;
+    let mut snapshot = PathBuf::from("riscv.snap");
+
+    while let Some(arg) = parser.next_arg()? {
+        match arg {
+            Arg::Positional(arg) => if file.replace(arg).is_some() {
+                return Err(parser.error(ErrorKind::TooManyArgument));
+            },
+            Arg::Option(option) => match option.as_str() {
+                "--snapshot" => snapshot = parser.value(&option)?.into(),
+                _            => return Err(parser.error(ErrorKind::UnknownOption(option))),
+            },
+        }
+    }
 
-    Ok(match args.len() {
-        0   => return Err(CliError::NoInputFile),
-        2.. => return Err(CliError::TooManyArgument),
-        // Safe: Here we sure args has the first element 
-        1   => args.next().unwrap(),
+    Ok(CliArgs {
+        file: file.ok_or_else(|| parser.error(ErrorKind::NoInputFile))?,
+        config: parser.finish(),
+        snapshot,
     })
 }
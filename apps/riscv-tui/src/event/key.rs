@@ -13,9 +13,14 @@ pub enum NormalKeyControl {
     ChangeMode,
     Reset,
     Step,
+    StepBack,
+    SaveSnapshot,
+    LoadSnapshot,
     RunToEnd,
     BreakPoint,
     SearchBus,
+    EditReg,
+    WriteBus,
     ChangeView,
     ShowInfo,
     ClosePopup,
@@ -42,11 +47,16 @@ pub fn get_normal_key(keycode: KeyCode) -> Option<NormalKeyControl> {
             KeyCode::Char('q' | 'Q') => Quit,
             KeyCode::Char('r' | 'R') => Reset,
             KeyCode::Char('s' | 'S') => Step,
+            KeyCode::Char('u' | 'U') => StepBack,
+            KeyCode::Char('k' | 'K') => SaveSnapshot,
+            KeyCode::Char('l' | 'L') => LoadSnapshot,
             KeyCode::Char('p' | 'P') => RunToEnd,
             #[cfg(feature = "zicsr")]
             KeyCode::Char('c' | 'C') => ChangeMid,
             KeyCode::Char('b' | 'B') => BreakPoint,
             KeyCode::Char('v' | 'V') => SearchBus,
+            KeyCode::Char('e' | 'E') => EditReg,
+            KeyCode::Char('w' | 'W') => WriteBus,
             KeyCode::Char('h' | 'H') => ChangeView,
             KeyCode::Char('i' | 'I') => ShowInfo,
             KeyCode::Up              => GoPrev,
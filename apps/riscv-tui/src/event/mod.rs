@@ -51,10 +51,14 @@ pub fn spawn_event_thread(tx: Sender<EmuEvent>) {
                                             emu_mode.stay();
                                             time = 16;
                                         },
-                                        NormalKeyControl::SearchBus => {
-                                            if emu_mode == EmuMode::Observation {
-                                                input_mode.edit();
-                                            }
+                                        NormalKeyControl::SearchBus if emu_mode == EmuMode::Observation => {
+                                            input_mode.edit();
+                                        },
+                                        // Editing stops running machine
+                                        NormalKeyControl::EditReg | NormalKeyControl::WriteBus 
+                                            if matches!(emu_mode, EmuMode::Stay | EmuMode::Running) => {
+                                            emu_mode.stay();
+                                            input_mode.edit();
                                         },
                                         _ => {},
                                     }
@@ -1,3 +1,5 @@
+use riscv_core::Xlen;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum InputMode {
     #[default]
@@ -15,16 +17,45 @@ impl InputMode {
     }
 }
 
+/// What the submitted input is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditTarget {
+    #[default]
+    BusSearch,
+    Register(u8),
+    /// `addr=byte`
+    BusByte,
+}
+
+impl EditTarget {
+    pub fn title(&self) -> String {
+        match self {
+            EditTarget::BusSearch    => "Enter Bus Address".to_string(),
+            EditTarget::Register(id) => format!("Edit x{id}"),
+            EditTarget::BusByte      => "Write Byte: addr=byte".to_string(),
+        }
+    }
+
+    /// Max input length with `0x` prefix
+    fn limit(&self) -> usize {
+        match self {
+            EditTarget::BusSearch | EditTarget::Register(_) => 10,
+            EditTarget::BusByte => 13,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EmuInput {
     pub mode: InputMode,
+    pub target: EditTarget,
     pub chars: String,
     pub cursor: usize,
 }
 
 impl EmuInput {
     pub fn enter_char(&mut self, new_char: char) {
-        if self.chars.len() < 10 {
+        if self.chars.len() < self.target.limit() {
             let idx = self.byte_index();
             self.chars.insert(idx, new_char);
             self.move_cursor_right();
@@ -55,12 +86,23 @@ impl EmuInput {
         }
     }
 
-    pub fn submit(&mut self) -> Option<u32> {
-        let res = u32::from_str_radix(&self.chars[2..], 16).ok();
+    pub fn submit(&mut self) -> Option<Xlen> {
+        let res = Xlen::from_str_radix(&self.chars[2..], 16).ok();
         self.clear();
         res
     } 
 
+    /// Parse `0xADDR=BB`
+    pub fn submit_byte(&mut self) -> Option<(Xlen, u8)> {
+        let res = self.chars[2..].split_once('=')
+            .and_then(|(addr, byte)| Some((
+                Xlen::from_str_radix(addr, 16).ok()?,
+                u8::from_str_radix(byte, 16).ok()?,
+            )));
+        self.clear();
+        res
+    }
+
     pub fn clear(&mut self) {
         self.chars = "0x".to_string();
         self.cursor = 2;
@@ -82,8 +124,9 @@ impl EmuInput {
 impl Default for EmuInput {
     fn default() -> Self {
         let mode = InputMode::default();
+        let target = EditTarget::default();
         let chars = "0x".to_string();
 
-        EmuInput { mode, chars, cursor: 2 } 
+        EmuInput { mode, target, chars, cursor: 2 } 
     }
 }
\ No newline at end of file
@@ -13,12 +13,12 @@ use crate::app::EmuApp;
 
 // Main entry for Risc-V emulator. Return any errors.
 fn main() -> Result<()> {
-    let filepath = &cli::load_arg()?;
+    let args = cli::load_arg()?;
 
     // Access file and load instructions into Risc-V's instruction memory
-    let info = load(filepath)?;
+    let info = load(&args.file)?;
 
-    let mut app = EmuApp::new(info)?;
+    let mut app = EmuApp::new(info, args.config, args.snapshot)?;
     
     // Go into the TUI app loop
     app.run()?;
@@ -2,9 +2,10 @@ mod list_state;
 mod mode;
 mod snapshot;
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 
-use riscv_core::debug::DebugInterface;
+use riscv_core::Xlen;
+use riscv_core::debug::{BreakpointId, DebugInterface};
 
 use snapshot::MachineSnapshot;
 
@@ -24,15 +25,16 @@ pub struct EmuState {
     pub show_search_popup: bool, 
     pub show_info_popup: bool, 
     pub show_bus_popup: bool, 
-    pub temp_bus_view: Option<(u32, Vec<u8>)>,
+    pub temp_bus_view: Option<(Xlen, Vec<u8>)>,
 
     pub input: EmuInput,
 
-    pub breakpoint_set: HashSet<usize>,
+    /// Instruction index to breakpoint in machine
+    pub breakpoint_set: HashMap<usize, BreakpointId>,
 }
 
 impl EmuState {
-    pub fn new<D: DebugInterface>(mach: &D, ins_list: Vec<(u32, String)>) -> Self {
+    pub fn new<D: DebugInterface>(mach: &D, ins_list: Vec<(Xlen, String)>) -> Self {
         let mach_snap = MachineSnapshot::new(mach, ins_list);
 
         let mode = EmuMode::default();
@@ -46,7 +48,7 @@ impl EmuState {
         let temp_bus_view = None;
         let input = EmuInput::default();
 
-        let breakpoint_set = HashSet::new();
+        let breakpoint_set = HashMap::new();
 
         EmuState { 
             mach_snap, 
@@ -117,12 +119,4 @@ impl EmuState {
         };
     }
 
-
-    pub fn breakpoint(&mut self) {
-        if !self.breakpoint_set
-            .remove(&self.mach_snap.ins.current_select) {
-            self.breakpoint_set
-                .insert(self.mach_snap.ins.current_select);
-        }
-    }
 }
\ No newline at end of file
@@ -1,34 +1,38 @@
-use riscv_core::Exception;
+use riscv_core::{StepEvent, Xlen};
 use riscv_core::debug::{DebugInterface, MachineInfo};
 
 use crate::state::list_state::ListStateRecord;
 
+const CONSOLE_LIMIT: usize = 16 * 1024;
+
 #[derive(Debug, PartialEq)]
 pub struct MachineSnapshot {
     pub info: MachineInfo,
 
-    pub ins: ListStateRecord<(u32, String)>,
-    pub reg: ListStateRecord<u32>,
+    pub ins: ListStateRecord<(Xlen, String)>,
+    pub reg: ListStateRecord<Xlen>,
     #[cfg(feature = "zicsr")]
-    pub csr: ListStateRecord<(String, u32)>,
-    pub pc: u32,
+    pub csr: ListStateRecord<(String, Xlen)>,
+    pub pc: Xlen,
     pub except: String,
+    pub console: String,
 }
 
 impl MachineSnapshot {
-    pub fn new<D: DebugInterface>(mach: &D, ins_list: Vec<(u32, String)>) -> Self {
+    pub fn new<D: DebugInterface>(mach: &D, ins_list: Vec<(Xlen, String)>) -> Self {
         let ins = ListStateRecord::new(ins_list);
         let reg = ListStateRecord::new(mach.inspect_regs().into_iter().collect());
         #[cfg(feature = "zicsr")]
         let csr = ListStateRecord::new(mach.inspect_csrs());
 
         let except = "".to_string();
+        let console = "".to_string();
         let pc = mach.inspect_pc();
 
         let info = mach.get_info();
 
         MachineSnapshot { info, ins, reg, 
-            #[cfg(feature = "c")]csr, pc, except }
+            #[cfg(feature = "c")]csr, pc, except, console }
     }
 
     pub fn update_snapshot<D: DebugInterface>(&mut self, mach: &D) {
@@ -41,11 +45,32 @@ impl MachineSnapshot {
         self.pc = mach.inspect_pc();
     }
 
-    pub fn update_exception(&mut self, except: Exception) {
+    pub fn update_exception(&mut self, except: StepEvent) {
         self.except = except.to_string()
     }
 
+    /// Show a message in exception panel
+    pub fn update_message(&mut self, message: String) {
+        self.except = message
+    }
+
     pub fn reset_exception(&mut self) {
         self.except = "".to_string()
     }
+
+    /// Append UART output. Only keep the last `CONSOLE_LIMIT` bytes
+    pub fn update_console(&mut self, output: &[u8]) {
+        self.console.push_str(&String::from_utf8_lossy(output));
+        if self.console.len() > CONSOLE_LIMIT {
+            let mut start = self.console.len() - CONSOLE_LIMIT;
+            while !self.console.is_char_boundary(start) {
+                start += 1;
+            }
+            self.console.drain(..start);
+        }
+    }
+
+    pub fn reset_console(&mut self) {
+        self.console = "".to_string()
+    }
 }
\ No newline at end of file
@@ -17,7 +17,9 @@ use popup::*;
 
 const HEADER: &str = concat!("RsRisc-V Emulator v", env!("CARGO_PKG_VERSION"));
 const OBSERVATION_HINT_MESSAGE: &str = "(Q) Leave  (TAB) Change mode  (I) Information  (↑/↓) Scroll  (←/→) Change panel  (B) Breakpoint  (V) Bus Search";
-const EMULATE_HINT_MESSAGE: &str = "(Q) Leave  (TAB) Change mode  (I) Information  (↑/↓) Scroll  (S) Single step  (P) Run/Stop  (R) Reset";
+const EMULATE_HINT_MESSAGE: &str = "(Q) Leave  (TAB) Change mode  (I) Information  (↑/↓) Scroll  (S) Single step  (U) Step back  (K/L) Save/Load snapshot  (P) Run/Stop  (R) Reset  (E) Edit reg  (W) Write byte";
+
+const CONSOLE_HEIGHT: u16 = 10;
 
 pub(crate) const ANTI_FLASH_WHITE: Color = Color::Rgb(242, 242, 242);
 pub(crate) const BERKELEY_BLUE: Color = Color::Rgb(0, 50, 98);
@@ -83,7 +85,13 @@ fn render_content(f: &mut Frame, area: Rect, emu: &mut EmuState) {
         Constraint::Percentage(30),
     ]).split(layout[0]);
 
-    Instruction::render(f, info_layout[0], emu);
+    let left_layout = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(CONSOLE_HEIGHT),
+    ]).split(info_layout[0]);
+
+    Instruction::render(f, left_layout[0], emu);
+    Console::render(f, left_layout[1], emu);
     match emu.mid_selected {
         Mid::Reg => Register::render(f, info_layout[1], emu),
         #[cfg(feature = "zicsr")]
@@ -0,0 +1,28 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Paragraph};
+
+use crate::state::EmuState;
+use crate::ui::component::Component;
+use crate::ui::{BERKELEY_BLUE, CALIFORNIA_GOLD};
+
+const CONSOLE_TITLE: &str = "Console";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Console;
+
+impl Component for Console {
+    fn render(f: &mut Frame, area: Rect, emu: &mut EmuState) {
+        // Always show the latest lines
+        let lines = emu.mach_snap.console.split('\n').count() as u16;
+        let scroll = lines.saturating_sub(area.height.saturating_sub(2));
+
+        let paragraph = Paragraph::new(emu.mach_snap.console.as_str())
+            .block(Block::bordered().title(CONSOLE_TITLE))
+            .style(Style::default().bg(BERKELEY_BLUE).fg(CALIFORNIA_GOLD))
+            .scroll((scroll, 0));
+        
+        f.render_widget(paragraph, area);
+    }
+}
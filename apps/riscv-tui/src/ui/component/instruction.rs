@@ -17,7 +17,7 @@ impl Component for Instruction {
         let items: Vec<ListItem> = emu.mach_snap.ins.list.iter().enumerate()
             .map(|(i, (addr, ins))| {
 
-            let breakpoint = if emu.breakpoint_set.contains(&i) {
+            let breakpoint = if emu.breakpoint_set.contains_key(&i) {
                 "•"
             } else {
                 " "
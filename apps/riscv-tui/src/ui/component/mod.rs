@@ -1,3 +1,4 @@
+mod console;
 mod instruction;
 mod exception;
 mod mid;
@@ -7,6 +8,7 @@ use ratatui::layout::Rect;
 
 use crate::state::EmuState;
 
+pub use console::Console;
 pub use instruction::Instruction;
 pub use exception::Exception;
 #[cfg(feature = "zicsr")]
@@ -11,7 +11,7 @@ use ratatui::{
 pub fn render_popup(f: &mut Frame, emu: &EmuState) {
     let items = vec![
         ListItem::new(format!(" Dram Base: {:#010x}", emu.mach_snap.info.dram_base)),
-        ListItem::new(format!(" Dram Size: {} GB", emu.mach_snap.info.dram_size)),
+        ListItem::new(format!(" Dram Size: {} MB", emu.mach_snap.info.dram_size_mb)),
         ListItem::new(format!(" Page Size: {} KB", emu.mach_snap.info.page_size)),
         #[cfg(feature = "s")]
         ListItem::new(format!(" TLB Hit Rate: {:.2} %", emu.mach_snap.info.hit_rate * 100.0)),
@@ -9,7 +9,7 @@ use ratatui::{
 
 pub fn render_popup(f: &mut Frame, emu: &EmuState) {
     let paragraph = Paragraph::new(emu.input.chars.as_str())
-        .block(Block::bordered().style(Style::default().fg(CALIFORNIA_GOLD)).title("Enter Bus Address"))
+        .block(Block::bordered().style(Style::default().fg(CALIFORNIA_GOLD)).title(emu.input.target.title()))
         .style(Style::default().bg(Color::Rgb(20, 20, 20)).fg(ANTI_FLASH_WHITE))
         .alignment(Alignment::Left);
     
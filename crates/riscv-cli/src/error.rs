@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+use crate::MACHINE_USAGE;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    #[error("No input file")]
+    NoInputFile,
+
+    #[error("Too many input file")]
+    TooManyArgument,
+
+    #[error("Unknown option `{0}`")]
+    UnknownOption(String),
+
+    #[error("Option `{0}` need a value")]
+    MissingValue(String),
+
+    #[error("Invalid value `{1}` for option `{0}`")]
+    InvalidValue(String, String),
+}
+
+/// Error followed by usage of the app and machine options
+#[derive(Error, Debug)]
+#[error("{kind}\n{usage}\n{machine}", machine = MACHINE_USAGE)]
+pub struct CliError {
+    pub kind: ErrorKind,
+    pub usage: &'static str,
+}
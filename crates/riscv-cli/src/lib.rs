@@ -0,0 +1,20 @@
+//! Command line parsing shared by `riscv-run` and `riscv-tui`
+
+mod error;
+mod parser;
+mod value;
+
+pub use error::{CliError, ErrorKind};
+pub use parser::{Arg, CliParser};
+pub use value::{parse_addr, parse_base, parse_size, parse_u32, parse_u64};
+
+/// Usage of machine options handled by `CliParser`
+pub const MACHINE_USAGE: &str = concat!(
+    "    --ram-base <ADDR>     RAM base address (default: 0x80000000)\n",
+    "    --ram-size <SIZE>     RAM size, accept K/M/G suffix (default: 2G)\n",
+    "    --reset-pc <ADDR>     PC after reset (default: RAM base)\n",
+    "    --hart-id <ID>        Value of mhartid (default: 0)\n",
+    "    --clint <ADDR|none>   CLINT base address (default: 0x02000000)\n",
+    "    --plic <ADDR|none>    PLIC base address (default: 0x0c000000)\n",
+    "    --uart <ADDR|none>    UART base address (default: 0x10000000)",
+);
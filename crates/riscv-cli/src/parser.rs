@@ -0,0 +1,129 @@
+use riscv_core::Xlen;
+use riscv_core::config::MachineConfig;
+
+use crate::{CliError, ErrorKind};
+use crate::value::{parse_addr, parse_base, parse_size, parse_u32};
+
+/// Command line argument left to the app by `CliParser`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// Argument without `--` prefix, like the input file
+    Positional(String),
+    /// Option of the app. Its value, if any, is taken by `CliParser::value`
+    Option(String),
+}
+
+/// Split command line into positional arguments and options. Machine options in
+/// `MACHINE_USAGE` are applied to `MachineConfig` and not returned.
+/// ## Example
+/// ```rust
+/// # use riscv_cli::{Arg, CliParser};
+/// let args = ["--ram-size", "64M", "--snapshot", "a.snap", "file"].map(String::from);
+/// let mut parser = CliParser::new(args.into_iter(), "Usage: app [OPTIONS] <.elf>");
+///
+/// assert_eq!(parser.next_arg().unwrap(), Some(Arg::Option("--snapshot".to_string())));
+/// assert_eq!(parser.value("--snapshot").unwrap(), "a.snap");
+/// assert_eq!(parser.next_arg().unwrap(), Some(Arg::Positional("file".to_string())));
+/// assert_eq!(parser.next_arg().unwrap(), None);
+/// assert_eq!(parser.finish().ram_size, 64 * 1024 * 1024);
+/// ```
+pub struct CliParser<I> {
+    args: I,
+    usage: &'static str,
+    config: MachineConfig,
+    reset_pc: Option<Xlen>,
+}
+
+impl<I: Iterator<Item = String>> CliParser<I> {
+    /// `usage` of the app is shown before machine options in errors
+    pub fn new(args: I, usage: &'static str) -> Self {
+        CliParser { args, usage, config: MachineConfig::default(), reset_pc: None }
+    }
+
+    /// Return next argument that is not a machine option, `None` at the end
+    pub fn next_arg(&mut self) -> Result<Option<Arg>, CliError> {
+        while let Some(arg) = self.args.next() {
+            if !arg.starts_with("--") {
+                return Ok(Some(Arg::Positional(arg)));
+            }
+
+            match arg.as_str() {
+                "--ram-base" => self.config.ram_base = self.value_with(&arg, parse_addr)?,
+                "--ram-size" => self.config.ram_size = self.value_with(&arg, parse_size)?,
+                "--reset-pc" => self.reset_pc = Some(self.value_with(&arg, parse_addr)?),
+                "--hart-id"  => self.config.hart_id = self.value_with(&arg, parse_u32)?,
+                "--clint"    => self.config.clint_base = self.value_with(&arg, parse_base)?,
+                "--plic"     => self.config.plic_base = self.value_with(&arg, parse_base)?,
+                "--uart"     => self.config.uart_base = self.value_with(&arg, parse_base)?,
+                _            => return Ok(Some(Arg::Option(arg))),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Take value of `option`
+    pub fn value(&mut self, option: &str) -> Result<String, CliError> {
+        self.args.next().ok_or_else(|| self.error(ErrorKind::MissingValue(option.to_string())))
+    }
+
+    /// Take value of `option` and convert it by `parse`
+    pub fn value_with<T>(&mut self, option: &str, parse: impl FnOnce(&str) -> Option<T>) -> Result<T, CliError> {
+        let value = self.value(option)?;
+        parse(&value).ok_or_else(|| self.error(ErrorKind::InvalidValue(option.to_string(), value)))
+    }
+
+    /// Take all the remaining arguments, like program's arguments after the file
+    pub fn rest(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.args.by_ref()
+    }
+
+    pub fn error(&self, kind: ErrorKind) -> CliError {
+        CliError { kind, usage: self.usage }
+    }
+
+    /// Machine configuration from options. Reset PC follows RAM base if not given
+    pub fn finish(self) -> MachineConfig {
+        let mut config = self.config;
+        config.reset_pc = self.reset_pc.unwrap_or(config.ram_base);
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arg, CliParser};
+    use crate::ErrorKind;
+
+    fn cli(args: &[&str]) -> CliParser<impl Iterator<Item = String>> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        CliParser::new(args.into_iter(), "Usage: test")
+    }
+
+    #[test]
+    fn test_machine_options() {
+        let mut parser = cli(&["--ram-base", "0x4000_0000", "--plic", "none", "--hart-id", "3"]);
+        assert_eq!(parser.next_arg().unwrap(), None);
+
+        let config = parser.finish();
+        assert_eq!(config.ram_base, 0x4000_0000);
+        assert_eq!(config.reset_pc, 0x4000_0000, "Reset PC follows RAM base");
+        assert_eq!(config.plic_base, None);
+        assert_eq!(config.hart_id, 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut parser = cli(&["--ram-size", "1T"]);
+        let err = parser.next_arg().unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidValue("--ram-size".to_string(), "1T".to_string()));
+        assert!(err.to_string().ends_with(crate::MACHINE_USAGE), "Usage is shown with error");
+
+        let mut parser = cli(&["--uart"]);
+        assert_eq!(parser.next_arg().unwrap_err().kind, ErrorKind::MissingValue("--uart".to_string()));
+
+        let mut parser = cli(&["--user", "file", "-a"]);
+        assert_eq!(parser.next_arg().unwrap(), Some(Arg::Option("--user".to_string())));
+        assert_eq!(parser.next_arg().unwrap(), Some(Arg::Positional("file".to_string())));
+        assert_eq!(parser.rest().collect::<Vec<_>>(), ["-a"]);
+    }
+}
@@ -0,0 +1,69 @@
+use riscv_core::Xlen;
+
+/// Accept hex with `0x` or `0X` prefix or decimal, `_` is ignored
+fn parse_num<T>(value: &str, from_hex: fn(&str, u32) -> Result<T, std::num::ParseIntError>) -> Option<T>
+where
+    T: std::str::FromStr,
+{
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => from_hex(&hex.replace('_', ""), 16).ok(),
+        None      => value.replace('_', "").parse().ok(),
+    }
+}
+
+pub fn parse_u32(value: &str) -> Option<u32> {
+    parse_num(value, u32::from_str_radix)
+}
+
+pub fn parse_u64(value: &str) -> Option<u64> {
+    parse_num(value, u64::from_str_radix)
+}
+
+/// Address is as wide as XLEN
+pub fn parse_addr(value: &str) -> Option<Xlen> {
+    parse_num(value, Xlen::from_str_radix)
+}
+
+/// `none` means the device is not mapped
+pub fn parse_base(value: &str) -> Option<Option<Xlen>> {
+    if value == "none" {
+        Some(None)
+    } else {
+        parse_addr(value).map(Some)
+    }
+}
+
+/// Size in byte with optional `K`, `M` or `G` suffix
+pub fn parse_size(value: &str) -> Option<usize> {
+    let (num, unit) = match value.char_indices().last()? {
+        (i, 'K' | 'k') => (&value[..i], 1 << 10),
+        (i, 'M' | 'm') => (&value[..i], 1 << 20),
+        (i, 'G' | 'g') => (&value[..i], 1 << 30),
+        _              => (value, 1),
+    };
+    (parse_u32(num)? as usize).checked_mul(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_base, parse_size, parse_u32, parse_u64};
+
+    #[test]
+    fn test_parse_num() {
+        assert_eq!(parse_u32("0x8000_0000"), Some(0x8000_0000));
+        assert_eq!(parse_u32("0X1F"), Some(0x1f));
+        assert_eq!(parse_u32("1_000"), Some(1000));
+        assert_eq!(parse_u32("0x1_0000_0000"), None);
+        assert_eq!(parse_u64("0x1_0000_0000"), Some(1 << 32));
+        assert_eq!(parse_u32("ten"), None);
+    }
+
+    #[test]
+    fn test_parse_base_and_size() {
+        assert_eq!(parse_base("none"), Some(None));
+        assert_eq!(parse_base("0x1000_0000"), Some(Some(0x1000_0000)));
+        assert_eq!(parse_size("64M"), Some(64 << 20));
+        assert_eq!(parse_size("0x1000"), Some(4096));
+        assert_eq!(parse_size("k"), None);
+    }
+}
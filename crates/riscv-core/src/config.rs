@@ -0,0 +1,130 @@
+//! Machine configuration like memory map, reset PC and hart id
+
+use std::path::PathBuf;
+
+use crate::{RiscV, RiscVError, StdResult, UartSink, Xlen};
+use crate::debug::TraceSink;
+use crate::device::bus::{CLINT_BASE, DRAM_BASE_ADDR, PLIC_BASE, UART_BASE};
+
+const _2GB: usize = 2 * 1024 * 1024 * 1024;
+
+/// Configuration to build `RiscV`. `Default` is the memory map of `RiscV::default()`.
+/// Device with `None` base address is not mapped on bus
+/// ## Example
+/// ```rust
+/// # use riscv_core::config::MachineConfig;
+/// let mach = MachineConfig::default()
+///     .with_ram(0x4000_0000, 64 * 1024 * 1024)
+///     .with_reset_pc(0x4000_0000)
+///     .with_plic(None)
+///     .build()
+///     .expect("Valid memory map");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineConfig {
+    pub ram_base: Xlen,
+    /// Unit is byte. Aligned up to page size
+    pub ram_size: usize,
+    pub reset_pc: Xlen,
+    pub hart_id: u32,
+    pub clint_base: Option<Xlen>,
+    pub plic_base: Option<Xlen>,
+    pub uart_base: Option<Xlen>,
+    pub uart_sink: UartSink,
+    /// `Some(argv)` enable Linux user-mode syscall emulation. `ecall` is serviced by host,
+    /// stdout and stderr go to `uart_sink`
+    pub user_mode: Option<Vec<String>>,
+    /// `Some(root)` enable semihosting. Guest can only open files under `root`
+    pub semihosting: Option<PathBuf>,
+    /// `Some(len)` record the last `len` steps for `RiscV::step_back`
+    pub history: Option<usize>,
+    /// `Some(sink)` give a `Commit` of each step to the tracer
+    pub tracer: Option<TraceSink>,
+}
+
+impl MachineConfig {
+    pub fn with_ram(mut self, base: Xlen, size: usize) -> Self {
+        self.ram_base = base;
+        self.ram_size = size;
+        self
+    }
+
+    pub fn with_reset_pc(mut self, pc: Xlen) -> Self {
+        self.reset_pc = pc;
+        self
+    }
+
+    pub fn with_hart_id(mut self, hart_id: u32) -> Self {
+        self.hart_id = hart_id;
+        self
+    }
+
+    pub fn with_clint(mut self, base: Option<Xlen>) -> Self {
+        self.clint_base = base;
+        self
+    }
+
+    pub fn with_plic(mut self, base: Option<Xlen>) -> Self {
+        self.plic_base = base;
+        self
+    }
+
+    pub fn with_uart(mut self, base: Option<Xlen>) -> Self {
+        self.uart_base = base;
+        self
+    }
+
+    /// UART's output go to `sink`
+    pub fn with_uart_sink(mut self, sink: UartSink) -> Self {
+        self.uart_sink = sink;
+        self
+    }
+
+    /// Run statically linked program without firmware. `args` is guest's `argv`
+    pub fn with_user_mode(mut self, args: Vec<String>) -> Self {
+        self.user_mode = Some(args);
+        self
+    }
+
+    /// Service semihosting calls, files are opened under host directory `root`
+    pub fn with_semihosting(mut self, root: impl Into<PathBuf>) -> Self {
+        self.semihosting = Some(root.into());
+        self
+    }
+
+    /// Record the last `len` steps, so they can be undone by `RiscV::step_back`
+    pub fn with_history(mut self, len: usize) -> Self {
+        self.history = Some(len);
+        self
+    }
+
+    /// Trace every step into `sink`, like `spike --log-commits`
+    pub fn with_tracer(mut self, sink: TraceSink) -> Self {
+        self.tracer = Some(sink);
+        self
+    }
+
+    /// Build machine. Return error if any ranges of RAM and devices are invalid or overlapped
+    pub fn build(self) -> StdResult<RiscV, RiscVError> {
+        RiscV::new(self)
+    }
+}
+
+impl Default for MachineConfig {
+    fn default() -> Self {
+        MachineConfig {
+            ram_base: DRAM_BASE_ADDR,
+            ram_size: _2GB,
+            reset_pc: DRAM_BASE_ADDR,
+            hart_id: 0,
+            clint_base: Some(CLINT_BASE),
+            plic_base: Some(PLIC_BASE),
+            uart_base: Some(UART_BASE),
+            uart_sink: UartSink::default(),
+            user_mode: None,
+            semihosting: None,
+            history: None,
+            tracer: None,
+        }
+    }
+}
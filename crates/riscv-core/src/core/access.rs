@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 
+use crate::Xlen;
 use crate::exception::Exception;
 
 use AccessType::*;
@@ -21,13 +22,13 @@ pub struct Physical;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Access<T = Virtual> {
-    pub addr: u32,
+    pub addr: Xlen,
     pub kind : AccessType,  
     _marker: PhantomData<T>,
 }
 
 impl<T> Access<T> {
-    pub fn new(addr: u32, kind: AccessType) -> Self {
+    pub fn new(addr: Xlen, kind: AccessType) -> Self {
         Access { addr, kind, _marker: PhantomData }
     }
     
@@ -55,7 +56,7 @@ impl<T> Access<T> {
 
 impl Access<Virtual> {
     #[cfg(feature = "s")]
-    pub fn into_physical(self, p_addr: u32) -> Access<Physical> {
+    pub fn into_physical(self, p_addr: Xlen) -> Access<Physical> {
         Access { addr: p_addr, kind: self.kind, _marker: PhantomData }
     }
 
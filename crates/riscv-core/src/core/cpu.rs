@@ -1,27 +1,45 @@
 mod debug;
+mod history;
+mod host;
+mod semihost;
+mod snapshot;
+mod syscall;
 
 use riscv_decoder::prelude::*;
 
 use riscv_loader::LoadInfo;
 
-use crate::{Exception, Result, RiscVError, StdResult};
+use crate::{Exception, Halt, Result, RiscVError, StdResult, StepEvent, StopReason, Trap, Xlen};
+#[cfg(feature = "zicsr")]
+use crate::Interrupt;
 #[cfg(feature = "s")]
-use crate::core::Mmu;
+use crate::core::{HpmEvent, Mmu};
 #[cfg(feature = "zicsr")]
 use crate::core::csr::CsrFile;
 #[cfg(feature = "zicsr")]
 use crate::core::privilege::PrivilegeMode;
 use crate::core::access::{Access, AccessType};
+use crate::debug::{Debugger, History, TraceSink};
 use crate::device::bus::SystemBus;
+use crate::config::MachineConfig;
 use crate::device::Device;
 
 
 use super::{PC, RegisterFile};
+#[cfg(feature = "f")]
+use super::FpRegisterFile;
+use semihost::Semihost;
+use syscall::Syscall;
+
+pub(crate) use host::IO_LIMIT;
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Cpu {
     #[cfg(feature = "zicsr")]
     pub(crate) mode: PrivilegeMode,
     pub(crate) regs: RegisterFile,
+    #[cfg(feature = "f")]
+    pub(crate) fregs: FpRegisterFile,
     pub(crate) pc: PC,
     #[cfg(feature = "zicsr")]
     pub(crate) csrs: CsrFile,
@@ -29,12 +47,49 @@ pub struct Cpu {
     pub(crate) mmu: Mmu,
     pub(crate) bus: SystemBus,
     #[cfg(feature = "a")]
-    pub(crate) reservation: Option<u32>,
+    pub(crate) reservation: Option<Xlen>,
     #[cfg(feature = "c")]
     pub(crate) is_compress: bool,
+    pub(crate) halt: Option<Halt>,
+    pub(crate) debugger: Debugger,
+    /// Step history, enabled by `MachineConfig::with_history`
+    pub(crate) history: Option<History>,
+    /// Instruction trace, enabled by `MachineConfig::with_tracer`
+    pub(crate) tracer: Option<TraceSink>,
+    /// Linux syscall emulation, enabled by `MachineConfig::with_user_mode`
+    pub(crate) syscall: Option<Syscall>,
+    /// Semihosting, enabled by `MachineConfig::with_semihosting`
+    pub(crate) semihost: Option<Semihost>,
 }
 
 impl Cpu {
+    /// Create machine by `config`. Return error if memory map is invalid
+    pub fn new(config: MachineConfig) -> StdResult<Self, RiscVError> {
+        Ok(Cpu {
+            #[cfg(feature = "zicsr")]
+            mode: PrivilegeMode::default(),
+            regs: RegisterFile::default(),
+            #[cfg(feature = "f")]
+            fregs: FpRegisterFile::default(),
+            pc: PC::new(config.reset_pc),
+            #[cfg(feature = "zicsr")]
+            csrs: CsrFile::new(config.hart_id),
+            #[cfg(feature = "s")]
+            mmu: Mmu::default(),
+            bus: SystemBus::new(&config)?,
+            #[cfg(feature = "a")]
+            reservation: None,
+            #[cfg(feature = "c")]
+            is_compress: false,
+            halt: None,
+            debugger: Debugger::default(),
+            history: config.history.map(History::new),
+            tracer: config.tracer,
+            syscall: config.user_mode.map(|args| Syscall::new(args, config.uart_sink.clone())),
+            semihost: config.semihosting.map(|root| Semihost::new(root, config.uart_sink)),
+        })
+    }
+
     pub fn load_info(&mut self, info: &LoadInfo) -> StdResult<(), RiscVError> {
         for (code, addr) in info.code.iter() {
             self.load(*addr, code)?
@@ -54,10 +109,16 @@ impl Cpu {
                 self.load(*addr, data)?
             }
         }
-        Ok(())
+        if let Some(symbols) = &info.symbols {
+            let find = |target: &str| symbols.iter()
+                .find(|(_, name)| *name == target)
+                .map(|(addr, _)| *addr);
+            self.bus.set_htif(find("tohost"), find("fromhost"));
+        }
+        self.user_setup(info)
     }
 
-    pub fn load(&mut self, addr: u32, data: &[u8]) -> StdResult<(), RiscVError> {
+    pub fn load(&mut self, addr: Xlen, data: &[u8]) -> StdResult<(), RiscVError> {
         let access = Access::new(addr, AccessType::Store);
         if self.bus.write_bytes(access, data.len(), data).is_err() {
             Err(RiscVError::LoadFailed)
@@ -66,58 +127,202 @@ impl Cpu {
         }
     }
 
-    pub fn set_pc(&mut self, entry: u32) {
+    pub fn set_pc(&mut self, entry: Xlen) {
         self.pc.set(entry);
     }
 
-    pub fn set_mem_zero(&mut self, addr: u32, size: usize) -> std::result::Result<(), RiscVError> {
+    /// Map user defined `device` at physical address `base..base + size`.
+    /// `irq` is PLIC source id of device's interrupt line
+    pub fn add_device<D: Device + Send + 'static>(&mut self, base: Xlen, size: u32, irq: Option<u32>, device: D) -> StdResult<(), RiscVError> {
+        self.bus.add_device(base, size, irq, device)
+    }
+
+    /// Send bytes to UART's receiver like they are typed in console
+    pub fn uart_input(&mut self, data: &[u8]) {
+        self.bus.uart_input(data);
+    }
+
+    /// Feed UART's receiver with host's stdin
+    pub fn uart_stdin(&mut self, enable: bool) {
+        self.bus.uart_stdin(enable);
+    }
+
+    pub fn set_mem_zero(&mut self, addr: Xlen, size: usize) -> std::result::Result<(), RiscVError> {
         let access = Access::new(addr, AccessType::Store); 
     
         self.bus.write_bytes(access, size, &vec![0; size]).map_err(|_| RiscVError::BssInitFailed)?;
         Ok(())
     }
 
-    pub fn run(&mut self) -> StdResult<(), RiscVError> {
-        loop { self.step()?; }
+    /// Run until machine halt
+    pub fn run(&mut self) -> StdResult<Halt, RiscVError> {
+        loop {
+            if let Some(StepEvent::Halt(halt)) = self.step()? {
+                return Ok(halt);
+            }
+        }
+    }
+
+    /// Run until breakpoint, halt or `max_steps` steps. Without Zicsr, trap also stops the run
+    pub fn run_until(&mut self, max_steps: Option<u64>) -> StdResult<StopReason, RiscVError> {
+        let mut count = 0;
+        loop {
+            if max_steps.is_some_and(|max| count >= max) {
+                return Ok(StopReason::Limit);
+            }
+
+            match self.step()? {
+                Some(StepEvent::Break(hit)) => return Ok(StopReason::Break(hit)),
+                Some(StepEvent::Halt(halt)) => return Ok(StopReason::Halt(halt)),
+                #[cfg(not(feature = "zicsr"))]
+                Some(StepEvent::Trap(trap)) => return Ok(StopReason::Trap(trap)),
+                _ => {},
+            }
+            count += 1;
+        }
+    }
+
+    /// Return the halt reason if machine is halted
+    pub fn halted(&self) -> Option<Halt> {
+        self.halt
     }
  
-    pub fn step(&mut self) -> StdResult<Option<Exception>, RiscVError> {      
-        #[cfg(feature = "zicsr")]  
-        return Ok(if let Err(execpt) = self.cycle() {      
-            self.trap_handle(execpt);
-            Some(execpt) 
-        } else {
-            None
-        });
-        #[cfg(not(feature = "zicsr"))] 
-        Ok(self.cycle().err())
+    pub fn step(&mut self) -> StdResult<Option<StepEvent>, RiscVError> {
+        if let Some(halt) = self.halt {
+            return Ok(Some(halt.into()));
+        }
+        if self.tracer.is_some() {
+            self.debugger.trace_start(self.pc.get(), self.privilege());
+        }
+
+        let checkpoint = self.history.is_some().then(|| self.checkpoint());
+        let result = self.step_once();
+        if let Some(checkpoint) = checkpoint {
+            self.record(checkpoint);
+        }
+        self.trace_commit();
+        result
+    }
+
+    fn step_once(&mut self) -> StdResult<Option<StepEvent>, RiscVError> {
+        self.bus.tick();
+        #[cfg(feature = "zicsr")]
+        self.csrs.set_time(self.bus.mtime());
+
+        #[cfg(feature = "zicsr")]
+        if let Some(irq) = self.interrupt_pending() {
+            self.debugger.trace_trap(irq.into());
+            self.trap_handle(irq.into());
+            self.csrs.tick_counters(false);
+            return Ok(Some(Trap::from(irq).into()));
+        }
+
+        #[cfg(feature = "s")]
+        let miss_count = self.mmu.miss_count;
+        let result = self.cycle();
+        #[cfg(feature = "s")]
+        self.csrs.count_event(HpmEvent::TlbMiss, (self.mmu.miss_count - miss_count) as u64);
+
+        if let Err(except) = result {
+            // Access of faulted instruction is not done
+            self.debugger.take_hit();
+
+            if self.is_syscall(&except) {
+                // `ecall` is done, continue at next instruction
+                self.pc.step();
+                #[cfg(feature = "zicsr")]
+                self.csrs.tick_counters(true);
+                if let Some(halt) = self.syscall() {
+                    self.halt = Some(halt);
+                    return Ok(Some(halt.into()));
+                }
+                return Ok(None);
+            }
+
+            self.debugger.trace_trap(except.into());
+            #[cfg(feature = "zicsr")] {
+                self.trap_handle(except.into());
+                self.csrs.tick_counters(false);
+            }
+            return Ok(Some(Trap::from(except).into()));
+        }
+
+        let hit = self.debugger.take_hit();
+        // Halted by semihosting or HTIF
+        if self.halt.is_none() {
+            self.halt = self.bus.htif_poll();
+        }
+        Ok(self.halt.map(StepEvent::from).or(hit.map(StepEvent::from)))
     }
 
     fn cycle(&mut self) -> Result<()> {
         #[cfg(feature = "c")]
-        let ins = if let Some(c_raw) = self.c_fetch()? {
+        let c_raw = self.c_fetch()?;
+        #[cfg(not(feature = "c"))]
+        let raw = self.fetch()?;
+
+        // Stop before the instruction at execute breakpoint
+        if self.debugger.fetch_stopped() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "c")]
+        let ins = if let Some(c_raw) = c_raw {
+            let c_raw = u16::from_le_bytes(c_raw);
+            self.debugger.trace_raw(c_raw as u32);
             self.is_compress = true;
-            self.decompress(u16::from_le_bytes(c_raw))?
+            self.decompress(c_raw)?
         } else {
             let raw = self.fetch()?;
+            self.debugger.trace_raw(raw);
             self.is_compress = false;
             self.decode(raw)?
         };
 
         #[cfg(not(feature = "c"))]
-        let raw = self.fetch()?;
-        #[cfg(not(feature = "c"))]
-        let ins = self.decode(raw)?;
+        let ins = {
+            self.debugger.trace_raw(raw);
+            self.decode(raw)?
+        };
         
         self.execute(ins)?;
+        #[cfg(feature = "zicsr")]
+        self.csrs.tick_counters(true);
         Ok(())
     }
 
+    /// Write back `rd` and give it to tracer
+    pub(crate) fn write_rd(&mut self, rd: u8, data: Xlen) {
+        self.regs.write(rd, data);
+        self.debugger.trace_reg(rd, data);
+    }
+
+    /// Privilege mode encoded like `mstatus.MPP`
+    fn privilege(&self) -> u8 {
+        #[cfg(feature = "zicsr")]
+        return self.mode as u8;
+        #[cfg(not(feature = "zicsr"))]
+        3
+    }
+
+    /// Give commit of the step to tracer. Step stopped by execute breakpoint is not traced
+    fn trace_commit(&mut self) {
+        if let Some(commit) = self.debugger.trace_take()
+            && (commit.raw.is_some() || commit.trap.is_some())
+            && let Some(tracer) = &self.tracer
+        {
+            tracer.commit(&commit);
+        }
+    }
+
     fn fetch(&mut self) -> Result<u32> {
         let va_access = Access::new(self.pc.get(), AccessType::Fetch);
 
+        #[cfg(all(feature = "zicsr", not(feature = "c")))]
+        self.csrs.trigger_check(va_access, 1, self.mode)?;
+
         #[cfg(not(feature = "s"))]
-        let pa_access = va_access;
+        let pa_access = va_access.bypass();
 
         #[cfg(feature = "s")]
         let pa_access = self.mmu.translate(va_access, self.mode, &self.csrs, &mut self.bus)?;
@@ -128,6 +333,10 @@ impl Cpu {
             _ => e
         })?;
 
+        // `c_fetch` already checked this PC
+        #[cfg(not(feature = "c"))]
+        self.debugger.fetch(va_access.addr, pa_access.addr);
+
         self.bus.read_u32(pa_access).map_err(|e| match e {
             Exception::InstructionAccessFault(_) => Exception::InstructionAccessFault(va_access.addr),
             _ => e
@@ -138,8 +347,11 @@ impl Cpu {
     fn c_fetch(&mut self) -> Result<Option<[u8; 2]>> {
         let va_access = Access::new(self.pc.get(), AccessType::Fetch);
 
+        #[cfg(feature = "zicsr")]
+        self.csrs.trigger_check(va_access, 1, self.mode)?;
+
         #[cfg(not(feature = "s"))]
-        let pa_access = va_access;
+        let pa_access = va_access.bypass();
 
         #[cfg(feature = "s")]
         let pa_access = self.mmu.translate(va_access, self.mode, &self.csrs, &mut self.bus)?;
@@ -150,6 +362,8 @@ impl Cpu {
             _ => e
         })?;
 
+        self.debugger.fetch(va_access.addr, pa_access.addr);
+
         let mut half_raw = [0; 2];
         self.bus.read_bytes(pa_access, 2, &mut half_raw).map_err(|e| match e {
             Exception::InstructionAccessFault(_) => Exception::InstructionAccessFault(va_access.addr),
@@ -183,12 +397,20 @@ impl Cpu {
             Instruction::Privileged(op, data)  => if self.execute_privileged(op, data)? {
                 return Ok(())
             },
+            #[cfg(feature = "rv64")]
+            Instruction::Rv64(op, data) => self.execute_rv64(op, data)?,
             #[cfg(feature = "m")]
             Instruction::M(op, data) => self.execute_m(op, data),
             #[cfg(feature = "a")]
             Instruction::A(op, data) => self.execute_a(op, data)?,
             #[cfg(feature = "zicsr")]
             Instruction::Zicsr(op, data, raw) => self.execute_zicsr(op, data, raw)?,
+            #[cfg(feature = "f")]
+            Instruction::F(op, data, raw) => self.execute_f(op, data, raw)?,
+            #[cfg(feature = "d")]
+            Instruction::D(op, data, raw) => self.execute_d(op, data, raw)?,
+            #[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+            Instruction::B(op, data) => self.execute_b(op, data),
             #[cfg(feature = "zifencei")]
             Instruction::Zifencei(_, _)  => {},          
         }
@@ -204,16 +426,33 @@ impl Cpu {
     }
 
     #[cfg(feature = "zicsr")]
-    fn trap_handle(&mut self, except: Exception) {
-        let (mode, pc) = self.csrs.trap_entry(self.pc.get(), except, self.mode);
+    fn trap_handle(&mut self, trap: Trap) {
+        let (mode, pc) = self.csrs.trap_entry(self.pc.get(), trap, self.mode);
         self.pc.directed_addressing(pc);
         self.mode = mode;
     }
 
+    #[cfg(feature = "zicsr")]
+    /// Sync device's interrupt lines into `mip`, and return the interrupt should be taken
+    fn interrupt_pending(&mut self) -> Option<Interrupt> {
+        let (software, timer) = self.bus.clint_pending();
+        self.csrs.set_pending(Interrupt::MachineSoftware, software);
+        self.csrs.set_pending(Interrupt::MachineTimer, timer);
+
+        let (machine_ext, _supervisor_ext) = self.bus.plic_pending();
+        self.csrs.set_pending(Interrupt::MachineExternal, machine_ext);
+        #[cfg(feature = "s")]
+        self.csrs.set_pending(Interrupt::SupervisorExternal, _supervisor_ext);
+
+        self.csrs.pending_interrupt(self.mode)
+    }
+
     pub fn reset(&mut self) {
         self.regs.reset();
+        #[cfg(feature = "f")]
+        self.fregs.reset();
         self.pc.reset();
-        self.bus.reset_ram();
+        self.bus.reset();
         #[cfg(feature = "zicsr")] {
             self.mode = PrivilegeMode::default();
             self.csrs.reset();
@@ -228,6 +467,17 @@ impl Cpu {
         #[cfg(feature = "c")] {
             self.is_compress = false;
         }
+        self.halt = None;
+        self.debugger.reset();
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+        if let Some(sys) = self.syscall.as_mut() {
+            sys.reset();
+        }
+        if let Some(semihost) = self.semihost.as_mut() {
+            semihost.reset();
+        }
     }
 }
 
@@ -1,41 +1,125 @@
-use crate::debug::{DebugInterface, MachineInfo};
+use crate::Xlen;
+use crate::debug::{Breakpoint, BreakpointId, DebugControl, DebugInterface, MachineInfo};
 use crate::core::access::{Access, AccessType};
 use crate::core::cpu::Cpu;
 use crate::device::Device;
+#[cfg(feature = "zicsr")]
+use crate::core::PrivilegeMode;
 
 impl DebugInterface for Cpu {
-    fn inspect_regs(&self) -> [u32; 32] {
+    fn inspect_regs(&self) -> [Xlen; 32] {
         self.regs.inspect()
     }
 
-    fn inspect_pc(&self) -> u32 {
+    fn inspect_pc(&self) -> Xlen {
         self.pc.get()
     }
 
+    #[cfg(feature = "f")]
+    fn inspect_fregs(&self) -> [u64; 32] {
+        self.fregs.inspect()
+    }
+
     #[cfg(feature = "zicsr")]
-    fn inspect_csrs(&self) -> Vec<(String, u32)> {
+    fn inspect_csrs(&self) -> Vec<(String, Xlen)> {
         self.csrs.inspect()
     }
 
-    fn inspect_bus(&self, addr: u32, len: usize) -> Vec<u8> {
+    #[cfg(feature = "zicsr")]
+    fn inspect_csr(&self, addr: u16) -> Option<Xlen> {
+        // `read` may update state, so read from a copy
+        let mut csrs = self.csrs;
+        csrs.read(addr, PrivilegeMode::Machine, 0).ok()
+    }
+
+    #[cfg(feature = "zicsr")]
+    fn inspect_privilege(&self) -> u8 {
+        self.mode as u8
+    }
+
+    fn inspect_bus(&self, addr: Xlen, len: usize) -> Vec<u8> {
         let mut bytes: Vec<u8> = vec![0; len]; 
         let access = Access::new(addr, AccessType::Load);
-        let _ = self.bus.read_bytes(access, len, &mut bytes);
+        let _ = self.bus.peek_bytes(access, len, &mut bytes);
         bytes
     }    
 
+    fn peek_bus(&self, addr: Xlen, len: usize) -> Option<Vec<u8>> {
+        let mut bytes = vec![0; len];
+        let access = Access::new(addr, AccessType::Load);
+        self.bus.peek_bytes(access, len, &mut bytes).ok()?;
+        Some(bytes)
+    }
+
     fn get_info(&self) -> MachineInfo {
         let (dram_size, dram_base, page_size) = self.bus.ram_info();
-        let dram_size = dram_size / 1024 / 1024 / 1024;
+        let dram_size_mb = dram_size / 1024 / 1024;
         let page_size = page_size / 1024;
         #[cfg(feature = "s")]
         let hit = self.mmu.hit_count;
         #[cfg(feature = "s")]
         let miss = self.mmu.miss_count;
 
-        MachineInfo::new(dram_size, dram_base, page_size, 
+        MachineInfo::new(dram_size_mb, dram_base, page_size, 
             #[cfg(feature = "s")]hit, 
             #[cfg(feature = "s")]miss, 
             #[cfg(feature = "s")]self.mode)
     }
-}
\ No newline at end of file
+}
+
+impl DebugControl for Cpu {
+    fn write_reg(&mut self, id: u8, data: Xlen) {
+        self.regs.write(id, data);
+    }
+
+    fn set_pc(&mut self, pc: Xlen) {
+        self.pc.set(pc);
+    }
+
+    #[cfg(feature = "zicsr")]
+    fn write_csr(&mut self, addr: u16, data: Xlen) -> bool {
+        self.csrs.write(addr, data, PrivilegeMode::Machine, 0).is_ok()
+    }
+
+    #[cfg(feature = "f")]
+    fn write_freg(&mut self, id: u8, data: u64) {
+        self.fregs.write(id, data);
+    }
+
+    fn write_bus(&mut self, addr: Xlen, data: &[u8]) -> bool {
+        let access = Access::new(addr, AccessType::Store);
+        self.bus.write_bytes(access, data.len(), data).is_ok()
+    }
+
+    #[cfg(feature = "zicsr")]
+    fn set_privilege(&mut self, mode: u8) -> bool {
+        self.mode = match mode {
+            0 => PrivilegeMode::User,
+            #[cfg(feature = "s")]
+            1 => PrivilegeMode::Supervisor,
+            3 => PrivilegeMode::Machine,
+            _ => return false,
+        };
+        true
+    }
+}
+
+impl Cpu {
+    /// Add breakpoint or watchpoint. `step` reports it by `StepEvent::Break` and `run_until` stops on it
+    pub fn add_breakpoint(&mut self, point: Breakpoint) -> BreakpointId {
+        self.debugger.add(point)
+    }
+
+    /// Return `false` if `id` does not exist
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) -> bool {
+        self.debugger.remove(id)
+    }
+
+    pub fn breakpoint(&self, id: BreakpointId) -> Option<&Breakpoint> {
+        self.debugger.get(id)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (BreakpointId, &Breakpoint)> {
+        self.debugger.iter()
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Halt, Xlen};
+use crate::core::access::{Access, AccessType};
+use crate::core::cpu::Cpu;
+#[cfg(feature = "zicsr")]
+use crate::core::{CsrFile, PrivilegeMode};
+use crate::core::RegisterFile;
+#[cfg(feature = "f")]
+use crate::core::FpRegisterFile;
+use crate::debug::{Delta, MemWrite};
+use crate::device::Device;
+
+/// Machine state before a step
+pub(super) struct Checkpoint {
+    pc: Xlen,
+    regs: RegisterFile,
+    #[cfg(feature = "f")]
+    fregs: FpRegisterFile,
+    #[cfg(feature = "zicsr")]
+    mode: PrivilegeMode,
+    #[cfg(feature = "zicsr")]
+    csrs: CsrFile,
+    #[cfg(feature = "a")]
+    reservation: Option<Xlen>,
+    halt: Option<Halt>,
+}
+
+impl Cpu {
+    /// Undo the last recorded step. Return `false` if there is no history.
+    /// Device state, host files and breakpoint hit counts are not restored
+    pub fn step_back(&mut self) -> bool {
+        let Some(delta) = self.history.as_mut().and_then(|history| history.back()).cloned() else {
+            return false;
+        };
+
+        for mem in delta.mem.iter().rev() {
+            self.bus.restore_ram(mem.addr, &mem.old);
+        }
+        for (id, old, _) in delta.regs.iter() {
+            self.regs.write(*id, *old);
+        }
+        #[cfg(feature = "f")]
+        for (id, old, _) in delta.fregs.iter() {
+            self.fregs.write(*id, *old);
+        }
+        self.pc.set(delta.pc.0);
+        #[cfg(feature = "zicsr")] {
+            self.mode = delta.mode.0;
+            if let Some(csrs) = delta.csrs.as_ref() {
+                self.csrs = csrs.0;
+            }
+            self.csrs.set_counters(delta.counters.0);
+        }
+        #[cfg(feature = "s")]
+        self.mmu.flush_all();
+        #[cfg(feature = "a")] {
+            self.reservation = delta.reservation.0;
+        }
+        self.halt = delta.halt.0;
+        true
+    }
+
+    /// Replay a step undone by `step_back`. Return `false` if there is nothing to replay
+    pub fn step_forward(&mut self) -> bool {
+        let Some(delta) = self.history.as_mut().and_then(|history| history.forward()).cloned() else {
+            return false;
+        };
+
+        for mem in delta.mem.iter() {
+            self.bus.restore_ram(mem.addr, &mem.new);
+        }
+        for (id, _, new) in delta.regs.iter() {
+            self.regs.write(*id, *new);
+        }
+        #[cfg(feature = "f")]
+        for (id, _, new) in delta.fregs.iter() {
+            self.fregs.write(*id, *new);
+        }
+        self.pc.set(delta.pc.1);
+        #[cfg(feature = "zicsr")] {
+            self.mode = delta.mode.1;
+            if let Some(csrs) = delta.csrs.as_ref() {
+                self.csrs = csrs.1;
+            }
+            self.csrs.set_counters(delta.counters.1);
+        }
+        #[cfg(feature = "s")]
+        self.mmu.flush_all();
+        #[cfg(feature = "a")] {
+            self.reservation = delta.reservation.1;
+        }
+        self.halt = delta.halt.1;
+        true
+    }
+
+    /// Return number of (undoable, replayable) steps. `(0, 0)` if history is disabled
+    pub fn history_len(&self) -> (usize, usize) {
+        self.history.as_ref().map_or((0, 0), |history| history.len())
+    }
+
+    pub(super) fn checkpoint(&mut self) -> Checkpoint {
+        self.bus.start_journal();
+        Checkpoint {
+            pc: self.pc.get(),
+            regs: self.regs,
+            #[cfg(feature = "f")]
+            fregs: self.fregs,
+            #[cfg(feature = "zicsr")]
+            mode: self.mode,
+            #[cfg(feature = "zicsr")]
+            csrs: self.csrs,
+            #[cfg(feature = "a")]
+            reservation: self.reservation,
+            halt: self.halt,
+        }
+    }
+
+    /// Push changes since `before` into history
+    pub(super) fn record(&mut self, before: Checkpoint) {
+        let mem = self.bus.take_journal().into_iter()
+            .map(|(addr, old)| {
+                let mut new = vec![0; old.len()];
+                let _ = self.bus.peek_bytes(Access::new(addr, AccessType::Load), old.len(), &mut new);
+                MemWrite { addr, old, new }
+            })
+            .collect();
+
+        let (old_regs, new_regs) = (before.regs.inspect(), self.regs.inspect());
+        let regs = (1..32u8)
+            .filter(|id| old_regs[*id as usize] != new_regs[*id as usize])
+            .map(|id| (id, old_regs[id as usize], new_regs[id as usize]))
+            .collect();
+
+        #[cfg(feature = "f")]
+        let (old_fregs, new_fregs) = (before.fregs.inspect(), self.fregs.inspect());
+        #[cfg(feature = "f")]
+        let fregs = (0..32u8)
+            .filter(|id| old_fregs[*id as usize] != new_fregs[*id as usize])
+            .map(|id| (id, old_fregs[id as usize], new_fregs[id as usize]))
+            .collect();
+
+        let delta = Delta {
+            pc: (before.pc, self.pc.get()),
+            regs,
+            #[cfg(feature = "f")]
+            fregs,
+            #[cfg(feature = "zicsr")]
+            mode: (before.mode, self.mode),
+            #[cfg(feature = "zicsr")]
+            csrs: (!before.csrs.eq_without_counters(&self.csrs)).then(|| Box::new((before.csrs, self.csrs))),
+            #[cfg(feature = "zicsr")]
+            counters: (before.csrs.counters(), self.csrs.counters()),
+            #[cfg(feature = "a")]
+            reservation: (before.reservation, self.reservation),
+            halt: (before.halt, self.halt),
+            mem,
+        };
+        // Safe: `step` only record when history is enabled
+        self.history.as_mut().unwrap().push(delta);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::{StdResult, Xlen};
+use crate::core::access::{Access, AccessType};
+use crate::core::cpu::Cpu;
+use crate::device::Device;
+use crate::device::memory::PAGE_SIZE;
+
+pub(super) const EIO: i32 = 5;
+pub(super) const EBADF: i32 = 9;
+pub(super) const EFAULT: i32 = 14;
+pub(super) const EINVAL: i32 = 22;
+
+/// Guest can not make host allocate too much in one call. Read and write are partial
+pub(crate) const IO_LIMIT: Xlen = 1 << 20;
+
+/// Host files opened by guest. Cloned table shares the same files
+#[derive(Clone, Default)]
+pub(super) struct FileTable(Arc<Mutex<HashMap<Xlen, File>>>);
+
+impl FileTable {
+    pub fn open(&self, file: File) -> Xlen {
+        let mut files = self.0.lock().unwrap();
+        // Lowest unused fd after stdin, stdout and stderr
+        // Safe: Can not open 4G files
+        let fd = (3..).find(|fd| !files.contains_key(fd)).unwrap();
+        files.insert(fd, file);
+        fd
+    }
+
+    pub fn close(&self, fd: Xlen) -> bool {
+        self.0.lock().unwrap().remove(&fd).is_some()
+    }
+
+    pub fn with<T>(&self, fd: Xlen, f: impl FnOnce(&mut File) -> io::Result<T>) -> StdResult<T, i32> {
+        let mut files = self.0.lock().unwrap();
+        let file = files.get_mut(&fd).ok_or(EBADF)?;
+        f(file).map_err(errno)
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+impl std::fmt::Debug for FileTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FileTable")
+    }
+}
+
+impl PartialEq for FileTable {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for FileTable {}
+
+pub(super) fn errno(e: io::Error) -> i32 {
+    e.raw_os_error().unwrap_or(EIO)
+}
+
+/// Guest memory access for host calls. Pointers are physical address
+impl Cpu {
+    pub(super) fn guest_read(&self, addr: Xlen, len: Xlen) -> StdResult<Vec<u8>, i32> {
+        let mut buf = vec![0; len as usize];
+        self.bus.read_bytes(Access::new(addr, AccessType::Load), buf.len(), &mut buf).map_err(|_| EFAULT)?;
+        Ok(buf)
+    }
+
+    pub(super) fn guest_write(&mut self, addr: Xlen, data: &[u8]) -> StdResult<(), i32> {
+        self.bus.write_bytes(Access::new(addr, AccessType::Store), data.len(), data).map_err(|_| EFAULT)
+    }
+
+    pub(super) fn guest_cstr(&self, addr: Xlen) -> StdResult<String, i32> {
+        let mut bytes = Vec::new();
+        for offset in 0..PAGE_SIZE as Xlen {
+            match self.guest_read(addr.wrapping_add(offset), 1)?[0] {
+                0    => return Ok(String::from_utf8_lossy(&bytes).into_owned()),
+                byte => bytes.push(byte),
+            }
+        }
+        Err(EINVAL)
+    }
+}
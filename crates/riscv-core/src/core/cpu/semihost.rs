@@ -0,0 +1,228 @@
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+
+use crate::{Halt, StdResult, UartSink, Xlen};
+use crate::core::cpu::Cpu;
+#[cfg(feature = "s")]
+use crate::core::PrivilegeMode;
+use super::host::{EINVAL, FileTable, IO_LIMIT};
+
+const A0: u8 = 10;
+const A1: u8 = 11;
+
+/// `slli x0, x0, 0x1f`, before `ebreak`
+const SEMIHOST_ENTRY: u32 = 0x01f01013;
+/// `srai x0, x0, 7`, after `ebreak`
+const SEMIHOST_EXIT: u32 = 0x40705013;
+
+const SYS_OPEN: Xlen = 0x01;
+const SYS_CLOSE: Xlen = 0x02;
+const SYS_WRITEC: Xlen = 0x03;
+const SYS_WRITE0: Xlen = 0x04;
+const SYS_WRITE: Xlen = 0x05;
+const SYS_READ: Xlen = 0x06;
+const SYS_CLOCK: Xlen = 0x10;
+const SYS_EXIT: Xlen = 0x18;
+const SYS_EXIT_EXTENDED: Xlen = 0x20;
+const SYS_ELAPSED: Xlen = 0x30;
+const SYS_TICKFREQ: Xlen = 0x31;
+
+const ADP_STOPPED_APPLICATION_EXIT: Xlen = 0x20026;
+
+/// Handles of `:tt`. Files start from 3
+const HANDLE_STDIN: Xlen = 1;
+const HANDLE_STDOUT: Xlen = 2;
+
+/// `SYS_ELAPSED` tick is microsecond
+const TICK_FREQ: Xlen = 1_000_000;
+
+/// RISC-V semihosting. Files are opened in `root` and can not escape it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Semihost {
+    root: PathBuf,
+    files: FileTable,
+    start: Instant,
+    /// `:tt` output go to UART's sink
+    sink: UartSink,
+}
+
+impl Semihost {
+    pub fn new(root: PathBuf, sink: UartSink) -> Self {
+        Semihost { root, files: FileTable::default(), start: Instant::now(), sink }
+    }
+
+    /// Close all files and restart clock
+    pub fn reset(&mut self) {
+        self.files.clear();
+        self.start = Instant::now();
+    }
+
+    /// Relative path without `..` under `root`
+    fn sandbox(&self, name: &str) -> Option<PathBuf> {
+        let path = Path::new(name);
+        path.components()
+            .all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+            .then(|| self.root.join(path))
+    }
+}
+
+impl Cpu {
+    /// `ebreak` is a semihosting call if it is between the entry and exit sequence.
+    /// Sequence and pointers are physical, so it is only detected without translation
+    pub(crate) fn is_semihost_call(&self) -> bool {
+        #[cfg(feature = "c")]
+        if self.is_compress {
+            return false;
+        }
+
+        #[cfg(feature = "s")]
+        if self.mode != PrivilegeMode::Machine && !matches!(self.csrs.check_satp(self.mode), Ok(None)) {
+            return false;
+        }
+
+        let word = |addr: Xlen| self.guest_read(addr, 4).ok()
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        let pc = self.pc.get();
+
+        self.semihost.is_some()
+            && word(pc.wrapping_sub(4)) == Some(SEMIHOST_ENTRY)
+            && word(pc.wrapping_add(4)) == Some(SEMIHOST_EXIT)
+    }
+
+    /// Operation `a0` with parameter `a1`, result is written to `a0`. Exit set machine halt
+    pub(crate) fn semihost_call(&mut self) {
+        let param = self.regs[A1];
+
+        let ret = match self.regs[A0] {
+            SYS_OPEN      => self.semi_open(param),
+            SYS_CLOSE     => self.semi_close(param),
+            SYS_WRITEC    => self.guest_read(param, 1).map(|ch| self.semi_console(&ch)),
+            SYS_WRITE0    => self.guest_cstr(param).map(|s| self.semi_console(s.as_bytes())),
+            SYS_WRITE     => self.semi_write(param),
+            SYS_READ      => self.semi_read(param),
+            SYS_CLOCK     => Ok(self.semi_elapsed().as_millis() as Xlen / 10),
+            SYS_ELAPSED   => {
+                let ticks = self.semi_elapsed().as_micros() as u64;
+                self.guest_write(param, &ticks.to_le_bytes()).map(|_| 0)
+            },
+            SYS_TICKFREQ  => Ok(TICK_FREQ),
+            SYS_EXIT if cfg!(not(feature = "rv64")) => {
+                // 32 bits `SYS_EXIT` only has reason
+                let code = if param == ADP_STOPPED_APPLICATION_EXIT { 0 } else { 1 };
+                self.halt = Some(Halt::Exit(code));
+                return;
+            },
+            // 64 bits `SYS_EXIT` has the same block as `SYS_EXIT_EXTENDED`
+            SYS_EXIT | SYS_EXIT_EXTENDED => {
+                let code = match self.semi_args::<2>(param) {
+                    Ok([ADP_STOPPED_APPLICATION_EXIT, code]) => code as u32,
+                    _ => 1,
+                };
+                self.halt = Some(Halt::Exit(code));
+                return;
+            },
+            _ => Err(EINVAL),
+        };
+
+        self.regs.write(A0, ret.unwrap_or(Xlen::MAX));
+    }
+
+    /// Parameter block of XLEN wide fields
+    fn semi_args<const N: usize>(&self, addr: Xlen) -> StdResult<[Xlen; N], i32> {
+        let bytes = self.guest_read(addr, (N * size_of::<Xlen>()) as Xlen)?;
+        let mut args = [0; N];
+        for (arg, word) in args.iter_mut().zip(bytes.chunks(size_of::<Xlen>())) {
+            // Safe: Chunk is XLEN bytes
+            *arg = Xlen::from_le_bytes(word.try_into().unwrap());
+        }
+        Ok(args)
+    }
+
+    fn semi_console(&self, data: &[u8]) -> Xlen {
+        // Safe: Only called when semihosting is enabled
+        self.semihost.as_ref().unwrap().sink.write_bytes(data);
+        0
+    }
+
+    fn semi_elapsed(&self) -> std::time::Duration {
+        // Safe: Only called when semihosting is enabled
+        self.semihost.as_ref().unwrap().start.elapsed()
+    }
+
+    /// `{ name, mode, len }`, mode is index of `r, rb, r+, r+b, w, wb, w+, w+b, a, ab, a+, a+b`
+    fn semi_open(&mut self, param: Xlen) -> StdResult<Xlen, i32> {
+        let [name, mode, len] = self.semi_args::<3>(param)?;
+        let name = String::from_utf8_lossy(&self.guest_read(name, len.min(IO_LIMIT))?).into_owned();
+
+        if name == ":tt" {
+            return Ok(if mode < 4 { HANDLE_STDIN } else { HANDLE_STDOUT });
+        }
+
+        // Safe: Only called when semihosting is enabled
+        let semihost = self.semihost.as_ref().unwrap();
+        let path = semihost.sandbox(&name).ok_or(EINVAL)?;
+        let mut options = OpenOptions::new();
+        match mode / 2 {
+            0 => options.read(true),
+            1 => options.read(true).write(true),
+            2 => options.write(true).create(true).truncate(true),
+            3 => options.read(true).write(true).create(true).truncate(true),
+            4 => options.append(true).create(true),
+            5 => options.read(true).append(true).create(true),
+            _ => return Err(EINVAL),
+        };
+        let file = options.open(path).map_err(|_| EINVAL)?;
+        Ok(semihost.files.open(file))
+    }
+
+    fn semi_close(&mut self, param: Xlen) -> StdResult<Xlen, i32> {
+        let [handle] = self.semi_args::<1>(param)?;
+        // Safe: Only called when semihosting is enabled
+        match handle {
+            HANDLE_STDIN | HANDLE_STDOUT => Ok(0),
+            _ if self.semihost.as_ref().unwrap().files.close(handle) => Ok(0),
+            _ => Err(EINVAL),
+        }
+    }
+
+    /// `{ handle, buf, len }`, return bytes NOT written
+    fn semi_write(&mut self, param: Xlen) -> StdResult<Xlen, i32> {
+        let [handle, buf, len] = self.semi_args::<3>(param)?;
+        let Ok(data) = self.guest_read(buf, len.min(IO_LIMIT)) else {
+            return Ok(len);
+        };
+
+        // Safe: Only called when semihosting is enabled
+        let semihost = self.semihost.as_ref().unwrap();
+        let written = match handle {
+            HANDLE_STDIN  => 0,
+            HANDLE_STDOUT => {
+                semihost.sink.write_bytes(&data);
+                data.len()
+            },
+            _ => semihost.files.with(handle, |file| file.write(&data)).unwrap_or(0),
+        };
+        Ok(len - written as Xlen)
+    }
+
+    /// `{ handle, buf, len }`, return bytes NOT read. Equal to `len` means end of file
+    fn semi_read(&mut self, param: Xlen) -> StdResult<Xlen, i32> {
+        let [handle, buf, len] = self.semi_args::<3>(param)?;
+        let mut data = vec![0; len.min(IO_LIMIT) as usize];
+
+        // Safe: Only called when semihosting is enabled
+        let files = self.semihost.as_ref().unwrap().files.clone();
+        let read = match handle {
+            HANDLE_STDIN  => io::stdin().read(&mut data).unwrap_or(0),
+            HANDLE_STDOUT => 0,
+            _ => files.with(handle, |file| file.read(&mut data)).unwrap_or(0),
+        };
+
+        match self.guest_write(buf, &data[..read]) {
+            Ok(_)  => Ok(len - read as Xlen),
+            Err(_) => Ok(len),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{Halt, StdResult};
+use crate::core::cpu::Cpu;
+#[cfg(feature = "zicsr")]
+use crate::core::PrivilegeMode;
+use crate::error::SnapshotError;
+use crate::snapshot::{self, Reader, Snapshot, Writer, MAGIC, VERSION};
+
+impl Snapshot for Cpu {
+    fn save(&self, w: &mut Writer) {
+        self.regs.save(w);
+        #[cfg(feature = "f")]
+        self.fregs.save(w);
+        self.pc.save(w);
+        #[cfg(feature = "zicsr")] {
+            w.u8(self.mode as u8);
+            self.csrs.save(w);
+        }
+        #[cfg(feature = "s")]
+        self.mmu.save(w);
+        #[cfg(feature = "a")]
+        w.option_xlen(self.reservation);
+        w.option_u32(self.halt.map(|Halt::Exit(code)| code));
+        self.bus.save(w);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        self.regs.restore(r)?;
+        #[cfg(feature = "f")]
+        self.fregs.restore(r)?;
+        self.pc.restore(r)?;
+        #[cfg(feature = "zicsr")] {
+            self.mode = match r.u8()? {
+                0 => PrivilegeMode::User,
+                #[cfg(feature = "s")]
+                1 => PrivilegeMode::Supervisor,
+                3 => PrivilegeMode::Machine,
+                _ => return Err(SnapshotError::Corrupted),
+            };
+            self.csrs.restore(r)?;
+        }
+        #[cfg(feature = "s")]
+        self.mmu.restore(r)?;
+        #[cfg(feature = "a")] {
+            self.reservation = r.option_xlen()?;
+        }
+        self.halt = r.option_u32()?.map(Halt::Exit);
+        self.bus.restore(r)
+    }
+}
+
+impl Cpu {
+    /// Save registers, FP registers, PC, CSRs, MMU, CLINT/PLIC/UART registers and populated RAM pages into `path`
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> StdResult<(), SnapshotError> {
+        let mut w = Writer::default();
+        w.bytes(MAGIC);
+        w.u32(VERSION);
+        w.u32(snapshot::extensions());
+        self.save(&mut w);
+        fs::write(path, w.buf)?;
+        Ok(())
+    }
+
+    /// Restore machine from snapshot saved by `save_snapshot`. Machine need the same extensions,
+    /// RAM size, hart id and reset PC, and is not changed if snapshot is invalid. Step history is cleared
+    pub fn load_snapshot(&mut self, path: impl AsRef<Path>) -> StdResult<(), SnapshotError> {
+        let data = fs::read(path)?;
+        let mut r = Reader::new(&data);
+        if r.bytes(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        match r.u32()? {
+            VERSION => {},
+            version => return Err(SnapshotError::Version(version)),
+        }
+        if r.u32()? != snapshot::extensions() {
+            return Err(SnapshotError::Extensions);
+        }
+
+        let mut mach = self.clone();
+        mach.restore(&mut r)?;
+        r.finish()?;
+
+        if let Some(history) = mach.history.as_mut() {
+            history.clear();
+        }
+        *self = mach;
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use riscv_loader::LoadInfo;
+
+use crate::{Exception, Halt, RiscVError, SXlen, StdResult, UartSink, Xlen};
+use crate::core::cpu::Cpu;
+use crate::device::memory::PAGE_SIZE;
+use super::host::{EBADF, EINVAL, FileTable, IO_LIMIT, errno};
+
+const SP: u8 = 2;
+const A0: u8 = 10;
+const A7: u8 = 17;
+
+const SYS_IOCTL: Xlen = 29;
+const SYS_OPENAT: Xlen = 56;
+const SYS_CLOSE: Xlen = 57;
+const SYS_LSEEK: Xlen = 62;
+const SYS_READ: Xlen = 63;
+const SYS_WRITE: Xlen = 64;
+const SYS_WRITEV: Xlen = 66;
+const SYS_FSTAT: Xlen = 80;
+const SYS_EXIT: Xlen = 93;
+const SYS_EXIT_GROUP: Xlen = 94;
+const SYS_SET_TID_ADDRESS: Xlen = 96;
+const SYS_CLOCK_GETTIME: Xlen = 113;
+const SYS_GETTIMEOFDAY: Xlen = 169;
+const SYS_GETPID: Xlen = 172;
+const SYS_BRK: Xlen = 214;
+const SYS_CLOCK_GETTIME64: Xlen = 403;
+
+const ENOTTY: i32 = 25;
+const ESPIPE: i32 = 29;
+const ENOSYS: i32 = 38;
+
+const AT_FDCWD: Xlen = -100i32 as Xlen;
+const O_ACCMODE: Xlen = 0o3;
+const O_CREAT: Xlen = 0o100;
+const O_EXCL: Xlen = 0o200;
+const O_TRUNC: Xlen = 0o1000;
+const O_APPEND: Xlen = 0o2000;
+
+const S_IFCHR: u32 = 0o020000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+/// Size of newlib's `struct kernel_stat`
+const STAT_SIZE: usize = 128;
+
+/// `argv`, `envp` and `auxv` entries are XLEN wide
+const WORD: usize = size_of::<Xlen>();
+
+const AT_NULL: Xlen = 0;
+const AT_PAGESZ: Xlen = 6;
+
+/// Linux user-mode syscall emulation for statically linked newlib / musl program.
+/// `ecall` is serviced by host instead of trapping, guest pointers are physical address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Syscall {
+    /// `argv` of guest program
+    args: Vec<String>,
+    brk_start: Xlen,
+    brk: Xlen,
+    files: FileTable,
+    /// stdout and stderr go to UART's sink
+    sink: UartSink,
+}
+
+impl Syscall {
+    pub fn new(args: Vec<String>, sink: UartSink) -> Self {
+        Syscall { args, brk_start: 0, brk: 0, files: FileTable::default(), sink }
+    }
+
+    /// Close all files and restore program break
+    pub fn reset(&mut self) {
+        self.files.clear();
+        self.brk = self.brk_start;
+    }
+}
+
+fn now() -> (u64, u32) {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    (time.as_secs(), time.subsec_nanos())
+}
+
+impl Cpu {
+    /// Place program break after loaded segments, and push `argc`, `argv`, `envp` and `auxv`
+    /// at the top of RAM like Linux does
+    pub(crate) fn user_setup(&mut self, info: &LoadInfo) -> StdResult<(), RiscVError> {
+        let segments = info.code.iter()
+            .chain(info.data.iter().flatten())
+            .chain(info.other.iter().flatten())
+            .map(|(bytes, addr)| *addr as u64 + bytes.len() as u64);
+        let bss = info.bss.map(|(start, size)| start as u64 + size as u64);
+        let end = segments.chain(bss).max().unwrap_or_default();
+
+        let Some(sys) = self.syscall.as_mut() else {
+            return Ok(());
+        };
+        // No firmware to enable FP unit
+        #[cfg(feature = "f")]
+        self.csrs.set_fs_initial();
+        sys.brk_start = end.next_multiple_of(PAGE_SIZE as u64).min(Xlen::MAX as u64) as Xlen;
+        sys.brk = sys.brk_start;
+        let args = sys.args.clone();
+
+        let (ram_size, ram_base, _) = self.bus.ram_info();
+        let mut addr = (ram_base as u64 + ram_size as u64).min((Xlen::MAX as u64).saturating_add(1));
+        let mut argv = Vec::new();
+        for arg in args.iter() {
+            addr -= arg.len() as u64 + 1;
+            self.load(addr as Xlen, &[arg.as_bytes(), &[0]].concat())?;
+            argv.push(addr as Xlen);
+        }
+
+        let mut words = vec![argv.len() as Xlen];
+        words.extend(argv);
+        // End of argv, empty envp and auxv
+        words.extend([0, 0, AT_PAGESZ, PAGE_SIZE as Xlen, AT_NULL, 0]);
+
+        let sp = (addr - (words.len() * WORD) as u64) & !0xf;
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        self.load(sp as Xlen, &bytes)?;
+        self.regs.write(SP, sp as Xlen);
+        Ok(())
+    }
+
+    /// `ecall` is serviced as syscall when user-mode emulation is enabled
+    pub(crate) fn is_syscall(&self, except: &Exception) -> bool {
+        #[cfg(not(feature = "zicsr"))]
+        let is_ecall = matches!(except, Exception::Ecall);
+        #[cfg(feature = "zicsr")]
+        let is_ecall = matches!(except,
+            Exception::EnvironmentCallFromUMode |
+            Exception::EnvironmentCallFromSMode |
+            Exception::EnvironmentCallFromMMode);
+
+        self.syscall.is_some() && is_ecall
+    }
+
+    /// Service syscall `a7` with arguments `a0..a3`, result is written to `a0`. Return halt reason if guest exit
+    pub(crate) fn syscall(&mut self) -> Option<Halt> {
+        let [a0, a1, a2, a3] = [10, 11, 12, 13].map(|id| self.regs[id]);
+
+        let ret = match self.regs[A7] {
+            SYS_EXIT | SYS_EXIT_GROUP => return Some(Halt::Exit(a0 as u32 & 0xff)),
+            SYS_READ            => self.sys_read(a0, a1, a2),
+            SYS_WRITE           => self.sys_write(a0, a1, a2),
+            SYS_WRITEV          => self.sys_writev(a0, a1, a2),
+            SYS_OPENAT          => self.sys_openat(a0, a1, a2, a3),
+            SYS_CLOSE           => self.sys_close(a0),
+            SYS_LSEEK           => self.sys_lseek(a0, a1, a2),
+            SYS_FSTAT           => self.sys_fstat(a0, a1),
+            SYS_BRK             => Ok(self.sys_brk(a0)),
+            SYS_CLOCK_GETTIME |
+            SYS_CLOCK_GETTIME64 => self.sys_time(a1, 1),
+            SYS_GETTIMEOFDAY    => self.sys_time(a0, 1000),
+            SYS_IOCTL           => Err(ENOTTY),
+            SYS_SET_TID_ADDRESS |
+            SYS_GETPID          => Ok(1),
+            _                   => Err(ENOSYS),
+        };
+
+        self.regs.write(A0, ret.unwrap_or_else(|e| e.wrapping_neg() as Xlen));
+        None
+    }
+
+    fn sys_read(&mut self, fd: Xlen, buf: Xlen, len: Xlen) -> StdResult<Xlen, i32> {
+        let mut data = vec![0; len.min(IO_LIMIT) as usize];
+        let files = self.syscall_files();
+        let n = match fd {
+            0     => io::stdin().read(&mut data).map_err(errno)?,
+            1 | 2 => return Err(EBADF),
+            _     => files.with(fd, |file| file.read(&mut data))?,
+        };
+        self.guest_write(buf, &data[..n])?;
+        Ok(n as Xlen)
+    }
+
+    fn sys_write(&mut self, fd: Xlen, buf: Xlen, len: Xlen) -> StdResult<Xlen, i32> {
+        let data = self.guest_read(buf, len.min(IO_LIMIT))?;
+        match fd {
+            0     => Err(EBADF),
+            1 | 2 => {
+                // Safe: Only called when emulation is enabled
+                self.syscall.as_ref().unwrap().sink.write_bytes(&data);
+                Ok(data.len() as Xlen)
+            },
+            _     => self.syscall_files().with(fd, |file| file.write(&data)).map(|n| n as Xlen),
+        }
+    }
+
+    /// `iov` is array of `{ base, len }`
+    fn sys_writev(&mut self, fd: Xlen, iov: Xlen, count: Xlen) -> StdResult<Xlen, i32> {
+        let entry_size = 2 * WORD as Xlen;
+        let mut total = 0;
+        for i in 0..count {
+            let entry = self.guest_read(iov.wrapping_add(i * entry_size), entry_size)?;
+            // Safe: Entry is 2 words
+            let base = Xlen::from_le_bytes(entry[..WORD].try_into().unwrap());
+            let len = Xlen::from_le_bytes(entry[WORD..].try_into().unwrap());
+            total += self.sys_write(fd, base, len)?;
+        }
+        Ok(total)
+    }
+
+    fn sys_openat(&mut self, dirfd: Xlen, path: Xlen, flags: Xlen, _mode: Xlen) -> StdResult<Xlen, i32> {
+        let path = self.guest_cstr(path)?;
+        // Only relative to host's current directory
+        if dirfd != AT_FDCWD && !path.starts_with('/') {
+            return Err(EBADF);
+        }
+
+        let access = flags & O_ACCMODE;
+        let file = OpenOptions::new()
+            .read(access != 1)
+            .write(access != 0)
+            .append(flags & O_APPEND != 0)
+            .truncate(flags & O_TRUNC != 0)
+            .create(flags & O_CREAT != 0)
+            .create_new(flags & O_CREAT != 0 && flags & O_EXCL != 0)
+            .open(path)
+            .map_err(errno)?;
+
+        Ok(self.syscall_files().open(file))
+    }
+
+    fn sys_close(&mut self, fd: Xlen) -> StdResult<Xlen, i32> {
+        match fd {
+            0..=2 => Ok(0),
+            _ if self.syscall_files().close(fd) => Ok(0),
+            _     => Err(EBADF),
+        }
+    }
+
+    /// newlib's `lseek(fd, offset, whence)`
+    fn sys_lseek(&mut self, fd: Xlen, offset: Xlen, whence: Xlen) -> StdResult<Xlen, i32> {
+        let pos = match whence {
+            0 => SeekFrom::Start(offset as u64),
+            1 => SeekFrom::Current(offset as SXlen as i64),
+            2 => SeekFrom::End(offset as SXlen as i64),
+            _ => return Err(EINVAL),
+        };
+        match fd {
+            0..=2 => Err(ESPIPE),
+            _     => self.syscall_files().with(fd, |file| file.seek(pos)).map(|pos| pos as Xlen),
+        }
+    }
+
+    /// Only `st_mode`, `st_nlink`, `st_size`, `st_blksize` and `st_blocks` are filled
+    fn sys_fstat(&mut self, fd: Xlen, buf: Xlen) -> StdResult<Xlen, i32> {
+        let (mode, size) = match fd {
+            0..=2 => (S_IFCHR | 0o620, 0),
+            _     => {
+                let meta = self.syscall_files().with(fd, |file| file.metadata())?;
+                let mode = if meta.is_dir() { S_IFDIR | 0o755 } else { S_IFREG | 0o644 };
+                (mode, meta.len())
+            },
+        };
+
+        let mut stat = [0; STAT_SIZE];
+        stat[16..20].copy_from_slice(&mode.to_le_bytes());
+        stat[20..24].copy_from_slice(&1u32.to_le_bytes());
+        stat[48..56].copy_from_slice(&size.to_le_bytes());
+        stat[56..60].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+        stat[64..72].copy_from_slice(&size.div_ceil(512).to_le_bytes());
+        self.guest_write(buf, &stat)?;
+        Ok(0)
+    }
+
+    /// Return current break if `addr` is out of RAM or before program's end
+    fn sys_brk(&mut self, addr: Xlen) -> Xlen {
+        let (ram_size, ram_base, _) = self.bus.ram_info();
+        let ram_end = ram_base as u64 + ram_size as u64;
+        // Safe: Only called when emulation is enabled
+        let sys = self.syscall.as_mut().unwrap();
+        if addr >= sys.brk_start && (addr as u64) < ram_end {
+            sys.brk = addr;
+        }
+        sys.brk
+    }
+
+    /// Write 64 bits `{ sec, nsec / div }` to `buf`. All clocks are real time
+    fn sys_time(&mut self, buf: Xlen, div: u32) -> StdResult<Xlen, i32> {
+        let (sec, nsec) = now();
+        let bytes = [sec.to_le_bytes(), ((nsec / div) as u64).to_le_bytes()].concat();
+        self.guest_write(buf, &bytes)?;
+        Ok(0)
+    }
+
+    fn syscall_files(&self) -> FileTable {
+        // Safe: Only called when emulation is enabled
+        self.syscall.as_ref().unwrap().files.clone()
+    }
+}
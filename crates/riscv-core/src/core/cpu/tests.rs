@@ -5,6 +5,8 @@
 use crate::core::access::{Access, AccessType};
 use crate::core::cpu::Cpu;
 use crate::constance::DRAM_BASE_ADDR;
+#[cfg(feature = "zicsr")] use crate::Xlen;
+#[cfg(feature = "zicsr")] use crate::trap::INTERRUPT_MASK;
 
 
 #[test]
@@ -102,12 +104,123 @@ fn test_exception_trap_handling() {
     assert_eq!(mepc, DRAM_BASE_ADDR, "mepc wrong");
 }
 
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_clint_timer_interrupt() {
+    use crate::{Interrupt, StepEvent, Trap};
+    use crate::device::bus::CLINT_BASE;
+
+    let mut cpu = Cpu::default();
+
+    // nop * 4
+    let code: Vec<u8> = 0x00000013u32.to_le_bytes().repeat(4);
+    cpu.load(DRAM_BASE_ADDR, &code).unwrap();
+
+    let handler_base = DRAM_BASE_ADDR + 0x100;
+    cpu.csrs.write(0x305, handler_base, PrivilegeMode::Machine, 0).unwrap();
+    // mie.MTIE, mstatus.MIE
+    cpu.csrs.write(0x304, 1 << 7, PrivilegeMode::Machine, 0).unwrap();
+    cpu.csrs.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+
+    // mtimecmp = 2
+    cpu.load(CLINT_BASE + 0x4000, &2u64.to_le_bytes()).unwrap();
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc.get(), DRAM_BASE_ADDR + 4, "Timer should not pending yet");
+
+    let trap = cpu.step().unwrap();
+    assert_eq!(trap, Some(StepEvent::Trap(Trap::Interrupt(Interrupt::MachineTimer))), "Interrupt should be reported");
+    assert_eq!(cpu.pc.get(), handler_base, "Did not trap to mtvec");
+
+    let mcause = cpu.csrs.read(0x342, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(mcause, INTERRUPT_MASK | 7, "mcause wrong");
+
+    let mepc = cpu.csrs.read(0x341, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(mepc, DRAM_BASE_ADDR + 4, "mepc should point to the interrupted instruction");
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_plic_external_interrupt() {
+    use crate::{Interrupt, StepEvent, Trap};
+    use crate::device::bus::PLIC_BASE;
+
+    let mut cpu = Cpu::default();
+
+    // nop * 4
+    let code: Vec<u8> = 0x00000013u32.to_le_bytes().repeat(4);
+    cpu.load(DRAM_BASE_ADDR, &code).unwrap();
+
+    let handler_base = DRAM_BASE_ADDR + 0x100;
+    cpu.csrs.write(0x305, handler_base, PrivilegeMode::Machine, 0).unwrap();
+    // mie.MEIE, mstatus.MIE
+    cpu.csrs.write(0x304, 1 << 11, PrivilegeMode::Machine, 0).unwrap();
+    cpu.csrs.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+
+    // Source 10: priority 1, enabled for M-Mode context
+    cpu.load(PLIC_BASE + 4 * 10, &1u32.to_le_bytes()).unwrap();
+    cpu.load(PLIC_BASE + 0x2000, &(1u32 << 10).to_le_bytes()).unwrap();
+
+    assert_eq!(cpu.step().unwrap(), None);
+
+    cpu.bus.set_irq(10, true);
+    let trap = cpu.step().unwrap();
+    assert_eq!(trap, Some(StepEvent::Trap(Trap::Interrupt(Interrupt::MachineExternal))));
+    assert_eq!(cpu.pc.get(), handler_base, "Did not trap to mtvec");
+
+    let mcause = cpu.csrs.read(0x342, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(mcause, INTERRUPT_MASK | 11, "mcause wrong");
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_uart_rx_interrupt() {
+    use crate::{Interrupt, StepEvent, Trap};
+    use crate::device::bus::{PLIC_BASE, UART_BASE, UART_IRQ};
+
+    let mut cpu = Cpu::default();
+
+    // nop * 4
+    let code: Vec<u8> = 0x00000013u32.to_le_bytes().repeat(4);
+    cpu.load(DRAM_BASE_ADDR, &code).unwrap();
+
+    let handler: Vec<u8> = [
+        0x0c2002b7, // lui t0, 0xc200
+        0x0042a503, // lw  a0, 4(t0)
+        0x10000337, // lui t1, 0x10000
+        0x00034583, // lbu a1, 0(t1)
+        0x00a2a223, // sw  a0, 4(t0)
+    ].iter().flat_map(|ins: &u32| ins.to_le_bytes()).collect();
+    let handler_base = DRAM_BASE_ADDR + 0x100;
+    cpu.load(handler_base, &handler).unwrap();
+    cpu.csrs.write(0x305, handler_base, PrivilegeMode::Machine, 0).unwrap();
+
+    // mie.MEIE, mstatus.MIE
+    cpu.csrs.write(0x304, 1 << 11, PrivilegeMode::Machine, 0).unwrap();
+    cpu.csrs.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+    cpu.load(PLIC_BASE + 4 * UART_IRQ as Xlen, &1u32.to_le_bytes()).unwrap();
+    cpu.load(PLIC_BASE + 0x2000, &(1u32 << UART_IRQ).to_le_bytes()).unwrap();
+    // IER.ERBFI
+    cpu.load(UART_BASE + 1, &[1]).unwrap();
+
+    assert_eq!(cpu.step().unwrap(), None);
+
+    cpu.uart_input(b"k");
+    assert_eq!(cpu.step().unwrap(), Some(StepEvent::Trap(Trap::Interrupt(Interrupt::MachineExternal))));
+    assert_eq!(cpu.pc.get(), handler_base);
+
+    for _ in 0..5 {
+        assert_eq!(cpu.step().unwrap(), None);
+    }
+    assert_eq!(cpu.regs[10], UART_IRQ as Xlen, "Claim UART source");
+    assert_eq!(cpu.regs[11], b'k' as Xlen);
+    assert!(!cpu.bus.plic_pending().0, "RX FIFO is empty after complete");
+}
+
 #[test]
 #[cfg(feature = "s")]
 fn test_sfence_vma() {
-    let mut cpu = Cpu::default();
-    
-    cpu.mode = PrivilegeMode::Supervisor;
+    let mut cpu = Cpu { mode: PrivilegeMode::Supervisor, ..Default::default() };
     
     // sfence.vma x10, x11
     let raw = 0x12a58073;
@@ -130,7 +243,7 @@ fn test_sfence_vma() {
 
     assert!(res.is_ok());
     let next_pc_manual = res.unwrap();
-    assert_eq!(next_pc_manual, false);
+    assert!(!next_pc_manual);
 
     cpu.mode = PrivilegeMode::User;
     let res_err = if let Instruction::Privileged(op, data) = ins {
@@ -142,4 +255,71 @@ fn test_sfence_vma() {
         Err(Exception::IllegalInstruction(_)) => (),
         _ => panic!(""),
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_htif_exit() {
+    use std::collections::HashMap;
+    use riscv_loader::LoadInfo;
+    use crate::{Halt, StepEvent};
+    use crate::debug::DebugInterface;
+
+    let tohost = DRAM_BASE_ADDR + 0x1000;
+    let code: Vec<u8> = [
+        0x00001297u32, // auipc t0, 1
+        0x00b00313,    // addi t1, zero, 11
+        0x0062a023,    // sw   t1, 0(t0)
+        0x0002a223,    // sw   zero, 4(t0)
+    ].iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    let info = LoadInfo {
+        pc_entry: DRAM_BASE_ADDR,
+        code: vec![(code, DRAM_BASE_ADDR)],
+        symbols: Some(HashMap::from([(tohost, "tohost".to_string())])),
+        ..Default::default()
+    };
+
+    let mut cpu = Cpu::default();
+    cpu.load_info(&info).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(cpu.step().unwrap(), None, "Low word only should not exit");
+    }
+    assert_eq!(cpu.step().unwrap(), Some(StepEvent::Halt(Halt::Exit(5))));
+    assert_eq!(cpu.inspect_bus(tohost, 8), vec![0; 8], "tohost is cleared");
+
+    let pc = cpu.pc.get();
+    assert_eq!(cpu.step().unwrap(), Some(StepEvent::Halt(Halt::Exit(5))), "Stay halted");
+    assert_eq!(cpu.pc.get(), pc);
+
+    cpu.reset();
+    assert_eq!(cpu.halted(), None);
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_history_counters() {
+    use crate::config::MachineConfig;
+
+    let mut cpu = Cpu::new(MachineConfig::default().with_history(4)).unwrap();
+    let code: Vec<u8> = [
+        0x00100093u32, // addi  x1, zero, 1
+        0x34009073,    // csrw  mscratch, x1
+    ].iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    cpu.load(DRAM_BASE_ADDR, &code).unwrap();
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+
+    let history = cpu.history.as_mut().unwrap();
+    let delta = history.back().unwrap();
+    assert!(delta.csrs.is_some(), "csrw changes mscratch");
+    let delta = history.back().unwrap();
+    assert!(delta.csrs.is_none(), "Counters only are not a CSR file copy");
+    assert_eq!((delta.counters.0.mcycle, delta.counters.1.mcycle), (0, 1));
+    history.forward();
+    history.forward();
+
+    assert!(cpu.step_back());
+    assert!(cpu.step_back());
+    assert_eq!(cpu.csrs.read(0xb00, PrivilegeMode::Machine, 0), Ok(0), "mcycle is restored");
+    assert!(cpu.step_forward());
+    assert_eq!(cpu.csrs.read(0xb00, PrivilegeMode::Machine, 0), Ok(1));
+}
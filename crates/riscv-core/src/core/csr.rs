@@ -1,56 +1,200 @@
 mod addr;
+mod counter;
 mod mstatus;
 mod pmpcfg;
 #[cfg(feature = "s")]
 mod satp;
+mod trigger;
 
-use crate::{Exception, Result};
-use crate::core::access::{Access, Physical};
+use crate::{Exception, Interrupt, Result, StdResult, Trap, Xlen};
+use crate::core::access::{Access, Physical, Virtual};
+#[cfg(feature = "s")]
+use crate::core::mmu::PagingMode;
 use crate::core::privilege::PrivilegeMode;
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
 
 use addr::CsrAddr;
+pub(crate) use counter::{Counters, HpmEvent};
 use mstatus::Mstatus;
 use pmpcfg::Pmpcfg;
 #[cfg(feature = "s")]
 use satp::Satp;
+use trigger::{Tcontrol, Trigger, TINFO, TRIGGER_NUM};
 
 pub(super) const PMPCFG_NUM: usize = 4;
+/// `pmpcfg` CSRs number step, odd ones are not exist on RV64
+#[cfg(not(feature = "rv64"))]
+pub(super) const PMPCFG_STEP: usize = 1;
+#[cfg(feature = "rv64")]
+pub(super) const PMPCFG_STEP: usize = 2;
+/// `pmpaddr` hold bits 2..=33 of address on RV32 and bits 2..=55 on RV64
+#[cfg(not(feature = "rv64"))]
+const PMPADDR_MASK: Xlen = Xlen::MAX;
+#[cfg(feature = "rv64")]
+const PMPADDR_MASK: Xlen = (1 << 54) - 1;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct CsrFile {
-    #[cfg(feature = "s")] stvec: u32,
-    #[cfg(feature = "s")] sepc: u32,
-    #[cfg(feature = "s")] scause: u32,
-    #[cfg(feature = "s")] sscratch: u32,
-    #[cfg(feature = "s")] stval: u32,
+    #[cfg(feature = "s")] stvec: Xlen,
+    #[cfg(feature = "s")] sepc: Xlen,
+    #[cfg(feature = "s")] scause: Xlen,
+    #[cfg(feature = "s")] sscratch: Xlen,
+    #[cfg(feature = "s")] stval: Xlen,
     #[cfg(feature = "s")] satp: Satp,
 
     mstatus: Mstatus,
-    #[cfg(feature = "s")] medeleg: u32,
-    #[cfg(feature = "s")] mideleg: u32,
-    mie: u32,
-    mtvec: u32,
-    mscratch: u32,
-    mepc: u32,
-    mcause: u32,
-    mtval: u32,
-    mip: u32,
+    #[cfg(feature = "s")] medeleg: Xlen,
+    #[cfg(feature = "s")] mideleg: Xlen,
+    mie: Xlen,
+    mtvec: Xlen,
+    mscratch: Xlen,
+    mepc: Xlen,
+    mcause: Xlen,
+    mtval: Xlen,
+    mip: Xlen,
 
     pmpcfg: [Pmpcfg; PMPCFG_NUM],
-    pmpaddr: [u32; PMPCFG_NUM * 4],
+    pmpaddr: [Xlen; PMPCFG_NUM * 4],
+
+    tselect: usize,
+    triggers: [Trigger; TRIGGER_NUM],
+    tcontrol: Tcontrol,
+
+    counters: Counters,
+
+    /// `frm` in bits 5..7 and `fflags` in bits 0..4
+    #[cfg(feature = "f")] fcsr: u32,
+
+    hart_id: u32,
+}
+
+impl Snapshot for CsrFile {
+    fn save(&self, w: &mut Writer) {
+        #[cfg(feature = "s")] {
+            [self.stvec, self.sepc, self.scause, self.sscratch, self.stval, self.satp.into()]
+                .into_iter().for_each(|csr| w.xlen(csr));
+            w.xlen(self.medeleg);
+            w.xlen(self.mideleg);
+        }
+        w.u32(self.mstatus.into());
+        [self.mie, self.mtvec, self.mscratch, self.mepc, self.mcause, self.mtval, self.mip]
+            .into_iter().for_each(|csr| w.xlen(csr));
+        self.pmpcfg.iter().for_each(|cfg| w.u32((*cfg).into()));
+        self.pmpaddr.iter().for_each(|addr| w.xlen(*addr));
+        w.u32(self.tselect as u32);
+        for trigger in self.triggers.iter() {
+            w.u32(trigger.tdata1.into());
+            w.xlen(trigger.tdata2);
+        }
+        w.u32(self.tcontrol.into());
+        let counters = &self.counters;
+        [counters.mcycle, counters.minstret, counters.time].into_iter().for_each(|counter| w.u64(counter));
+        counters.mhpmcounter.iter().for_each(|counter| w.u64(*counter));
+        counters.mhpmevent.iter().for_each(|event| w.u32(*event));
+        w.u32(counters.mcountinhibit);
+        w.u32(counters.mcounteren);
+        #[cfg(feature = "s")]
+        w.u32(counters.scounteren);
+        #[cfg(feature = "f")]
+        w.u32(self.fcsr);
+        w.u32(self.hart_id);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        #[cfg(feature = "s")] {
+            self.stvec = r.xlen()?;
+            self.sepc = r.xlen()?;
+            self.scause = r.xlen()?;
+            self.sscratch = r.xlen()?;
+            self.stval = r.xlen()?;
+            self.satp = r.xlen()?.into();
+            self.medeleg = r.xlen()?;
+            self.mideleg = r.xlen()?;
+        }
+        self.mstatus = r.u32()?.into();
+        self.mie = r.xlen()?;
+        self.mtvec = r.xlen()?;
+        self.mscratch = r.xlen()?;
+        self.mepc = r.xlen()?;
+        self.mcause = r.xlen()?;
+        self.mtval = r.xlen()?;
+        self.mip = r.xlen()?;
+        for cfg in self.pmpcfg.iter_mut() {
+            *cfg = r.u32()?.into();
+        }
+        for addr in self.pmpaddr.iter_mut() {
+            *addr = r.xlen()?;
+        }
+        self.tselect = r.u32()? as usize;
+        if self.tselect >= TRIGGER_NUM {
+            return Err(SnapshotError::Corrupted);
+        }
+        for trigger in self.triggers.iter_mut() {
+            trigger.tdata1 = r.u32()?.into();
+            trigger.tdata2 = r.xlen()?;
+        }
+        self.tcontrol = r.u32()?.into();
+        let counters = &mut self.counters;
+        counters.mcycle = r.u64()?;
+        counters.minstret = r.u64()?;
+        counters.time = r.u64()?;
+        for counter in counters.mhpmcounter.iter_mut() {
+            *counter = r.u64()?;
+        }
+        for event in counters.mhpmevent.iter_mut() {
+            *event = r.u32()?;
+        }
+        counters.mcountinhibit = r.u32()?;
+        counters.mcounteren = r.u32()?;
+        #[cfg(feature = "s")] {
+            counters.scounteren = r.u32()?;
+        }
+        #[cfg(feature = "f")] {
+            self.fcsr = r.u32()? & FCSR_MASK;
+        }
+        // Hart id is given by `MachineConfig`, snapshot can not change it
+        let hart_id = r.u32()?;
+        if hart_id != self.hart_id {
+            return Err(SnapshotError::HartId(hart_id));
+        }
+        Ok(())
+    }
 }
 
 const MODE_MASK: u16 = 3 << 8;
-// const INTERRUPT_MASK: u32 = 1 << 31;
+
+// External and machine level pending bits are driven by devices, 
+// only SSIP and STIP can be written
+#[cfg(feature = "s")]
+const MIP_WRITE_MASK: Xlen = (1 << 1) | (1 << 5);
+// Only SSIP is writable through `sip`
+#[cfg(feature = "s")]
+const SIP_WRITE_MASK: Xlen = 1 << 1;
+#[cfg(not(feature = "s"))]
+const MIP_WRITE_MASK: Xlen = 0;
+
+#[cfg(feature = "f")]
+const FFLAGS_MASK: u32 = 0x1f;
+#[cfg(feature = "f")]
+const FCSR_MASK: u32 = 0xff;
 
 impl CsrFile {
-    pub fn read(&mut self, addr: u16, mode: PrivilegeMode, raw: u32) -> Result<u32> {    
+    pub fn read(&mut self, addr: u16, mode: PrivilegeMode, raw: u32) -> Result<Xlen> {    
         if (mode as u16) < ((addr & MODE_MASK) >> 8) {
             Err(Exception::IllegalInstruction(raw))
         } else {
             Ok(match CsrAddr::get_csr(addr, raw)? {
                 CsrAddr::Ustatus => 0,
 
+                #[cfg(feature = "f")]
+                CsrAddr::Fflags | CsrAddr::Frm | CsrAddr::Fcsr if !self.fp_enabled() => {
+                    return Err(Exception::IllegalInstruction(raw));
+                }
+                #[cfg(feature = "f")] CsrAddr::Fflags => (self.fcsr & FFLAGS_MASK) as Xlen,
+                #[cfg(feature = "f")] CsrAddr::Frm => (self.fcsr >> 5) as Xlen,
+                #[cfg(feature = "f")] CsrAddr::Fcsr => self.fcsr as Xlen,
+
                 #[cfg(feature = "s")] CsrAddr::Sstatus => self.mstatus.read_s(),
                 #[cfg(feature = "s")] CsrAddr::Sie => self.mie & self.mideleg,
                 #[cfg(feature = "s")] CsrAddr::Stvec => self.stvec,
@@ -65,6 +209,19 @@ impl CsrFile {
                     }
                     self.satp.into()
                 }
+                #[cfg(feature = "s")] CsrAddr::Scounteren => self.counters.scounteren as Xlen,
+
+                CsrAddr::Counter(num) if !self.counters.accessible(num, mode) => {
+                    return Err(Exception::IllegalInstruction(raw));
+                }
+                #[cfg(not(feature = "rv64"))]
+                CsrAddr::CounterH(num) if !self.counters.accessible(num, mode) => {
+                    return Err(Exception::IllegalInstruction(raw));
+                }
+                CsrAddr::Counter(num) | CsrAddr::Mcounter(num) => self.counters.read(num) as Xlen,
+                #[cfg(not(feature = "rv64"))]
+                CsrAddr::CounterH(num) | CsrAddr::McounterH(num) => (self.counters.read(num) >> 32) as Xlen,
+
                 CsrAddr::Mstatus => self.mstatus.read_m(),
                 #[cfg(feature = "s")] CsrAddr::Medeleg => self.medeleg,
                 #[cfg(feature = "s")] CsrAddr::Mideleg => self.mideleg,
@@ -75,23 +232,50 @@ impl CsrFile {
                 CsrAddr::Mcause => self.mcause,
                 CsrAddr::Mtval => self.mtval,
                 CsrAddr::Mip => self.mip,
+                CsrAddr::Mcounteren => self.counters.mcounteren as Xlen,
+                CsrAddr::Mcountinhibit => self.counters.mcountinhibit as Xlen,
+                CsrAddr::Mhpmevent(num) => self.counters.read_event(num) as Xlen,
 
-                CsrAddr::Pmpcfg(num) => self.pmpcfg[num].into(),
+                CsrAddr::Pmpcfg(num) => self.read_pmpcfg(num),
                 CsrAddr::Pmpaddr(num) => self.pmpaddr[num],
 
+                CsrAddr::Tselect  => self.tselect as Xlen,
+                CsrAddr::Tdata1   => self.triggers[self.tselect].tdata1.read(),
+                CsrAddr::Tdata2   => self.triggers[self.tselect].tdata2,
+                CsrAddr::Tdata3   => 0,
+                CsrAddr::Tinfo    => TINFO as Xlen,
+                CsrAddr::Tcontrol => u32::from(self.tcontrol) as Xlen,
+
                 CsrAddr::Mnstatus => 0,
-                CsrAddr::Mhartid  => 0,
+                CsrAddr::Mhartid  => self.hart_id as Xlen,
             })
         }
     }
 
-    pub fn write(&mut self, addr: u16, data: u32, mode: PrivilegeMode, raw: u32) -> Result<()> {
+    pub fn write(&mut self, addr: u16, data: Xlen, mode: PrivilegeMode, raw: u32) -> Result<()> {
         if (mode as u16) < ((addr & MODE_MASK) >> 8) {
             Err(Exception::IllegalInstruction(addr as u32))
         } else {
             match CsrAddr::get_csr(addr, raw)? {
                 CsrAddr::Ustatus => {},
 
+                #[cfg(feature = "f")]
+                CsrAddr::Fflags | CsrAddr::Frm | CsrAddr::Fcsr if !self.fp_enabled() => {
+                    return Err(Exception::IllegalInstruction(raw));
+                }
+                #[cfg(feature = "f")] CsrAddr::Fflags => {
+                    self.fcsr = (self.fcsr & !FFLAGS_MASK) | (data as u32 & FFLAGS_MASK);
+                    self.set_fs_dirty();
+                },
+                #[cfg(feature = "f")] CsrAddr::Frm => {
+                    self.fcsr = (self.fcsr & FFLAGS_MASK) | ((data as u32 & 0x7) << 5);
+                    self.set_fs_dirty();
+                },
+                #[cfg(feature = "f")] CsrAddr::Fcsr => {
+                    self.fcsr = data as u32 & FCSR_MASK;
+                    self.set_fs_dirty();
+                },
+
                 #[cfg(feature = "s")] CsrAddr::Sstatus => self.mstatus.write_s(data),
                 #[cfg(feature = "s")] CsrAddr::Sie => self.mie = (self.mie & !self.mideleg) | (data & self.mideleg),
                 #[cfg(feature = "s")] CsrAddr::Stvec => self.stvec = data,
@@ -99,13 +283,23 @@ impl CsrFile {
                 #[cfg(feature = "s")] CsrAddr::Sepc => self.sepc = data,
                 #[cfg(feature = "s")] CsrAddr::Scause => self.scause = data,
                 #[cfg(feature = "s")] CsrAddr::Stval => self.stval = data,
-                #[cfg(feature = "s")] CsrAddr::Sip => self.mip = (self.mip & !self.mideleg) | (data & self.mideleg),
+                #[cfg(feature = "s")] CsrAddr::Sip => {
+                    let mask = self.mideleg & SIP_WRITE_MASK;
+                    self.mip = (self.mip & !mask) | (data & mask)
+                },
                 #[cfg(feature = "s")] CsrAddr::Satp => {
                     if mode == PrivilegeMode::Supervisor && self.check_tvm() {
                         return Err(Exception::IllegalInstruction(addr as u32));
                     }
-                    self.satp = data.into()
+                    self.satp.write(data)
                 }
+                #[cfg(feature = "s")] CsrAddr::Scounteren => self.counters.scounteren = Counters::write_counteren(data as u32),
+
+                // User level counters are read-only
+                CsrAddr::Counter(_) => return Err(Exception::IllegalInstruction(raw)),
+                #[cfg(not(feature = "rv64"))]
+                CsrAddr::CounterH(_) => return Err(Exception::IllegalInstruction(raw)),
+
                 CsrAddr::Mstatus => self.mstatus.write_m(data),
                 #[cfg(feature = "s")] CsrAddr::Medeleg => self.medeleg = data,
                 #[cfg(feature = "s")] CsrAddr::Mideleg => self.mideleg = data,
@@ -115,10 +309,29 @@ impl CsrFile {
                 CsrAddr::Mepc => self.mepc = data,
                 CsrAddr::Mcause => self.mcause = data,
                 CsrAddr::Mtval => self.mtval = data,
-                CsrAddr::Mip => self.mip = data,
-
-                CsrAddr::Pmpcfg(num) => self.pmpcfg[num] = data.into(),
-                CsrAddr::Pmpaddr(num) => self.pmpaddr[num] = data, 
+                CsrAddr::Mip => self.mip = (self.mip & !MIP_WRITE_MASK) | (data & MIP_WRITE_MASK),
+                CsrAddr::Mcounteren => self.counters.mcounteren = Counters::write_counteren(data as u32),
+                CsrAddr::Mcountinhibit => self.counters.write_inhibit(data as u32),
+                CsrAddr::Mhpmevent(num) => self.counters.write_event(num, data as u32),
+                #[cfg(not(feature = "rv64"))]
+                CsrAddr::Mcounter(num) => self.counters.write(num, data, false),
+                #[cfg(not(feature = "rv64"))]
+                CsrAddr::McounterH(num) => self.counters.write(num, data, true),
+                #[cfg(feature = "rv64")]
+                CsrAddr::Mcounter(num) => self.counters.write(num, data),
+
+                CsrAddr::Pmpcfg(num) => self.write_pmpcfg(num, data),
+                CsrAddr::Pmpaddr(num) => self.pmpaddr[num] = data & PMPADDR_MASK, 
+
+                // Unimplemented trigger is WARL to current one
+                CsrAddr::Tselect  => if data < TRIGGER_NUM as Xlen {
+                    self.tselect = data as usize
+                },
+                CsrAddr::Tdata1   => self.triggers[self.tselect].tdata1.write(data),
+                CsrAddr::Tdata2   => self.triggers[self.tselect].tdata2 = data,
+                CsrAddr::Tdata3   => {},
+                CsrAddr::Tinfo    => {},
+                CsrAddr::Tcontrol => self.tcontrol.write(data as u32),
 
                 CsrAddr::Mnstatus => {},
                 CsrAddr::Mhartid  => return Err(Exception::IllegalInstruction(raw)),
@@ -127,12 +340,19 @@ impl CsrFile {
         }
     }
 
-    pub fn trap_entry(&mut self, curr_pc: u32, except_code: Exception, mode: PrivilegeMode) -> (PrivilegeMode, u32) {
+    pub fn trap_entry(&mut self, curr_pc: Xlen, trap: impl Into<Trap>, mode: PrivilegeMode) -> (PrivilegeMode, Xlen) {
+        let trap = trap.into();
+        self.counters.count(HpmEvent::Trap, 1);
+
+        // Exceptions are delegated by `medeleg`, interrupts by `mideleg`
+        #[cfg(feature = "s")]
+        let deleg = if trap.is_interrupt() { self.mideleg } else { self.medeleg };
+
         let target_mode = match mode {
             PrivilegeMode::Machine => PrivilegeMode::Machine,
             #[cfg(feature = "s")]
             PrivilegeMode::Supervisor | PrivilegeMode::User => {
-                if self.medeleg & (1 << (u32::from(except_code))) > 0 {
+                if deleg & (1 << trap.code()) > 0 {
                     PrivilegeMode::Supervisor
                 } else {
                     PrivilegeMode::Machine
@@ -142,30 +362,30 @@ impl CsrFile {
             PrivilegeMode::User => PrivilegeMode::Machine
         };
 
-        let tval = match except_code {
-            Exception::IllegalInstruction(raw) => raw,
-            Exception::LoadAccessFault(addr) |
-            Exception::StoreOrAmoAccessFault(addr) |
-            Exception::InstructionAccessFault(addr) | 
-            Exception::LoadPageFault(addr) |
-            Exception::StoreOrAmoPageFault(addr) |
-            Exception::InstructionPageFault(addr)
-                => addr,
-            _   => 0,
+        // Vectored mode only apply offset to interrupt
+        let offset = if trap.is_interrupt() {
+            4 * trap.code() as Xlen
+        } else {
+            0
         };
-   
+        let cause = trap.into();
+        let tval = trap.tval();
+
         match target_mode {
             PrivilegeMode::Machine => {
                 self.mepc = curr_pc;
-                self.mcause = except_code.into();
+                self.mcause = cause;
                 self.mtval = tval;
                 self.mstatus.set_mpie(self.mstatus.mie());
                 self.mstatus.set_mie(0);
                 self.mstatus.set_mpp(mode as u8);
+                // Disable M-mode triggers in handler
+                self.tcontrol.set_mpte(self.tcontrol.mte());
+                self.tcontrol.set_mte(0);
                 let base_addr = self.mtvec & !0b11;
                 (target_mode,
                 if self.mtvec & 0b11 == 0b01 {
-                    base_addr + 4 * u32::from(except_code)
+                    base_addr + offset
                 } else {
                     base_addr
                 })
@@ -173,7 +393,7 @@ impl CsrFile {
             #[cfg(feature = "s")]
             PrivilegeMode::Supervisor => {
                 self.sepc = curr_pc;
-                self.scause = except_code.into();
+                self.scause = cause;
                 self.stval = tval;
                 self.mstatus.set_spie(self.mstatus.sie());
                 self.mstatus.set_sie(0);
@@ -181,7 +401,7 @@ impl CsrFile {
                 let base_addr = self.stvec & !0b11;
                 (target_mode,
                 if self.stvec & 0b11 == 0b01 {
-                    base_addr + 4 * u32::from(except_code)
+                    base_addr + offset
                 } else {
                     base_addr
                 })
@@ -190,18 +410,57 @@ impl CsrFile {
         }
     } 
 
-    pub fn trap_mret(&mut self) -> (PrivilegeMode, u32) {
+    /// Set pending bit of `irq` in `mip`. For device's interrupt line
+    pub fn set_pending(&mut self, irq: Interrupt, level: bool) {
+        if level {
+            self.mip |= irq.mask();
+        } else {
+            self.mip &= !irq.mask();
+        }
+    }
+
+    /// Return the interrupt should be taken under current `mode`.
+    /// Check `mip`, `mie`, `mideleg` and global enable bit in `mstatus`
+    pub fn pending_interrupt(&self, mode: PrivilegeMode) -> Option<Interrupt> {
+        let pending = self.mip & self.mie;
+        if pending == 0 {
+            return None;
+        }
+
+        #[cfg(feature = "s")]
+        let deleg = self.mideleg;
+        #[cfg(not(feature = "s"))]
+        let deleg = 0;
+
+        let m_enable = mode != PrivilegeMode::Machine || self.mstatus.mie() > 0;
+        let m_pending = if m_enable { pending & !deleg } else { 0 };
+
+        #[cfg(feature = "s")]
+        let s_enable = mode == PrivilegeMode::User 
+            || (mode == PrivilegeMode::Supervisor && self.mstatus.sie() > 0);
+        #[cfg(feature = "s")]
+        let s_pending = if s_enable { pending & deleg } else { 0 };
+        #[cfg(not(feature = "s"))]
+        let s_pending = 0;
+
+        Interrupt::PRIORITY.iter().find(|irq| m_pending & irq.mask() != 0)
+            .or_else(|| Interrupt::PRIORITY.iter().find(|irq| s_pending & irq.mask() != 0))
+            .copied()
+    }
+
+    pub fn trap_mret(&mut self) -> (PrivilegeMode, Xlen) {
         let mode = self.mstatus.mpp().into();
         
         self.mstatus.set_mie(self.mstatus.mpie());
         self.mstatus.set_mpie(1);
         self.mstatus.set_mpp(0);
+        self.tcontrol.set_mte(self.tcontrol.mpte());
 
         (mode, self.mepc)
     } 
 
     #[cfg(feature = "s")]
-    pub fn trap_sret(&mut self, curr_mode: PrivilegeMode) -> Result<(PrivilegeMode, u32)> {
+    pub fn trap_sret(&mut self, curr_mode: PrivilegeMode) -> Result<(PrivilegeMode, Xlen)> {
         if self.mstatus.tsr() > 0 && curr_mode == PrivilegeMode::Supervisor {
             return Err(Exception::IllegalInstruction(0x10200073));
         };
@@ -219,15 +478,76 @@ impl CsrFile {
     } 
 
     #[cfg(feature = "s")]
-    pub fn check_satp(&self, mode: PrivilegeMode) -> Result<Option<(u16, u32)>> {
+    pub fn check_satp(&self, mode: PrivilegeMode) -> Result<Option<(PagingMode, u16, Xlen)>> {
         if mode == PrivilegeMode::Supervisor && self.check_tvm() {
             return Err(Exception::IllegalInstruction(0x180));
         }
-        Ok(if self.satp.mode() > 0  {
-            Some((self.satp.asid(), self.satp.ppn()))
-        } else {
-            None
-        })
+        Ok(self.satp.paging_mode()
+            .map(|paging| (paging, self.satp.asid(), self.satp.ppn())))
+    }
+
+    /// Sync `time` with CLINT's `mtime`
+    pub fn set_time(&mut self, mtime: u64) {
+        self.counters.time = mtime;
+    }
+
+    /// Increase `mcycle` and `minstret` at the end of a step, see `Counters::tick`
+    pub fn tick_counters(&mut self, retired: bool) {
+        self.counters.tick(retired);
+    }
+
+    /// Count `num` of `event` by `mhpmcounter` selected it
+    pub fn count_event(&mut self, event: HpmEvent, num: u64) {
+        self.counters.count(event, num);
+    }
+
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    pub fn set_counters(&mut self, counters: Counters) {
+        self.counters = counters;
+    }
+
+    /// Compare CSRs except counters, which change on every step
+    pub fn eq_without_counters(&self, other: &CsrFile) -> bool {
+        CsrFile { counters: other.counters, ..*self } == *other
+    }
+
+    /// FP instructions and CSRs are illegal if `mstatus.FS` is Off
+    #[cfg(feature = "f")]
+    pub fn fp_enabled(&self) -> bool {
+        self.mstatus.fs() != 0
+    }
+
+    /// `mstatus.FS` is Dirty after FP state is written
+    #[cfg(feature = "f")]
+    pub fn set_fs_dirty(&mut self) {
+        self.mstatus.set_fs(0b11);
+        self.mstatus.set_sd(1);
+    }
+
+    /// Set `mstatus.FS` to Initial, for user mode program without firmware
+    #[cfg(feature = "f")]
+    pub fn set_fs_initial(&mut self) {
+        self.mstatus.set_fs(0b01);
+    }
+
+    /// Dynamic rounding mode
+    #[cfg(feature = "f")]
+    pub fn frm(&self) -> u8 {
+        (self.fcsr >> 5) as u8
+    }
+
+    /// Accrue exception flags of FP instruction
+    #[cfg(feature = "f")]
+    pub fn accrue_fflags(&mut self, flags: u8) {
+        self.fcsr |= flags as u32;
+        self.set_fs_dirty();
+    }
+
+    pub fn check_tw(&self) -> bool {
+        self.mstatus.tw() > 0
     }
 
     #[cfg(feature = "s")]
@@ -277,23 +597,34 @@ impl CsrFile {
         }
     }
 
-    fn top_of_range(&self, idx: usize, addr: u32, size: usize) -> bool {
+    /// Check triggers before `access` of `len` bytes. Return Breakpoint exception with
+    /// virtual address if any trigger fire
+    pub fn trigger_check(&self, access: Access<Virtual>, len: usize, mode: PrivilegeMode) -> Result<()> {
+        let mte = self.tcontrol.mte() > 0;
+        if self.triggers.iter().any(|trigger| trigger.check(access, len as Xlen, mode, mte)) {
+            Err(Exception::Breakpoint(access.addr))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn top_of_range(&self, idx: usize, addr: Xlen, size: usize) -> bool {
         let lower = match idx.checked_sub(1) {
             Some(i) => self.pmpaddr[i] << 2,
             None      => 0,
         };
         let upper = self.pmpaddr[idx] << 2;
 
-        lower <= addr && addr + (size as u32) < upper
+        lower <= addr && addr + (size as Xlen) < upper
     }
 
-    fn na4(&self, idx: usize, addr: u32, size: usize) -> bool {
+    fn na4(&self, idx: usize, addr: Xlen, size: usize) -> bool {
         let base = self.pmpaddr[idx] << 2;
 
-        base <= addr && addr + (size as u32) < base + 4
+        base <= addr && addr + (size as Xlen) < base + 4
     }
 
-    fn napot(&self, idx: usize, addr: u32, size: usize) -> bool {
+    fn napot(&self, idx: usize, addr: Xlen, size: usize) -> bool {
         let mask_bit = self.pmpaddr[idx].trailing_ones();
         let chunck_size = 1 << (3 + mask_bit);
         let base = (self.pmpaddr[idx] & !((1 << mask_bit) - 1)) << 2;
@@ -301,19 +632,46 @@ impl CsrFile {
         base <= addr && (addr as usize) + size < (base as usize)  + chunck_size
     }
 
+    /// `pmpcfg` CSR. On RV64 only even one exist, and hold 8 entries
+    fn read_pmpcfg(&self, num: usize) -> Xlen {
+        #[cfg(not(feature = "rv64"))]
+        return self.pmpcfg[num].into();
+        #[cfg(feature = "rv64")]
+        return u32::from(self.pmpcfg[num]) as Xlen | (u32::from(self.pmpcfg[num + 1]) as Xlen) << 32;
+    }
+
+    fn write_pmpcfg(&mut self, num: usize, data: Xlen) {
+        self.pmpcfg[num] = (data as u32).into();
+        #[cfg(feature = "rv64")] {
+            self.pmpcfg[num + 1] = ((data >> 32) as u32).into();
+        }
+    }
+
+    pub fn new(hart_id: u32) -> Self {
+        CsrFile { hart_id, ..Default::default() }
+    }
+
+    /// CSR at `addr` exists in this build, whether or not it can be accessed now
+    pub fn implemented(addr: u16) -> bool {
+        CsrAddr::get_csr(addr, 0).is_ok()
+    }
+
     pub fn reset(&mut self) {
-        *self = Self::default()
+        *self = Self::new(self.hart_id)
     }
 
-    pub fn inspect(&self) -> Vec<(String, u32)> {
-        let pmp_list = self.pmpcfg.iter().enumerate()
-            .map(|(i, cfg)| (format!("pmpcfg{}", i), (*cfg).into()))
+    pub fn inspect(&self) -> Vec<(String, Xlen)> {
+        let pmp_list = (0..PMPCFG_NUM).step_by(PMPCFG_STEP)
+            .map(|i| (format!("pmpcfg{}", i), self.read_pmpcfg(i)))
             .chain(self.pmpaddr.iter().enumerate()
                 .map(|(i, addr)| (format!("pmpaddr{}", i), *addr))
             );
 
-        let mut csr_list: Vec<(String, u32)> = vec![
+        let mut csr_list: Vec<(String, Xlen)> = vec![
             ("ustatus".to_string(), 0),
+            #[cfg(feature = "f")] ("fflags".to_string(), (self.fcsr & FFLAGS_MASK) as Xlen),
+            #[cfg(feature = "f")] ("frm".to_string(), (self.fcsr >> 5) as Xlen),
+            #[cfg(feature = "f")] ("fcsr".to_string(), self.fcsr as Xlen),
             ("sstatus".to_string(), self.mstatus.read_s()),
             #[cfg(feature = "s")] ("sie".to_string(), self.mie & self.mideleg),
             #[cfg(feature = "s")] ("stvec".to_string(), self.stvec),
@@ -332,10 +690,21 @@ impl CsrFile {
             ("mepc".to_string(), self.mepc),
             ("mcause".to_string(), self.mcause),
             ("mip".to_string(), self.mip),
+            ("mcounteren".to_string(), self.counters.mcounteren as Xlen),
+            #[cfg(feature = "s")] ("scounteren".to_string(), self.counters.scounteren as Xlen),
+            ("mcountinhibit".to_string(), self.counters.mcountinhibit as Xlen),
+            ("mcycle".to_string(), self.counters.mcycle as Xlen),
+            ("minstret".to_string(), self.counters.minstret as Xlen),
+            ("time".to_string(), self.counters.time as Xlen),
         ];
         csr_list.extend(pmp_list);
+        csr_list.extend(vec![("tselect".to_string(), self.tselect as Xlen),
+            ("tdata1".to_string(), self.triggers[self.tselect].tdata1.read()),
+            ("tdata2".to_string(), self.triggers[self.tselect].tdata2),
+            ("tinfo".to_string(), TINFO as Xlen),
+            ("tcontrol".to_string(), u32::from(self.tcontrol) as Xlen)]);
         csr_list.extend(vec![("mnstatus".to_string(), 0),
-            ("mhartid".to_string(), 0)]);
+            ("mhartid".to_string(), self.hart_id as Xlen)]);
 
         csr_list
     }
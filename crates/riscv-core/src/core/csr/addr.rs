@@ -10,6 +10,9 @@ const PMPADDR_END: u16 = 0x3b0 + (PMPCFG_NUM * 4) as u16;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CsrAddr {
     Ustatus,
+    #[cfg(feature = "f")] Fflags,
+    #[cfg(feature = "f")] Frm,
+    #[cfg(feature = "f")] Fcsr,
 
     #[cfg(feature = "s")] Sstatus,
     #[cfg(feature = "s")] Sie,
@@ -20,6 +23,11 @@ pub enum CsrAddr {
     #[cfg(feature = "s")] Stval,
     #[cfg(feature = "s")] Sip,
     #[cfg(feature = "s")] Satp,
+    #[cfg(feature = "s")] Scounteren,
+
+    Counter(usize),
+    #[cfg(not(feature = "rv64"))]
+    CounterH(usize),
 
     Mstatus,
     #[cfg(feature = "s")] Medeleg,
@@ -31,9 +39,22 @@ pub enum CsrAddr {
     Mcause,
     Mtval,
     Mip,
+    Mcounteren,
+    Mcountinhibit,
+    Mhpmevent(usize),
+    Mcounter(usize),
+    #[cfg(not(feature = "rv64"))]
+    McounterH(usize),
     Pmpcfg(usize),
     Pmpaddr(usize),
 
+    Tselect,
+    Tdata1,
+    Tdata2,
+    Tdata3,
+    Tinfo,
+    Tcontrol,
+
     Mnstatus,
     Mhartid,
 }
@@ -42,6 +63,9 @@ impl CsrAddr {
     pub fn get_csr(addr: u16, raw: u32) -> Result<Self> {
         Ok(match addr {
             0x000 => Ustatus,
+            #[cfg(feature = "f")] 0x001 => Fflags,
+            #[cfg(feature = "f")] 0x002 => Frm,
+            #[cfg(feature = "f")] 0x003 => Fcsr,
 
             #[cfg(feature = "s")] 0x100 => Sstatus,
             #[cfg(feature = "s")] 0x104 => Sie,
@@ -52,7 +76,12 @@ impl CsrAddr {
             #[cfg(feature = "s")] 0x143 => Stval,
             #[cfg(feature = "s")] 0x144 => Sip,
             #[cfg(feature = "s")] 0x180 => Satp,
-            
+            #[cfg(feature = "s")] 0x106 => Scounteren,
+
+            num @ 0xc00..=0xc1f => Counter((num - 0xc00) as usize),
+            #[cfg(not(feature = "rv64"))]
+            num @ 0xc80..=0xc9f => CounterH((num - 0xc80) as usize),
+
             0x300 => Mstatus,
             #[cfg(feature = "s")] 0x302 => Medeleg,
             #[cfg(feature = "s")] 0x303 => Mideleg,
@@ -63,8 +92,23 @@ impl CsrAddr {
             0x342 => Mcause,
             0x343 => Mtval,
             0x344 => Mip,
-            num @ 0x3a0..=PMPCFG_END => Pmpcfg((num - 0x3a0) as usize),
-            num @ 0x3b0..=PMPADDR_END => Pmpaddr((num - 0x3b0) as usize),
+            0x306 => Mcounteren,
+            0x320 => Mcountinhibit,
+            num @ 0x323..=0x33f => Mhpmevent((num - 0x320) as usize),
+            // No `mtime` CSR at 0xb01, it is memory mapped in CLINT
+            num @ (0xb00 | 0xb02..=0xb1f) => Mcounter((num - 0xb00) as usize),
+            #[cfg(not(feature = "rv64"))]
+            num @ (0xb80 | 0xb82..=0xb9f) => McounterH((num - 0xb80) as usize),
+            // Odd `pmpcfg` is not exist on RV64
+            num @ 0x3a0..PMPCFG_END if cfg!(not(feature = "rv64")) || num % 2 == 0 => Pmpcfg((num - 0x3a0) as usize),
+            num @ 0x3b0..PMPADDR_END => Pmpaddr((num - 0x3b0) as usize),
+
+            0x7a0 => Tselect,
+            0x7a1 => Tdata1,
+            0x7a2 => Tdata2,
+            0x7a3 => Tdata3,
+            0x7a4 => Tinfo,
+            0x7a5 => Tcontrol,
             
             0x744 => Mnstatus,
             0xf14 => Mhartid, 
@@ -0,0 +1,135 @@
+use crate::core::PrivilegeMode;
+
+/// Implemented `mhpmcounter3` to `mhpmcounter6`, others are read-only zero
+pub(super) const HPM_NUM: usize = 4;
+
+const CY: u32 = 1 << 0;
+const IR: u32 = 1 << 2;
+// `cycle`, `time`, `instret` and implemented `hpmcounter`
+const COUNTEREN_MASK: u32 = (1 << (3 + HPM_NUM)) - 1;
+// `time` can not be inhibited
+const INHIBIT_MASK: u32 = COUNTEREN_MASK & !(1 << 1);
+
+/// Event selected by `mhpmevent`, other value is WARL to 0 (no event)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpmEvent {
+    /// Exception or interrupt taken
+    Trap    = 1,
+    /// Conditional branch retired
+    Branch  = 2,
+    /// TLB miss of address translation
+    TlbMiss = 3,
+}
+
+/// Zicntr and Zihpm counters. Counter index is the low 5 bits of address:
+/// 0 is `cycle`, 1 is `time`, 2 is `instret`, 3 and after are `hpmcounter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counters {
+    pub mcycle: u64,
+    pub minstret: u64,
+    /// Copy of CLINT's `mtime`, synced for each step
+    pub time: u64,
+    pub mhpmcounter: [u64; HPM_NUM],
+    pub mhpmevent: [u32; HPM_NUM],
+    pub mcountinhibit: u32,
+    pub mcounteren: u32,
+    #[cfg(feature = "s")]
+    pub scounteren: u32,
+    /// Counters written by current instruction, not increased at the end of it
+    written: u32,
+}
+
+impl Counters {
+    pub fn read(&self, index: usize) -> u64 {
+        match index {
+            0 => self.mcycle,
+            1 => self.time,
+            2 => self.minstret,
+            _ => self.mhpmcounter.get(index - 3).copied().unwrap_or(0),
+        }
+    }
+
+    /// Write low or high 32 bits of machine counter
+    #[cfg(not(feature = "rv64"))]
+    pub fn write(&mut self, index: usize, data: u32, high: bool) {
+        let old = self.read(index);
+        let value = if high {
+            (old & 0xffff_ffff) | (data as u64) << 32
+        } else {
+            (old & !0xffff_ffff) | data as u64
+        };
+        self.set(index, value);
+    }
+
+    /// Write whole machine counter
+    #[cfg(feature = "rv64")]
+    pub fn write(&mut self, index: usize, data: u64) {
+        self.set(index, data);
+    }
+
+    fn set(&mut self, index: usize, value: u64) {
+        match index {
+            0 => self.mcycle = value,
+            2 => self.minstret = value,
+            _ => match self.mhpmcounter.get_mut(index - 3) {
+                Some(counter) => *counter = value,
+                None          => return,
+            },
+        }
+        self.written |= 1 << index;
+    }
+
+    pub fn read_event(&self, index: usize) -> u32 {
+        self.mhpmevent.get(index - 3).copied().unwrap_or(0)
+    }
+
+    pub fn write_event(&mut self, index: usize, data: u32) {
+        if let Some(event) = self.mhpmevent.get_mut(index - 3) {
+            *event = if (1..=HpmEvent::TlbMiss as u32).contains(&data) { data } else { 0 };
+        }
+    }
+
+    pub fn write_inhibit(&mut self, data: u32) {
+        self.mcountinhibit = data & INHIBIT_MASK;
+    }
+
+    pub fn write_counteren(data: u32) -> u32 {
+        data & COUNTEREN_MASK
+    }
+
+    /// User level counter can be read in S-Mode if enabled by `mcounteren`,
+    /// and in U-Mode if also enabled by `scounteren`
+    pub fn accessible(&self, index: usize, mode: PrivilegeMode) -> bool {
+        let bit = 1 << index;
+        match mode {
+            PrivilegeMode::Machine => true,
+            #[cfg(feature = "s")]
+            PrivilegeMode::Supervisor => self.mcounteren & bit != 0,
+            #[cfg(feature = "s")]
+            PrivilegeMode::User => self.mcounteren & self.scounteren & bit != 0,
+            #[cfg(not(feature = "s"))]
+            PrivilegeMode::User => self.mcounteren & bit != 0,
+        }
+    }
+
+    /// Increase counters at the end of a step. `mcycle` for each step, `minstret` if `retired`
+    pub fn tick(&mut self, retired: bool) {
+        let enabled = !self.mcountinhibit & !self.written;
+        if enabled & CY != 0 {
+            self.mcycle = self.mcycle.wrapping_add(1);
+        }
+        if retired && enabled & IR != 0 {
+            self.minstret = self.minstret.wrapping_add(1);
+        }
+        self.written = 0;
+    }
+
+    /// Add `num` of `event` to the counters selected it
+    pub fn count(&mut self, event: HpmEvent, num: u64) {
+        for i in 0..HPM_NUM {
+            if self.mhpmevent[i] == event as u32 && self.mcountinhibit & (1 << (3 + i)) == 0 {
+                self.mhpmcounter[i] = self.mhpmcounter[i].wrapping_add(num);
+            }
+        }
+    }
+}
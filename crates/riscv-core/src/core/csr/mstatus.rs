@@ -1,5 +1,7 @@
 use modular_bitfield::prelude::*;
 
+use crate::Xlen;
+
 #[bitfield]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Mstatus {
@@ -36,21 +38,45 @@ const S_MODE_WRITE_MASK: u32 =
 
 const S_MODE_READ_MASK: u32 = S_MODE_WRITE_MASK | (1 << 31);
 
+// UXL and SXL are read-only 2 (64 bits)
+#[cfg(feature = "rv64")]
+const UXL: Xlen = 2 << 32;
+#[cfg(all(feature = "rv64", feature = "s"))]
+const SXL: Xlen = 2 << 34;
+#[cfg(all(feature = "rv64", not(feature = "s")))]
+const SXL: Xlen = 0;
+
 impl Mstatus {
-    pub fn read_m(&self) -> u32{
-        u32::from(*self)
+    pub fn read_m(&self) -> Xlen {
+        #[cfg(feature = "rv64")]
+        let res = Self::extend(u32::from(*self)) | UXL | SXL;
+        #[cfg(not(feature = "rv64"))]
+        let res = u32::from(*self);
+        res
+    }
+
+    pub fn read_s(&self) -> Xlen {
+        #[cfg(feature = "rv64")]
+        let res = Self::extend(u32::from(*self) & S_MODE_READ_MASK) | UXL;
+        #[cfg(not(feature = "rv64"))]
+        let res = u32::from(*self) & S_MODE_READ_MASK;
+        res
     }
 
-    pub fn read_s(&self) -> u32{
-        u32::from(*self) & S_MODE_READ_MASK
+    /// SD is the last bit of `mstatus`, bit 31 is reserved on RV64
+    #[cfg(feature = "rv64")]
+    fn extend(data: u32) -> Xlen {
+        (data & !(1 << 31)) as Xlen | ((data >> 31) as Xlen) << 63
     }
 
-    pub fn write_m(&mut self, data: u32) {
+    pub fn write_m(&mut self, data: Xlen) {
+        let data = data as u32;
         *self = ((u32::from(*self) & !M_MODE_WRITE_MASK) | (data & M_MODE_WRITE_MASK)).into();
         self.check_update_sd();
     }
 
-    pub fn write_s(&mut self, data: u32) {
+    pub fn write_s(&mut self, data: Xlen) {
+        let data = data as u32;
         *self = ((u32::from(*self) & !S_MODE_WRITE_MASK) | (data & S_MODE_WRITE_MASK)).into();
         self.check_update_sd();
     }
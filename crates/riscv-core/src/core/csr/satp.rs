@@ -1,5 +1,9 @@
 use modular_bitfield::prelude::*;
 
+use crate::Xlen;
+use crate::core::mmu::PagingMode;
+
+#[cfg(not(feature = "rv64"))]
 #[bitfield]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Satp {
@@ -8,14 +12,48 @@ pub struct Satp {
     pub mode: B1,
 }
 
-impl From<Satp> for u32 {
+#[cfg(feature = "rv64")]
+#[bitfield]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Satp {
+    pub ppn: B44,
+    pub asid: B16,
+    pub mode: B4,
+}
+
+impl Satp {
+    /// Write with unsupported `mode` has no effect
+    pub fn write(&mut self, data: Xlen) {
+        let satp = Satp::from(data);
+        #[cfg(feature = "rv64")]
+        if satp.mode() != 0 && satp.paging_mode().is_none() {
+            return;
+        }
+        *self = satp;
+    }
+
+    /// `None` is Bare, no translation
+    pub fn paging_mode(&self) -> Option<PagingMode> {
+        match self.mode() {
+            #[cfg(not(feature = "rv64"))]
+            1 => Some(PagingMode::Sv32),
+            #[cfg(feature = "rv64")]
+            8 => Some(PagingMode::Sv39),
+            #[cfg(feature = "rv64")]
+            9 => Some(PagingMode::Sv48),
+            _ => None,
+        }
+    }
+}
+
+impl From<Satp> for Xlen {
     fn from(value: Satp) -> Self {
         Self::from_le_bytes(value.into_bytes())
     }
 }
 
-impl From<u32> for Satp {
-    fn from(value: u32) -> Self {
+impl From<Xlen> for Satp {
+    fn from(value: Xlen) -> Self {
         Self::from_bytes(value.to_le_bytes())
     }
 }
@@ -24,4 +62,4 @@ impl Default for Satp {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
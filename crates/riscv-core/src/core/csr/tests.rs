@@ -1,6 +1,9 @@
-use crate::core::CsrFile;
+use crate::{XLEN, Xlen};
+use crate::core::{CsrFile, HpmEvent};
+use crate::trap::INTERRUPT_MASK;
 use crate::core::privilege::PrivilegeMode;
 use crate::exception::Exception;
+use crate::interrupt::Interrupt;
 
 #[test]
 fn test_csr_rw_permission() {
@@ -28,7 +31,10 @@ fn test_mstatus_behavior() {
     
     #[cfg(feature = "s")] {
         let sstatus = csr.read(0x100, PrivilegeMode::Supervisor, 0).unwrap();
+        #[cfg(not(feature = "rv64"))]
         assert_eq!(sstatus, 0);
+        #[cfg(feature = "rv64")]
+        assert_eq!(sstatus, 2 << 32, "UXL is 64 bits");
     }
 }
 
@@ -51,7 +57,7 @@ fn test_trap_entry() {
     assert_eq!(next_pc, handler_base);
 
     assert_eq!(csr.mepc, fault_pc);
-    assert_eq!(csr.mcause, u32::from(cause));
+    assert_eq!(csr.mcause, u32::from(cause) as Xlen);
 
     let mstatus_new = csr.read(0x300, PrivilegeMode::Machine, 0).unwrap();
     assert_eq!(mstatus_new & (1 << 3), 0);
@@ -83,7 +89,7 @@ fn test_trap_return_mret() {
 fn test_exception_delegation() {
     let mut csr = CsrFile::default();
     let fault_pc = 0x8000_3000;
-    let cause = Exception::Breakpoint;
+    let cause = Exception::Breakpoint(fault_pc);
 
     csr.write(0x302, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
     
@@ -96,24 +102,153 @@ fn test_exception_delegation() {
     assert_eq!(next_pc, s_handler);
     
     assert_eq!(csr.sepc, fault_pc);
-    assert_eq!(csr.scause, u32::from(cause));
+    assert_eq!(csr.scause, u32::from(cause) as Xlen);
 
     assert_eq!(csr.mcause, 0); 
 }
 
+#[test]
+fn test_interrupt_enable() {
+    let mut csr = CsrFile::default();
+    
+    csr.set_pending(Interrupt::MachineTimer, true);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), None, "mie not set");
+
+    // mie.MTIE
+    csr.write(0x304, 1 << 7, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), None, "mstatus.MIE not set");
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::User), Some(Interrupt::MachineTimer),
+        "Lower privilege should ignore mstatus.MIE");
+
+    // mstatus.MIE
+    csr.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), Some(Interrupt::MachineTimer));
+
+    // mip.MSIP
+    csr.write(0x304, (1 << 7) | (1 << 3), PrivilegeMode::Machine, 0).unwrap();
+    csr.set_pending(Interrupt::MachineSoftware, true);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), Some(Interrupt::MachineSoftware));
+
+    csr.set_pending(Interrupt::MachineSoftware, false);
+    csr.set_pending(Interrupt::MachineTimer, false);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), None);
+}
+
+#[test]
+fn test_interrupt_priority() {
+    let mut csr = CsrFile::default();
+
+    // mie.MEIE, mie.MTIE, mie.MSIE, mstatus.MIE
+    csr.write(0x304, (1 << 11) | (1 << 7) | (1 << 3), PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+
+    csr.set_pending(Interrupt::MachineTimer, true);
+    csr.set_pending(Interrupt::MachineSoftware, true);
+    csr.set_pending(Interrupt::MachineExternal, true);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), Some(Interrupt::MachineExternal));
+
+    csr.set_pending(Interrupt::MachineExternal, false);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), Some(Interrupt::MachineSoftware));
+
+    csr.set_pending(Interrupt::MachineSoftware, false);
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), Some(Interrupt::MachineTimer));
+}
+
+#[test]
+fn test_mip_read_only_bits() {
+    let mut csr = CsrFile::default();
+
+    csr.write(0x344, 1 << 7, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x344, PrivilegeMode::Machine, 0), Ok(0), "mip.MTIP is driven by CLINT");
+}
+
+#[test]
+fn test_interrupt_entry() {
+    let mut csr = CsrFile::default();
+    let curr_pc = 0x8000_1000;
+
+    // Vectored mtvec
+    let handler_base = 0x8000_0100;
+    csr.write(0x305, handler_base | 1, PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x300, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+
+    let (next_mode, next_pc) = csr.trap_entry(curr_pc, Interrupt::MachineTimer, PrivilegeMode::Machine);
+
+    assert_eq!(next_mode, PrivilegeMode::Machine);
+    assert_eq!(next_pc, handler_base + 4 * 7);
+    assert_eq!(csr.mepc, curr_pc);
+    assert_eq!(csr.mcause, INTERRUPT_MASK | 7);
+
+    let mstatus = csr.read(0x300, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(mstatus & (1 << 3), 0);
+    assert_eq!(mstatus & (1 << 7), 1 << 7);
+
+    // Exception always use base address
+    let (_, next_pc) = csr.trap_entry(curr_pc, Exception::Breakpoint(curr_pc), PrivilegeMode::Machine);
+    assert_eq!(next_pc, handler_base);
+}
+
+#[test]
+#[cfg(feature = "s")]
+fn test_interrupt_delegation() {
+    let mut csr = CsrFile::default();
+    let curr_pc = 0x8000_2000;
+
+    // mideleg.SSIP, mie.SSIE, sstatus.SIE
+    csr.write(0x303, 1 << 1, PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x304, 1 << 1, PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x100, 1 << 1, PrivilegeMode::Supervisor, 0).unwrap();
+    csr.write(0x344, 1 << 1, PrivilegeMode::Machine, 0).unwrap();
+
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Machine), None, 
+        "Delegated interrupt never be taken in M-Mode");
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Supervisor), Some(Interrupt::SupervisorSoftware));
+
+    let s_handler = 0x8000_4000;
+    csr.write(0x105, s_handler, PrivilegeMode::Supervisor, 0).unwrap();
+
+    let (next_mode, next_pc) = csr.trap_entry(curr_pc, Interrupt::SupervisorSoftware, PrivilegeMode::User);
+
+    assert_eq!(next_mode, PrivilegeMode::Supervisor);
+    assert_eq!(next_pc, s_handler);
+    assert_eq!(csr.sepc, curr_pc);
+    assert_eq!(csr.scause, INTERRUPT_MASK | 1);
+    assert_eq!(csr.mcause, 0);
+
+    // sstatus.SIE is cleared by trap
+    assert_eq!(csr.pending_interrupt(PrivilegeMode::Supervisor), None);
+
+    // Exception code 1 is not delegated by mideleg
+    let (next_mode, _) = csr.trap_entry(curr_pc, Exception::InstructionAccessFault(0), PrivilegeMode::User);
+    assert_eq!(next_mode, PrivilegeMode::Machine);
+}
+
+#[test]
+#[cfg(feature = "s")]
+fn test_sip_write_mask() {
+    let mut csr = CsrFile::default();
+
+    // mideleg.SSIP, mideleg.STIP, mideleg.SEIP
+    csr.write(0x303, (1 << 1) | (1 << 5) | (1 << 9), PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x144, (1 << 1) | (1 << 5) | (1 << 9), PrivilegeMode::Supervisor, 0).unwrap();
+
+    assert_eq!(csr.read(0x144, PrivilegeMode::Supervisor, 0), Ok(1 << 1), "Only sip.SSIP is writable");
+}
+
 mod pmp {
+    use crate::Xlen;
     use crate::core::CsrFile;
     use crate::core::access::{Access, AccessType};
     use crate::core::privilege::PrivilegeMode;
     use crate::exception::Exception;
 
-    fn set_pmp_entry(csr: &mut CsrFile, idx: usize, cfg: u8, addr: u32) {
+    fn set_pmp_entry(csr: &mut CsrFile, idx: usize, cfg: u8, addr: Xlen) {
         let shift = (idx % 4) * 8;
         
         let mut curr_cfg = csr.read(0x3a0, PrivilegeMode::Machine, 0).unwrap();
         curr_cfg &= !(0xff << shift);
 
-        curr_cfg |= (cfg as u32) << shift;
+        curr_cfg |= (cfg as Xlen) << shift;
         csr.write(0x3a0, curr_cfg, PrivilegeMode::Machine, 0).unwrap();
 
         let csr_addr = 0x3b0 + idx as u16;
@@ -141,7 +276,7 @@ mod pmp {
         let addr = 0x8000_0000;
 
         let cfg = (1 << 3) | (1 << 0); // A = 01, R = 1
-        set_pmp_entry(&mut csr, 0, cfg, addr + 1000 >> 2);
+        set_pmp_entry(&mut csr, 0, cfg, (addr + 1000) >> 2);
 
         let mut access = Access::new(addr, AccessType::Load);
         let mode = PrivilegeMode::User;
@@ -183,7 +318,7 @@ mod pmp {
         let mut csr = CsrFile::default();
         let addr = 0x8000_0000;
 
-        let cfg = (3 << 3) | (3 << 0); // A = 11, R = 1, W = 1
+        let cfg = (3 << 3) | 3; // A = 11, R = 1, W = 1
         let pmpaddr = (0x8000_0000 >> 2) | 0x3FF;
         set_pmp_entry(&mut csr, 0, cfg, pmpaddr);
 
@@ -207,7 +342,7 @@ mod pmp {
         // pmp0: A = 01
         set_pmp_entry(&mut csr, 0, 1 << 3, 0x8000_1000 >> 2);
         // pmp1: A = 01, R = 1, W = 1, X = 1
-        set_pmp_entry(&mut csr, 1, (1 << 3) | (7 << 0), 0x8000_2000 >> 2);
+        set_pmp_entry(&mut csr, 1, (1 << 3) | 7, 0x8000_2000 >> 2);
 
         let access0 = Access::new(0x8000_0050, AccessType::Load);
         assert!(csr.pmp_check(access0, 4, mode).is_err());
@@ -231,4 +366,130 @@ mod pmp {
         assert_eq!(csr.pmp_check(access, 4, PrivilegeMode::Machine), 
             Err(Exception::StoreOrAmoAccessFault(0x8000_0050)));
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_trigger_csr_warl() {
+    let mut csr = CsrFile::default();
+
+    // `type` is the highest 4 bits, `dmode` is below it
+    let shift = XLEN - 4;
+    assert_eq!(csr.read(0x7a1, PrivilegeMode::Machine, 0), Ok(15 << shift), "Trigger is disabled");
+    assert_eq!(csr.read(0x7a4, PrivilegeMode::Machine, 0).unwrap() & (1 << 6), 1 << 6);
+
+    csr.write(0x7a0, 8, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x7a0, PrivilegeMode::Machine, 0), Ok(0), "No trigger 8");
+
+    // Unsupported type disable the trigger, unsupported match become equal
+    csr.write(0x7a1, (2 << shift) | (1 << 2), PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x7a1, PrivilegeMode::Machine, 0), Ok(15 << shift));
+    csr.write(0x7a1, (6 << shift) | (1 << (shift - 1)) | (1 << 7) | (1 << 6) | (1 << 2), PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x7a1, PrivilegeMode::Machine, 0), Ok((6 << shift) | (1 << 6) | (1 << 2)));
+
+    assert!(csr.read(0x7a1, PrivilegeMode::User, 0).is_err());
+}
+
+#[test]
+fn test_trigger_tcontrol() {
+    let mut csr = CsrFile::default();
+    csr.write(0x7a5, 1 << 3, PrivilegeMode::Machine, 0).unwrap();
+
+    csr.trap_entry(0x8000_0000, Exception::Breakpoint(0), PrivilegeMode::User);
+    assert_eq!(csr.read(0x7a5, PrivilegeMode::Machine, 0), Ok(1 << 7));
+
+    csr.trap_mret();
+    assert_eq!(csr.read(0x7a5, PrivilegeMode::Machine, 0), Ok((1 << 7) | (1 << 3)));
+}
+
+#[test]
+fn test_counter_counteren() {
+    let mut csr = CsrFile::default();
+    let time: u64 = 0x1_0000_0002;
+    csr.set_time(time);
+
+    assert_eq!(csr.read(0xc01, PrivilegeMode::Machine, 0), Ok(time as Xlen));
+    #[cfg(not(feature = "rv64"))]
+    assert_eq!(csr.read(0xc81, PrivilegeMode::Machine, 0), Ok(1));
+    #[cfg(feature = "rv64")]
+    assert!(csr.read(0xc81, PrivilegeMode::Machine, 0).is_err(), "No high half on RV64");
+    assert!(csr.read(0xc01, PrivilegeMode::User, 0).is_err(), "Disabled by mcounteren");
+    assert!(csr.write(0xc00, 0, PrivilegeMode::Machine, 0).is_err(), "User counter is read-only");
+    assert!(csr.read(0xb01, PrivilegeMode::Machine, 0).is_err(), "No mtime CSR");
+
+    csr.write(0x306, Xlen::MAX, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x306, PrivilegeMode::Machine, 0), Ok(0x7f), "Only implemented counters");
+
+    #[cfg(feature = "s")] {
+        assert_eq!(csr.read(0xc01, PrivilegeMode::Supervisor, 0), Ok(time as Xlen));
+        assert!(csr.read(0xc01, PrivilegeMode::User, 0).is_err(), "Disabled by scounteren");
+        csr.write(0x106, 1 << 1, PrivilegeMode::Supervisor, 0).unwrap();
+    }
+    assert_eq!(csr.read(0xc01, PrivilegeMode::User, 0), Ok(time as Xlen));
+    #[cfg(feature = "s")]
+    assert!(csr.read(0xc00, PrivilegeMode::User, 0).is_err(), "Disabled by scounteren");
+}
+
+#[test]
+fn test_counter_tick() {
+    let mut csr = CsrFile::default();
+
+    csr.tick_counters(true);
+    csr.tick_counters(false);
+    assert_eq!(csr.read(0xb00, PrivilegeMode::Machine, 0), Ok(2));
+    assert_eq!(csr.read(0xb02, PrivilegeMode::Machine, 0), Ok(1));
+
+    // Written value is kept after the writing instruction
+    #[cfg(not(feature = "rv64"))]
+    csr.write(0xb82, 1, PrivilegeMode::Machine, 0).unwrap();
+    #[cfg(feature = "rv64")]
+    csr.write(0xb02, 1, PrivilegeMode::Machine, 0).unwrap();
+    csr.tick_counters(true);
+    assert_eq!(csr.read(0xb02, PrivilegeMode::Machine, 0), Ok(1));
+    #[cfg(not(feature = "rv64"))]
+    assert_eq!(csr.read(0xb82, PrivilegeMode::Machine, 0), Ok(1));
+    csr.tick_counters(true);
+    assert_eq!(csr.read(0xb02, PrivilegeMode::Machine, 0), Ok(2));
+
+    csr.write(0x320, 0b101, PrivilegeMode::Machine, 0).unwrap();
+    csr.tick_counters(true);
+    assert_eq!(csr.read(0xb00, PrivilegeMode::Machine, 0), Ok(4), "Writing instruction is not inhibited");
+    csr.tick_counters(true);
+    assert_eq!(csr.read(0xb00, PrivilegeMode::Machine, 0), Ok(4));
+}
+
+#[test]
+fn test_hpm_event() {
+    let mut csr = CsrFile::default();
+
+    csr.write(0x323, 1, PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x324, 99, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0x324, PrivilegeMode::Machine, 0), Ok(0), "Unknown event");
+
+    csr.trap_entry(0x8000_0000, Exception::IllegalInstruction(0), PrivilegeMode::Machine);
+    csr.count_event(HpmEvent::Branch, 1);
+    assert_eq!(csr.read(0xb03, PrivilegeMode::Machine, 0), Ok(1));
+    assert_eq!(csr.read(0xb04, PrivilegeMode::Machine, 0), Ok(0));
+
+    csr.write(0xb1f, 5, PrivilegeMode::Machine, 0).unwrap();
+    assert_eq!(csr.read(0xb1f, PrivilegeMode::Machine, 0), Ok(0), "Unimplemented counter is zero");
+}
+
+#[cfg(feature = "f")]
+#[test]
+fn test_fcsr() {
+    let mut csr = CsrFile::default();
+    assert!(csr.read(0x003, PrivilegeMode::User, 0).is_err(), "Illegal when FS is Off");
+
+    csr.write(0x300, 1 << 13, PrivilegeMode::Machine, 0).unwrap();
+    csr.write(0x003, Xlen::MAX, PrivilegeMode::User, 0).unwrap();
+    assert_eq!(csr.read(0x003, PrivilegeMode::User, 0), Ok(0xff));
+    assert_eq!(csr.read(0x300, PrivilegeMode::Machine, 0).unwrap() >> 13 & 0b11, 0b11, "FS is Dirty");
+
+    csr.write(0x001, 0, PrivilegeMode::User, 0).unwrap();
+    assert_eq!(csr.read(0x003, PrivilegeMode::User, 0), Ok(0xe0));
+    csr.write(0x002, 0b001, PrivilegeMode::User, 0).unwrap();
+    assert_eq!(csr.read(0x002, PrivilegeMode::User, 0), Ok(0b001));
+    assert_eq!(csr.frm(), 0b001);
+
+    csr.accrue_fflags(0x3);
+    assert_eq!(csr.read(0x001, PrivilegeMode::User, 0), Ok(0x3));
+}
@@ -0,0 +1,166 @@
+use modular_bitfield::prelude::*;
+
+use crate::Xlen;
+use crate::core::access::{Access, AccessType, Virtual};
+use crate::core::PrivilegeMode;
+
+pub(super) const TRIGGER_NUM: usize = 4;
+
+const TYPE_MCONTROL6: u8 = 6;
+const TYPE_DISABLED: u8 = 15;
+
+// Sdtrig version 1, support `mcontrol6` and disabled trigger
+pub(super) const TINFO: u32 = (1 << 24) | (1 << TYPE_MCONTROL6) | (1 << TYPE_DISABLED);
+
+// `hit`, `select`, `size`, `action` and `chain` are always 0
+#[cfg(feature = "s")]
+const MCONTROL6_WRITE_MASK: u32 = 
+    0b111 | (1 << 3) | (1 << 4) | (1 << 6) | (0xf << 7);
+#[cfg(not(feature = "s"))]
+const MCONTROL6_WRITE_MASK: u32 = 
+    0b111 | (1 << 3) | (1 << 6) | (0xf << 7);
+
+const TCONTROL_WRITE_MASK: u32 = (1 << 3) | (1 << 7);
+
+/// `tdata1` of type 6. Only address match with `action` 0 (raise Breakpoint exception)
+#[bitfield]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mcontrol6 {
+    pub load: B1,
+    pub store: B1,
+    pub execute: B1,
+    pub u: B1,
+    pub s: B1,
+    #[skip] __: B1, // uncertainen
+    pub m: B1,
+    pub matching: B4,
+    #[skip] __: B1, // chain
+    #[skip] __: B4, // action: only Breakpoint exception
+    #[skip] __: B3, // size: any size
+    #[skip] __: B2, // Reserved
+    #[skip] __: B1, // select: only address
+    #[skip] __: B1, // hit0
+    #[skip] __: B2, // vu, vs: need H extension
+    #[skip] __: B1, // hit1
+    #[skip] __: B1, // uncertain
+    #[skip] __: B1, // dmode: no Debug Mode
+    pub kind: B4,
+}
+
+#[bitfield]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tcontrol {
+    #[skip] __: B3,
+    pub mte: B1,
+    #[skip] __: B3,
+    pub mpte: B1,
+    #[skip] __: B24,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Trigger {
+    pub tdata1: Mcontrol6,
+    pub tdata2: Xlen,
+}
+
+// `type` and `dmode` are the highest 5 bits of `tdata1`, other bits are in the low 27 bits
+#[cfg(feature = "rv64")]
+const TDATA1_LOW_MASK: u32 = (1 << 27) - 1;
+
+impl Mcontrol6 {
+    pub fn read(&self) -> Xlen {
+        let data = u32::from(*self);
+        #[cfg(feature = "rv64")]
+        return (data & TDATA1_LOW_MASK) as Xlen | ((data >> 27) as Xlen) << 59;
+        #[cfg(not(feature = "rv64"))]
+        data
+    }
+
+    pub fn write(&mut self, data: Xlen) {
+        #[cfg(feature = "rv64")]
+        let data = (data as u32 & TDATA1_LOW_MASK) | ((data >> 59) as u32) << 27;
+        *self = if (data >> 28) as u8 == TYPE_MCONTROL6 {
+            let mut ctrl = Mcontrol6::from(data & MCONTROL6_WRITE_MASK);
+            // Unsupported match is WARL to equal
+            if !matches!(ctrl.matching(), 0 | 2 | 3) {
+                ctrl.set_matching(0);
+            }
+            ctrl.with_kind(TYPE_MCONTROL6)
+        } else {
+            Mcontrol6::default()
+        }
+    }
+}
+
+impl Trigger {
+    /// Return `true` if trigger fire on `access` of `len` bytes under `mode`.
+    /// M-mode only fire when `tcontrol.mte` is set
+    pub fn check(&self, access: Access<Virtual>, len: Xlen, mode: PrivilegeMode, mte: bool) -> bool {
+        let ctrl = self.tdata1;
+        if ctrl.kind() != TYPE_MCONTROL6 {
+            return false;
+        }
+
+        let enable = match mode {
+            PrivilegeMode::Machine    => ctrl.m() > 0 && mte,
+            #[cfg(feature = "s")]
+            PrivilegeMode::Supervisor => ctrl.s() > 0,
+            PrivilegeMode::User       => ctrl.u() > 0,
+        };
+        let kind = match access.kind {
+            AccessType::Load  => ctrl.load() > 0,
+            AccessType::Store => ctrl.store() > 0,
+            AccessType::Fetch => ctrl.execute() > 0,
+            #[cfg(feature = "a")]
+            AccessType::Amo   => ctrl.load() > 0 || ctrl.store() > 0,
+        };
+        if !enable || !kind {
+            return false;
+        }
+
+        // Any accessed byte matching fire the trigger
+        let last = access.addr.wrapping_add(len - 1);
+        match ctrl.matching() {
+            0 => access.addr <= self.tdata2 && self.tdata2 <= last,
+            2 => last >= self.tdata2,
+            3 => access.addr < self.tdata2,
+            _ => unreachable!("Unsupported match is cleared by write"),
+        }
+    }
+}
+
+impl From<Mcontrol6> for u32 {
+    fn from(value: Mcontrol6) -> Self {
+        Self::from_le_bytes(value.into_bytes())
+    }
+}
+
+impl From<u32> for Mcontrol6 {
+    fn from(value: u32) -> Self {
+        Self::from_bytes(value.to_le_bytes())
+    }
+}
+
+impl Default for Mcontrol6 {
+    fn default() -> Self {
+        Self::new().with_kind(TYPE_DISABLED)
+    }
+}
+
+impl Tcontrol {
+    pub fn write(&mut self, data: u32) {
+        *self = (data & TCONTROL_WRITE_MASK).into();
+    }
+}
+
+impl From<Tcontrol> for u32 {
+    fn from(value: Tcontrol) -> Self {
+        Self::from_le_bytes(value.into_bytes())
+    }
+}
+
+impl From<u32> for Tcontrol {
+    fn from(value: u32) -> Self {
+        Self::from_bytes(value.to_le_bytes())
+    }
+}
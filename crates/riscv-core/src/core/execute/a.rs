@@ -1,72 +1,101 @@
 use riscv_decoder::instruction::AmoInsData;
 use riscv_decoder::instruction::AOp::{self, *};
 
-use crate::Result;
+use crate::{Result, Xlen};
 use crate::engine::{Alu, Lsu};
 use crate::core::cpu::Cpu;
 
 impl Cpu {
     pub(crate) fn execute_a(&mut self, op: AOp, data: AmoInsData) -> Result<()> {
+        let size = if op.is_double() { 8 } else { 4 };
         let rs1_data = self.regs[data.rs1];
-        let rs2_data = self.regs[data.rs2];
+        // Word operation compare sign-extended low 32 bits on RV64
+        let rs2_data = self.regs[data.rs2] as i32 as Xlen;
+        #[cfg(feature = "rv64")]
+        let rs2_data = if op.is_double() { self.regs[data.rs2] } else { rs2_data };
         
         let rd_data = match op {
-            LrW      => self.load_reserved(rs1_data)?,
-            ScW      => self.store_conditional(rs1_data, rs2_data)?,
-            AmoSwapW => self.atomic_operate(rs1_data, rs2_data, direct_out)?,
-            AmoAddW  => self.atomic_operate(rs1_data, rs2_data, Alu::add)?,
-            AmoXorW  => self.atomic_operate(rs1_data, rs2_data, Alu::xor)?,
-            AmoAndW  => self.atomic_operate(rs1_data, rs2_data, Alu::and)?,
-            AmoOrW   => self.atomic_operate(rs1_data, rs2_data, Alu::or)?,
-            AmoMinW  => self.atomic_operate(rs1_data, rs2_data, Alu::min)?,
-            AmoMaxW  => self.atomic_operate(rs1_data, rs2_data, Alu::max)?,
-            AmoMaxuW => self.atomic_operate(rs1_data, rs2_data, Alu::max_unsigned)?,
-            AmoMinuW => self.atomic_operate(rs1_data, rs2_data, Alu::min_unsigned)?,    
+            LrW      => self.load_reserved(rs1_data, size)?,
+            ScW      => self.store_conditional(rs1_data, rs2_data, size)?,
+            AmoSwapW => self.atomic_operate(rs1_data, rs2_data, size, direct_out)?,
+            AmoAddW  => self.atomic_operate(rs1_data, rs2_data, size, Alu::add)?,
+            AmoXorW  => self.atomic_operate(rs1_data, rs2_data, size, Alu::xor)?,
+            AmoAndW  => self.atomic_operate(rs1_data, rs2_data, size, Alu::and)?,
+            AmoOrW   => self.atomic_operate(rs1_data, rs2_data, size, Alu::or)?,
+            AmoMinW  => self.atomic_operate(rs1_data, rs2_data, size, Alu::min)?,
+            AmoMaxW  => self.atomic_operate(rs1_data, rs2_data, size, Alu::max)?,
+            AmoMaxuW => self.atomic_operate(rs1_data, rs2_data, size, Alu::max_unsigned)?,
+            AmoMinuW => self.atomic_operate(rs1_data, rs2_data, size, Alu::min_unsigned)?,    
+            #[cfg(feature = "rv64")]
+            LrD      => self.load_reserved(rs1_data, size)?,
+            #[cfg(feature = "rv64")]
+            ScD      => self.store_conditional(rs1_data, rs2_data, size)?,
+            #[cfg(feature = "rv64")]
+            AmoSwapD => self.atomic_operate(rs1_data, rs2_data, size, direct_out)?,
+            #[cfg(feature = "rv64")]
+            AmoAddD  => self.atomic_operate(rs1_data, rs2_data, size, Alu::add)?,
+            #[cfg(feature = "rv64")]
+            AmoXorD  => self.atomic_operate(rs1_data, rs2_data, size, Alu::xor)?,
+            #[cfg(feature = "rv64")]
+            AmoAndD  => self.atomic_operate(rs1_data, rs2_data, size, Alu::and)?,
+            #[cfg(feature = "rv64")]
+            AmoOrD   => self.atomic_operate(rs1_data, rs2_data, size, Alu::or)?,
+            #[cfg(feature = "rv64")]
+            AmoMinD  => self.atomic_operate(rs1_data, rs2_data, size, Alu::min)?,
+            #[cfg(feature = "rv64")]
+            AmoMaxD  => self.atomic_operate(rs1_data, rs2_data, size, Alu::max)?,
+            #[cfg(feature = "rv64")]
+            AmoMaxuD => self.atomic_operate(rs1_data, rs2_data, size, Alu::max_unsigned)?,
+            #[cfg(feature = "rv64")]
+            AmoMinuD => self.atomic_operate(rs1_data, rs2_data, size, Alu::min_unsigned)?,
         };
 
-        self.regs.write(data.rd, rd_data);
+        self.write_rd(data.rd, rd_data);
         Ok(())
     }   
 
-    fn load_reserved(&mut self, src: u32) -> Result<u32> {
+    fn load_reserved(&mut self, src: Xlen, size: usize) -> Result<Xlen> {
         let mut lsu = Lsu::new(
-            &mut self.bus, 
+            &mut self.bus,
+            &mut self.debugger,
             #[cfg(feature = "s")] &mut self.mmu, 
             #[cfg(feature = "zicsr")] &self.csrs, 
             #[cfg(feature = "zicsr")] self.mode
         );
-        let (res_data, addr) = lsu.atomic_load(src)?;
+        let (res_data, addr) = lsu.atomic_load(src, size)?;
         self.reservation = Some(addr);
         Ok(res_data)
     }
 
-    fn store_conditional(&mut self, des: u32, src: u32) -> Result<u32> {
+    fn store_conditional(&mut self, des: Xlen, src: Xlen, size: usize) -> Result<Xlen> {
         let mut lsu = Lsu::new(
-            &mut self.bus, 
+            &mut self.bus,
+            &mut self.debugger,
             #[cfg(feature = "s")] &mut self.mmu, 
             #[cfg(feature = "zicsr")] &self.csrs, 
             #[cfg(feature = "zicsr")] self.mode
         );
-        Ok(if lsu.atomic_store(des, src, &mut self.reservation)? {
+        Ok(if lsu.atomic_store(des, src, size, &mut self.reservation)? {
             0
         } else {
             1
         }) 
     }
 
-    fn atomic_operate<F>(&mut self, des: u32, data: u32, ope: F) -> Result<u32> 
-        where F: Fn(u32, u32) -> u32
+    fn atomic_operate<F>(&mut self, des: Xlen, data: Xlen, size: usize, ope: F) -> Result<Xlen> 
+        where F: Fn(Xlen, Xlen) -> Xlen
     {
         let mut lsu = Lsu::new(
-            &mut self.bus, 
+            &mut self.bus,
+            &mut self.debugger,
             #[cfg(feature = "s")] &mut self.mmu, 
             #[cfg(feature = "zicsr")] &self.csrs, 
             #[cfg(feature = "zicsr")] self.mode
         );
-        lsu.atomic_operate(des, data, ope, &mut self.reservation)
+        lsu.atomic_operate(des, data, size, ope, &mut self.reservation)
     }
 }
 
-fn direct_out(_: u32, data: u32) -> u32 {
+fn direct_out(_: Xlen, data: Xlen) -> Xlen {
     data
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use riscv_decoder::instruction::InstructionData;
+use riscv_decoder::instruction::BOp::{self, *};
+
+use crate::Xlen;
+use crate::engine::Alu;
+use crate::core::cpu::Cpu;
+
+impl Cpu {
+    pub(crate) fn execute_b(&mut self, op: BOp, data: InstructionData) {
+        let rs1_data = self.regs[data.rs1];
+        let rs2_data = self.regs[data.rs2];
+        // `imm` of immediate instructions is `shamt`
+        #[allow(unused)]
+        let shamt = data.imm as Xlen;
+
+        self.write_rd(data.rd,
+            match op {
+                #[cfg(feature = "zba")]
+                Sh1add   => Alu::shift_add(rs1_data, rs2_data, 1),
+                #[cfg(feature = "zba")]
+                Sh2add   => Alu::shift_add(rs1_data, rs2_data, 2),
+                #[cfg(feature = "zba")]
+                Sh3add   => Alu::shift_add(rs1_data, rs2_data, 3),
+                #[cfg(all(feature = "zba", feature = "rv64"))]
+                AddUw    => Alu::shift_add_unsigned_word(rs1_data, rs2_data, 0),
+                #[cfg(all(feature = "zba", feature = "rv64"))]
+                Sh1addUw => Alu::shift_add_unsigned_word(rs1_data, rs2_data, 1),
+                #[cfg(all(feature = "zba", feature = "rv64"))]
+                Sh2addUw => Alu::shift_add_unsigned_word(rs1_data, rs2_data, 2),
+                #[cfg(all(feature = "zba", feature = "rv64"))]
+                Sh3addUw => Alu::shift_add_unsigned_word(rs1_data, rs2_data, 3),
+                #[cfg(all(feature = "zba", feature = "rv64"))]
+                SlliUw   => Alu::shl_logic_unsigned_word(rs1_data, shamt),
+
+                #[cfg(feature = "zbb")]
+                Andn     => Alu::and_not(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Orn      => Alu::or_not(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Xnor     => Alu::xnor(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Clz      => Alu::count_leading_zeros(rs1_data),
+                #[cfg(feature = "zbb")]
+                Ctz      => Alu::count_trailing_zeros(rs1_data),
+                #[cfg(feature = "zbb")]
+                Cpop     => Alu::count_ones(rs1_data),
+                #[cfg(feature = "zbb")]
+                Max      => Alu::max(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Maxu     => Alu::max_unsigned(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Min      => Alu::min(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Minu     => Alu::min_unsigned(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                SextB    => Alu::sign_extend_byte(rs1_data),
+                #[cfg(feature = "zbb")]
+                SextH    => Alu::sign_extend_half(rs1_data),
+                #[cfg(feature = "zbb")]
+                ZextH    => Alu::zero_extend_half(rs1_data),
+                #[cfg(feature = "zbb")]
+                Rol      => Alu::rotate_left(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Ror      => Alu::rotate_right(rs1_data, rs2_data),
+                #[cfg(feature = "zbb")]
+                Rori     => Alu::rotate_right(rs1_data, shamt),
+                #[cfg(feature = "zbb")]
+                OrcB     => Alu::or_combine_byte(rs1_data),
+                #[cfg(feature = "zbb")]
+                Rev8     => Alu::byte_reverse(rs1_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Clzw     => Alu::count_leading_zeros_word(rs1_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Ctzw     => Alu::count_trailing_zeros_word(rs1_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Cpopw    => Alu::count_ones_word(rs1_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Rolw     => Alu::rotate_left_word(rs1_data, rs2_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Rorw     => Alu::rotate_right_word(rs1_data, rs2_data),
+                #[cfg(all(feature = "zbb", feature = "rv64"))]
+                Roriw    => Alu::rotate_right_word(rs1_data, shamt),
+
+                #[cfg(feature = "zbc")]
+                Clmul    => Alu::carryless_mul(rs1_data, rs2_data),
+                #[cfg(feature = "zbc")]
+                Clmulh   => Alu::carryless_mulh(rs1_data, rs2_data),
+                #[cfg(feature = "zbc")]
+                Clmulr   => Alu::carryless_mulr(rs1_data, rs2_data),
+
+                #[cfg(feature = "zbs")]
+                Bclr     => Alu::bit_clear(rs1_data, rs2_data),
+                #[cfg(feature = "zbs")]
+                Bclri    => Alu::bit_clear(rs1_data, shamt),
+                #[cfg(feature = "zbs")]
+                Bext     => Alu::bit_extract(rs1_data, rs2_data),
+                #[cfg(feature = "zbs")]
+                Bexti    => Alu::bit_extract(rs1_data, shamt),
+                #[cfg(feature = "zbs")]
+                Binv     => Alu::bit_invert(rs1_data, rs2_data),
+                #[cfg(feature = "zbs")]
+                Binvi    => Alu::bit_invert(rs1_data, shamt),
+                #[cfg(feature = "zbs")]
+                Bset     => Alu::bit_set(rs1_data, rs2_data),
+                #[cfg(feature = "zbs")]
+                Bseti    => Alu::bit_set(rs1_data, shamt),
+            }
+        )
+    }
+}
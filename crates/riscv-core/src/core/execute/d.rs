@@ -0,0 +1,83 @@
+use riscv_decoder::instruction::FpInsData;
+use riscv_decoder::instruction::DOp::{self, *};
+
+use crate::{Result, Xlen};
+use crate::core::cpu::Cpu;
+use crate::engine::{Fpu, Lsu, DOUBLE, SINGLE};
+
+impl Cpu {
+    pub(crate) fn execute_d(&mut self, op: DOp, data: FpInsData, raw: u32) -> Result<()> {
+        let mut fpu = self.fpu(op.is_rounded(), data.rm, raw)?;
+        let rs1_data = self.fregs.read(data.rs1);
+        let rs2_data = self.fregs.read(data.rs2);
+        let rs3_data = self.fregs.read(data.rs3);
+
+        match op {
+            Fld => {
+                let mut lsu = Lsu::new(
+                    &mut self.bus,
+                    &mut self.debugger,
+                    #[cfg(feature = "s")] &mut self.mmu,
+                    &self.csrs,
+                    self.mode
+                );
+                let res = lsu.load_double(self.regs[data.rs1], data.imm)?;
+                self.write_frd(data.rd, res);
+            },
+            Fsd => {
+                let mut lsu = Lsu::new(
+                    &mut self.bus,
+                    &mut self.debugger,
+                    #[cfg(feature = "s")] &mut self.mmu,
+                    &self.csrs,
+                    self.mode
+                );
+                lsu.store_double(self.regs[data.rs1], rs2_data, data.imm,
+                    #[cfg(feature = "a")] &mut self.reservation)?;
+            },
+            FmaddD  => self.write_frd(data.rd, fpu.mul_add(DOUBLE, rs1_data, rs2_data, rs3_data, false, false)),
+            FmsubD  => self.write_frd(data.rd, fpu.mul_add(DOUBLE, rs1_data, rs2_data, rs3_data, false, true)),
+            FnmsubD => self.write_frd(data.rd, fpu.mul_add(DOUBLE, rs1_data, rs2_data, rs3_data, true, false)),
+            FnmaddD => self.write_frd(data.rd, fpu.mul_add(DOUBLE, rs1_data, rs2_data, rs3_data, true, true)),
+            FaddD   => self.write_frd(data.rd, fpu.add(DOUBLE, rs1_data, rs2_data)),
+            FsubD   => self.write_frd(data.rd, fpu.sub(DOUBLE, rs1_data, rs2_data)),
+            FmulD   => self.write_frd(data.rd, fpu.mul(DOUBLE, rs1_data, rs2_data)),
+            FdivD   => self.write_frd(data.rd, fpu.div(DOUBLE, rs1_data, rs2_data)),
+            FsqrtD  => self.write_frd(data.rd, fpu.sqrt(DOUBLE, rs1_data)),
+            FsgnjD  => self.write_frd(data.rd, Fpu::sign_inject(DOUBLE, rs1_data, rs2_data, false, false)),
+            FsgnjnD => self.write_frd(data.rd, Fpu::sign_inject(DOUBLE, rs1_data, rs2_data, true, false)),
+            FsgnjxD => self.write_frd(data.rd, Fpu::sign_inject(DOUBLE, rs1_data, rs2_data, false, true)),
+            FminD   => self.write_frd(data.rd, fpu.min(DOUBLE, rs1_data, rs2_data)),
+            FmaxD   => self.write_frd(data.rd, fpu.max(DOUBLE, rs1_data, rs2_data)),
+            FcvtSD  => self.write_frd_s(data.rd, fpu.convert(DOUBLE, SINGLE, rs1_data) as u32),
+            FcvtDS  => {
+                let src = self.fregs.read_s(data.rs1) as u64;
+                self.write_frd(data.rd, fpu.convert(SINGLE, DOUBLE, src))
+            },
+            FcvtWD  => self.write_rd(data.rd, fpu.float_to_int(DOUBLE, rs1_data, 32, true) as i32 as Xlen),
+            FcvtWuD => self.write_rd(data.rd, fpu.float_to_int(DOUBLE, rs1_data, 32, false) as i32 as Xlen),
+            FcvtDW  => self.write_frd(data.rd, fpu.int_to_float(DOUBLE, self.regs[data.rs1] as u64, 32, true)),
+            FcvtDWu => self.write_frd(data.rd, fpu.int_to_float(DOUBLE, self.regs[data.rs1] as u64, 32, false)),
+            #[cfg(feature = "rv64")]
+            FcvtLD  => self.write_rd(data.rd, fpu.float_to_int(DOUBLE, rs1_data, 64, true)),
+            #[cfg(feature = "rv64")]
+            FcvtLuD => self.write_rd(data.rd, fpu.float_to_int(DOUBLE, rs1_data, 64, false)),
+            #[cfg(feature = "rv64")]
+            FcvtDL  => self.write_frd(data.rd, fpu.int_to_float(DOUBLE, self.regs[data.rs1], 64, true)),
+            #[cfg(feature = "rv64")]
+            FcvtDLu => self.write_frd(data.rd, fpu.int_to_float(DOUBLE, self.regs[data.rs1], 64, false)),
+            // Move raw bits
+            #[cfg(feature = "rv64")]
+            FmvXD   => self.write_rd(data.rd, rs1_data),
+            #[cfg(feature = "rv64")]
+            FmvDX   => self.write_frd(data.rd, self.regs[data.rs1]),
+            FeqD    => self.write_rd(data.rd, fpu.feq(DOUBLE, rs1_data, rs2_data) as Xlen),
+            FltD    => self.write_rd(data.rd, fpu.flt(DOUBLE, rs1_data, rs2_data) as Xlen),
+            FleD    => self.write_rd(data.rd, fpu.fle(DOUBLE, rs1_data, rs2_data) as Xlen),
+            FclassD => self.write_rd(data.rd, Fpu::classify(DOUBLE, rs1_data) as Xlen),
+        }
+
+        self.accrue_fflags(fpu.flags);
+        Ok(())
+    }
+}
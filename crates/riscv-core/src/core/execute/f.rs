@@ -0,0 +1,119 @@
+use riscv_decoder::instruction::FpInsData;
+use riscv_decoder::instruction::FOp::{self, *};
+
+use crate::{Exception, Result, Xlen};
+use crate::core::cpu::Cpu;
+use crate::engine::{Fpu, Lsu, RoundingMode, SINGLE};
+
+// `fflags` address for tracer
+const FFLAGS: u16 = 0x001;
+
+impl Cpu {
+    pub(crate) fn execute_f(&mut self, op: FOp, data: FpInsData, raw: u32) -> Result<()> {
+        let mut fpu = self.fpu(op.is_rounded(), data.rm, raw)?;
+        let rs1_data = self.fregs.read_s(data.rs1) as u64;
+        let rs2_data = self.fregs.read_s(data.rs2) as u64;
+        let rs3_data = self.fregs.read_s(data.rs3) as u64;
+
+        match op {
+            Flw => {
+                let mut lsu = Lsu::new(
+                    &mut self.bus,
+                    &mut self.debugger,
+                    #[cfg(feature = "s")] &mut self.mmu,
+                    &self.csrs,
+                    self.mode
+                );
+                let res = lsu.load(self.regs[data.rs1], data.imm, 4)?;
+                self.write_frd_s(data.rd, res as u32);
+            },
+            Fsw => {
+                // Raw bits are stored without NaN-boxing check
+                let src = self.fregs.read(data.rs2) as u32 as Xlen;
+                let mut lsu = Lsu::new(
+                    &mut self.bus,
+                    &mut self.debugger,
+                    #[cfg(feature = "s")] &mut self.mmu,
+                    &self.csrs,
+                    self.mode
+                );
+                lsu.store(self.regs[data.rs1], src, data.imm, 4,
+                    #[cfg(feature = "a")] &mut self.reservation)?;
+            },
+            FmaddS  => self.write_frd_s(data.rd, fpu.mul_add(SINGLE, rs1_data, rs2_data, rs3_data, false, false) as u32),
+            FmsubS  => self.write_frd_s(data.rd, fpu.mul_add(SINGLE, rs1_data, rs2_data, rs3_data, false, true) as u32),
+            FnmsubS => self.write_frd_s(data.rd, fpu.mul_add(SINGLE, rs1_data, rs2_data, rs3_data, true, false) as u32),
+            FnmaddS => self.write_frd_s(data.rd, fpu.mul_add(SINGLE, rs1_data, rs2_data, rs3_data, true, true) as u32),
+            FaddS   => self.write_frd_s(data.rd, fpu.add(SINGLE, rs1_data, rs2_data) as u32),
+            FsubS   => self.write_frd_s(data.rd, fpu.sub(SINGLE, rs1_data, rs2_data) as u32),
+            FmulS   => self.write_frd_s(data.rd, fpu.mul(SINGLE, rs1_data, rs2_data) as u32),
+            FdivS   => self.write_frd_s(data.rd, fpu.div(SINGLE, rs1_data, rs2_data) as u32),
+            FsqrtS  => self.write_frd_s(data.rd, fpu.sqrt(SINGLE, rs1_data) as u32),
+            FsgnjS  => self.write_frd_s(data.rd, Fpu::sign_inject(SINGLE, rs1_data, rs2_data, false, false) as u32),
+            FsgnjnS => self.write_frd_s(data.rd, Fpu::sign_inject(SINGLE, rs1_data, rs2_data, true, false) as u32),
+            FsgnjxS => self.write_frd_s(data.rd, Fpu::sign_inject(SINGLE, rs1_data, rs2_data, false, true) as u32),
+            FminS   => self.write_frd_s(data.rd, fpu.min(SINGLE, rs1_data, rs2_data) as u32),
+            FmaxS   => self.write_frd_s(data.rd, fpu.max(SINGLE, rs1_data, rs2_data) as u32),
+            FcvtWS  => self.write_rd(data.rd, fpu.float_to_int(SINGLE, rs1_data, 32, true) as i32 as Xlen),
+            FcvtWuS => self.write_rd(data.rd, fpu.float_to_int(SINGLE, rs1_data, 32, false) as i32 as Xlen),
+            FcvtSW  => self.write_frd_s(data.rd, fpu.int_to_float(SINGLE, self.regs[data.rs1] as u64, 32, true) as u32),
+            FcvtSWu => self.write_frd_s(data.rd, fpu.int_to_float(SINGLE, self.regs[data.rs1] as u64, 32, false) as u32),
+            #[cfg(feature = "rv64")]
+            FcvtLS  => self.write_rd(data.rd, fpu.float_to_int(SINGLE, rs1_data, 64, true)),
+            #[cfg(feature = "rv64")]
+            FcvtLuS => self.write_rd(data.rd, fpu.float_to_int(SINGLE, rs1_data, 64, false)),
+            #[cfg(feature = "rv64")]
+            FcvtSL  => self.write_frd_s(data.rd, fpu.int_to_float(SINGLE, self.regs[data.rs1], 64, true) as u32),
+            #[cfg(feature = "rv64")]
+            FcvtSLu => self.write_frd_s(data.rd, fpu.int_to_float(SINGLE, self.regs[data.rs1], 64, false) as u32),
+            // Move raw bits, NaN-boxing is not checked
+            FmvXW   => self.write_rd(data.rd, self.fregs.read(data.rs1) as i32 as Xlen),
+            FmvWX   => self.write_frd_s(data.rd, self.regs[data.rs1] as u32),
+            FeqS    => self.write_rd(data.rd, fpu.feq(SINGLE, rs1_data, rs2_data) as Xlen),
+            FltS    => self.write_rd(data.rd, fpu.flt(SINGLE, rs1_data, rs2_data) as Xlen),
+            FleS    => self.write_rd(data.rd, fpu.fle(SINGLE, rs1_data, rs2_data) as Xlen),
+            FclassS => self.write_rd(data.rd, Fpu::classify(SINGLE, rs1_data) as Xlen),
+        }
+
+        self.accrue_fflags(fpu.flags);
+        Ok(())
+    }
+
+    /// FPU of the instruction. Illegal if `mstatus.FS` is Off, or rounding mode is reserved.
+    /// `rm` of 7 is dynamic rounding mode in `frm`
+    pub(crate) fn fpu(&self, rounded: bool, rm: u8, raw: u32) -> Result<Fpu> {
+        if !self.csrs.fp_enabled() {
+            return Err(Exception::IllegalInstruction(raw));
+        }
+        if !rounded {
+            return Ok(Fpu::new(RoundingMode::Rne));
+        }
+        let rm = if rm == 0b111 { self.csrs.frm() } else { rm };
+        RoundingMode::from_bits(rm)
+            .map(Fpu::new)
+            .ok_or(Exception::IllegalInstruction(raw))
+    }
+
+    /// Write back FP register, set `mstatus.FS` dirty and give it to tracer
+    #[cfg(feature = "d")]
+    pub(crate) fn write_frd(&mut self, rd: u8, data: u64) {
+        self.fregs.write(rd, data);
+        self.csrs.set_fs_dirty();
+        self.debugger.trace_freg(rd, data);
+    }
+
+    /// Write back single precision value with NaN-boxing
+    pub(crate) fn write_frd_s(&mut self, rd: u8, data: u32) {
+        self.fregs.write_s(rd, data);
+        self.csrs.set_fs_dirty();
+        self.debugger.trace_freg(rd, self.fregs.read(rd));
+    }
+
+    /// Accrue raised exception flags into `fflags`
+    pub(crate) fn accrue_fflags(&mut self, flags: u8) {
+        if flags != 0 {
+            self.csrs.accrue_fflags(flags);
+            self.trace_csr(FFLAGS);
+        }
+    }
+}
@@ -9,7 +9,7 @@ impl Cpu {
         let rs1_data = self.regs[data.rs1];
         let rs2_data = self.regs[data.rs2];
         
-        self.regs.write(data.rd, 
+        self.write_rd(data.rd, 
             match op {
                 Mul    => Alu::mul(rs1_data, rs2_data),
                 Mulh   => Alu::mulh(rs1_data, rs2_data),
@@ -19,6 +19,16 @@ impl Cpu {
                 Divu   => Alu::div_unsigned(rs1_data, rs2_data),
                 Rem    => Alu::rem(rs1_data, rs2_data),
                 Remu   => Alu::rem_unsigned(rs1_data, rs2_data),
+                #[cfg(feature = "rv64")]
+                Mulw   => Alu::mul_word(rs1_data, rs2_data),
+                #[cfg(feature = "rv64")]
+                Divw   => Alu::div_word(rs1_data, rs2_data),
+                #[cfg(feature = "rv64")]
+                Divuw  => Alu::div_unsigned_word(rs1_data, rs2_data),
+                #[cfg(feature = "rv64")]
+                Remw   => Alu::rem_word(rs1_data, rs2_data),
+                #[cfg(feature = "rv64")]
+                Remuw  => Alu::rem_unsigned_word(rs1_data, rs2_data),
             }
         )
     }   
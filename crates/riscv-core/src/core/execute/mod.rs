@@ -1,4 +1,6 @@
 pub mod rv32i;
+#[cfg(feature = "rv64")]
+pub mod rv64;
 #[cfg(feature = "m")]
 pub mod m;
 #[cfg(feature = "a")]
@@ -6,4 +8,10 @@ pub mod a;
 #[cfg(feature = "zicsr")]
 pub mod zicsr;
 #[cfg(feature = "zicsr")]
-pub mod privilege;
\ No newline at end of file
+pub mod privilege;
+#[cfg(feature = "f")]
+pub mod f;
+#[cfg(feature = "d")]
+pub mod d;
+#[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+pub mod b;
\ No newline at end of file
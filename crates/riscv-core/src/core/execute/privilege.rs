@@ -7,10 +7,19 @@ use crate::{Exception, Result};
 use crate::core::PrivilegeMode;
 use crate::core::cpu::Cpu;
 
+const CSR_MSTATUS: u16 = 0x300;
+
 impl Cpu {
     pub(crate) fn execute_privileged(&mut self, op: PrivilegeOp, data: InstructionData) -> Result<bool> {
         let (mode, pc) = match op {
             Mret           => self.csrs.trap_mret(),
+            // Implemented as nop. Pending interrupts are checked at the start of every step
+            Wfi            => {
+                if self.mode != PrivilegeMode::Machine && self.csrs.check_tw() {
+                    return Err(Exception::IllegalInstruction(0x10500073));
+                }
+                return Ok(false);
+            },
             #[cfg(feature = "s")]
             Sret           => self.csrs.trap_sret(self.mode)?,
             #[cfg(feature = "s")]
@@ -27,6 +36,8 @@ impl Cpu {
                 return Ok(false);
             }
         };
+        // `mret` and `sret` restore `mstatus`
+        self.trace_csr(CSR_MSTATUS);
         self.pc.directed_addressing(pc);
         self.mode = mode;
         Ok(true)
@@ -1,9 +1,11 @@
 use riscv_decoder::instruction::InstructionData;
 use riscv_decoder::instruction::Rv32iOp::{self, *};
 
-use crate::{Exception, Result};
+use crate::{Exception, Result, SXlen, Xlen};
 use crate::engine::{Alu, Branch, Lsu};
 use crate::core::cpu::Cpu;
+#[cfg(feature = "zicsr")]
+use crate::core::HpmEvent;
 
 impl Cpu {
     pub(crate) fn execute_rv32i(&mut self, op: Rv32iOp, data: InstructionData) -> Result<bool> {
@@ -11,17 +13,17 @@ impl Cpu {
         let rs2_data = self.regs[data.rs2];
 
         if let Some(res) = Self::alu_imm(op, rs1_data, data.imm, self.pc.get()) {
-            self.regs.write(data.rd, res);
+            self.write_rd(data.rd, res);
             return Ok(false);
         } 
         
         if let Some(res) = Self::alu_reg(op, rs1_data, rs2_data) {
-            self.regs.write(data.rd, res);
+            self.write_rd(data.rd, res);
             return Ok(false);
         } 
 
         if let Some(res) = self.lsu_load(op, rs1_data, data.imm) {
-            self.regs.write(data.rd, res?);
+            self.write_rd(data.rd, res?);
             return Ok(false);
         }
 
@@ -31,6 +33,8 @@ impl Cpu {
         }
 
         if let Some(res) = Self::branch(op, rs1_data, rs2_data) {
+            #[cfg(feature = "zicsr")]
+            self.csrs.count_event(HpmEvent::Branch, 1);
             if res {
                 self.pc.related_addressing(data.imm)
             }
@@ -47,9 +51,9 @@ impl Cpu {
             #[cfg(not(feature = "c"))]
             let next_ins_addr = 4;
             
-            self.regs.write(data.rd, self.pc.get() + next_ins_addr);
+            self.write_rd(data.rd, self.pc.get() + next_ins_addr);
             match res {
-                true  => self.pc.directed_addressing(rs1_data.wrapping_add_signed(data.imm)),
+                true  => self.pc.directed_addressing(rs1_data.wrapping_add_signed(data.imm as SXlen)),
                 false => self.pc.related_addressing(data.imm),
             }
             return Ok(true);
@@ -61,52 +65,53 @@ impl Cpu {
         Ok(false)
     }
 
-    fn alu_imm(op: Rv32iOp, data: u32, imm: i32, pc: u32) -> Option<u32> {
+    fn alu_imm(op: Rv32iOp, data: Xlen, imm: i32, pc: Xlen) -> Option<Xlen> {
         Some(match op {
-            Addi  => Alu::add_signed(data, imm),
-            Slli  => Alu::shl_logic(data, imm as u32),
-            Slti  => Alu::set_less_than(data as i32, imm),
-            Sltiu => Alu::set_less_than_unsigned(data, imm as u32),
-            Xori  => Alu::xor(data, imm as u32),
-            Srli  => Alu::shr_logic(data, imm as u32),
-            Srai  => Alu::shr_ar(data as i32, imm as u32),
-            Ori   => Alu::or(data, imm as u32),
-            Andi  => Alu::and(data, imm as u32),
-
-            Lui   => imm as u32,
-            Auipc => Alu::add(pc, imm as u32),
+            Addi  => Alu::add_signed(data, imm as SXlen),
+            Slli  => Alu::shl_logic(data, imm as Xlen),
+            Slti  => Alu::set_less_than(data as SXlen, imm as SXlen),
+            Sltiu => Alu::set_less_than_unsigned(data, imm as Xlen),
+            Xori  => Alu::xor(data, imm as Xlen),
+            Srli  => Alu::shr_logic(data, imm as Xlen),
+            Srai  => Alu::shr_ar(data as SXlen, imm as Xlen),
+            Ori   => Alu::or(data, imm as Xlen),
+            Andi  => Alu::and(data, imm as Xlen),
+
+            Lui   => imm as Xlen,
+            Auipc => Alu::add(pc, imm as Xlen),
             _     => return None,
         })
     }
 
-    fn alu_reg(op: Rv32iOp, data1: u32, data2: u32) -> Option<u32> {
+    fn alu_reg(op: Rv32iOp, data1: Xlen, data2: Xlen) -> Option<Xlen> {
         Some(match op {
             Add  => Alu::add(data1, data2),
             Sub  => Alu::sub(data1, data2),
             Sll  => Alu::shl_logic(data1, data2),
-            Slt  => Alu::set_less_than(data1 as i32, data2 as i32),
+            Slt  => Alu::set_less_than(data1 as SXlen, data2 as SXlen),
             Sltu => Alu::set_less_than_unsigned(data1, data2),
             Xor  => Alu::xor(data1, data2),
             Srl  => Alu::shr_logic(data1, data2),
-            Sra  => Alu::shr_ar(data1 as i32, data2),
+            Sra  => Alu::shr_ar(data1 as SXlen, data2),
             Or   => Alu::or(data1, data2),
             And  => Alu::and(data1, data2),
             _    => return None,
         })
     }
 
-    fn lsu_load(&mut self, op: Rv32iOp, src: u32, offset: i32) -> Option<Result<u32>> {
+    fn lsu_load(&mut self, op: Rv32iOp, src: Xlen, offset: i32) -> Option<Result<Xlen>> {
         let (is_signed, byte_num) = match op {
             Lb  => (true, 1),
             Lh  => (true, 2),
-            Lw  => (false, 4),
+            Lw  => (true, 4),
             Lbu => (false, 1),
             Lhu => (false, 2),
             _   => return None,
         };
 
         let mut lsu = Lsu::new(
-            &mut self.bus, 
+            &mut self.bus,
+            &mut self.debugger,
             #[cfg(feature = "s")] &mut self.mmu, 
             #[cfg(feature = "zicsr")] &self.csrs, 
             #[cfg(feature = "zicsr")] self.mode
@@ -120,7 +125,7 @@ impl Cpu {
         )
     }
 
-    fn lsu_store(&mut self, op: Rv32iOp, des: u32, src: u32, offset: i32) -> Option<Result<()>> {
+    fn lsu_store(&mut self, op: Rv32iOp, des: Xlen, src: Xlen, offset: i32) -> Option<Result<()>> {
         let byte_num = match op {
             Sb => 1,
             Sh => 2,
@@ -129,6 +134,7 @@ impl Cpu {
         };
         let mut lsu = Lsu::new(
             &mut self.bus,
+            &mut self.debugger,
             #[cfg(feature = "s")] &mut self.mmu, 
             #[cfg(feature = "zicsr")] &self.csrs, 
             #[cfg(feature = "zicsr")] self.mode
@@ -138,12 +144,12 @@ impl Cpu {
             #[cfg(feature = "a")] &mut self.reservation))
     }
 
-    fn branch(op: Rv32iOp, data1: u32, data2: u32) -> Option<bool> {
+    fn branch(op: Rv32iOp, data1: Xlen, data2: Xlen) -> Option<bool> {
         Some(match op {
             Beq  => Branch::equal(data1, data2),
             Bne  => Branch::not_equal(data1, data2),
-            Blt  => Branch::less(data1 as i32, data2 as i32),
-            Bge  => Branch::greater_eqaul(data1 as i32, data2 as i32),
+            Blt  => Branch::less(data1 as SXlen, data2 as SXlen),
+            Bge  => Branch::greater_eqaul(data1 as SXlen, data2 as SXlen),
             Bltu => Branch::less_unsigned(data1, data2),
             Bgeu => Branch::greater_eqaul_unsigned(data1, data2),
             _    => return None,
@@ -158,20 +164,24 @@ impl Cpu {
         })
     }
 
-    fn system(&self, op: Rv32iOp) -> Option<Result<()>> {
+    fn system(&mut self, op: Rv32iOp) -> Option<Result<()>> {
         Some(match op {
             Fence  => Ok(()),
             Ecall  => {
                 #[cfg(not(feature = "zicsr"))]
-                return Some(Err(Exception::Ebreak));
+                return Some(Err(Exception::Ecall));
                 #[cfg(feature = "zicsr")]
                 Err(self.mode.call_exception())
             },
             Ebreak => {
+                if self.is_semihost_call() {
+                    self.semihost_call();
+                    return Some(Ok(()));
+                }
                 #[cfg(not(feature = "zicsr"))]
-                return Some(Err(Exception::Ecall));
+                return Some(Err(Exception::Ebreak));
                 #[cfg(feature = "zicsr")]
-                Err(Exception::Breakpoint)
+                Err(Exception::Breakpoint(self.pc.get()))
             }
             _ => unreachable!("Last of Rv32i op"),
         })
@@ -0,0 +1,47 @@
+use riscv_decoder::instruction::InstructionData;
+use riscv_decoder::instruction::Rv64iOp::{self, *};
+
+use crate::{Result, SXlen, Xlen};
+use crate::engine::{Alu, Lsu};
+use crate::core::cpu::Cpu;
+
+impl Cpu {
+    pub(crate) fn execute_rv64(&mut self, op: Rv64iOp, data: InstructionData) -> Result<()> {
+        let rs1_data = self.regs[data.rs1];
+        let rs2_data = self.regs[data.rs2];
+        // Shift amount of `*iw` is `imm[4:0]`, `imm[10]` select arithmetic shift
+        let shamt = (data.imm & 0x1f) as Xlen;
+
+        let res = match op {
+            Addiw => Alu::sign_extend_word(Alu::add_signed(rs1_data, data.imm as SXlen) as u32),
+            Slliw => Alu::shl_logic_word(rs1_data, shamt),
+            Srliw => Alu::shr_logic_word(rs1_data, shamt),
+            Sraiw => Alu::shr_ar_word(rs1_data, shamt),
+            Addw  => Alu::sign_extend_word(Alu::add(rs1_data, rs2_data) as u32),
+            Subw  => Alu::sign_extend_word(Alu::sub(rs1_data, rs2_data) as u32),
+            Sllw  => Alu::shl_logic_word(rs1_data, rs2_data),
+            Srlw  => Alu::shr_logic_word(rs1_data, rs2_data),
+            Sraw  => Alu::shr_ar_word(rs1_data, rs2_data),
+            Lwu | Ld | Sd => {
+                let mut lsu = Lsu::new(
+                    &mut self.bus,
+                    &mut self.debugger,
+                    #[cfg(feature = "s")] &mut self.mmu, 
+                    #[cfg(feature = "zicsr")] &self.csrs, 
+                    #[cfg(feature = "zicsr")] self.mode
+                );
+                match op {
+                    Lwu => lsu.load(rs1_data, data.imm, 4)?,
+                    Ld  => lsu.load(rs1_data, data.imm, 8)?,
+                    _   => {
+                        return lsu.store(rs1_data, rs2_data, data.imm, 8,
+                            #[cfg(feature = "a")] &mut self.reservation);
+                    },
+                }
+            },
+        };
+
+        self.write_rd(data.rd, res);
+        Ok(())
+    }
+}
@@ -1,14 +1,15 @@
 use riscv_decoder::instruction::InstructionData;
 use riscv_decoder::instruction::ZicsrOp;
 
-use crate::Result;
+use crate::{Result, Xlen};
 use crate::core::cpu::Cpu;
+use crate::debug::DebugInterface;
 
 impl Cpu {
     pub(crate) fn execute_zicsr(&mut self, op: ZicsrOp, data: InstructionData, raw: u32) -> Result<()> {
         let addr = (data.imm & 0xfff) as u16;
         let (val, check_val) = if op.is_imm() {
-            (data.rs1 as u32, data.rs1)
+            (data.rs1 as Xlen, data.rs1)
         } else {
             (self.regs[data.rs1], data.rs1)
         };
@@ -17,10 +18,11 @@ impl Cpu {
             if data.rd != 0 {
                 let csr_data = self.csrs.read(addr, self.mode, raw)?;
                 self.csrs.write(addr, val, self.mode, raw)?;
-                self.regs.write(data.rd, csr_data);
+                self.write_rd(data.rd, csr_data);
             } else {
                 self.csrs.write(addr, val, self.mode, raw)?;
             }
+            self.trace_csr(addr);
         } else {
             let csr_data = self.csrs.read(addr, self.mode, raw)?;
             if check_val != 0 {
@@ -30,10 +32,18 @@ impl Cpu {
                     (!val) & csr_data
                 };
                 self.csrs.write(addr, write_val, self.mode, raw)?;
+                self.trace_csr(addr);
             }
-            self.regs.write(data.rd, csr_data);
+            self.write_rd(data.rd, csr_data);
         }
 
         Ok(())
     }
+
+    /// Give written CSR to tracer with the value read back
+    pub(crate) fn trace_csr(&mut self, addr: u16) {
+        if self.tracer.is_some() && let Some(data) = self.inspect_csr(addr) {
+            self.debugger.trace_csr(addr, data);
+        }
+    }
 }
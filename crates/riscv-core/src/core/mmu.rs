@@ -1,15 +1,70 @@
+#[cfg(not(feature = "rv64"))]
 mod sv32;
+#[cfg(feature = "rv64")]
+mod sv39;
 mod tlb;
 
-use crate::Result;
+use crate::{Result, SXlen, StdResult, XLEN, Xlen};
 use crate::core::CsrFile;
 use crate::core::privilege::PrivilegeMode;
 use crate::device::bus::SystemBus;
 use crate::core::access::{Access, AccessType, Physical, Virtual};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
 
-use sv32::{Sv32Pte, Sv32Vpn};
+#[cfg(not(feature = "rv64"))]
+pub(crate) use sv32::Sv32Pte as Pte;
+#[cfg(feature = "rv64")]
+pub(crate) use sv39::Sv39Pte as Pte;
 use tlb::{Tlb, TlbResult};
 
+/// PTE is XLEN wide in Sv32, Sv39 and Sv48
+const PTE_SIZE: usize = size_of::<Xlen>();
+
+/// Translation scheme selected by `satp.MODE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagingMode {
+    #[cfg(not(feature = "rv64"))]
+    Sv32,
+    #[cfg(feature = "rv64")]
+    Sv39,
+    #[cfg(feature = "rv64")]
+    Sv48,
+}
+
+impl PagingMode {
+    fn levels(&self) -> u32 {
+        match self {
+            #[cfg(not(feature = "rv64"))]
+            PagingMode::Sv32 => 2,
+            #[cfg(feature = "rv64")]
+            PagingMode::Sv39 => 3,
+            #[cfg(feature = "rv64")]
+            PagingMode::Sv48 => 4,
+        }
+    }
+
+    /// Bits of VPN in each level
+    fn vpn_bits(&self) -> u32 {
+        match self {
+            #[cfg(not(feature = "rv64"))]
+            PagingMode::Sv32 => 10,
+            #[cfg(feature = "rv64")]
+            PagingMode::Sv39 | PagingMode::Sv48 => 9,
+        }
+    }
+
+    /// Upper bits of virtual address must be the same as its highest bit
+    fn is_canonical(&self, v_addr: Xlen) -> bool {
+        let shift = XLEN - (12 + self.levels() * self.vpn_bits());
+        (((v_addr << shift) as SXlen) >> shift) as Xlen == v_addr
+    }
+
+    fn vpn(&self, v_addr: Xlen, level: u32) -> Xlen {
+        (v_addr >> (12 + level * self.vpn_bits())) & ((1 << self.vpn_bits()) - 1)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Mmu {
     tlb: Tlb,
@@ -17,6 +72,21 @@ pub struct Mmu {
     pub miss_count: usize,
 }
 
+impl Snapshot for Mmu {
+    fn save(&self, w: &mut Writer) {
+        self.tlb.save(w);
+        w.u64(self.hit_count as u64);
+        w.u64(self.miss_count as u64);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        self.tlb.restore(r)?;
+        self.hit_count = r.u64()? as usize;
+        self.miss_count = r.u64()? as usize;
+        Ok(())
+    }
+}
+
 impl Mmu {
     pub fn translate(
         &mut self, 
@@ -31,17 +101,21 @@ impl Mmu {
             return Ok(access.bypass());
         }
 
-        let (asid, root_ppn) = match csrs.check_satp(mode)? {
-            Some((asid, ppn)) => (asid, ppn),
+        let (paging, asid, root_ppn) = match csrs.check_satp(mode)? {
+            Some(satp) => satp,
             None => return Ok(access.bypass()),
         };
 
+        if !paging.is_canonical(v_addr) {
+            return Err(access.into_page_exception());
+        }
+
         let tlb_res = self.tlb.lookup(csrs, v_addr, asid, access.kind, mode);
 
         match tlb_res {
-            TlbResult::Hit(is_mega, ppn) => {
+            TlbResult::Hit(level, ppn) => {
                 self.hit_count += 1;
-                let p_addr = Self::get_physical(v_addr, ppn, is_mega);
+                let p_addr = Self::get_physical(paging, v_addr, ppn, level);
                 return Ok(access.into_physical(p_addr));
             },
             TlbResult::PageFault => {
@@ -52,28 +126,16 @@ impl Mmu {
             TlbResult::Miss => self.miss_count += 1,
         }
 
-        let vpn = Sv32Vpn::from(v_addr);
-
-        let (mut pte, pte_addr, is_mega) = {
-            let (pte1, addr1, is_leaf) = Self::pte_walk(
-                vpn.vpn_1(), root_ppn, &access, bus
-            )?;
-
-            if is_leaf {
-                (pte1, addr1, true)
-            } else {
-                let (pte0, addr0, is_leaf) = Self::pte_walk(
-                    vpn.vpn_0(), pte1.ppn(), &access, bus
-                )?;
-                if !is_leaf {
-                    return Err(access.into_page_exception());
-                }
-                (pte0, addr0, false)
-            }
-        };
+        let (mut pte, pte_addr, level) = Self::walk(paging, root_ppn, &access, bus)?;
 
         Self::access_check(&pte, &access, mode, csrs)?;
 
+        // Superpage must be aligned to its size
+        let ppn_mask = (1 << (level * paging.vpn_bits())) - 1;
+        if (pte.ppn() as Xlen) & ppn_mask != 0 {
+            return Err(access.into_page_exception());
+        }
+
         let mut update_pte = false;
 
         if pte.is_access_zero_and_set() {
@@ -84,35 +146,46 @@ impl Mmu {
         }
         if update_pte {
             let pte_access = Access::new(pte_addr, access.kind);
-            bus.write_u32(pte_access, pte.into())?;
+            bus.write_xlen_bytes(pte_access, pte.into(), PTE_SIZE)?;
         }
 
-        if is_mega && (pte.ppn() & 0x3ff) != 0 {
-            return Err(access.into_page_exception());
-        }
+        self.tlb.fill(v_addr, pte, asid, level as u8);
 
-        self.tlb.fill(v_addr, pte, asid, is_mega);
-
-        let p_addr = Self::get_physical(v_addr, pte.ppn(), is_mega);
+        let p_addr = Self::get_physical(paging, v_addr, pte.ppn() as Xlen, level as u8);
 
         Ok(access.into_physical(p_addr))
     }
 
-    fn pte_walk(vpn: u16, ppn: u32, access: &Access<Virtual>, bus: &mut SystemBus) -> Result<(Sv32Pte, u32, bool)> {
-        let pte_addr = (ppn << 12) + (vpn * 4) as u32;
+    /// Walk from root to the leaf PTE. Return it with its address and level, 0 is the 4KiB page
+    fn walk(paging: PagingMode, root_ppn: Xlen, access: &Access<Virtual>, bus: &mut SystemBus) -> Result<(Pte, Xlen, u32)> {
+        let mut ppn = root_ppn;
+
+        for level in (0..paging.levels()).rev() {
+            let pte_addr = (ppn << 12) + paging.vpn(access.addr, level) * PTE_SIZE as Xlen;
 
-        let pte_access = Access::new(pte_addr, AccessType::Load);
+            let pte_access = Access::new(pte_addr, AccessType::Load);
 
-        let pte = Sv32Pte::from(bus.read_u32(pte_access)?);
+            let pte = Pte::from(bus.read_xlen_bytes(pte_access, PTE_SIZE, false)?);
 
-        if !pte.is_valid() || (!pte.can_read() && pte.can_write()) {
-            return  Err(access.into_page_exception())
-        } 
+            if !pte.is_valid() || (!pte.can_read() && pte.can_write()) {
+                return Err(access.into_page_exception());
+            }
+            #[cfg(feature = "rv64")]
+            if pte.reserved() != 0 {
+                return Err(access.into_page_exception());
+            }
+
+            if pte.is_leaf() {
+                return Ok((pte, pte_addr, level));
+            }
+            ppn = pte.ppn() as Xlen;
+        }
 
-        Ok((pte, pte_addr, pte.is_leaf()))
+        // Not leaf at level 0
+        Err(access.into_page_exception())
     }
 
-    fn access_check(pte: &Sv32Pte, access: &Access<Virtual>, mode: PrivilegeMode, csrs: &CsrFile) -> Result<()> {
+    fn access_check(pte: &Pte, access: &Access<Virtual>, mode: PrivilegeMode, csrs: &CsrFile) -> Result<()> {
         let can_access = match access.kind {
             AccessType::Load  => pte.can_read() || (pte.can_execute() && csrs.check_mxr()),
             AccessType::Store => pte.can_write(),
@@ -132,21 +205,22 @@ impl Mmu {
         }
     }
 
-    fn get_physical(v_addr: u32, ppn: u32, is_mega: bool) -> u32 {
-        let level_size = if is_mega {
-                1 << 22
-            } else {
-                1 << 12
-            };
-        let offset_mask = level_size - 1;
+    /// Offset in superpage of `level` is taken from virtual address
+    fn get_physical(paging: PagingMode, v_addr: Xlen, ppn: Xlen, level: u8) -> Xlen {
+        let offset_mask = (1 << (12 + level as u32 * paging.vpn_bits())) - 1;
         (ppn << 12) | (v_addr & offset_mask)
     }
 
-    pub fn flush_tlb(&mut self, v_addr: u32, asid: u32) {
+    pub fn flush_tlb(&mut self, v_addr: Xlen, asid: Xlen) {
         let vpn = v_addr >> 12; 
         self.tlb.flush(vpn, asid as u16);
     }
 
+    /// Drop all TLB entries, for memory restored by history
+    pub fn flush_all(&mut self) {
+        self.tlb.reset();
+    }
+
     pub fn reset(&mut self) {
         self.tlb.reset();
         self.hit_count = 0;
@@ -154,5 +228,8 @@ impl Mmu {
     }
 }
 
-#[cfg(test)]
+// Sv32 only
+#[cfg(all(test, not(feature = "rv64")))]
 mod tests;
+#[cfg(all(test, feature = "rv64"))]
+mod sv39_tests;
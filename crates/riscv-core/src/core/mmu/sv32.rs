@@ -1,25 +1,5 @@
 use modular_bitfield::prelude::*;
 
-#[bitfield]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Sv32Vpn {
-    pub offset: B12,
-    pub vpn_0: B10,
-    pub vpn_1: B10,
-}
-
-impl From<u32> for Sv32Vpn {
-    fn from(value: u32) -> Self {
-        Self::from_bytes(value.to_le_bytes())
-    }
-}
-
-impl From<Sv32Vpn> for u32 {
-    fn from(value: Sv32Vpn) -> Self {
-        Self::from_le_bytes(value.into_bytes())
-    }
-}
-
 #[bitfield]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Sv32Pte {
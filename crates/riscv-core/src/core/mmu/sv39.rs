@@ -0,0 +1,87 @@
+use modular_bitfield::prelude::*;
+
+/// PTE of Sv39 and Sv48, they only differ in number of levels
+#[bitfield]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sv39Pte {
+    v: B1,
+    r: B1,
+    w: B1,
+    x: B1,
+    u: B1,
+    g: B1,
+    a: B1,
+    d: B1,
+    #[skip] __: B2,
+    pub ppn: B44,
+    /// Reserved, `PBMT` and `N` bits. Svpbmt and Svnapot are not supported
+    pub reserved: B10,
+}
+
+impl From<u64> for Sv39Pte {
+    fn from(value: u64) -> Self {
+        Self::from_bytes(value.to_le_bytes())
+    }
+}
+
+impl From<Sv39Pte> for u64 {
+    fn from(value: Sv39Pte) -> Self {
+        Self::from_le_bytes(value.into_bytes())
+    }
+}
+
+impl Sv39Pte {
+    pub fn is_valid(&self) -> bool {
+        self.v() > 0
+    }
+
+    pub fn is_global(&self) -> bool {
+        self.g() > 0
+    }
+
+    pub fn is_access_zero_and_set(&mut self) -> bool {
+        if self.a() == 0 {
+            self.set_a(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_dirty_zero_and_set(&mut self) -> bool {
+        if self.d() == 0 {
+            self.set_d(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.can_write() | self.can_read() | self.can_execute()
+    }
+
+    pub fn can_write(&self) -> bool {
+        self.w() > 0
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.r() > 0
+    }
+
+    pub fn can_execute(&self) -> bool {
+        self.x() > 0
+    }
+
+    pub fn can_user(&self) -> bool {
+        self.u() > 0
+    }
+
+    pub fn is_accessed(&self) -> bool {
+        self.a() > 0
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.d() > 0
+    }
+}
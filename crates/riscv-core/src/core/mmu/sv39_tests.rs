@@ -0,0 +1,165 @@
+use crate::Exception;
+use crate::core::{CsrFile, Mmu};
+use crate::core::privilege::PrivilegeMode;
+use crate::core::access::{Access, AccessType};
+use crate::device::bus::SystemBus;
+
+const V: u64 = 1 << 0;
+const R: u64 = 1 << 1;
+const W: u64 = 1 << 2;
+const A: u64 = 1 << 6;
+const D: u64 = 1 << 7;
+
+const SV39: u64 = 8 << 60;
+const SV48: u64 = 9 << 60;
+
+fn make_pte(ppn: u64, flags: u64) -> u64 {
+    (ppn << 10) | flags
+}
+
+fn write_pte(bus: &mut SystemBus, addr: u64, pte: u64) {
+    let access = Access::new(addr, AccessType::Store);
+    bus.write_xlen_bytes(access.into_physical(addr), pte, 8).expect("Setup PTE failed");
+}
+
+fn read_pte(bus: &mut SystemBus, addr: u64) -> u64 {
+    let access = Access::new(addr, AccessType::Load);
+    bus.read_xlen_bytes(access.into_physical(addr), 8, false).expect("Read RAM failed")
+}
+
+fn pte_addr(ppn: u64, va: u64, level: u32) -> u64 {
+    (ppn << 12) + ((va >> (12 + 9 * level)) & 0x1ff) * 8
+}
+
+#[test]
+fn test_sv39_4k_page_translation_and_accessed_bit() {
+    let mut mmu = Mmu::default();
+    let mut csrs = CsrFile::default();
+    let mut bus = SystemBus::default();
+
+    let root_ppn = 0x80001;
+    let l1_ppn = 0x80002;
+    let l0_ppn = 0x80003;
+    let target_ppn = 0x80004;
+    // VPN2 = 1, VPN1 = 1, VPN0 = 1
+    let va = 0x4020_1abc;
+
+    write_pte(&mut bus, pte_addr(root_ppn, va, 2), make_pte(l1_ppn, V));
+    write_pte(&mut bus, pte_addr(l1_ppn, va, 1), make_pte(l0_ppn, V));
+    let leaf_pte_addr = pte_addr(l0_ppn, va, 0);
+    write_pte(&mut bus, leaf_pte_addr, make_pte(target_ppn, V | R | W));
+
+    csrs.write(0x180, SV39 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let access = Access::new(va, AccessType::Load);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+
+    assert_eq!(res.unwrap().addr, (target_ppn << 12) | 0xabc);
+    let pte = read_pte(&mut bus, leaf_pte_addr);
+    assert_eq!(pte & (A | D), A, "Only A bit is set by load");
+}
+
+#[test]
+fn test_sv39_gigapage_and_tlb() {
+    let mut mmu = Mmu::default();
+    let mut csrs = CsrFile::default();
+    let mut bus = SystemBus::default();
+
+    let root_ppn = 0x80001;
+    let target_ppn = 0x80000;
+    // Upper half of address space
+    let va = 0xffff_ffc0_8012_3456;
+
+    let root_pte_addr = pte_addr(root_ppn, va, 2);
+    write_pte(&mut bus, root_pte_addr, make_pte(target_ppn, V | R | W));
+
+    csrs.write(0x180, SV39 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let access = Access::new(va, AccessType::Store);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+
+    assert_eq!(res.unwrap().addr, 0x8012_3456);
+    let pte = read_pte(&mut bus, root_pte_addr);
+    assert_eq!(pte & (A | D), A | D, "A and D bits are set by store");
+
+    write_pte(&mut bus, root_pte_addr, 0);
+    let access = Access::new(va + 0x10, AccessType::Load);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+    assert_eq!(res.unwrap().addr, 0x8012_3466, "Gigapage offset is kept by TLB");
+    assert_eq!(mmu.hit_count, 1);
+}
+
+#[test]
+fn test_sv39_misaligned_superpage() {
+    let mut mmu = Mmu::default();
+    let mut csrs = CsrFile::default();
+    let mut bus = SystemBus::default();
+
+    let root_ppn = 0x80001;
+    let l1_ppn = 0x80002;
+    let va = 0x4020_0000;
+
+    write_pte(&mut bus, pte_addr(root_ppn, va, 2), make_pte(l1_ppn, V));
+    // Megapage PPN must be aligned to 512 pages
+    let leaf_pte_addr = pte_addr(l1_ppn, va, 1);
+    write_pte(&mut bus, leaf_pte_addr, make_pte(0x80201, V | R));
+
+    csrs.write(0x180, SV39 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let access = Access::new(va, AccessType::Load);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+
+    assert_eq!(res.unwrap_err(), Exception::LoadPageFault(va));
+    assert_eq!(read_pte(&mut bus, leaf_pte_addr) & A, 0, "Faulted PTE is not updated");
+}
+
+#[test]
+fn test_sv39_reserved_bits() {
+    let mut mmu = Mmu::default();
+    let mut csrs = CsrFile::default();
+    let mut bus = SystemBus::default();
+
+    let root_ppn = 0x80001;
+    let va = 0x8000_0000;
+
+    // PBMT is set, Svpbmt is not supported
+    write_pte(&mut bus, pte_addr(root_ppn, va, 2), make_pte(0x80000, V | R) | (1 << 61));
+
+    csrs.write(0x180, SV39 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let access = Access::new(va, AccessType::Load);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+
+    assert_eq!(res.unwrap_err(), Exception::LoadPageFault(va));
+}
+
+#[test]
+fn test_sv48_terapage_and_canonical() {
+    let mut mmu = Mmu::default();
+    let mut csrs = CsrFile::default();
+    let mut bus = SystemBus::default();
+
+    let root_ppn = 0x80001;
+    let target_ppn = 0x800_0000;
+    // Bit 39 is set, only canonical in Sv48
+    let va = 0x0080_1234_5678;
+
+    write_pte(&mut bus, pte_addr(root_ppn, va, 3), make_pte(target_ppn, V | R | A));
+
+    csrs.write(0x180, SV39 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let access = Access::new(va, AccessType::Load);
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+    assert_eq!(res.unwrap_err(), Exception::LoadPageFault(va), "Not canonical in Sv39");
+
+    csrs.write(0x180, SV48 | root_ppn, PrivilegeMode::Machine, 0).unwrap();
+    let res = mmu.translate(access, PrivilegeMode::Supervisor, &csrs, &mut bus);
+    assert_eq!(res.unwrap().addr, (target_ppn << 12) | 0x1234_5678);
+}
+
+#[test]
+fn test_satp_unsupported_mode() {
+    let mut csrs = CsrFile::default();
+
+    csrs.write(0x180, SV48 | 0x80001, PrivilegeMode::Machine, 0).unwrap();
+    // Sv57
+    csrs.write(0x180, (10 << 60) | 0x80002, PrivilegeMode::Machine, 0).unwrap();
+
+    assert_eq!(csrs.read(0x180, PrivilegeMode::Machine, 0).unwrap(), SV48 | 0x80001,
+        "Write with unsupported mode has no effect");
+}
@@ -1,11 +1,12 @@
 use crate::Exception;
 use crate::core::{CsrFile, Mmu};
-use crate::core::mmu::{Sv32Pte};
+use crate::core::mmu::sv32::Sv32Pte;
 use crate::core::privilege::PrivilegeMode;
 use crate::core::access::{Access, AccessType};
 use crate::device::bus::SystemBus;
 use crate::core::mmu::tlb::TlbResult;
 
+#[allow(clippy::too_many_arguments)]
 fn make_pte(ppn: u32, 
     v: bool, 
     r: bool, 
@@ -230,15 +231,15 @@ fn test_flush_tlb() {
 
     // Entry 1
     let pte_a = make_pte(ppn_a, true, true, true, false, true, false, true, true); // V, R, W, U
-    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, false);
+    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, 0);
 
     // Entry 2
     let pte_b = make_pte(ppn_b, true, true, false, false, false, true, true, true); 
-    mmu.tlb.fill(0x20 << 12, Sv32Pte::from(pte_b), 1, false);
+    mmu.tlb.fill(0x20 << 12, Sv32Pte::from(pte_b), 1, 0);
 
     // Entry 3
     let pte_c = make_pte(ppn_c, true, true, true, false, true, false, true, true);
-    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_c), 2, false);
+    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_c), 2, 0);
 
     mmu.flush_tlb(0x10 << 12, 1);
 
@@ -255,7 +256,7 @@ fn test_flush_tlb() {
     assert!(matches!(res3, TlbResult::Hit(_, _)));
 
     // --- Refill Entry 1 for next test ---
-    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, false);
+    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, 0);
 
     mmu.flush_tlb(0, 1); // vaddr=0 triggers ASID flush
 
@@ -265,7 +266,7 @@ fn test_flush_tlb() {
     let res2 = mmu.tlb.lookup(&csrs, 0x20 << 12, 1, AccessType::Load, PrivilegeMode::Supervisor);
     assert!(matches!(res2, TlbResult::Hit(_, _)));
 
-    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, false); // Refill A
+    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, 0); // Refill A
     mmu.flush_tlb(0x10 << 12, 0); // asid=0 triggers VAddr flush
 
     let res1 = mmu.tlb.lookup(&csrs, 0x10 << 12, 1, AccessType::Load, PrivilegeMode::User);
@@ -275,7 +276,7 @@ fn test_flush_tlb() {
 
 
     // Flush All
-    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, false);
+    mmu.tlb.fill(0x10 << 12, Sv32Pte::from(pte_a), 1, 0);
     mmu.flush_tlb(0, 0);
 
     let res2 = mmu.tlb.lookup(&csrs, 0x20 << 12, 1, AccessType::Load, PrivilegeMode::Supervisor);
@@ -5,11 +5,15 @@ mod set;
 
 use std::ops::IndexMut;
 
+use crate::{StdResult, Xlen};
 use crate::core::{CsrFile, PrivilegeMode}; 
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
 use crate::core::access::AccessType;
-use crate::core::mmu::sv32::Sv32Pte;
+use crate::core::mmu::Pte;
 
-use entry::TlbEntry;
+use entry::{Tag, TlbEntry, TAG_BITS};
+use plru::PlruState;
 use set::TlbSet;
 
 pub use result::TlbResult;
@@ -23,17 +27,37 @@ pub struct Tlb {
     sets: [TlbSet; TLB_SET_NUM],
 }
 
+impl Snapshot for Tlb {
+    fn save(&self, w: &mut Writer) {
+        for set in self.sets.iter() {
+            set.entries.iter().for_each(|entry| w.bytes(&entry.into_bytes()));
+            w.u8(set.plru.into_bytes()[0]);
+        }
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        for set in self.sets.iter_mut() {
+            for entry in set.entries.iter_mut() {
+                // Safe: Length is checked by `bytes`
+                *entry = TlbEntry::from_bytes(r.bytes(size_of::<TlbEntry>())?.try_into().unwrap());
+            }
+            set.plru = PlruState::from_bytes([r.u8()?]);
+        }
+        Ok(())
+    }
+}
+
 impl Tlb {
     pub fn lookup(
         &mut self, 
         csrs: &CsrFile,
-        v_addr: u32,
+        v_addr: Xlen,
         asid: u16, 
         kind: AccessType, 
         mode: PrivilegeMode) -> TlbResult {
         let vpn = v_addr >> 12;
         let set_idx = Self::get_set_idx(vpn);
-        let tag = (vpn >> TLB_SET_SHIFT) as u16;
+        let tag = Self::get_tag(vpn);
         
         let target = self.find(set_idx, tag, asid);
         
@@ -44,7 +68,7 @@ impl Tlb {
 
                 if entry.access_check(kind, mode, csrs) {
                     if entry.ad_check(kind) {
-                        TlbResult::Hit(entry.is_mega_page(), entry.ppn())
+                        TlbResult::Hit(entry.page_size(), entry.ppn())
                     } else {
                         TlbResult::UpdateAD
                     }
@@ -56,10 +80,11 @@ impl Tlb {
         }
     }
 
-    pub fn fill(&mut self, v_addr: u32, pte: Sv32Pte, asid: u16, is_mega: bool) {
+    /// `level` of the leaf PTE, 0 is 4KiB page
+    pub fn fill(&mut self, v_addr: Xlen, pte: Pte, asid: u16, level: u8) {
         let vpn = v_addr >> 12;
         let set_idx = Self::get_set_idx(vpn);
-        let tag = (vpn >> TLB_SET_SHIFT) as u16;
+        let tag = Self::get_tag(vpn);
 
         let in_tlb = self.find(set_idx, tag, asid);
 
@@ -73,12 +98,12 @@ impl Tlb {
         victim.set_flags(pte);
         victim.set_ppn(pte.ppn());
         victim.set_asid(asid);
-        victim.set_page_size(is_mega as u8);
+        victim.set_page_size(level);
         
         self.sets[set_idx].plru.update(idx);
     }
 
-    fn find(&self, set_idx: usize, tag: u16, asid: u16) -> Option<usize> {
+    fn find(&self, set_idx: usize, tag: Tag, asid: u16) -> Option<usize> {
         for (i, entry) in self.sets[set_idx].entries.iter().enumerate() {
             if entry.is_valid() 
                 && entry.tag() == tag
@@ -89,11 +114,16 @@ impl Tlb {
         None
     }
 
-    fn get_set_idx(vpn: u32) -> usize {
+    fn get_set_idx(vpn: Xlen) -> usize {
         vpn as usize & (TLB_SET_NUM - 1)
     }
 
-    pub fn flush(&mut self, vpn: u32, asid: u16) {
+    /// Sign extended upper bits of RV64 virtual address are dropped
+    fn get_tag(vpn: Xlen) -> Tag {
+        ((vpn >> TLB_SET_SHIFT) & ((1 << TAG_BITS) - 1)) as Tag
+    }
+
+    pub fn flush(&mut self, vpn: Xlen, asid: u16) {
         match (vpn, asid) {
             (0, 0) => self.flush_all(),
             (0, a) => self.flush_by_asid(a),
@@ -118,9 +148,9 @@ impl Tlb {
         });
     }
 
-    fn flush_by_address(&mut self, vpn: u32) {
+    fn flush_by_address(&mut self, vpn: Xlen) {
         let set_index = Self::get_set_idx(vpn);
-        let tag = (vpn >> TLB_SET_SHIFT) as u16;
+        let tag = Self::get_tag(vpn);
 
         self.sets[set_index].entries.iter_mut()
             .for_each(|entry| {
@@ -130,9 +160,9 @@ impl Tlb {
             }); 
     }
 
-    fn flush_by_both(&mut self, vpn: u32, asid: u16) {
+    fn flush_by_both(&mut self, vpn: Xlen, asid: u16) {
         let set_index = Self::get_set_idx(vpn);
-        let tag = (vpn >> TLB_SET_SHIFT) as u16;
+        let tag = Self::get_tag(vpn);
 
         self.sets[set_index].entries.iter_mut()
             .for_each(|entry| {
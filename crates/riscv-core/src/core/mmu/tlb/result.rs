@@ -1,6 +1,9 @@
+use crate::Xlen;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TlbResult {
-    Hit(bool, u32),
+    /// Level of the page and its PPN
+    Hit(u8, Xlen),
     Miss,
     PageFault,
     UpdateAD,
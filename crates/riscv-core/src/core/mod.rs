@@ -13,13 +13,16 @@ pub(crate) mod access;
 
 use pc::PC;
 use register::RegisterFile;
+#[cfg(feature = "f")]
+pub(crate) use register::FpRegisterFile;
 
 #[cfg(feature = "zicsr")]
-pub(crate) use csr::CsrFile;
+pub(crate) use csr::{Counters, CsrFile, HpmEvent};
 #[cfg(feature = "zicsr")]
 pub(crate) use privilege::PrivilegeMode;
 #[cfg(feature = "s")]
 pub(crate) use mmu::Mmu;
 
 
+pub(crate) use cpu::IO_LIMIT;
 pub use cpu::Cpu as RiscV;
@@ -1,11 +1,19 @@
+use crate::{SXlen, StdResult, Xlen};
 use crate::constance::DRAM_BASE_ADDR;
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PC {
-    pointer: u32,
+    pointer: Xlen,
+    reset_vector: Xlen,
 }
 
 impl PC {
+    pub fn new(reset_vector: Xlen) -> Self {
+        PC { pointer: reset_vector, reset_vector }
+    }
+
     pub fn step(&mut self) {
         self.pointer += 4;
     }
@@ -15,29 +23,46 @@ impl PC {
         self.pointer += 2;
     }
 
-    pub fn get(&self) -> u32 {
+    pub fn get(&self) -> Xlen {
         self.pointer
     }
 
-    pub fn set(&mut self, pointer: u32) {
+    pub fn set(&mut self, pointer: Xlen) {
         self.pointer = pointer;
     }
 
     pub fn reset(&mut self) {
-        self.pointer = DRAM_BASE_ADDR;
+        self.pointer = self.reset_vector;
     }
 
     pub fn related_addressing(&mut self, offset: i32) {
-        self.pointer = self.pointer.wrapping_add_signed(offset);
+        self.pointer = self.pointer.wrapping_add_signed(offset as SXlen);
     }
 
-    pub fn directed_addressing(&mut self, address: u32) {
+    pub fn directed_addressing(&mut self, address: Xlen) {
         self.pointer = address & !1;
     }
 }
 
+impl Snapshot for PC {
+    fn save(&self, w: &mut Writer) {
+        w.xlen(self.pointer);
+        w.xlen(self.reset_vector);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        self.pointer = r.xlen()?;
+        // Reset vector is given by `MachineConfig`, snapshot can not change it
+        let reset_vector = r.xlen()?;
+        if reset_vector != self.reset_vector {
+            return Err(SnapshotError::ResetPc(reset_vector as u64));
+        }
+        Ok(())
+    }
+}
+
 impl Default for PC {
     fn default() -> Self {
-        PC { pointer: DRAM_BASE_ADDR }
+        Self::new(DRAM_BASE_ADDR)
     }
 }
@@ -1,10 +1,14 @@
+use crate::{StdResult, Xlen};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct RegisterFile {
-    regs: [u32; 32],
+    regs: [Xlen; 32],
 }
 
 impl RegisterFile {
-    pub fn write(&mut self, id: u8, data: u32) {
+    pub fn write(&mut self, id: u8, data: Xlen) {
         if id == 0 {
             return;
         }
@@ -15,14 +19,88 @@ impl RegisterFile {
         self.regs.fill(0);
     }
 
-    pub fn inspect(&self) -> [u32; 32] {
+    pub fn inspect(&self) -> [Xlen; 32] {
         self.regs
     }
 }
 
+impl Snapshot for RegisterFile {
+    fn save(&self, w: &mut Writer) {
+        self.regs.iter().for_each(|reg| w.xlen(*reg));
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        for reg in self.regs.iter_mut() {
+            *reg = r.xlen()?;
+        }
+        self.regs[0] = 0;
+        Ok(())
+    }
+}
+
 impl std::ops::Index<u8> for RegisterFile {
-    type Output = u32;
+    type Output = Xlen;
     fn index(&self, index: u8) -> &Self::Output {
         &self.regs[index as usize]
     }
-}
\ No newline at end of file
+}
+
+/// F and D registers. Single precision value is NaN-boxed in the low 32 bits
+#[cfg(feature = "f")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FpRegisterFile {
+    regs: [u64; 32],
+}
+
+#[cfg(feature = "f")]
+const NAN_BOX: u64 = 0xffff_ffff_0000_0000;
+#[cfg(feature = "f")]
+const CANONICAL_NAN_S: u32 = 0x7fc0_0000;
+
+#[cfg(feature = "f")]
+impl FpRegisterFile {
+    /// Single precision value, not correctly NaN-boxed one is canonical NaN
+    pub fn read_s(&self, id: u8) -> u32 {
+        let data = self.regs[id as usize];
+        if data & NAN_BOX == NAN_BOX {
+            data as u32
+        } else {
+            CANONICAL_NAN_S
+        }
+    }
+
+    pub fn write_s(&mut self, id: u8, data: u32) {
+        self.regs[id as usize] = NAN_BOX | data as u64;
+    }
+
+    /// Whole 64 bits register
+    pub fn read(&self, id: u8) -> u64 {
+        self.regs[id as usize]
+    }
+
+    pub fn write(&mut self, id: u8, data: u64) {
+        self.regs[id as usize] = data;
+    }
+
+    pub fn reset(&mut self) {
+        self.regs.fill(0);
+    }
+
+    pub fn inspect(&self) -> [u64; 32] {
+        self.regs
+    }
+}
+
+#[cfg(feature = "f")]
+impl Snapshot for FpRegisterFile {
+    fn save(&self, w: &mut Writer) {
+        self.regs.iter().for_each(|reg| w.u64(*reg));
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        for reg in self.regs.iter_mut() {
+            *reg = r.u64()?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,22 @@
+mod breakpoint;
+mod history;
+mod trace;
+
+use crate::Xlen;
+#[cfg(feature = "zicsr")]
+use crate::core::CsrFile;
 #[cfg(feature = "s")]
 use crate::core::PrivilegeMode;
 
+pub use breakpoint::{AddrSpace, BreakHit, Breakpoint, BreakpointId, HitCondition, WatchKind};
+pub use trace::{Commit, MemAccess, TraceSink, Tracer};
+pub(crate) use breakpoint::Debugger;
+pub(crate) use history::{Delta, History, MemWrite};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct MachineInfo {
-    pub dram_size: usize,
-    pub dram_base: u32,
+    pub dram_size_mb: usize,
+    pub dram_base: Xlen,
     pub page_size: usize,
     #[cfg(feature = "s")]
     pub hit_rate: f32,
@@ -13,7 +25,7 @@ pub struct MachineInfo {
 }
 
 impl MachineInfo {
-    pub fn new(dram_size: usize, dram_base: u32 , page_size: usize, 
+    pub fn new(dram_size_mb: usize, dram_base: Xlen , page_size: usize, 
         #[cfg(feature = "s")] hit: usize, 
         #[cfg(feature = "s")] miss: usize, 
         #[cfg(feature = "s")] mode: PrivilegeMode) -> Self {
@@ -31,21 +43,66 @@ impl MachineInfo {
             PrivilegeMode::User       => "User"
         }.to_string();
 
-        MachineInfo { dram_size, dram_base, page_size, 
+        MachineInfo { dram_size_mb, dram_base, page_size, 
             #[cfg(feature = "s")] hit_rate, 
             #[cfg(feature = "s")] curr_mode}
     }
 }
 
 pub trait DebugInterface {
-    fn inspect_regs(&self) -> [u32; 32];
+    fn inspect_regs(&self) -> [Xlen; 32];
+
+    fn inspect_pc(&self) -> Xlen;
+
+    /// FP registers, single precision value is NaN-boxed
+    #[cfg(feature = "f")]
+    fn inspect_fregs(&self) -> [u64; 32];
+
+    #[cfg(feature = "zicsr")]
+    fn inspect_csrs(&self) -> Vec<(String, Xlen)>;
 
-    fn inspect_pc(&self) -> u32;
+    /// Read CSR by address without privilege check. `None` if not implemented
+    #[cfg(feature = "zicsr")]
+    fn inspect_csr(&self, addr: u16) -> Option<Xlen>;
 
+    /// Current privilege mode, encoded like `mstatus.MPP`
     #[cfg(feature = "zicsr")]
-    fn inspect_csrs(&self) -> Vec<(String, u32)>;
+    fn inspect_privilege(&self) -> u8;
+
+    /// Unmapped bytes are read as zero
+    fn inspect_bus(&self, start: Xlen, len: usize) -> Vec<u8>;
 
-    fn inspect_bus(&self, start: u32, len: usize) -> Vec<u8>;
+    /// Same as `inspect_bus`, but `None` if any byte is not mapped
+    fn peek_bus(&self, start: Xlen, len: usize) -> Option<Vec<u8>>;
 
     fn get_info(&self) -> MachineInfo;
-}
\ No newline at end of file
+}
+/// Modify machine state for debugger. Writes bypass privilege and PMP checks
+pub trait DebugControl {
+    /// Write general register `id`. Writing `x0` is ignored
+    fn write_reg(&mut self, id: u8, data: Xlen);
+
+    fn set_pc(&mut self, pc: Xlen);
+
+    /// Return `false` if CSR is not implemented or read-only
+    #[cfg(feature = "zicsr")]
+    fn write_csr(&mut self, addr: u16, data: Xlen) -> bool;
+
+    /// Write FP register `id`, single precision value need to be NaN-boxed
+    #[cfg(feature = "f")]
+    fn write_freg(&mut self, id: u8, data: u64);
+
+    /// Return `false` if any byte is not mapped
+    fn write_bus(&mut self, addr: Xlen, data: &[u8]) -> bool;
+
+    /// Override privilege mode encoded like `mstatus.MPP`. Return `false` if mode is not supported
+    #[cfg(feature = "zicsr")]
+    fn set_privilege(&mut self, mode: u8) -> bool;
+}
+
+/// CSR at `addr` is implemented by this build. Unlike `DebugInterface::inspect_csr`,
+/// it does not depend on machine state like `mstatus.FS`
+#[cfg(feature = "zicsr")]
+pub fn csr_implemented(addr: u16) -> bool {
+    CsrFile::implemented(addr)
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use crate::{Trap, Xlen};
+use crate::core::access::AccessType;
+use crate::debug::{Commit, MemAccess};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BreakpointId(pub u32);
+
+/// Access that breakpoint is watching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Execute,
+    Read,
+    Write,
+    /// Read or write
+    Access,
+}
+
+/// Address space that breakpoint address is compared in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddrSpace {
+    #[default]
+    Virtual,
+    Physical,
+}
+
+/// Stop only when hit count meet the condition. Hit count start from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HitCondition {
+    #[default]
+    Always,
+    Equal(u64),
+    AtLeast(u64),
+    /// Every N hits
+    Multiple(u64),
+}
+
+impl HitCondition {
+    fn check(&self, hits: u64) -> bool {
+        match *self {
+            HitCondition::Always      => true,
+            HitCondition::Equal(n)    => hits == n,
+            HitCondition::AtLeast(n)  => hits >= n,
+            HitCondition::Multiple(n) => n != 0 && hits.is_multiple_of(n),
+        }
+    }
+}
+
+/// Breakpoint or watchpoint on `addr..addr + len`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: Xlen,
+    pub len: Xlen,
+    pub kind: WatchKind,
+    pub space: AddrSpace,
+    pub condition: HitCondition,
+    /// Times of matched access, include the ones not meeting condition
+    pub hits: u64,
+}
+
+impl Breakpoint {
+    /// Execute breakpoint at virtual `addr`
+    pub fn exec(addr: Xlen) -> Self {
+        Breakpoint::watch(addr, 1, WatchKind::Execute)
+    }
+
+    /// Watch `len` bytes from virtual `addr`
+    pub fn watch(addr: Xlen, len: Xlen, kind: WatchKind) -> Self {
+        Breakpoint { 
+            addr, len, kind, 
+            space: AddrSpace::default(), 
+            condition: HitCondition::default(), 
+            hits: 0 
+        }
+    }
+
+    pub fn with_space(mut self, space: AddrSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    pub fn with_condition(mut self, condition: HitCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Range can end past the top of address space, so compare in `u128`
+    fn overlap(&self, addr: Xlen, len: Xlen) -> bool {
+        let (start, end) = (self.addr as u128, self.addr as u128 + self.len.max(1) as u128);
+        (addr as u128) < end && start < addr as u128 + len as u128
+    }
+}
+
+/// Breakpoint that stopped the machine. Execute breakpoint stop before the instruction,
+/// watchpoint stop after the instruction that accessed `addr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakHit {
+    pub id: BreakpointId,
+    /// `Execute`, `Read` or `Write`
+    pub kind: WatchKind,
+    /// Accessed address in breakpoint's address space
+    pub addr: Xlen,
+}
+
+impl std::fmt::Display for BreakHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.kind {
+            WatchKind::Execute => "Breakpoint",
+            WatchKind::Read    => "Read watchpoint",
+            WatchKind::Write   => "Write watchpoint",
+            WatchKind::Access  => "Access watchpoint",
+        };
+        write!(f, "{name} {} hit at {:#010x}", self.id.0, self.addr)
+    }
+}
+
+/// Breakpoints of a hart, checked by `fetch` and `Lsu`. Also collect the commit of current step for tracer
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct Debugger {
+    points: BTreeMap<BreakpointId, Breakpoint>,
+    next_id: u32,
+    /// Hit in current step
+    hit: Option<BreakHit>,
+    /// PC stopped by execute breakpoint, the next fetch from it is not checked
+    resume: Option<Xlen>,
+    /// Commit of current step, `Some` only when tracing
+    trace: Option<Commit>,
+}
+
+impl Debugger {
+    pub fn add(&mut self, point: Breakpoint) -> BreakpointId {
+        let id = BreakpointId(self.next_id);
+        self.next_id += 1;
+        self.points.insert(id, point);
+        id
+    }
+
+    pub fn remove(&mut self, id: BreakpointId) -> bool {
+        self.points.remove(&id).is_some()
+    }
+
+    pub fn get(&self, id: BreakpointId) -> Option<&Breakpoint> {
+        self.points.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BreakpointId, &Breakpoint)> {
+        self.points.iter().map(|(id, point)| (*id, point))
+    }
+
+    /// Instruction fetch at `pc`
+    pub fn fetch(&mut self, va: Xlen, pa: Xlen) {
+        if self.resume.take() == Some(va) {
+            return;
+        }
+        self.check(va, pa, 1, None);
+        if self.fetch_stopped() {
+            self.resume = Some(va);
+        }
+    }
+
+    /// Data access of `len` bytes by `Lsu`
+    pub fn access(&mut self, va: Xlen, pa: Xlen, len: usize, kind: AccessType) {
+        self.check(va, pa, len as Xlen, Some(kind));
+    }
+
+    /// Execute breakpoint is hit, the instruction should not be executed
+    pub fn fetch_stopped(&self) -> bool {
+        self.hit.is_some_and(|hit| hit.kind == WatchKind::Execute)
+    }
+
+    pub fn take_hit(&mut self) -> Option<BreakHit> {
+        self.hit.take()
+    }
+
+    /// Clear hit counts and stop state, breakpoints are kept
+    pub fn reset(&mut self) {
+        self.points.values_mut().for_each(|point| point.hits = 0);
+        self.hit = None;
+        self.resume = None;
+        self.trace = None;
+    }
+
+    /// Start collecting commit of the step at `pc`
+    pub fn trace_start(&mut self, pc: Xlen, mode: u8) {
+        self.trace = Some(Commit::new(pc, mode));
+    }
+
+    pub fn trace_take(&mut self) -> Option<Commit> {
+        self.trace.take()
+    }
+
+    pub fn trace_raw(&mut self, raw: u32) {
+        if let Some(commit) = self.trace.as_mut() {
+            commit.raw = Some(raw);
+        }
+    }
+
+    pub fn trace_reg(&mut self, id: u8, data: Xlen) {
+        if let Some(commit) = self.trace.as_mut() && id != 0 {
+            commit.regs.push((id, data));
+        }
+    }
+
+    #[cfg(feature = "zicsr")]
+    pub fn trace_csr(&mut self, addr: u16, data: Xlen) {
+        if let Some(commit) = self.trace.as_mut() {
+            commit.csrs.push((addr, data));
+        }
+    }
+
+    #[cfg(feature = "f")]
+    pub fn trace_freg(&mut self, id: u8, data: u64) {
+        if let Some(commit) = self.trace.as_mut() {
+            commit.fregs.push((id, data));
+        }
+    }
+
+    /// Only the low `len` bytes of `data` are kept
+    pub fn trace_mem(&mut self, kind: AccessType, addr: Xlen, len: usize, data: u64) {
+        if let Some(commit) = self.trace.as_mut() {
+            let data = data & u64::MAX.checked_shr(64 - 8 * len as u32).unwrap_or(0);
+            commit.mem.push(MemAccess { kind, addr, len: len as u8, data });
+        }
+    }
+
+    pub fn trace_trap(&mut self, trap: Trap) {
+        if let Some(commit) = self.trace.as_mut() {
+            commit.trap = Some(trap);
+        }
+    }
+
+    /// `access` is `None` for instruction fetch. All matched breakpoints are counted,
+    /// the first one meeting condition is the hit
+    fn check(&mut self, va: Xlen, pa: Xlen, len: Xlen, access: Option<AccessType>) {
+        if self.points.is_empty() {
+            return;
+        }
+        let (read, write) = match access {
+            None                    => (false, false),
+            Some(AccessType::Load)  => (true, false),
+            Some(AccessType::Store) => (false, true),
+            Some(AccessType::Fetch) => return,
+            #[cfg(feature = "a")]
+            Some(AccessType::Amo)   => (true, true),
+        };
+
+        for (id, point) in self.points.iter_mut() {
+            let kind = match point.kind {
+                WatchKind::Execute if access.is_none() => WatchKind::Execute,
+                WatchKind::Read if read                => WatchKind::Read,
+                WatchKind::Write if write              => WatchKind::Write,
+                WatchKind::Access if write             => WatchKind::Write,
+                WatchKind::Access if read              => WatchKind::Read,
+                _ => continue,
+            };
+            let addr = match point.space {
+                AddrSpace::Virtual  => va,
+                AddrSpace::Physical => pa,
+            };
+            if !point.overlap(addr, len) {
+                continue;
+            }
+
+            point.hits += 1;
+            if self.hit.is_none() && point.condition.check(point.hits) {
+                self.hit = Some(BreakHit { id: *id, kind, addr });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_condition() {
+        let mut debugger = Debugger::default();
+        let id = debugger.add(Breakpoint::exec(0x100).with_condition(HitCondition::Equal(2)));
+
+        debugger.fetch(0x100, 0x100);
+        assert_eq!(debugger.take_hit(), None);
+        debugger.fetch(0x100, 0x100);
+        assert_eq!(debugger.take_hit(), Some(BreakHit { id, kind: WatchKind::Execute, addr: 0x100 }));
+        assert_eq!(debugger.get(id).unwrap().hits, 2);
+    }
+
+    #[test]
+    fn test_resume_from_breakpoint() {
+        let mut debugger = Debugger::default();
+        debugger.add(Breakpoint::exec(0x100));
+
+        debugger.fetch(0x100, 0x100);
+        assert!(debugger.fetch_stopped());
+        debugger.take_hit();
+
+        debugger.fetch(0x100, 0x100);
+        assert_eq!(debugger.take_hit(), None, "Resume skip the breakpoint once");
+        debugger.fetch(0x100, 0x100);
+        assert!(debugger.fetch_stopped());
+    }
+
+    #[test]
+    fn test_watch_range() {
+        let mut debugger = Debugger::default();
+        let id = debugger.add(Breakpoint::watch(0x1004, 4, WatchKind::Access).with_space(AddrSpace::Physical));
+
+        debugger.access(0x0, 0x1000, 4, AccessType::Store);
+        assert_eq!(debugger.take_hit(), None);
+        debugger.access(0x0, 0x1006, 1, AccessType::Load);
+        assert_eq!(debugger.take_hit(), Some(BreakHit { id, kind: WatchKind::Read, addr: 0x1006 }));
+        debugger.access(0x1004, 0x2000, 4, AccessType::Store);
+        assert_eq!(debugger.take_hit(), None, "Virtual address is not compared");
+    }
+
+    #[test]
+    fn test_top_of_address_space() {
+        let mut debugger = Debugger::default();
+        let exec = debugger.add(Breakpoint::exec(Xlen::MAX));
+        let watch = debugger.add(Breakpoint::watch(Xlen::MAX - 1, 4, WatchKind::Write));
+
+        debugger.fetch(Xlen::MAX - 3, Xlen::MAX - 3);
+        assert_eq!(debugger.take_hit(), None);
+        debugger.fetch(Xlen::MAX, Xlen::MAX);
+        assert_eq!(debugger.take_hit(), Some(BreakHit { id: exec, kind: WatchKind::Execute, addr: Xlen::MAX }));
+
+        debugger.access(Xlen::MAX, Xlen::MAX, 1, AccessType::Store);
+        assert_eq!(debugger.take_hit(), Some(BreakHit { id: watch, kind: WatchKind::Write, addr: Xlen::MAX }));
+    }
+}
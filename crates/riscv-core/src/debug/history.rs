@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+
+use crate::{Halt, Xlen};
+#[cfg(feature = "zicsr")]
+use crate::core::{Counters, CsrFile, PrivilegeMode};
+
+/// RAM bytes of `addr` before and after a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MemWrite {
+    pub addr: Xlen,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// Changes of a step, each as `(old, new)`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Delta {
+    pub pc: (Xlen, Xlen),
+    /// Only changed registers, `(id, old, new)`
+    pub regs: Vec<(u8, Xlen, Xlen)>,
+    /// Only changed FP registers, `(id, old, new)`
+    #[cfg(feature = "f")]
+    pub fregs: Vec<(u8, u64, u64)>,
+    #[cfg(feature = "zicsr")]
+    pub mode: (PrivilegeMode, PrivilegeMode),
+    /// Whole CSR file if any CSR other than counters changed
+    #[cfg(feature = "zicsr")]
+    pub csrs: Option<Box<(CsrFile, CsrFile)>>,
+    /// Counters are kept apart since `mcycle` changes on every step
+    #[cfg(feature = "zicsr")]
+    pub counters: (Counters, Counters),
+    #[cfg(feature = "a")]
+    pub reservation: (Option<Xlen>, Option<Xlen>),
+    pub halt: (Option<Halt>, Option<Halt>),
+    pub mem: Vec<MemWrite>,
+}
+
+/// Bounded ring buffer of step deltas. Deltas before `cursor` can be undone,
+/// the others are undone steps can be replayed
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct History {
+    deltas: VecDeque<Delta>,
+    capacity: usize,
+    cursor: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        History { deltas: VecDeque::with_capacity(capacity), capacity, cursor: 0 }
+    }
+
+    /// Record a new step. Replayable steps are dropped, and the oldest one if full
+    pub fn push(&mut self, delta: Delta) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.deltas.truncate(self.cursor);
+        if self.deltas.len() == self.capacity {
+            self.deltas.pop_front();
+        }
+        self.deltas.push_back(delta);
+        self.cursor = self.deltas.len();
+    }
+
+    /// Move back a step and return it to be undone
+    pub fn back(&mut self) -> Option<&Delta> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        self.deltas.get(self.cursor)
+    }
+
+    /// Move forward a step and return it to be replayed
+    pub fn forward(&mut self) -> Option<&Delta> {
+        let delta = self.deltas.get(self.cursor)?;
+        self.cursor += 1;
+        Some(delta)
+    }
+
+    /// Return number of (undoable, replayable) steps
+    pub fn len(&self) -> (usize, usize) {
+        (self.cursor, self.deltas.len() - self.cursor)
+    }
+
+    pub fn clear(&mut self) {
+        self.deltas.clear();
+        self.cursor = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delta, History};
+    use crate::Xlen;
+
+    fn delta(pc: Xlen) -> Delta {
+        Delta {
+            pc: (pc, pc + 4),
+            regs: Vec::new(),
+            #[cfg(feature = "f")]
+            fregs: Vec::new(),
+            #[cfg(feature = "zicsr")]
+            mode: Default::default(),
+            #[cfg(feature = "zicsr")]
+            csrs: None,
+            #[cfg(feature = "zicsr")]
+            counters: Default::default(),
+            #[cfg(feature = "a")]
+            reservation: (None, None),
+            halt: (None, None),
+            mem: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_bounded() {
+        let mut history = History::new(2);
+        history.push(delta(0));
+        history.push(delta(4));
+        history.push(delta(8));
+        assert_eq!(history.len(), (2, 0));
+
+        assert_eq!(history.back().map(|d| d.pc.0), Some(8));
+        assert_eq!(history.back().map(|d| d.pc.0), Some(4));
+        assert!(history.back().is_none(), "Oldest step is dropped");
+        assert_eq!(history.len(), (0, 2));
+    }
+
+    #[test]
+    fn test_history_replay() {
+        let mut history = History::new(4);
+        history.push(delta(0));
+        history.push(delta(4));
+        history.back();
+        history.back();
+
+        assert_eq!(history.forward().map(|d| d.pc.0), Some(0));
+        assert_eq!(history.len(), (1, 1));
+
+        // New step drop the undone one
+        history.push(delta(100));
+        assert_eq!(history.len(), (2, 0));
+        assert!(history.forward().is_none());
+        assert_eq!(history.back().map(|d| d.pc.0), Some(100));
+    }
+}
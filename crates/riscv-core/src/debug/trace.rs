@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{Trap, Xlen};
+use crate::core::access::AccessType;
+
+/// Data access of a step. AMO is traced as a load then a store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemAccess {
+    /// `Load` or `Store`
+    pub kind: AccessType,
+    /// Virtual address
+    pub addr: Xlen,
+    pub len: u8,
+    /// Loaded or stored `len` bytes
+    pub data: u64,
+}
+
+/// Architectural effects of a step, given to `Tracer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub pc: Xlen,
+    /// Privilege mode before the step, encoded like `mstatus.MPP`. Always 3 without Zicsr
+    pub mode: u8,
+    /// `None` if fetch failed or interrupt is taken. Compressed instruction has 16 bits
+    pub raw: Option<u32>,
+    /// Written general registers in order, `x0` is not included
+    pub regs: Vec<(u8, Xlen)>,
+    /// Written CSRs in order, with the value read back. Empty without Zicsr
+    pub csrs: Vec<(u16, Xlen)>,
+    /// Written FP registers in order, whole 64 bits. Empty without F
+    pub fregs: Vec<(u8, u64)>,
+    pub mem: Vec<MemAccess>,
+    /// Taken trap. Effects before the trap are kept, but the instruction is not committed
+    pub trap: Option<Trap>,
+}
+
+impl Commit {
+    pub(crate) fn new(pc: Xlen, mode: u8) -> Self {
+        Commit { pc, mode, raw: None, regs: Vec::new(), csrs: Vec::new(), fregs: Vec::new(), mem: Vec::new(), trap: None }
+    }
+}
+
+/// Receive a `Commit` after each step. Enabled by `MachineConfig::with_tracer`
+pub trait Tracer: Send {
+    fn commit(&mut self, commit: &Commit);
+}
+
+/// Shared `Tracer`, cloned machine traces into the same one
+#[derive(Clone)]
+pub struct TraceSink(Arc<Mutex<dyn Tracer>>);
+
+impl TraceSink {
+    pub fn new<T: Tracer + 'static>(tracer: T) -> Self {
+        TraceSink(Arc::new(Mutex::new(tracer)))
+    }
+
+    pub(crate) fn commit(&self, commit: &Commit) {
+        self.0.lock().unwrap().commit(commit);
+    }
+}
+
+impl std::fmt::Debug for TraceSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TraceSink")
+    }
+}
+
+impl PartialEq for TraceSink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TraceSink {}
@@ -1,115 +1,403 @@
-use crate::Result;
-use crate::core::access::{Access, Physical};
+use std::sync::{Arc, Mutex};
+
+use crate::{Halt, Result, RiscVError, StdResult, Xlen};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::config::MachineConfig;
+use crate::core::access::{Access, AccessType, Physical};
 use super::Device;
+use super::clint::Clint;
+use super::htif::{self, Htif};
 use super::memory::{Memory, PAGE_SIZE};
+use super::plic::{Plic, SOURCE_NUM};
 use super::uart::Uart;
 
 use MappedDevice::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MappedDevice {
+    Clint,
+    Plic,
     Uart,
     Ram,
+    Mmio(usize),
+}
+
+/// Device registered by `add_device`
+#[derive(Clone)]
+struct MmioDevice {
+    irq: Option<u32>,
+    device: Arc<Mutex<dyn Device + Send>>,
+}
+
+impl std::fmt::Debug for MmioDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MmioDevice")
+            .field("irq", &self.irq)
+            .finish_non_exhaustive()
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+impl PartialEq for MmioDevice {
+    fn eq(&self, other: &Self) -> bool {
+        self.irq == other.irq && Arc::ptr_eq(&self.device, &other.device)
+    }
+}
+
+impl Eq for MmioDevice {}
+
+/// Physical address range of a device. `end` is inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Region {
+    base: Xlen,
+    end: Xlen,
+    device: MappedDevice,
+}
+
+impl Region {
+    fn new(base: Xlen, size: usize, device: MappedDevice) -> StdResult<Self, RiscVError> {
+        let end = size.checked_sub(1)
+            .and_then(|len| Xlen::try_from(len).ok())
+            .and_then(|len| base.checked_add(len))
+            .ok_or(RiscVError::InvalidDeviceRange(base, size.min(Xlen::MAX as usize) as Xlen))?;
+
+        Ok(Region { base, end, device })
+    }
+
+    fn overlap(&self, other: &Region) -> bool {
+        self.base <= other.end && other.base <= self.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SystemBus {
+    clint: Clint,
+    plic: Plic,
     uart: Uart,
+    htif: Htif,
     ram: Memory,
+    mmio: Vec<MmioDevice>,
+    /// RAM is the first region that most of access hit it
+    regions: Vec<Region>,
+    /// Old bytes of RAM writes, `Some` while a step is recorded into history
+    journal: Option<Vec<(Xlen, Vec<u8>)>>,
 }
 
-pub const UART_BASE: u32 = 0x1000_0000;
-pub const UART_END: u32 = 0x1000_00FF;
-pub const DRAM_BASE_ADDR: u32 = 0x8000_0000;
+pub const CLINT_BASE: Xlen = 0x0200_0000;
+pub const CLINT_SIZE: usize = 0x1_0000;
+pub const PLIC_BASE: Xlen = 0x0C00_0000;
+pub const PLIC_SIZE: usize = 0x400_0000;
+pub const UART_BASE: Xlen = 0x1000_0000;
+pub const UART_SIZE: usize = 0x100;
+/// PLIC source id of UART's interrupt line
+pub const UART_IRQ: u32 = 10;
+pub const DRAM_BASE_ADDR: Xlen = 0x8000_0000;
 
 impl SystemBus {
+    pub fn new(config: &MachineConfig) -> StdResult<Self, RiscVError> {
+        let ram = Memory::new(config.ram_size);
+
+        let mut bus = SystemBus {
+            clint: Clint::default(),
+            plic: Plic::default(),
+            uart: Uart::new(config.uart_sink.clone()),
+            htif: Htif::new(config.uart_sink.clone()),
+            ram,
+            mmio: Vec::new(),
+            regions: Vec::new(),
+            journal: None,
+        };
+
+        bus.add_region(Region::new(config.ram_base, bus.ram.size, Ram)?)?;
+        let devices = [
+            (config.clint_base, CLINT_SIZE, Clint),
+            (config.plic_base, PLIC_SIZE, Plic),
+            (config.uart_base, UART_SIZE, Uart),
+        ];
+        for (base, size, device) in devices {
+            if let Some(base) = base {
+                bus.add_region(Region::new(base, size, device)?)?;
+            }
+        }
+
+        Ok(bus)
+    }
+
+    fn add_region(&mut self, region: Region) -> StdResult<(), RiscVError> {
+        if self.regions.iter().any(|r| r.overlap(&region)) {
+            Err(RiscVError::DeviceOverlap(region.base, region.end))
+        } else {
+            self.regions.push(region);
+            Ok(())
+        }
+    }
+
     fn mapping(&self, access: &mut Access<Physical>) -> Result<MappedDevice> {
         let addr = access.addr;
-        Ok(match addr {
-            UART_BASE..=UART_END => {
-               access.addr = addr - UART_BASE;
-               Uart
-            }
-            DRAM_BASE_ADDR.. => {
-                let ram_addr = addr - DRAM_BASE_ADDR;
-                if ram_addr as usize >= self.ram.size {
-                    return Err(access.into_access_exception())
-                } else {
-                    access.addr = ram_addr;
-                    Ram
-                }
+        match self.regions.iter().find(|r| (r.base..=r.end).contains(&addr)) {
+            Some(region) => {
+                access.addr = addr - region.base;
+                Ok(region.device)
             },
-            _ => return Err(access.into_access_exception()),
-        })
+            None => Err(access.into_access_exception()),
+        }
+    }
+
+    /// Keep old bytes of RAM write at `addr`. `access` is already mapped
+    fn journal(&mut self, addr: Xlen, access: Access<Physical>, len: usize) {
+        if let Some(journal) = self.journal.as_mut() {
+            // Page not allocated yet is zero
+            let mut old = vec![0; len];
+            let _ = self.ram.read_bytes(access, len, &mut old);
+            journal.push((addr, old));
+        }
+    }
+
+    /// Start keeping old bytes of RAM writes
+    pub fn start_journal(&mut self) {
+        self.journal = Some(Vec::new());
+    }
+
+    /// Stop keeping RAM writes and return them in order
+    pub fn take_journal(&mut self) -> Vec<(Xlen, Vec<u8>)> {
+        self.journal.take().unwrap_or_default()
+    }
+
+    /// Write RAM without HTIF watch and journal. For restoring history
+    pub fn restore_ram(&mut self, addr: Xlen, data: &[u8]) {
+        let mut access = Access::new(addr, AccessType::Store);
+        if let Ok(Ram) = self.mapping(&mut access) {
+            let _ = self.ram.write_bytes(access, data.len(), data);
+        }
+    }
+
+    /// Map `device` at `base..base + size`. Connect to PLIC source `irq` if given
+    pub fn add_device<D: Device + Send + 'static>(&mut self, base: Xlen, size: u32, irq: Option<u32>, device: D) -> StdResult<(), RiscVError> {
+        let region = Region::new(base, size as usize, Mmio(self.mmio.len()))?;
+
+        if let Some(id) = irq 
+            && (id == 0 || id as usize >= SOURCE_NUM || id == UART_IRQ 
+                || self.mmio.iter().any(|dev| dev.irq == Some(id))) {
+            return Err(RiscVError::InvalidIrq(id));
+        }
+
+        self.add_region(region)?;
+        self.mmio.push(MmioDevice { irq, device: Arc::new(Mutex::new(device)) });
+        Ok(())
     }
 
     pub fn read_u32(&self, access: Access<Physical>) -> Result<u32> { 
-        self.read_u32_bytes(access, 4, false)
+        self.read_xlen_bytes(access, 4, false).map(|data| data as u32)
     }
 
-    pub fn read_u32_bytes(&self, mut access: Access<Physical>, len: usize, is_signed: bool) -> Result<u32> {
-        let mut four_bytes = [0; 4];
+    /// Read `len` bytes, up to `XLEN / 8`, and extend to `Xlen`
+    pub fn read_xlen_bytes(&self, mut access: Access<Physical>, len: usize, is_signed: bool) -> Result<Xlen> {
+        let mut bytes = [0; size_of::<Xlen>()];
 
         match self.mapping(&mut access)? {
-            Uart => self.uart.read_bytes(access, len, &mut four_bytes[..len])?,
-            Ram  => self.ram.read_bytes(access, len, &mut four_bytes[..len])?,
+            Clint => self.clint.read_bytes(access, len, &mut bytes[..len])?,
+            Plic  => self.plic.read_bytes(access, len, &mut bytes[..len])?,
+            Uart  => self.uart.read_bytes(access, len, &mut bytes[..len])?,
+            Ram   => self.ram.read_bytes(access, len, &mut bytes[..len])?,
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().read_bytes(access, len, &mut bytes[..len])?,
         }
         
-        if is_signed && (four_bytes[len - 1] & 0x80 != 0) {
-            four_bytes[len..].fill(0xff);
+        if is_signed && (bytes[len - 1] & 0x80 != 0) {
+            bytes[len..].fill(0xff);
         }
 
-        Ok(u32::from_le_bytes(four_bytes))
+        Ok(Xlen::from_le_bytes(bytes))
     }
 
     #[allow(unused)]
     pub fn write_u32(&mut self, access: Access<Physical>, data: u32) -> Result<()> {
-        self.write_u32_bytes(access, data, 4)
+        self.write_xlen_bytes(access, data as Xlen, 4)
     }
 
-    pub fn write_u32_bytes(&mut self, mut access: Access<Physical>, data: u32, len: usize) -> Result<()> {
+    /// Write low `len` bytes of `data`
+    pub fn write_xlen_bytes(&mut self, mut access: Access<Physical>, data: Xlen, len: usize) -> Result<()> {
+        let addr = access.addr;
+        self.htif.watch(addr, len);
         match self.mapping(&mut access)? {
-            Uart => self.uart.write_bytes(access, len, &data.to_le_bytes())?,
-            Ram  => self.ram.write_bytes(access, len, &data.to_le_bytes())?,
+            Clint => self.clint.write_bytes(access, len, &data.to_le_bytes())?,
+            Plic  => self.plic.write_bytes(access, len, &data.to_le_bytes())?,
+            Uart  => self.uart.write_bytes(access, len, &data.to_le_bytes())?,
+            Ram   => {
+                self.journal(addr, access, len);
+                self.ram.write_bytes(access, len, &data.to_le_bytes())?
+            },
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().write_bytes(access, len, &data.to_le_bytes())?,
         }
         Ok(())
     }
 
-    pub fn ram_info(&self) -> (usize, u32, usize) {
-        (self.ram.size, DRAM_BASE_ADDR, PAGE_SIZE)
+    pub fn ram_info(&self) -> (usize, Xlen, usize) {
+        (self.ram.size, self.regions[0].base, PAGE_SIZE)
+    }
+
+    /// Advance timer of devices. Called once for each `step`
+    pub fn tick(&mut self) {
+        self.clint.tick();
+        self.uart.tick();
+        self.plic.set_level(UART_IRQ, self.uart.irq_pending());
+
+        for dev in self.mmio.iter() {
+            let mut device = dev.device.lock().unwrap();
+            device.tick();
+            if let Some(id) = dev.irq {
+                self.plic.set_level(id, device.irq_pending());
+            }
+        }
+    }
+
+    /// Return CLINT's (software, timer) interrupt pending
+    #[cfg(feature = "zicsr")]
+    pub fn clint_pending(&self) -> (bool, bool) {
+        (self.clint.software_pending(), self.clint.timer_pending())
+    }
+
+    /// CLINT's `mtime`, source of `time` CSR
+    #[cfg(feature = "zicsr")]
+    pub fn mtime(&self) -> u64 {
+        self.clint.mtime()
+    }
+
+    /// Return PLIC's (M-Mode, S-Mode) external interrupt pending
+    #[cfg(feature = "zicsr")]
+    pub fn plic_pending(&self) -> (bool, bool) {
+        (self.plic.context_pending(0), self.plic.context_pending(1))
+    }
+
+    /// Set interrupt line of PLIC source `id`
+    #[allow(unused)]
+    pub fn set_irq(&mut self, id: u32, level: bool) {
+        self.plic.set_level(id, level);
+    }
+
+    /// Queue bytes to UART's receiver
+    pub fn uart_input(&mut self, data: &[u8]) {
+        self.uart.push_input(data);
+    }
+
+    /// Let UART receive from host's stdin
+    pub fn uart_stdin(&mut self, enable: bool) {
+        self.uart.set_stdin(enable);
     }
 
-    pub fn reset_ram(&mut self) {
+    /// Set HTIF's `tohost` and `fromhost` address. `None` disable it
+    pub fn set_htif(&mut self, tohost: Option<Xlen>, fromhost: Option<Xlen>) {
+        self.htif.set_addr(tohost, fromhost);
+    }
+
+    /// Handle HTIF command if `tohost` is written. Return halt reason if guest exit
+    pub fn htif_poll(&mut self) -> Option<Halt> {
+        let tohost = self.htif.take_request()?;
+        let cmd = htif::read_u64(self, tohost).filter(|cmd| *cmd != 0)?;
+        let _ = self.write_bytes(Access::new(tohost, AccessType::Store), 8, &[0; 8]);
+
+        // Clone is cheap, and let HTIF access bus
+        let htif = self.htif.clone();
+        let (halt, reply) = htif.handle(cmd, self);
+        if let Some(reply) = reply
+            && let Some(fromhost) = htif.fromhost() {
+            let _ = self.write_bytes(Access::new(fromhost, AccessType::Store), 8, &reply.to_le_bytes());
+        }
+        // Writes by host are not commands
+        let _ = self.htif.take_request();
+        halt
+    }
+
+    pub fn reset(&mut self) {
+        self.clint.reset();
+        self.plic.reset();
+        self.uart.reset();
+        self.mmio.iter().for_each(|dev| dev.device.lock().unwrap().reset());
         self.ram.reset();
     }
 }
 
+/// Devices added by `add_device` and HTIF are not saved
+impl Snapshot for SystemBus {
+    fn save(&self, w: &mut Writer) {
+        self.clint.save(w);
+        self.plic.save(w);
+        self.uart.save(w);
+        self.ram.save(w);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        self.clint.restore(r)?;
+        self.plic.restore(r)?;
+        self.uart.restore(r)?;
+        self.ram.restore(r)
+    }
+}
+
+impl Default for SystemBus {
+    fn default() -> Self {
+        // Safe: Default memory map has no overlap
+        Self::new(&MachineConfig::default()).unwrap()
+    }
+}
+
 impl Device for SystemBus {
     fn read_byte(&self, mut access: Access<Physical>) -> Result<u8> {
         match self.mapping(&mut access)? {
-            Uart => self.uart.read_byte(access),
-            Ram  => self.ram.read_byte(access),
+            Clint => self.clint.read_byte(access),
+            Plic  => self.plic.read_byte(access),
+            Uart  => self.uart.read_byte(access),
+            Ram   => self.ram.read_byte(access),
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().read_byte(access),
         }
     }
 
     fn write_byte(&mut self, mut access: Access<Physical>, data: u8) -> Result<()> {
+        let addr = access.addr;
+        self.htif.watch(addr, 1);
         match self.mapping(&mut access)? {
-            Uart => self.uart.write_byte(access, data),
-            Ram  => self.ram.write_byte(access, data),
+            Clint => self.clint.write_byte(access, data),
+            Plic  => self.plic.write_byte(access, data),
+            Uart  => self.uart.write_byte(access, data),
+            Ram   => {
+                self.journal(addr, access, 1);
+                self.ram.write_byte(access, data)
+            },
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().write_byte(access, data),
         }
     }
 
     fn read_bytes(&self, mut access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<()> {
         match self.mapping(&mut access)? {
-            Uart => self.uart.read_bytes(access, size, des),
-            Ram  => self.ram.read_bytes(access, size, des),
+            Clint => self.clint.read_bytes(access, size, des),
+            Plic  => self.plic.read_bytes(access, size, des),
+            Uart  => self.uart.read_bytes(access, size, des),
+            Ram   => self.ram.read_bytes(access, size, des),
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().read_bytes(access, size, des),
         }
     }
 
     fn write_bytes(&mut self, mut access: Access<Physical>, size: usize, src: &[u8]) -> Result<()> {
+        let addr = access.addr;
+        self.htif.watch(addr, size);
+        match self.mapping(&mut access)? {
+            Clint => self.clint.write_bytes(access, size, src),
+            Plic  => self.plic.write_bytes(access, size, src),
+            Uart  => self.uart.write_bytes(access, size, src),
+            Ram   => {
+                self.journal(addr, access, size);
+                self.ram.write_bytes(access, size, src)
+            },
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().write_bytes(access, size, src),
+        }
+    }
+
+    fn peek_bytes(&self, mut access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<()> {
         match self.mapping(&mut access)? {
-            Uart => self.uart.write_bytes(access, size, src),
-            Ram  => self.ram.write_bytes(access, size, src),
+            Clint => self.clint.peek_bytes(access, size, des),
+            Plic  => self.plic.peek_bytes(access, size, des),
+            Uart  => self.uart.peek_bytes(access, size, des),
+            Ram   => self.ram.read_bytes(access, size, des),
+            Mmio(idx) => self.mmio[idx].device.lock().unwrap().peek_bytes(access, size, des),
         }
     }
 }
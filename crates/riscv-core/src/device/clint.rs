@@ -0,0 +1,155 @@
+//! Core Local Interruptor (CLINT). Provide machine software and timer interrupts
+
+use crate::{Result, StdResult, Xlen};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::core::access::{Access, Physical};
+use super::Device;
+
+const MSIP: Xlen = 0x0000;
+const MSIP_END: Xlen = MSIP + 3;
+const MTIMECMP: Xlen = 0x4000;
+const MTIMECMP_END: Xlen = MTIMECMP + 7;
+const MTIME: Xlen = 0xbff8;
+const MTIME_END: Xlen = MTIME + 7;
+
+/// CLINT registers for single hart.
+/// `mtime` increase by one for each `tick`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clint {
+    msip: u32,
+    mtimecmp: u64,
+    mtime: u64,
+}
+
+impl Snapshot for Clint {
+    fn save(&self, w: &mut Writer) {
+        w.u32(self.msip);
+        w.u64(self.mtimecmp);
+        w.u64(self.mtime);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        self.msip = r.u32()?;
+        self.mtimecmp = r.u64()?;
+        self.mtime = r.u64()?;
+        Ok(())
+    }
+}
+
+impl Clint {
+    pub fn tick(&mut self) {
+        self.mtime = self.mtime.wrapping_add(1);
+    }
+
+    #[cfg(feature = "zicsr")]
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    #[cfg(feature = "zicsr")]
+    pub fn software_pending(&self) -> bool {
+        self.msip & 1 != 0
+    }
+
+    #[cfg(feature = "zicsr")]
+    pub fn timer_pending(&self) -> bool {
+        self.mtime >= self.mtimecmp
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default()
+    }
+}
+
+impl Default for Clint {
+    fn default() -> Self {
+        // `mtimecmp` start at max value that timer interrupt will not pending after reset
+        Clint { msip: 0, mtimecmp: u64::MAX, mtime: 0 }
+    }
+}
+
+impl Device for Clint {
+    fn read_byte(&self, access: Access<Physical>) -> Result<u8> {
+        let addr = access.addr;
+        Ok(match addr {
+            MSIP..=MSIP_END         => self.msip.to_le_bytes()[(addr - MSIP) as usize],
+            MTIMECMP..=MTIMECMP_END => self.mtimecmp.to_le_bytes()[(addr - MTIMECMP) as usize],
+            MTIME..=MTIME_END       => self.mtime.to_le_bytes()[(addr - MTIME) as usize],
+            _                       => 0,
+        })
+    }
+
+    fn write_byte(&mut self, access: Access<Physical>, data: u8) -> Result<()> {
+        let addr = access.addr;
+        match addr {
+            MSIP..=MSIP_END => {
+                let mut bytes = self.msip.to_le_bytes();
+                bytes[(addr - MSIP) as usize] = data;
+                // Only bit 0 is implemented
+                self.msip = u32::from_le_bytes(bytes) & 1;
+            },
+            MTIMECMP..=MTIMECMP_END => {
+                let mut bytes = self.mtimecmp.to_le_bytes();
+                bytes[(addr - MTIMECMP) as usize] = data;
+                self.mtimecmp = u64::from_le_bytes(bytes);
+            },
+            MTIME..=MTIME_END => {
+                let mut bytes = self.mtime.to_le_bytes();
+                bytes[(addr - MTIME) as usize] = data;
+                self.mtime = u64::from_le_bytes(bytes);
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "zicsr"))]
+mod tests {
+    use crate::core::access::{Access, AccessType};
+    use crate::device::Device;
+    use crate::device::clint::{Clint, MSIP, MTIME, MTIMECMP};
+
+    #[test]
+    fn test_reset_state() {
+        let clint = Clint::default();
+
+        assert!(!clint.software_pending());
+        assert!(!clint.timer_pending());
+    }
+
+    #[test]
+    fn test_msip() {
+        let mut clint = Clint::default();
+
+        let access = Access::new(MSIP, AccessType::Store);
+        clint.write_bytes(access, 4, &0xffff_ffffu32.to_le_bytes()).unwrap();
+        assert!(clint.software_pending());
+
+        let mut bytes = [0; 4];
+        clint.read_bytes(Access::new(MSIP, AccessType::Load), 4, &mut bytes).unwrap();
+        assert_eq!(u32::from_le_bytes(bytes), 1, "Only bit 0 of msip is writable");
+
+        clint.write_byte(access, 0).unwrap();
+        assert!(!clint.software_pending());
+    }
+
+    #[test]
+    fn test_timer_compare() {
+        let mut clint = Clint::default();
+
+        // mtimecmp = 2, written by two 32-bit halves
+        clint.write_bytes(Access::new(MTIMECMP, AccessType::Store), 4, &2u32.to_le_bytes()).unwrap();
+        clint.write_bytes(Access::new(MTIMECMP + 4, AccessType::Store), 4, &0u32.to_le_bytes()).unwrap();
+
+        clint.tick();
+        assert!(!clint.timer_pending());
+        clint.tick();
+        assert!(clint.timer_pending());
+
+        let mut bytes = [0; 8];
+        clint.read_bytes(Access::new(MTIME, AccessType::Load), 8, &mut bytes).unwrap();
+        assert_eq!(u64::from_le_bytes(bytes), 2);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Halt, Xlen};
+use crate::core::access::{Access, AccessType, Physical};
+use crate::core::IO_LIMIT;
+use super::Device;
+use super::uart::UartSink;
+
+const DEV_SYSCALL: u64 = 0;
+const DEV_CONSOLE: u64 = 1;
+
+const CMD_PUTCHAR: u64 = 1;
+
+const SYS_WRITE: u64 = 64;
+const SYS_EXIT: u64 = 93;
+
+const EBADF: i64 = 9;
+const ENOSYS: i64 = 38;
+
+/// Host-Target Interface used by riscv-tests and riscv-pk.
+/// `tohost` and `fromhost` are 64 bits words in RAM, found by symbols of loaded program.
+///
+/// Command is `device[63:56] | cmd[55:48] | payload[47:0]`. RV32 guest write low word first,
+/// so command is taken when high word of `tohost` is written.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Htif {
+    tohost: Option<Xlen>,
+    fromhost: Option<Xlen>,
+    /// High word of `tohost` is written since last poll
+    requested: bool,
+    sink: UartSink,
+}
+
+impl Htif {
+    /// Console output go to `sink`, same as UART
+    pub fn new(sink: UartSink) -> Self {
+        Htif { sink, ..Default::default() }
+    }
+
+    pub fn set_addr(&mut self, tohost: Option<Xlen>, fromhost: Option<Xlen>) {
+        self.tohost = tohost;
+        self.fromhost = fromhost;
+        self.requested = false;
+    }
+
+    pub fn fromhost(&self) -> Option<Xlen> {
+        self.fromhost
+    }
+
+    /// Called by every store on bus
+    pub fn watch(&mut self, addr: Xlen, len: usize) {
+        if let Some(tohost) = self.tohost {
+            let high = tohost as u64 + 4;
+            let start = addr as u64;
+            self.requested |= start < high + 4 && high < start + len as u64;
+        }
+    }
+
+    /// Return `tohost` address if a command is written
+    pub fn take_request(&mut self) -> Option<Xlen> {
+        std::mem::take(&mut self.requested).then_some(self.tohost).flatten()
+    }
+
+    /// Handle `cmd` with guest's memory `mem`. Return halt reason and value should write to `fromhost`
+    pub fn handle<D: Device>(&self, cmd: u64, mem: &mut D) -> (Option<Halt>, Option<u64>) {
+        let device = cmd >> 56;
+        let command = (cmd >> 48) & 0xff;
+        let payload = cmd & 0xffff_ffff_ffff;
+
+        match (device, command) {
+            (DEV_SYSCALL, 0) if payload & 1 == 1 => (Some(Halt::Exit((payload >> 1) as u32)), None),
+            (DEV_SYSCALL, 0)                     => self.syscall(payload as Xlen, mem),
+            (DEV_CONSOLE, CMD_PUTCHAR)           => {
+                self.sink.write_bytes(&[payload as u8]);
+                (None, None)
+            },
+            // Unsupported command is ignored
+            _ => (None, None),
+        }
+    }
+
+    /// `magic_mem` is `[which, arg0, arg1, arg2, ..]` in 64 bits words, return value is written to `which`
+    fn syscall<D: Device>(&self, magic_mem: Xlen, mem: &mut D) -> (Option<Halt>, Option<u64>) {
+        let mut args = [0; 4];
+        for (i, arg) in args.iter_mut().enumerate() {
+            let val = magic_mem.checked_add(i as Xlen * 8).and_then(|addr| read_u64(mem, addr));
+            match val {
+                Some(val) => *arg = val,
+                None      => return (None, None),
+            }
+        }
+        let [which, arg0, arg1, arg2] = args;
+
+        let ret = match which {
+            SYS_EXIT  => return (Some(Halt::Exit(arg0 as u32)), None),
+            SYS_WRITE => match arg0 {
+                1 | 2 => {
+                    // Partial write, same as syscall emulation
+                    let mut buf = vec![0; arg2.min(IO_LIMIT as u64) as usize];
+                    let access = Access::new(arg1 as Xlen, AccessType::Load);
+                    match mem.read_bytes(access, buf.len(), &mut buf) {
+                        Ok(_)  => {
+                            self.sink.write_bytes(&buf);
+                            buf.len() as i64
+                        },
+                        Err(_) => -EBADF,
+                    }
+                },
+                _ => -EBADF,
+            },
+            _ => -ENOSYS,
+        };
+
+        let access = Access::new(magic_mem, AccessType::Store);
+        let _ = mem.write_bytes(access, 8, &ret.to_le_bytes());
+        (None, Some(1))
+    }
+}
+
+pub fn read_u64<D: Device>(mem: &D, addr: Xlen) -> Option<u64> {
+    let mut bytes = [0; 8];
+    let access: Access<Physical> = Access::new(addr, AccessType::Load);
+    mem.read_bytes(access, 8, &mut bytes).ok()?;
+    Some(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::memory::Memory;
+
+    const TOHOST: Xlen = 0x1000;
+
+    #[test]
+    fn test_watch_high_word() {
+        let mut htif = Htif::default();
+        htif.set_addr(Some(TOHOST), None);
+
+        htif.watch(TOHOST, 4);
+        assert_eq!(htif.take_request(), None, "Low word only");
+
+        htif.watch(TOHOST + 4, 4);
+        assert_eq!(htif.take_request(), Some(TOHOST));
+        assert_eq!(htif.take_request(), None, "Request is taken");
+
+        htif.watch(TOHOST + 7, 1);
+        assert_eq!(htif.take_request(), Some(TOHOST));
+        htif.watch(TOHOST + 8, 4);
+        assert_eq!(htif.take_request(), None, "Out of tohost");
+    }
+
+    #[test]
+    fn test_exit_and_syscall() {
+        let (sink, output) = UartSink::buffer();
+        let htif = Htif::new(sink);
+        let mut mem = Memory::new(0x1000);
+
+        assert_eq!(htif.handle(1, &mut mem), (Some(Halt::Exit(0)), None));
+        assert_eq!(htif.handle((3 << 1) | 1, &mut mem), (Some(Halt::Exit(3)), None));
+
+        // write(1, 0x100, 2)
+        mem.write_bytes(Access::new(0x100, AccessType::Store), 2, b"ok").unwrap();
+        for (i, arg) in [SYS_WRITE, 1, 0x100, 2].iter().enumerate() {
+            let access = Access::new(0x200 + i as Xlen * 8, AccessType::Store);
+            mem.write_bytes(access, 8, &arg.to_le_bytes()).unwrap();
+        }
+        assert_eq!(htif.handle(0x200, &mut mem), (None, Some(1)));
+        assert_eq!(read_u64(&mem, 0x200), Some(2), "Return written length");
+        assert_eq!(output.lock().unwrap().as_slice(), b"ok");
+
+        // putchar
+        htif.handle((DEV_CONSOLE << 56) | (CMD_PUTCHAR << 48) | b'!' as u64, &mut mem);
+        assert_eq!(output.lock().unwrap().as_slice(), b"ok!");
+    }
+
+    #[test]
+    fn test_syscall_limits() {
+        let (sink, output) = UartSink::buffer();
+        let htif = Htif::new(sink);
+        let mut mem = Memory::new(2 * IO_LIMIT as usize);
+
+        // write(1, 0, u64::MAX) is cut to `IO_LIMIT`
+        let data = vec![b'a'; 2 * IO_LIMIT as usize];
+        mem.write_bytes(Access::new(0, AccessType::Store), data.len(), &data).unwrap();
+        for (i, arg) in [SYS_WRITE, 1, 0, u64::MAX].iter().enumerate() {
+            let access = Access::new(0x200 + i as Xlen * 8, AccessType::Store);
+            mem.write_bytes(access, 8, &arg.to_le_bytes()).unwrap();
+        }
+        assert_eq!(htif.handle(0x200, &mut mem), (None, Some(1)));
+        assert_eq!(read_u64(&mem, 0x200), Some(IO_LIMIT as u64), "Return written length");
+        assert_eq!(output.lock().unwrap().len(), IO_LIMIT as usize);
+
+        // `arg1` wraps around address space
+        assert_eq!(htif.syscall(Xlen::MAX - 15, &mut Zero), (None, None));
+    }
+
+    /// Every address reads as zero
+    struct Zero;
+
+    impl Device for Zero {
+        fn read_byte(&self, _access: Access<Physical>) -> Result<u8, crate::Exception> {
+            Ok(0)
+        }
+
+        fn write_byte(&mut self, _access: Access<Physical>, _data: u8) -> Result<(), crate::Exception> {
+            Ok(())
+        }
+    }
+}
@@ -2,8 +2,10 @@
 
 mod page;
 
-use crate::Result;
+use crate::{Result, StdResult, Xlen};
 use crate::core::access::{Access, Physical};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
 use super::Device;
 
 use page::Page;
@@ -18,6 +20,36 @@ pub struct Memory {
     pages: Vec<Option<Box<Page>>>,
 }
 
+/// Only populated pages are saved
+impl Snapshot for Memory {
+    fn save(&self, w: &mut Writer) {
+        w.u64(self.size as u64);
+        let populated = self.pages.iter().enumerate()
+            .filter_map(|(idx, page)| page.as_ref().map(|page| (idx, page)));
+        w.u32(populated.clone().count() as u32);
+        for (idx, page) in populated {
+            w.u32(idx as u32);
+            w.bytes(&page.space);
+        }
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        let size = r.u64()?;
+        if size != self.size as u64 {
+            return Err(SnapshotError::RamSize(size));
+        }
+        self.pages.fill(None);
+        for _ in 0..r.u32()? {
+            let idx = r.u32()? as usize;
+            let slot = self.pages.get_mut(idx).ok_or(SnapshotError::Corrupted)?;
+            let mut page = Box::new(Page::default());
+            page.space.copy_from_slice(r.bytes(PAGE_SIZE)?);
+            *slot = Some(page);
+        }
+        Ok(())
+    }
+}
+
 impl Memory {
     pub fn new(size: usize) -> Self {
         let aligned_size = size.max(PAGE_SIZE).next_multiple_of(PAGE_SIZE);
@@ -91,7 +123,7 @@ impl Device for Memory {
             }
 
             start += len;
-            access.addr += len as u32;
+            access.addr += len as Xlen;
         }
         Ok(())
     }
@@ -114,7 +146,7 @@ impl Device for Memory {
             }
             
             start += len;
-            access.addr += len as u32;
+            access.addr += len as Xlen;
         }
         Ok(())
     }
@@ -155,7 +187,7 @@ impl std::fmt::Debug for Memory {
 
 #[cfg(test)]
 mod tests {
-    use crate::Exception;
+    use crate::{Exception, Xlen};
     use crate::core::access::{Access, AccessType};
     use crate::device::Device;
     use crate::device::memory::{Memory, _2GB};
@@ -194,7 +226,7 @@ mod tests {
         let addr = PAGE_SIZE - 2;
         let data = [0x11, 0x22, 0x33, 0x44];
         
-        let access = Access::new(addr as u32, AccessType::Store);
+        let access = Access::new(addr as Xlen, AccessType::Store);
         
         assert!(mem.write_bytes(access, 4, &data).is_ok());
         
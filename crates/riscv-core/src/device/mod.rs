@@ -1,16 +1,46 @@
 pub mod bus;
+pub mod clint;
+pub mod htif;
 pub mod memory;
+pub mod plic;
 pub mod uart;
 
 use crate::core::access::{Access, Physical};
-use crate::Exception;
+use crate::{Exception, Xlen};
 
+/// Memory-mapped device on `SystemBus`. `access.addr` is the offset from device's base address
 pub trait Device {
     fn read_byte(&self, access: Access<Physical>) -> Result<u8, Exception>;
 
     fn write_byte(&mut self, access: Access<Physical>, data: u8) -> Result<(), Exception>;
 
-    fn read_bytes(&self, access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<(), Exception>;
+    fn read_bytes(&self, access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<(), Exception> {
+        for (i, byte) in des.iter_mut().take(size).enumerate() {
+            *byte = self.read_byte(Access::new(access.addr + i as Xlen, access.kind))?;
+        }
+        Ok(())
+    }
 
-    fn write_bytes(&mut self, access: Access<Physical>, size: usize, src: &[u8]) -> Result<(), Exception>;
+    fn write_bytes(&mut self, access: Access<Physical>, size: usize, src: &[u8]) -> Result<(), Exception> {
+        for (i, byte) in src.iter().take(size).enumerate() {
+            self.write_byte(Access::new(access.addr + i as Xlen, access.kind), *byte)?;
+        }
+        Ok(())
+    }
+
+    /// Read without side effect, used by debugger. Device whose read changes its state should override it
+    fn peek_bytes(&self, access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<(), Exception> {
+        self.read_bytes(access, size, des)
+    }
+
+    /// Called once for each `step`
+    fn tick(&mut self) {}
+
+    /// Interrupt line to PLIC
+    fn irq_pending(&self) -> bool {
+        false
+    }
+
+    /// Called when machine reset
+    fn reset(&mut self) {}
 }
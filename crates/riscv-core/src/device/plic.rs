@@ -0,0 +1,307 @@
+//! Platform-Level Interrupt Controller (PLIC). Gather device's interrupt lines
+//! and drive `mip.MEIP` / `mip.SEIP`
+
+use std::cell::Cell;
+
+use crate::{Result, StdResult, Xlen};
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::core::access::{Access, Physical};
+use super::Device;
+
+/// Number of interrupt sources include the reserved source 0
+pub const SOURCE_NUM: usize = 32;
+/// Context 0 is hart 0 M-Mode, context 1 is hart 0 S-Mode
+pub const CONTEXT_NUM: usize = 2;
+
+const PRIORITY_MASK: u32 = 0b111;
+
+const PRIORITY: Xlen = 0x0000;
+const PRIORITY_END: Xlen = PRIORITY + 4 * SOURCE_NUM as Xlen - 1;
+const PENDING: Xlen = 0x1000;
+const PENDING_END: Xlen = PENDING + 3;
+const ENABLE: Xlen = 0x2000;
+const ENABLE_STRIDE: Xlen = 0x80;
+const ENABLE_END: Xlen = ENABLE + ENABLE_STRIDE * CONTEXT_NUM as Xlen - 1;
+const CONTEXT: Xlen = 0x20_0000;
+const CONTEXT_STRIDE: Xlen = 0x1000;
+const CONTEXT_END: Xlen = CONTEXT + CONTEXT_STRIDE * CONTEXT_NUM as Xlen - 1;
+const THRESHOLD: Xlen = 0x0;
+const CLAIM: Xlen = 0x4;
+
+/// PLIC for single hart with level-triggered sources.
+/// `pending` and `claimed` are changed by reading claim register, so they are `Cell`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Plic {
+    priority: [u32; SOURCE_NUM],
+    level: u32,
+    pending: Cell<u32>,
+    claimed: Cell<u32>,
+    enable: [u32; CONTEXT_NUM],
+    threshold: [u32; CONTEXT_NUM],
+}
+
+impl Snapshot for Plic {
+    fn save(&self, w: &mut Writer) {
+        self.priority.iter().for_each(|priority| w.u32(*priority));
+        w.u32(self.level);
+        w.u32(self.pending.get());
+        w.u32(self.claimed.get());
+        self.enable.iter().for_each(|enable| w.u32(*enable));
+        self.threshold.iter().for_each(|threshold| w.u32(*threshold));
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        for priority in self.priority.iter_mut() {
+            *priority = r.u32()?;
+        }
+        self.level = r.u32()?;
+        self.pending.set(r.u32()?);
+        self.claimed.set(r.u32()?);
+        for enable in self.enable.iter_mut() {
+            *enable = r.u32()?;
+        }
+        for threshold in self.threshold.iter_mut() {
+            *threshold = r.u32()?;
+        }
+        Ok(())
+    }
+}
+
+impl Plic {
+    /// Set interrupt line of source `id`.
+    /// Source become pending when line is high and it is not claimed
+    pub fn set_level(&mut self, id: u32, level: bool) {
+        if id == 0 || id as usize >= SOURCE_NUM {
+            return;
+        }
+        let bit = 1 << id;
+        if level {
+            self.level |= bit;
+            if self.claimed.get() & bit == 0 {
+                self.pending.set(self.pending.get() | bit);
+            }
+        } else {
+            self.level &= !bit;
+        }
+    }
+
+    /// Return whether `ctx` has interrupt over its threshold
+    #[cfg(feature = "zicsr")]
+    pub fn context_pending(&self, ctx: usize) -> bool {
+        self.best_source(ctx) != 0
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default()
+    }
+
+    /// Highest priority pending source enabled for `ctx`, tie is broken by smaller id
+    fn best_source(&self, ctx: usize) -> u32 {
+        let candidate = self.pending.get() & self.enable[ctx];
+        let mut best = (0, self.threshold[ctx]);
+        for id in 1..SOURCE_NUM as u32 {
+            if candidate & (1 << id) != 0 && self.priority[id as usize] > best.1 {
+                best = (id, self.priority[id as usize]);
+            }
+        }
+        best.0
+    }
+
+    fn claim(&self, ctx: usize) -> u32 {
+        let id = self.best_source(ctx);
+        if id != 0 {
+            self.pending.set(self.pending.get() & !(1 << id));
+            self.claimed.set(self.claimed.get() | (1 << id));
+        }
+        id
+    }
+
+    fn complete(&mut self, ctx: usize, id: u32) {
+        if id == 0 || id as usize >= SOURCE_NUM || self.enable[ctx] & (1 << id) == 0 {
+            return;
+        }
+        let bit = 1 << id;
+        self.claimed.set(self.claimed.get() & !bit);
+        // Line still high, pending again
+        if self.level & bit != 0 {
+            self.pending.set(self.pending.get() | bit);
+        }
+    }
+
+    /// Byte at `addr`. Reading claim register claims the source only when `claim` is set
+    fn read_reg(&self, addr: Xlen, claim: bool) -> u8 {
+        let shift = 8 * (addr & 3);
+        match addr {
+            PRIORITY..=PRIORITY_END => (self.priority[((addr - PRIORITY) / 4) as usize] >> shift) as u8,
+            PENDING..=PENDING_END   => (self.pending.get() >> shift) as u8,
+            ENABLE..=ENABLE_END     => {
+                let offset = addr - ENABLE;
+                if offset % ENABLE_STRIDE < 4 {
+                    (self.enable[(offset / ENABLE_STRIDE) as usize] >> shift) as u8
+                } else {
+                    0
+                }
+            },
+            CONTEXT..=CONTEXT_END   => {
+                let offset = addr - CONTEXT;
+                let ctx = (offset / CONTEXT_STRIDE) as usize;
+                match offset % CONTEXT_STRIDE {
+                    // Source id fit in byte 0, claim only happen on it
+                    CLAIM if claim => self.claim(ctx) as u8,
+                    CLAIM => self.best_source(ctx) as u8,
+                    reg if reg & !3 == THRESHOLD => (self.threshold[ctx] >> shift) as u8,
+                    _ => 0,
+                }
+            },
+            _ => 0,
+        }
+    }
+}
+
+impl Device for Plic {
+    fn read_byte(&self, access: Access<Physical>) -> Result<u8> {
+        Ok(self.read_reg(access.addr, true))
+    }
+
+    fn write_byte(&mut self, access: Access<Physical>, data: u8) -> Result<()> {
+        let addr = access.addr;
+        let shift = 8 * (addr & 3);
+        let mask = !(0xff << shift);
+        let data = (data as u32) << shift;
+        match addr {
+            PRIORITY..=PRIORITY_END => {
+                let id = ((addr - PRIORITY) / 4) as usize;
+                // Source 0 is reserved
+                if id != 0 {
+                    self.priority[id] = ((self.priority[id] & mask) | data) & PRIORITY_MASK;
+                }
+            },
+            ENABLE..=ENABLE_END => {
+                let offset = addr - ENABLE;
+                if offset % ENABLE_STRIDE < 4 {
+                    let ctx = (offset / ENABLE_STRIDE) as usize;
+                    self.enable[ctx] = ((self.enable[ctx] & mask) | data) & !1;
+                }
+            },
+            CONTEXT..=CONTEXT_END => {
+                let offset = addr - CONTEXT;
+                let ctx = (offset / CONTEXT_STRIDE) as usize;
+                match offset % CONTEXT_STRIDE {
+                    CLAIM => self.complete(ctx, data),
+                    reg if reg & !3 == THRESHOLD => {
+                        self.threshold[ctx] = ((self.threshold[ctx] & mask) | data) & PRIORITY_MASK;
+                    },
+                    _ => {},
+                }
+            },
+            // Pending bits are read only
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn peek_bytes(&self, access: Access<Physical>, size: usize, des: &mut [u8]) -> Result<()> {
+        for (i, byte) in des.iter_mut().take(size).enumerate() {
+            *byte = self.read_reg(access.addr + i as Xlen, false);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "zicsr"))]
+mod tests {
+    use crate::Xlen;
+    use crate::core::access::{Access, AccessType};
+    use crate::device::Device;
+    use crate::device::plic::{Plic, CLAIM, CONTEXT, CONTEXT_STRIDE, ENABLE, ENABLE_STRIDE, PENDING, PRIORITY};
+
+    fn write(plic: &mut Plic, addr: Xlen, data: u32) {
+        plic.write_bytes(Access::new(addr, AccessType::Store), 4, &data.to_le_bytes()).unwrap();
+    }
+
+    fn read(plic: &Plic, addr: Xlen) -> u32 {
+        let mut bytes = [0; 4];
+        plic.read_bytes(Access::new(addr, AccessType::Load), 4, &mut bytes).unwrap();
+        u32::from_le_bytes(bytes)
+    }
+
+    #[test]
+    fn test_claim_complete() {
+        let mut plic = Plic::default();
+
+        write(&mut plic, PRIORITY + 4 * 10, 1);
+        write(&mut plic, ENABLE, 1 << 10);
+
+        plic.set_level(10, true);
+        assert_eq!(read(&plic, PENDING), 1 << 10);
+        assert!(plic.context_pending(0));
+        assert!(!plic.context_pending(1), "Not enabled for S-Mode context");
+
+        assert_eq!(read(&plic, CONTEXT + CLAIM), 10);
+        assert_eq!(read(&plic, PENDING), 0, "Claim clear pending bit");
+        assert!(!plic.context_pending(0));
+        assert_eq!(read(&plic, CONTEXT + CLAIM), 0, "Nothing to claim");
+
+        // Line still high after complete
+        write(&mut plic, CONTEXT + CLAIM, 10);
+        assert!(plic.context_pending(0));
+
+        assert_eq!(read(&plic, CONTEXT + CLAIM), 10);
+        plic.set_level(10, false);
+        write(&mut plic, CONTEXT + CLAIM, 10);
+        assert!(!plic.context_pending(0));
+    }
+
+    #[test]
+    fn test_priority_threshold() {
+        let mut plic = Plic::default();
+
+        write(&mut plic, PRIORITY + 4 * 3, 2);
+        write(&mut plic, PRIORITY + 4 * 5, 7);
+        write(&mut plic, PRIORITY + 4 * 7, 0xffff_ffff);
+        assert_eq!(read(&plic, PRIORITY + 4 * 7), 7, "Priority only have 3 bits");
+
+        let ctx = CONTEXT + CONTEXT_STRIDE;
+        write(&mut plic, ENABLE + ENABLE_STRIDE, (1 << 3) | (1 << 5));
+        write(&mut plic, ctx, 2);
+
+        plic.set_level(3, true);
+        assert!(!plic.context_pending(1), "Priority not over threshold");
+
+        plic.set_level(5, true);
+        assert!(plic.context_pending(1));
+        assert_eq!(read(&plic, ctx + CLAIM), 5);
+
+        write(&mut plic, ctx, 0);
+        assert_eq!(read(&plic, ctx + CLAIM), 3);
+    }
+
+    #[test]
+    fn test_source_zero_reserved() {
+        let mut plic = Plic::default();
+
+        write(&mut plic, PRIORITY, 7);
+        write(&mut plic, ENABLE, 0xffff_ffff);
+        plic.set_level(0, true);
+
+        assert_eq!(read(&plic, PRIORITY), 0);
+        assert_eq!(read(&plic, ENABLE), 0xffff_fffe);
+        assert_eq!(read(&plic, PENDING), 0);
+    }
+
+    #[test]
+    fn test_peek_not_claim() {
+        let mut plic = Plic::default();
+
+        write(&mut plic, PRIORITY + 4 * 10, 1);
+        write(&mut plic, ENABLE, 1 << 10);
+        plic.set_level(10, true);
+
+        let mut bytes = [0; 4];
+        plic.peek_bytes(Access::new(CONTEXT + CLAIM, AccessType::Load), 4, &mut bytes).unwrap();
+        assert_eq!(u32::from_le_bytes(bytes), 10);
+        assert_eq!(read(&plic, PENDING), 1 << 10, "Peek keep pending bit");
+        assert_eq!(read(&plic, CONTEXT + CLAIM), 10);
+    }
+}
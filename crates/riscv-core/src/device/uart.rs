@@ -1,28 +1,311 @@
-use std::io::{self, Write};
+//! NS16550A UART. Transmit to `UartSink`, receive from host's byte queue or stdin
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
 
 use crate::core::access::{Access, Physical};
 use crate::device::Device;
-use crate::Result;
+use crate::error::SnapshotError;
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::{Result, StdResult, Xlen};
+
+/// RBR (read) / THR (write) / DLL (DLAB = 1)
+const RBR: Xlen = 0;
+/// IER / DLM (DLAB = 1)
+const IER: Xlen = 1;
+/// IIR (read) / FCR (write)
+const IIR: Xlen = 2;
+const LCR: Xlen = 3;
+const MCR: Xlen = 4;
+const LSR: Xlen = 5;
+const MSR: Xlen = 6;
+const SCR: Xlen = 7;
+
+const FIFO_SIZE: usize = 16;
+
+const IER_RDI: u8 = 1 << 0;
+const IER_THRI: u8 = 1 << 1;
+const IER_RLSI: u8 = 1 << 2;
+
+const IIR_NO_INT: u8 = 0x01;
+const IIR_THRI: u8 = 0x02;
+const IIR_RDI: u8 = 0x04;
+const IIR_RLSI: u8 = 0x06;
+const IIR_FIFO_ENABLED: u8 = 0xc0;
+
+const FCR_ENABLE: u8 = 1 << 0;
+const FCR_CLEAR_RX: u8 = 1 << 1;
+
+const LCR_DLAB: u8 = 1 << 7;
+
+const MCR_LOOP: u8 = 1 << 4;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+const LSR_DR: u8 = 1 << 0;
+const LSR_OE: u8 = 1 << 1;
+const LSR_THRE: u8 = 1 << 5;
+const LSR_TEMT: u8 = 1 << 6;
+
+/// DCD, DSR and CTS are always asserted when not in loopback mode
+const MSR_DEFAULT: u8 = 0xb0;
+
+/// Where UART's transmitted bytes go. Any `Write` like stdout, file, pipe or `Vec<u8>`.
+/// Cloned sink shares the same writer
+#[derive(Clone)]
+pub struct UartSink(Arc<Mutex<dyn Write + Send>>);
+
+impl UartSink {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        UartSink(Arc::new(Mutex::new(writer)))
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Sink into memory. Return the buffer that can be read by host
+    pub fn buffer() -> (Self, Arc<Mutex<Vec<u8>>>) {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        (UartSink(buf.clone()), buf)
+    }
+
+    pub(crate) fn write_bytes(&self, data: &[u8]) {
+        let mut writer = self.0.lock().unwrap();
+        // Guest output lost is not the guest's fault, so ignore error
+        let _ = writer.write_all(data).and_then(|_| writer.flush());
+    }
+}
+
+impl Default for UartSink {
+    fn default() -> Self {
+        Self::stdout()
+    }
+}
+
+impl std::fmt::Debug for UartSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UartSink")
+    }
+}
+
+impl PartialEq for UartSink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for UartSink {}
+
+/// Register model of 16550 with 16 bytes RX FIFO.
+/// Reading RBR, IIR and LSR have side effect, so their states are `Cell`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Uart {
-    // thr: u32,
-    // lsr: u32,
+    ier: u8,
+    fcr: u8,
+    lcr: u8,
+    mcr: u8,
+    scr: u8,
+    divisor: u16,
+    rx_fifo: RefCell<VecDeque<u8>>,
+    thr_empty_pending: Cell<bool>,
+    overrun: Cell<bool>,
+
+    /// Bytes from host wait to be received
+    input: VecDeque<u8>,
+    stdin: bool,
+    sink: UartSink,
+}
+
+/// Host input queue is not saved
+impl Snapshot for Uart {
+    fn save(&self, w: &mut Writer) {
+        w.bytes(&[self.ier, self.fcr, self.lcr, self.mcr, self.scr]);
+        w.u16(self.divisor);
+        let fifo = self.rx_fifo.borrow();
+        w.u8(fifo.len() as u8);
+        fifo.iter().for_each(|byte| w.u8(*byte));
+        w.u8(self.thr_empty_pending.get() as u8);
+        w.u8(self.overrun.get() as u8);
+    }
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError> {
+        let regs = r.bytes(5)?;
+        (self.ier, self.fcr, self.lcr, self.mcr, self.scr) = (regs[0], regs[1], regs[2], regs[3], regs[4]);
+        self.divisor = r.u16()?;
+        let len = r.u8()? as usize;
+        if len > FIFO_SIZE {
+            return Err(SnapshotError::Corrupted);
+        }
+        *self.rx_fifo.get_mut() = r.bytes(len)?.iter().copied().collect();
+        self.thr_empty_pending.set(r.u8()? != 0);
+        self.overrun.set(r.u8()? != 0);
+        Ok(())
+    }
+}
+
+impl Uart {
+    pub fn new(sink: UartSink) -> Self {
+        Uart { sink, ..Default::default() }
+    }
+
+    /// Queue bytes into receiver from host
+    pub fn push_input(&mut self, data: &[u8]) {
+        self.input.extend(data);
+    }
+
+    /// Receive bytes from host's stdin
+    pub fn set_stdin(&mut self, enable: bool) {
+        if enable {
+            stdin_rx();
+        }
+        self.stdin = enable;
+    }
+
+    /// Move host input into RX FIFO while FIFO has space. Called once for each `step`
+    pub fn tick(&mut self) {
+        if self.stdin {
+            let rx = stdin_rx().lock().unwrap();
+            while self.input.len() < FIFO_SIZE && let Ok(byte) = rx.try_recv() {
+                self.input.push_back(byte);
+            }
+        }
+
+        let fifo = self.rx_fifo.get_mut();
+        while fifo.len() < FIFO_SIZE && let Some(byte) = self.input.pop_front() {
+            fifo.push_back(byte);
+        }
+    }
+
+    /// Interrupt line connected to PLIC
+    pub fn irq_pending(&self) -> bool {
+        self.interrupt_id() != IIR_NO_INT
+    }
+
+    pub fn reset(&mut self) {
+        *self = Uart { stdin: self.stdin, sink: self.sink.clone(), ..Default::default() }
+    }
+
+    fn interrupt_id(&self) -> u8 {
+        if self.ier & IER_RLSI != 0 && self.overrun.get() {
+            IIR_RLSI
+        } else if self.ier & IER_RDI != 0 && !self.rx_fifo.borrow().is_empty() {
+            IIR_RDI
+        } else if self.ier & IER_THRI != 0 && self.thr_empty_pending.get() {
+            IIR_THRI
+        } else {
+            IIR_NO_INT
+        }
+    }
+
+    fn receive(&mut self, data: u8) {
+        let fifo = self.rx_fifo.get_mut();
+        if fifo.len() < FIFO_SIZE {
+            fifo.push_back(data);
+        } else {
+            self.overrun.set(true);
+        }
+    }
+
+    fn transmit(&mut self, data: u8) {
+        if self.mcr & MCR_LOOP != 0 {
+            self.receive(data);
+        } else {
+            self.sink.write_bytes(&[data]);
+        }
+        // Transmit is done immediately, THR is empty again
+        self.thr_empty_pending.set(true);
+    }
+
+    fn dlab(&self) -> bool {
+        self.lcr & LCR_DLAB != 0
+    }
+
+    /// Register at `addr`. Side effect of reading RBR, IIR and LSR only happen when `consume` is set
+    fn read_reg(&self, addr: Xlen, consume: bool) -> u8 {
+        match addr {
+            RBR if self.dlab() => self.divisor as u8,
+            RBR if consume => self.rx_fifo.borrow_mut().pop_front().unwrap_or(0),
+            RBR => self.rx_fifo.borrow().front().copied().unwrap_or(0),
+            IER if self.dlab() => (self.divisor >> 8) as u8,
+            IER => self.ier,
+            IIR => {
+                let id = self.interrupt_id();
+                // Reading IIR clear THR empty interrupt
+                if consume && id == IIR_THRI {
+                    self.thr_empty_pending.set(false);
+                }
+                let fifo = if self.fcr & FCR_ENABLE != 0 { IIR_FIFO_ENABLED } else { 0 };
+                id | fifo
+            },
+            LCR => self.lcr,
+            MCR => self.mcr,
+            LSR => {
+                let dr = if self.rx_fifo.borrow().is_empty() { 0 } else { LSR_DR };
+                // Reading LSR clear overrun error
+                let overrun = if consume { self.overrun.replace(false) } else { self.overrun.get() };
+                let oe = if overrun { LSR_OE } else { 0 };
+                dr | oe | LSR_THRE | LSR_TEMT
+            },
+            MSR if self.mcr & MCR_LOOP != 0 => {
+                // RTS -> CTS, DTR -> DSR, OUT1 -> RI, OUT2 -> DCD
+                let mcr = self.mcr;
+                ((mcr & 0b0010) << 3) | ((mcr & 0b0001) << 5) | ((mcr & 0b1100) << 4)
+            },
+            MSR => MSR_DEFAULT,
+            SCR => self.scr,
+            _   => 0,
+        }
+    }
+}
+
+/// Only one reader thread for process's stdin, start at first use
+fn stdin_rx() -> &'static Mutex<Receiver<u8>> {
+    static STDIN_RX: OnceLock<Mutex<Receiver<u8>>> = OnceLock::new();
+    STDIN_RX.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut buf = [0; 64];
+            while let Ok(len) = stdin.read(&mut buf) && len > 0 {
+                if buf[..len].iter().any(|byte| tx.send(*byte).is_err()) {
+                    break;
+                }
+            }
+        });
+        Mutex::new(rx)
+    })
 }
 
 impl Device for Uart {
     fn read_byte(&self, assess: Access<Physical>) -> Result<u8> {
-        Ok(match assess.addr {
-            0x05 => 0x20,
-            _    => 0,
-        })
+        Ok(self.read_reg(assess.addr, true))
     }
 
     fn write_byte(&mut self, assess: Access<Physical>, data: u8) -> Result<()> {
-        if assess.addr == 0x00 { 
-            print!("{}", data as char);
-   
-            io::stdout().flush().unwrap();
+        match assess.addr {
+            RBR if self.dlab() => self.divisor = (self.divisor & 0xff00) | data as u16,
+            RBR => self.transmit(data),
+            IER if self.dlab() => self.divisor = (self.divisor & 0x00ff) | ((data as u16) << 8),
+            IER => {
+                // Enable THR empty interrupt when THR is empty also raise interrupt
+                if self.ier & IER_THRI == 0 && data & IER_THRI != 0 {
+                    self.thr_empty_pending.set(true);
+                }
+                self.ier = data & 0x0f;
+            },
+            IIR => {
+                if data & FCR_CLEAR_RX != 0 {
+                    self.rx_fifo.get_mut().clear();
+                }
+                self.fcr = data & FCR_ENABLE;
+            },
+            LCR => self.lcr = data,
+            MCR => self.mcr = data & 0x1f,
+            SCR => self.scr = data,
+            _   => {},
         }
         Ok(())
     }
@@ -40,4 +323,154 @@ impl Device for Uart {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn peek_bytes(&self, assess: Access<Physical>, size: usize, des: &mut [u8]) -> Result<()> {
+        if size > 0 {
+            des[0] = self.read_reg(assess.addr, false);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::access::{Access, AccessType};
+    use crate::device::Device;
+    use crate::device::uart::*;
+
+    fn write(uart: &mut Uart, addr: Xlen, data: u8) {
+        uart.write_byte(Access::new(addr, AccessType::Store), data).unwrap();
+    }
+
+    fn read(uart: &Uart, addr: Xlen) -> u8 {
+        uart.read_byte(Access::new(addr, AccessType::Load)).unwrap()
+    }
+
+    #[test]
+    fn test_receive() {
+        let mut uart = Uart::default();
+
+        assert_eq!(read(&uart, LSR), LSR_THRE | LSR_TEMT);
+
+        uart.push_input(b"hi");
+        assert_eq!(read(&uart, LSR) & LSR_DR, 0, "Input not ticked into FIFO yet");
+
+        uart.tick();
+        assert_eq!(read(&uart, LSR) & LSR_DR, LSR_DR);
+        assert_eq!(read(&uart, RBR), b'h');
+        assert_eq!(read(&uart, RBR), b'i');
+        assert_eq!(read(&uart, LSR) & LSR_DR, 0);
+    }
+
+    #[test]
+    fn test_fifo_limit() {
+        let mut uart = Uart::default();
+
+        uart.push_input(&[0xaa; FIFO_SIZE + 4]);
+        uart.tick();
+        for _ in 0..FIFO_SIZE {
+            assert_eq!(read(&uart, RBR), 0xaa);
+        }
+        assert_eq!(read(&uart, LSR) & LSR_DR, 0);
+
+        uart.tick();
+        assert_eq!(read(&uart, LSR) & (LSR_DR | LSR_OE), LSR_DR, "Rest of input no overrun");
+    }
+
+    #[test]
+    fn test_transmit_sink() {
+        let (sink, buf) = UartSink::buffer();
+        let mut uart = Uart::new(sink);
+
+        for byte in b"ok\n" {
+            write(&mut uart, RBR, *byte);
+        }
+        assert_eq!(buf.lock().unwrap().as_slice(), b"ok\n");
+
+        uart.reset();
+        write(&mut uart, RBR, b'!');
+        assert_eq!(buf.lock().unwrap().as_slice(), b"ok\n!", "Sink kept after reset");
+    }
+
+    #[test]
+    fn test_divisor_latch() {
+        let mut uart = Uart::default();
+
+        write(&mut uart, IER, IER_RDI);
+        write(&mut uart, LCR, LCR_DLAB | 0x03);
+        write(&mut uart, RBR, 0x34);
+        write(&mut uart, IER, 0x12);
+        assert_eq!(uart.divisor, 0x1234);
+        assert_eq!(read(&uart, RBR), 0x34);
+        assert_eq!(read(&uart, IER), 0x12);
+
+        write(&mut uart, LCR, 0x03);
+        assert_eq!(read(&uart, IER), IER_RDI, "IER not changed by DLM write");
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let mut uart = Uart::default();
+        write(&mut uart, IIR, FCR_ENABLE);
+
+        uart.push_input(b"a");
+        uart.tick();
+        assert!(!uart.irq_pending(), "IER not set");
+
+        write(&mut uart, IER, IER_RDI);
+        assert!(uart.irq_pending());
+        assert_eq!(read(&uart, IIR), IIR_FIFO_ENABLED | IIR_RDI);
+
+        read(&uart, RBR);
+        assert!(!uart.irq_pending());
+
+        write(&mut uart, IER, IER_RDI | IER_THRI);
+        assert_eq!(read(&uart, IIR), IIR_FIFO_ENABLED | IIR_THRI);
+        assert_eq!(read(&uart, IIR), IIR_FIFO_ENABLED | IIR_NO_INT, "Reading IIR clear THR empty interrupt");
+    }
+
+    #[test]
+    fn test_loopback() {
+        let mut uart = Uart::default();
+
+        write(&mut uart, MCR, MCR_LOOP | 0b0011);
+        assert_eq!(read(&uart, MSR), 0x30);
+
+        write(&mut uart, RBR, b'x');
+        assert_eq!(read(&uart, LSR) & LSR_DR, LSR_DR);
+        assert_eq!(read(&uart, RBR), b'x');
+
+        for _ in 0..=FIFO_SIZE {
+            write(&mut uart, RBR, b'y');
+        }
+        assert_eq!(read(&uart, LSR) & LSR_OE, LSR_OE);
+        assert_eq!(read(&uart, LSR) & LSR_OE, 0, "Reading LSR clear overrun");
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut uart = Uart::default();
+        let peek = |uart: &Uart, addr| {
+            let mut byte = [0];
+            uart.peek_bytes(Access::new(addr, AccessType::Load), 1, &mut byte).unwrap();
+            byte[0]
+        };
+
+        write(&mut uart, MCR, MCR_LOOP);
+        for _ in 0..=FIFO_SIZE {
+            write(&mut uart, RBR, b'z');
+        }
+        write(&mut uart, IER, IER_THRI);
+
+        assert_eq!(peek(&uart, RBR), b'z');
+        assert_eq!(peek(&uart, LSR) & (LSR_DR | LSR_OE), LSR_DR | LSR_OE);
+        assert_eq!(peek(&uart, IIR), IIR_THRI);
+
+        assert_eq!(read(&uart, LSR) & LSR_OE, LSR_OE, "Peek keep overrun");
+        assert_eq!(read(&uart, IIR), IIR_THRI, "Peek keep THR empty interrupt");
+        for _ in 0..FIFO_SIZE {
+            assert_eq!(read(&uart, RBR), b'z');
+        }
+        assert_eq!(read(&uart, LSR) & LSR_DR, 0, "Peek does not pop RX FIFO");
+    }
+}
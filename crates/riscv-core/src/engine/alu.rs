@@ -1,106 +1,117 @@
+use crate::{SXlen, XLEN, Xlen};
+
+// Double width for high part of multiplication
+#[cfg(all(feature = "m", not(feature = "rv64")))]
+type Double = u64;
+#[cfg(all(feature = "m", feature = "rv64"))]
+type Double = u128;
+#[cfg(all(feature = "m", not(feature = "rv64")))]
+type SDouble = i64;
+#[cfg(all(feature = "m", feature = "rv64"))]
+type SDouble = i128;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Alu;
 
 impl Alu {
-    pub fn add(data1: u32, data2: u32) -> u32 {
+    pub fn add(data1: Xlen, data2: Xlen) -> Xlen {
         data1.wrapping_add(data2)
     }
 
-    pub fn add_signed(data1: u32, data2: i32) -> u32 {
+    pub fn add_signed(data1: Xlen, data2: SXlen) -> Xlen {
         data1.wrapping_add_signed(data2)
     }
 
-    pub fn sub(data1: u32, data2: u32) -> u32 {
+    pub fn sub(data1: Xlen, data2: Xlen) -> Xlen {
         data1.wrapping_sub(data2)
     }
 
-    pub fn xor(data1: u32, data2: u32) -> u32 {
+    pub fn xor(data1: Xlen, data2: Xlen) -> Xlen {
         data1 ^ data2
     }
 
-    pub fn or(data1: u32, data2: u32) -> u32 {
+    pub fn or(data1: Xlen, data2: Xlen) -> Xlen {
         data1 | data2
     }
 
-    pub fn and(data1: u32, data2: u32) -> u32 {
+    pub fn and(data1: Xlen, data2: Xlen) -> Xlen {
         data1 & data2
     }
 
-    pub fn shl_logic(data: u32, shift: u32) -> u32 {
-        data << (shift % 32)
+    pub fn shl_logic(data: Xlen, shift: Xlen) -> Xlen {
+        data << (shift % XLEN as Xlen)
     }
 
-    pub fn shr_logic(data: u32, shift: u32) -> u32 {
-        data >> (shift % 32)
+    pub fn shr_logic(data: Xlen, shift: Xlen) -> Xlen {
+        data >> (shift % XLEN as Xlen)
     }
 
-    pub fn shr_ar(data: i32, shift: u32) -> u32 {
-        (data >> (shift % 32)) as u32
+    pub fn shr_ar(data: SXlen, shift: Xlen) -> Xlen {
+        (data >> (shift % XLEN as Xlen)) as Xlen
     }
 
-    pub fn set_less_than(data: i32, cmp_data: i32) -> u32 {
+    pub fn set_less_than(data: SXlen, cmp_data: SXlen) -> Xlen {
         (data < cmp_data).into()
     }
 
-    pub fn set_less_than_unsigned(data: u32, cmp_data: u32) -> u32 {
+    pub fn set_less_than_unsigned(data: Xlen, cmp_data: Xlen) -> Xlen {
         (data < cmp_data).into()
     }
 
     #[cfg(feature = "m")]
-    pub fn mul(data1: u32, data2: u32) -> u32 {
-        (data1 as i32 as i64)
-            .wrapping_mul(data2 as i32 as i64) as u32
+    pub fn mul(data1: Xlen, data2: Xlen) -> Xlen {
+        data1.wrapping_mul(data2)
     }
 
     #[cfg(feature = "m")]
-    pub fn mulh(data1: u32, data2: u32) -> u32 {
-        ((data1 as i32 as i64)
-            .wrapping_mul(data2 as i32 as i64) >> 32) as u32
+    pub fn mulh(data1: Xlen, data2: Xlen) -> Xlen {
+        ((data1 as SXlen as SDouble)
+            .wrapping_mul(data2 as SXlen as SDouble) >> XLEN) as Xlen
     }
 
     #[cfg(feature = "m")]
-    pub fn mulh_unsigned(data1: u32, data2: u32) -> u32 {
-        ((data1 as u64)
-            .wrapping_mul(data2 as u64) >> 32) as u32
+    pub fn mulh_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
+        ((data1 as Double)
+            .wrapping_mul(data2 as Double) >> XLEN) as Xlen
     }
     
     #[cfg(feature = "m")]
-    pub fn mulh_signed_unsigned(data1: u32, data2: u32) -> u32 {
-        ((data1 as i32 as i64)
-            .wrapping_mul(data2 as i64) >> 32) as u32
+    pub fn mulh_signed_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
+        ((data1 as SXlen as SDouble)
+            .wrapping_mul(data2 as SDouble) >> XLEN) as Xlen
     }
     
     #[cfg(feature = "m")]
-    pub fn div(data1: u32, data2: u32) -> u32 {
+    pub fn div(data1: Xlen, data2: Xlen) -> Xlen {
         if data2 == 0 {
-            u32::MAX
+            Xlen::MAX
         } else {
-            (data1 as i32)
-                .wrapping_div(data2 as i32) as u32
+            (data1 as SXlen)
+                .wrapping_div(data2 as SXlen) as Xlen
         }
     }
 
     #[cfg(feature = "m")]
-    pub fn div_unsigned(data1: u32, data2: u32) -> u32 {
+    pub fn div_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
         if data2 == 0 {
-            u32::MAX
+            Xlen::MAX
         } else {
             data1.wrapping_div(data2) 
         }
     }
 
     #[cfg(feature = "m")]
-    pub fn rem(data1: u32, data2: u32) -> u32 {
+    pub fn rem(data1: Xlen, data2: Xlen) -> Xlen {
         if data2 == 0 {
             data1
         } else {
-            (data1 as i32)
-                .wrapping_rem(data2 as i32) as u32
+            (data1 as SXlen)
+                .wrapping_rem(data2 as SXlen) as Xlen
         }
     }
 
     #[cfg(feature = "m")]
-    pub fn rem_unsigned(data1: u32, data2: u32) -> u32 {
+    pub fn rem_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
         if data2 == 0 {
             data1
         } else {
@@ -108,37 +119,257 @@ impl Alu {
         }
     }
 
-    #[cfg(feature = "a")]
-    pub fn max(data1: u32, data2: u32) -> u32 {
-        std::cmp::max(data1 as i32, data2 as i32) as u32
+    #[cfg(any(feature = "a", feature = "zbb"))]
+    pub fn max(data1: Xlen, data2: Xlen) -> Xlen {
+        std::cmp::max(data1 as SXlen, data2 as SXlen) as Xlen
     }
 
-    #[cfg(feature = "a")]
-    pub fn max_unsigned(data1: u32, data2: u32) -> u32 {
+    #[cfg(any(feature = "a", feature = "zbb"))]
+    pub fn max_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
         std::cmp::max(data1, data2)
     }
 
-    #[cfg(feature = "a")]
-    pub fn min(data1: u32, data2: u32) -> u32 {
-        std::cmp::min(data1 as i32, data2 as i32) as u32
+    #[cfg(any(feature = "a", feature = "zbb"))]
+    pub fn min(data1: Xlen, data2: Xlen) -> Xlen {
+        std::cmp::min(data1 as SXlen, data2 as SXlen) as Xlen
     }
 
-    #[cfg(feature = "a")]
-    pub fn min_unsigned(data1: u32, data2: u32) -> u32 {
+    #[cfg(any(feature = "a", feature = "zbb"))]
+    pub fn min_unsigned(data1: Xlen, data2: Xlen) -> Xlen {
         std::cmp::min(data1, data2)
     }
+
+    /// Sign-extend 32 bits result of `*w` instructions
+    #[cfg(feature = "rv64")]
+    pub fn sign_extend_word(data: u32) -> Xlen {
+        data as i32 as Xlen
+    }
+
+    #[cfg(feature = "rv64")]
+    pub fn shl_logic_word(data: Xlen, shift: Xlen) -> Xlen {
+        Self::sign_extend_word((data as u32) << (shift % 32))
+    }
+
+    #[cfg(feature = "rv64")]
+    pub fn shr_logic_word(data: Xlen, shift: Xlen) -> Xlen {
+        Self::sign_extend_word((data as u32) >> (shift % 32))
+    }
+
+    #[cfg(feature = "rv64")]
+    pub fn shr_ar_word(data: Xlen, shift: Xlen) -> Xlen {
+        ((data as i32) >> (shift % 32)) as Xlen
+    }
+
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    pub fn mul_word(data1: Xlen, data2: Xlen) -> Xlen {
+        Self::sign_extend_word((data1 as u32).wrapping_mul(data2 as u32))
+    }
+
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    pub fn div_word(data1: Xlen, data2: Xlen) -> Xlen {
+        if data2 as u32 == 0 {
+            Xlen::MAX
+        } else {
+            (data1 as i32).wrapping_div(data2 as i32) as Xlen
+        }
+    }
+
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    pub fn div_unsigned_word(data1: Xlen, data2: Xlen) -> Xlen {
+        if data2 as u32 == 0 {
+            Xlen::MAX
+        } else {
+            Self::sign_extend_word((data1 as u32).wrapping_div(data2 as u32))
+        }
+    }
+
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    pub fn rem_word(data1: Xlen, data2: Xlen) -> Xlen {
+        if data2 as u32 == 0 {
+            data1 as i32 as Xlen
+        } else {
+            (data1 as i32).wrapping_rem(data2 as i32) as Xlen
+        }
+    }
+
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    pub fn rem_unsigned_word(data1: Xlen, data2: Xlen) -> Xlen {
+        if data2 as u32 == 0 {
+            data1 as i32 as Xlen
+        } else {
+            Self::sign_extend_word((data1 as u32).wrapping_rem(data2 as u32))
+        }
+    }
+
+    /// `(data1 << shift) + data2` of `sh*add`
+    #[cfg(feature = "zba")]
+    pub fn shift_add(data1: Xlen, data2: Xlen, shift: u32) -> Xlen {
+        (data1 << shift).wrapping_add(data2)
+    }
+
+    /// Shift and add with zero-extended lower word of `data1`
+    #[cfg(all(feature = "zba", feature = "rv64"))]
+    pub fn shift_add_unsigned_word(data1: Xlen, data2: Xlen, shift: u32) -> Xlen {
+        Self::shift_add(data1 as u32 as Xlen, data2, shift)
+    }
+
+    #[cfg(all(feature = "zba", feature = "rv64"))]
+    pub fn shl_logic_unsigned_word(data: Xlen, shift: Xlen) -> Xlen {
+        Self::shl_logic(data as u32 as Xlen, shift)
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn and_not(data1: Xlen, data2: Xlen) -> Xlen {
+        data1 & !data2
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn or_not(data1: Xlen, data2: Xlen) -> Xlen {
+        data1 | !data2
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn xnor(data1: Xlen, data2: Xlen) -> Xlen {
+        !(data1 ^ data2)
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn count_leading_zeros(data: Xlen) -> Xlen {
+        data.leading_zeros() as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn count_trailing_zeros(data: Xlen) -> Xlen {
+        data.trailing_zeros() as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn count_ones(data: Xlen) -> Xlen {
+        data.count_ones() as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn sign_extend_byte(data: Xlen) -> Xlen {
+        data as i8 as SXlen as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn sign_extend_half(data: Xlen) -> Xlen {
+        data as i16 as SXlen as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn zero_extend_half(data: Xlen) -> Xlen {
+        data as u16 as Xlen
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn rotate_left(data: Xlen, shift: Xlen) -> Xlen {
+        data.rotate_left((shift % XLEN as Xlen) as u32)
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn rotate_right(data: Xlen, shift: Xlen) -> Xlen {
+        data.rotate_right((shift % XLEN as Xlen) as u32)
+    }
+
+    /// Each byte is `0xff` if any bit of it is set, otherwise `0x00`
+    #[cfg(feature = "zbb")]
+    pub fn or_combine_byte(data: Xlen) -> Xlen {
+        let mut bytes = data.to_le_bytes();
+        for byte in bytes.iter_mut() {
+            *byte = if *byte == 0 { 0x00 } else { 0xff };
+        }
+        Xlen::from_le_bytes(bytes)
+    }
+
+    #[cfg(feature = "zbb")]
+    pub fn byte_reverse(data: Xlen) -> Xlen {
+        data.swap_bytes()
+    }
+
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    pub fn count_leading_zeros_word(data: Xlen) -> Xlen {
+        (data as u32).leading_zeros() as Xlen
+    }
+
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    pub fn count_trailing_zeros_word(data: Xlen) -> Xlen {
+        (data as u32).trailing_zeros() as Xlen
+    }
+
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    pub fn count_ones_word(data: Xlen) -> Xlen {
+        (data as u32).count_ones() as Xlen
+    }
+
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    pub fn rotate_left_word(data: Xlen, shift: Xlen) -> Xlen {
+        Self::sign_extend_word((data as u32).rotate_left((shift % 32) as u32))
+    }
+
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    pub fn rotate_right_word(data: Xlen, shift: Xlen) -> Xlen {
+        Self::sign_extend_word((data as u32).rotate_right((shift % 32) as u32))
+    }
+
+    /// Lower XLEN bits of carry-less product
+    #[cfg(feature = "zbc")]
+    pub fn carryless_mul(data1: Xlen, data2: Xlen) -> Xlen {
+        (0..XLEN)
+            .filter(|i| (data2 >> i) & 1 != 0)
+            .fold(0, |res, i| res ^ (data1 << i))
+    }
+
+    /// Upper XLEN bits of carry-less product
+    #[cfg(feature = "zbc")]
+    pub fn carryless_mulh(data1: Xlen, data2: Xlen) -> Xlen {
+        (1..XLEN)
+            .filter(|i| (data2 >> i) & 1 != 0)
+            .fold(0, |res, i| res ^ (data1 >> (XLEN - i)))
+    }
+
+    /// Bits `[2*XLEN-2:XLEN-1]` of carry-less product
+    #[cfg(feature = "zbc")]
+    pub fn carryless_mulr(data1: Xlen, data2: Xlen) -> Xlen {
+        (0..XLEN)
+            .filter(|i| (data2 >> i) & 1 != 0)
+            .fold(0, |res, i| res ^ (data1 >> (XLEN - i - 1)))
+    }
+
+    #[cfg(feature = "zbs")]
+    pub fn bit_clear(data: Xlen, index: Xlen) -> Xlen {
+        data & !(1 << (index % XLEN as Xlen))
+    }
+
+    #[cfg(feature = "zbs")]
+    pub fn bit_extract(data: Xlen, index: Xlen) -> Xlen {
+        (data >> (index % XLEN as Xlen)) & 1
+    }
+
+    #[cfg(feature = "zbs")]
+    pub fn bit_invert(data: Xlen, index: Xlen) -> Xlen {
+        data ^ (1 << (index % XLEN as Xlen))
+    }
+
+    #[cfg(feature = "zbs")]
+    pub fn bit_set(data: Xlen, index: Xlen) -> Xlen {
+        data | (1 << (index % XLEN as Xlen))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::{XLEN, Xlen};
+    #[cfg(any(feature = "m", feature = "zbb", feature = "zbc"))] use crate::SXlen;
     use crate::engine::Alu;
 
     #[test]
     fn test_basic_arithmetic() {
         // wrapping add
-        assert_eq!(Alu::add(0xFFFFFFFF, 1), 0);
+        assert_eq!(Alu::add(Xlen::MAX, 1), 0);
         // wrapping sub
-        assert_eq!(Alu::sub(0, 1), 0xFFFFFFFF);
+        assert_eq!(Alu::sub(0, 1), Xlen::MAX);
         
         // logic
         assert_eq!(Alu::and(0b1100, 0b1010), 0b1000);
@@ -152,19 +383,19 @@ mod tests {
         assert_eq!(Alu::set_less_than(-1, 1), 1);
         
         // Unsigned
-        assert_eq!(Alu::set_less_than_unsigned(-1_i32 as u32, 1), 0);
+        assert_eq!(Alu::set_less_than_unsigned(-1_i32 as Xlen, 1), 0);
     }
     
     #[test] 
     fn test_shifts() {
-        // 1 << 33 should be 1 << 1 = 2
-        assert_eq!(Alu::shl_logic(1, 33), 2);
+        // 1 << (XLEN + 1) should be 1 << 1 = 2
+        assert_eq!(Alu::shl_logic(1, XLEN as Xlen + 1), 2);
 
         // Sign extension
-        assert_eq!(Alu::shr_ar(-4, 1), -2_i32 as u32);
+        assert_eq!(Alu::shr_ar(-4, 1), -2_i32 as Xlen);
 
         // Zero extension
-        assert_eq!(Alu::shr_logic(-4_i32 as u32, 1), 0x7FFFFFFE);
+        assert_eq!(Alu::shr_logic(-4_i32 as Xlen, 1), Xlen::MAX >> 1 ^ 1);
     }
 
     #[test]
@@ -174,13 +405,13 @@ mod tests {
         assert_eq!(Alu::mul(10, 20), 200);
     
         // Mulh (Signed * Signed, High bits)
-        assert_eq!(Alu::mulh(-1_i32 as u32, -1_i32 as u32), 0);
+        assert_eq!(Alu::mulh(-1_i32 as Xlen, -1_i32 as Xlen), 0);
         
         // Mulhu (Unsigned * Unsigned, High bits)
-        assert_eq!(Alu::mulh_unsigned(u32::MAX, 2), 1);
+        assert_eq!(Alu::mulh_unsigned(Xlen::MAX, 2), 1);
 
         // Mulhsu (Signed * Unsigned, High bits)
-        assert_eq!(Alu::mulh_signed_unsigned(-1_i32 as u32, 2), u32::MAX);
+        assert_eq!(Alu::mulh_signed_unsigned(-1_i32 as Xlen, 2), Xlen::MAX);
     }
 
     #[test]
@@ -188,8 +419,8 @@ mod tests {
     fn test_division_edge_cases() {
         // Division by Zero
         // RISC-V spec: div by 0 returns -1
-        assert_eq!(Alu::div(100, 0), u32::MAX);
-        assert_eq!(Alu::div_unsigned(100, 0), u32::MAX);
+        assert_eq!(Alu::div(100, 0), Xlen::MAX);
+        assert_eq!(Alu::div_unsigned(100, 0), Xlen::MAX);
         
         // RISC-V spec: rem by 0 returns dividend
         assert_eq!(Alu::rem(100, 0), 100);
@@ -197,10 +428,124 @@ mod tests {
 
         // Signed Overflow Division
         // RISC-V spec: returns INT_MIN
-        let int_min = i32::MIN as u32;
-        assert_eq!(Alu::div(int_min, -1_i32 as u32), int_min);
+        let int_min = SXlen::MIN as Xlen;
+        assert_eq!(Alu::div(int_min, -1_i32 as Xlen), int_min);
         
         // Remainder should be 0
-        assert_eq!(Alu::rem(int_min, -1_i32 as u32), 0);
+        assert_eq!(Alu::rem(int_min, -1_i32 as Xlen), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[cfg(feature = "rv64")]
+    fn test_word() {
+        assert_eq!(Alu::sign_extend_word(0x8000_0000), 0xffff_ffff_8000_0000);
+        // Upper 32 bits are ignored
+        assert_eq!(Alu::shl_logic_word(0x1_0000_0001, 31), 0xffff_ffff_8000_0000);
+        assert_eq!(Alu::shr_logic_word(0xffff_ffff_8000_0000, 31), 1);
+        assert_eq!(Alu::shr_ar_word(0x8000_0000, 31), Xlen::MAX);
+    }
+
+    #[test]
+    #[cfg(all(feature = "m", feature = "rv64"))]
+    fn test_word_division() {
+        assert_eq!(Alu::mul_word(0x1_0000_0002, 0x4000_0000), 0xffff_ffff_8000_0000);
+        assert_eq!(Alu::div_word(i32::MIN as Xlen, -1_i32 as Xlen), i32::MIN as Xlen);
+        assert_eq!(Alu::div_unsigned_word(100, 0x1_0000_0000), Xlen::MAX);
+        assert_eq!(Alu::rem_word(0x1_8000_0000, 0), 0xffff_ffff_8000_0000);
+        assert_eq!(Alu::rem_unsigned_word(7, 0x1_0000_0002), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "zba")]
+    fn test_shift_add() {
+        assert_eq!(Alu::shift_add(3, 100, 1), 106);
+        assert_eq!(Alu::shift_add(3, 100, 2), 112);
+        assert_eq!(Alu::shift_add(3, 100, 3), 124);
+        // Wrapping
+        assert_eq!(Alu::shift_add(Xlen::MAX, 2, 1), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "zba", feature = "rv64"))]
+    fn test_shift_add_word() {
+        assert_eq!(Alu::shift_add_unsigned_word(0xffff_ffff_8000_0000, 1, 0), 0x8000_0001);
+        assert_eq!(Alu::shift_add_unsigned_word(0x1_8000_0000, 1, 3), 0x4_0000_0001);
+        assert_eq!(Alu::shl_logic_unsigned_word(0xffff_ffff_8000_0000, 4), 0x8_0000_0000);
+    }
+
+    #[test]
+    #[cfg(feature = "zbb")]
+    fn test_logic_with_negate() {
+        assert_eq!(Alu::and_not(0b1100, 0b1010), 0b0100);
+        assert_eq!(Alu::or_not(0b1100, 0b1010), !0b0010);
+        assert_eq!(Alu::xnor(0b1100, 0b1010), !0b0110);
+    }
+
+    #[test]
+    #[cfg(feature = "zbb")]
+    fn test_count_bits() {
+        assert_eq!(Alu::count_leading_zeros(1), XLEN as Xlen - 1);
+        assert_eq!(Alu::count_leading_zeros(0), XLEN as Xlen);
+        assert_eq!(Alu::count_trailing_zeros(0b1000), 3);
+        assert_eq!(Alu::count_trailing_zeros(0), XLEN as Xlen);
+        assert_eq!(Alu::count_ones(0xf0f0), 8);
+        assert_eq!(Alu::count_ones(Xlen::MAX), XLEN as Xlen);
+    }
+
+    #[test]
+    #[cfg(feature = "zbb")]
+    fn test_min_max_extend() {
+        assert_eq!(Alu::max(-1_i32 as Xlen, 1), 1);
+        assert_eq!(Alu::max_unsigned(-1_i32 as Xlen, 1), Xlen::MAX);
+        assert_eq!(Alu::min(-1_i32 as Xlen, 1), Xlen::MAX);
+        assert_eq!(Alu::min_unsigned(-1_i32 as Xlen, 1), 1);
+
+        assert_eq!(Alu::sign_extend_byte(0x1280), -128_i32 as Xlen);
+        assert_eq!(Alu::sign_extend_half(0x1_8000), -32768_i32 as Xlen);
+        assert_eq!(Alu::zero_extend_half(Xlen::MAX), 0xffff);
+    }
+
+    #[test]
+    #[cfg(feature = "zbb")]
+    fn test_rotate_and_bytes() {
+        assert_eq!(Alu::rotate_left(SXlen::MIN as Xlen | 1, 1), 0b11);
+        // Only low bits of shift amount are used
+        assert_eq!(Alu::rotate_right(0b11, XLEN as Xlen + 1), SXlen::MIN as Xlen | 1);
+
+        assert_eq!(Alu::or_combine_byte(0x0100_8000), 0xff00_ff00);
+        assert_eq!(Alu::byte_reverse(0x1234), 0x3412 << (XLEN - 16));
+    }
+
+    #[test]
+    #[cfg(all(feature = "zbb", feature = "rv64"))]
+    fn test_count_and_rotate_word() {
+        assert_eq!(Alu::count_leading_zeros_word(0xffff_ffff_0000_0001), 31);
+        assert_eq!(Alu::count_trailing_zeros_word(0x1_0000_0000), 32);
+        assert_eq!(Alu::count_ones_word(0xffff_ffff_0000_0003), 2);
+        assert_eq!(Alu::rotate_left_word(0x4000_0001, 1), 0xffff_ffff_8000_0002);
+        assert_eq!(Alu::rotate_right_word(0x1_0000_0001, 1), 0xffff_ffff_8000_0000);
+    }
+
+    #[test]
+    #[cfg(feature = "zbc")]
+    fn test_carryless_mul() {
+        // (x + 1) * (x + 1) = x^2 + 1
+        assert_eq!(Alu::carryless_mul(0b11, 0b11), 0b101);
+        assert_eq!(Alu::carryless_mulh(0b11, 0b11), 0);
+
+        let top = SXlen::MIN as Xlen;
+        assert_eq!(Alu::carryless_mul(top | 1, 0b10), 0b10);
+        assert_eq!(Alu::carryless_mulh(top | 1, 0b10), 1);
+        assert_eq!(Alu::carryless_mulr(top | 1, 0b10), 0b10);
+        assert_eq!(Alu::carryless_mulr(top, top), top);
+    }
+
+    #[test]
+    #[cfg(feature = "zbs")]
+    fn test_single_bit() {
+        assert_eq!(Alu::bit_clear(0b111, 1), 0b101);
+        assert_eq!(Alu::bit_extract(0b100, 2), 1);
+        assert_eq!(Alu::bit_invert(0b100, 2), 0);
+        assert_eq!(Alu::bit_set(0, XLEN as Xlen + 3), 0b1000);
+    }
+}
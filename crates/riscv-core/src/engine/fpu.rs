@@ -0,0 +1,712 @@
+//! Software IEEE-754 arithmetic for F and D extensions. Host float can not use other
+//! rounding modes or report exceptions, so values are computed on bits.
+//! Both formats are in `u64`, single precision use the low 32 bits
+
+use std::cmp::Ordering;
+
+/// Exception flags in `fflags`
+pub const NV: u8 = 1 << 4; // Invalid operation
+pub const DZ: u8 = 1 << 3; // Divide by zero
+pub const OF: u8 = 1 << 2; // Overflow
+pub const UF: u8 = 1 << 1; // Underflow
+pub const NX: u8 = 1 << 0; // Inexact
+
+/// Rounding mode encoded like `frm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Rne = 0, // Round to nearest, ties to even
+    Rtz = 1, // Round towards zero
+    Rdn = 2, // Round down
+    Rup = 3, // Round up
+    Rmm = 4, // Round to nearest, ties to max magnitude
+}
+
+impl RoundingMode {
+    /// `None` for reserved encoding
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        Some(match bits {
+            0 => RoundingMode::Rne,
+            1 => RoundingMode::Rtz,
+            2 => RoundingMode::Rdn,
+            3 => RoundingMode::Rup,
+            4 => RoundingMode::Rmm,
+            _ => return None,
+        })
+    }
+}
+
+/// Binary interchange format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    exp_bits: u32,
+    frac_bits: u32,
+}
+
+pub const SINGLE: Format = Format { exp_bits: 8, frac_bits: 23 };
+#[cfg(feature = "d")]
+pub const DOUBLE: Format = Format { exp_bits: 11, frac_bits: 52 };
+
+impl Format {
+    fn bias(&self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    /// Exponent of the minimum normal number
+    fn emin(&self) -> i32 {
+        1 - self.bias()
+    }
+
+    /// Significant bits with the hidden one
+    fn precision(&self) -> u32 {
+        self.frac_bits + 1
+    }
+
+    fn exp_max(&self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    fn frac_mask(&self) -> u64 {
+        (1 << self.frac_bits) - 1
+    }
+
+    fn sign_bit(&self) -> u64 {
+        1 << (self.exp_bits + self.frac_bits)
+    }
+
+    fn sign(&self, sign: bool) -> u64 {
+        if sign { self.sign_bit() } else { 0 }
+    }
+
+    /// Positive quiet NaN with only the highest fraction bit set
+    pub fn canonical_nan(&self) -> u64 {
+        (self.exp_max() << self.frac_bits) | (1 << (self.frac_bits - 1))
+    }
+
+    fn zero(&self, sign: bool) -> u64 {
+        self.sign(sign)
+    }
+
+    fn infinity(&self, sign: bool) -> u64 {
+        self.sign(sign) | (self.exp_max() << self.frac_bits)
+    }
+
+    fn max_finite(&self, sign: bool) -> u64 {
+        self.sign(sign) | ((self.exp_max() - 1) << self.frac_bits) | self.frac_mask()
+    }
+
+    fn unpack(&self, bits: u64) -> Value {
+        let sign = bits & self.sign_bit() != 0;
+        let exp = (bits >> self.frac_bits) & self.exp_max();
+        let frac = bits & self.frac_mask();
+
+        if exp == 0 {
+            if frac == 0 {
+                Value::Zero(sign)
+            } else {
+                Value::Finite(Finite { sign, exp: self.emin() - self.frac_bits as i32, sig: frac as u128 })
+            }
+        } else if exp == self.exp_max() {
+            if frac == 0 {
+                Value::Inf(sign)
+            } else {
+                Value::NaN { signaling: frac >> (self.frac_bits - 1) == 0 }
+            }
+        } else {
+            let exp = exp as i32 - self.bias() - self.frac_bits as i32;
+            Value::Finite(Finite { sign, exp, sig: (frac | (1 << self.frac_bits)) as u128 })
+        }
+    }
+
+    /// Key ordering the magnitude and sign of non-NaN value, `-0` is less than `+0`
+    fn order_key(&self, bits: u64) -> i128 {
+        let magnitude = (bits & !self.sign_bit()) as i128;
+        if bits & self.sign_bit() != 0 { -magnitude - 1 } else { magnitude }
+    }
+}
+
+/// Finite nonzero number `sig * 2^exp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Finite {
+    sign: bool,
+    exp: i32,
+    sig: u128,
+}
+
+impl Finite {
+    /// Exponent of the highest set bit
+    fn top(&self) -> i32 {
+        self.exp + bit_len(self.sig) as i32 - 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Zero(bool),
+    Finite(Finite),
+    Inf(bool),
+    NaN { signaling: bool },
+}
+
+impl Value {
+    fn is_nan(&self) -> bool {
+        matches!(self, Value::NaN { .. })
+    }
+
+    fn is_signaling(&self) -> bool {
+        matches!(self, Value::NaN { signaling: true })
+    }
+
+    fn negate(self) -> Self {
+        match self {
+            Value::Zero(sign) => Value::Zero(!sign),
+            Value::Finite(x) => Value::Finite(Finite { sign: !x.sign, ..x }),
+            Value::Inf(sign) => Value::Inf(!sign),
+            nan => nan,
+        }
+    }
+}
+
+fn bit_len(data: u128) -> u32 {
+    128 - data.leading_zeros()
+}
+
+/// Shift right, lowest bit is set if any shifted out bit is set
+fn shift_right_jam(data: u128, shift: u32) -> u128 {
+    if shift >= 128 {
+        (data != 0) as u128
+    } else {
+        (data >> shift) | (data & ((1 << shift) - 1) != 0) as u128
+    }
+}
+
+/// Floor of square root and the remainder
+fn isqrt(mut data: u128) -> (u128, u128) {
+    let mut root = 0;
+    let mut bit = if data == 0 { 0 } else { 1 << ((bit_len(data) - 1) & !1) };
+    while bit != 0 {
+        if data >= root + bit {
+            data -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    (root, data)
+}
+
+/// Operations of one instruction. Raised exceptions are accrued in `flags`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fpu {
+    rm: RoundingMode,
+    pub flags: u8,
+}
+
+impl Fpu {
+    pub fn new(rm: RoundingMode) -> Self {
+        Fpu { rm, flags: 0 }
+    }
+
+    pub fn add(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        self.add_value(fmt, fmt.unpack(a), fmt.unpack(b))
+    }
+
+    pub fn sub(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        self.add_value(fmt, fmt.unpack(a), fmt.unpack(b).negate())
+    }
+
+    pub fn mul(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        let sign = (a ^ b) & fmt.sign_bit() != 0;
+        match (fmt.unpack(a), fmt.unpack(b)) {
+            (va, vb) if va.is_nan() || vb.is_nan() => self.nan(fmt, &[va, vb]),
+            (Value::Inf(_), Value::Zero(_)) | (Value::Zero(_), Value::Inf(_)) => self.invalid(fmt),
+            (Value::Inf(_), _) | (_, Value::Inf(_)) => fmt.infinity(sign),
+            (Value::Zero(_), _) | (_, Value::Zero(_)) => fmt.zero(sign),
+            (Value::Finite(a), Value::Finite(b)) => self.round_pack(fmt, sign, a.exp + b.exp, a.sig * b.sig),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn div(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        let (va, vb) = (fmt.unpack(a), fmt.unpack(b));
+        if va.is_nan() || vb.is_nan() {
+            return self.nan(fmt, &[va, vb]);
+        }
+        let sign = (a ^ b) & fmt.sign_bit() != 0;
+        match (va, vb) {
+            (Value::Inf(_), Value::Inf(_)) | (Value::Zero(_), Value::Zero(_)) => self.invalid(fmt),
+            (Value::Inf(_), _) => fmt.infinity(sign),
+            (_, Value::Inf(_)) | (Value::Zero(_), _) => fmt.zero(sign),
+            (_, Value::Zero(_)) => {
+                self.flags |= DZ;
+                fmt.infinity(sign)
+            },
+            (Value::Finite(a), Value::Finite(b)) => {
+                // Quotient has at least 3 bits more than precision, remainder is jammed
+                let shift = (fmt.precision() as i32 + 4 + bit_len(b.sig) as i32 - bit_len(a.sig) as i32).max(0);
+                let num = a.sig << shift;
+                let sig = (num / b.sig) | !num.is_multiple_of(b.sig) as u128;
+                self.round_pack(fmt, sign, a.exp - b.exp - shift, sig)
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn sqrt(&mut self, fmt: Format, a: u64) -> u64 {
+        match fmt.unpack(a) {
+            nan @ Value::NaN { .. } => self.nan(fmt, &[nan]),
+            Value::Zero(_) => a,
+            Value::Inf(false) => a,
+            Value::Inf(true) | Value::Finite(Finite { sign: true, .. }) => self.invalid(fmt),
+            Value::Finite(a) => {
+                // Even exponent, and at least 3 bits more than precision in the root
+                let mut shift = (2 * (fmt.precision() as i32 + 4) - bit_len(a.sig) as i32).max(0);
+                if (a.exp - shift) % 2 != 0 {
+                    shift += 1;
+                }
+                let (root, rem) = isqrt(a.sig << shift);
+                self.round_pack(fmt, false, (a.exp - shift) / 2, root | (rem != 0) as u128)
+            },
+        }
+    }
+
+    /// `(a * b) + c`, product and addend are negated by `negate_product` and `negate_addend`
+    pub fn mul_add(&mut self, fmt: Format, a: u64, b: u64, c: u64, negate_product: bool, negate_addend: bool) -> u64 {
+        let (va, vb, mut vc) = (fmt.unpack(a), fmt.unpack(b), fmt.unpack(c));
+        // Invalid even if addend is quiet NaN
+        if matches!((va, vb), (Value::Inf(_), Value::Zero(_)) | (Value::Zero(_), Value::Inf(_))) {
+            return self.invalid(fmt);
+        }
+        if va.is_nan() || vb.is_nan() || vc.is_nan() {
+            return self.nan(fmt, &[va, vb, vc]);
+        }
+        if negate_addend {
+            vc = vc.negate();
+        }
+
+        let sign = ((a ^ b) & fmt.sign_bit() != 0) ^ negate_product;
+        let product = match (va, vb) {
+            (Value::Inf(_), _) | (_, Value::Inf(_)) => Value::Inf(sign),
+            (Value::Zero(_), _) | (_, Value::Zero(_)) => Value::Zero(sign),
+            (Value::Finite(a), Value::Finite(b)) => Value::Finite(Finite { sign, exp: a.exp + b.exp, sig: a.sig * b.sig }),
+            _ => unreachable!(),
+        };
+        self.add_value(fmt, product, vc)
+    }
+
+    pub fn min(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        self.min_max(fmt, a, b, Ordering::Less)
+    }
+
+    pub fn max(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        self.min_max(fmt, a, b, Ordering::Greater)
+    }
+
+    /// Quiet compare, only signaling NaN is invalid
+    pub fn feq(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, false) == Some(Ordering::Equal)
+    }
+
+    /// Signaling compare, any NaN is invalid
+    pub fn flt(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, true) == Some(Ordering::Less)
+    }
+
+    /// Signaling compare, any NaN is invalid
+    pub fn fle(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        matches!(self.compare(fmt, a, b, true), Some(Ordering::Less | Ordering::Equal))
+    }
+
+    /// Convert to signed or unsigned integer of `width` bits, result is in the low `width` bits.
+    /// NaN and out of range value are invalid and saturated
+    pub fn float_to_int(&mut self, fmt: Format, a: u64, width: u32, signed: bool) -> u64 {
+        let mask = u64::MAX >> (64 - width);
+        let signed_max = mask >> 1;
+        let saturate = |sign: bool| match (signed, sign) {
+            (true, false)  => signed_max,
+            (true, true)   => signed_max + 1,
+            (false, false) => mask,
+            (false, true)  => 0,
+        };
+
+        match fmt.unpack(a) {
+            Value::NaN { .. } => {
+                self.flags |= NV;
+                saturate(false)
+            },
+            Value::Inf(sign) => {
+                self.flags |= NV;
+                saturate(sign)
+            },
+            Value::Zero(_) => 0,
+            Value::Finite(x) => {
+                let (magnitude, inexact) = if x.exp >= 0 {
+                    // Too large value is always out of range
+                    (if x.exp > 64 { u128::MAX } else { x.sig << x.exp }, false)
+                } else {
+                    self.round_sig(x.sig, x.exp.unsigned_abs(), x.sign)
+                };
+
+                let in_range = match (signed, x.sign) {
+                    (true, false)  => magnitude <= signed_max as u128,
+                    (true, true)   => magnitude <= signed_max as u128 + 1,
+                    (false, false) => magnitude <= mask as u128,
+                    (false, true)  => magnitude == 0,
+                };
+                if !in_range {
+                    self.flags |= NV;
+                    return saturate(x.sign);
+                }
+                if inexact {
+                    self.flags |= NX;
+                }
+                if x.sign { (magnitude as u64).wrapping_neg() & mask } else { magnitude as u64 }
+            },
+        }
+    }
+
+    /// Convert from signed or unsigned integer in the low `width` bits of `data`
+    pub fn int_to_float(&mut self, fmt: Format, data: u64, width: u32, signed: bool) -> u64 {
+        let data = data & (u64::MAX >> (64 - width));
+        // Sign extend to `i64`
+        let value = ((data << (64 - width)) as i64) >> (64 - width);
+        let sign = signed && value < 0;
+        let magnitude = if sign { value.unsigned_abs() } else { data };
+        if magnitude == 0 {
+            return fmt.zero(false);
+        }
+        self.round_pack(fmt, sign, 0, magnitude as u128)
+    }
+
+    /// Convert between formats
+    #[cfg(feature = "d")]
+    pub fn convert(&mut self, from: Format, to: Format, a: u64) -> u64 {
+        match from.unpack(a) {
+            nan @ Value::NaN { .. } => {
+                self.nan(to, &[nan])
+            },
+            Value::Zero(sign) => to.zero(sign),
+            Value::Inf(sign) => to.infinity(sign),
+            Value::Finite(x) => self.round_pack(to, x.sign, x.exp, x.sig),
+        }
+    }
+
+    /// `fclass` mask: -inf, -normal, -subnormal, -0, +0, +subnormal, +normal, +inf, sNaN, qNaN
+    pub fn classify(fmt: Format, a: u64) -> u32 {
+        let sign = a & fmt.sign_bit() != 0;
+        let subnormal = (a >> fmt.frac_bits) & fmt.exp_max() == 0;
+        let bit = match fmt.unpack(a) {
+            Value::Inf(_)                => if sign { 0 } else { 7 },
+            Value::Finite(_) if subnormal => if sign { 2 } else { 5 },
+            Value::Finite(_)             => if sign { 1 } else { 6 },
+            Value::Zero(_)               => if sign { 3 } else { 4 },
+            Value::NaN { signaling }     => if signaling { 8 } else { 9 },
+        };
+        1 << bit
+    }
+
+    /// Sign injection: `a` with sign of `b`, negated sign of `b` or their xor
+    pub fn sign_inject(fmt: Format, a: u64, b: u64, negate: bool, xor: bool) -> u64 {
+        let sign_bit = fmt.sign_bit();
+        let sign = if xor {
+            (a ^ b) & sign_bit
+        } else if negate {
+            !b & sign_bit
+        } else {
+            b & sign_bit
+        };
+        (a & !sign_bit) | sign
+    }
+
+    fn add_value(&mut self, fmt: Format, a: Value, b: Value) -> u64 {
+        match (a, b) {
+            (a, b) if a.is_nan() || b.is_nan() => self.nan(fmt, &[a, b]),
+            (Value::Inf(sa), Value::Inf(sb)) if sa != sb => self.invalid(fmt),
+            (Value::Inf(sign), _) | (_, Value::Inf(sign)) => fmt.infinity(sign),
+            (Value::Zero(sa), Value::Zero(sb)) => fmt.zero(if sa == sb { sa } else { self.rm == RoundingMode::Rdn }),
+            (Value::Zero(_), Value::Finite(x)) | (Value::Finite(x), Value::Zero(_)) => self.round_pack(fmt, x.sign, x.exp, x.sig),
+            (Value::Finite(a), Value::Finite(b)) => self.add_finite(fmt, a, b),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Put the larger operand at bit 125. The other is exact unless it is far smaller,
+    /// then it is jammed into a sticky bit below rounding position
+    fn add_finite(&mut self, fmt: Format, a: Finite, b: Finite) -> u64 {
+        let (x, y) = if a.top() >= b.top() { (a, b) } else { (b, a) };
+        let exp = x.top() - 125;
+        let x_sig = x.sig << (x.exp - exp);
+        let y_sig = if y.exp >= exp {
+            y.sig << (y.exp - exp)
+        } else {
+            shift_right_jam(y.sig, (exp - y.exp) as u32)
+        };
+
+        let (sign, sig) = if x.sign == y.sign {
+            (x.sign, x_sig + y_sig)
+        } else if x_sig >= y_sig {
+            (x.sign, x_sig - y_sig)
+        } else {
+            (y.sign, y_sig - x_sig)
+        };
+        if sig == 0 {
+            // Exact zero of different signs is +0, except rounding down
+            return fmt.zero(self.rm == RoundingMode::Rdn);
+        }
+        self.round_pack(fmt, sign, exp, sig)
+    }
+
+    fn min_max(&mut self, fmt: Format, a: u64, b: u64, pick: Ordering) -> u64 {
+        let (va, vb) = (fmt.unpack(a), fmt.unpack(b));
+        if va.is_signaling() || vb.is_signaling() {
+            self.flags |= NV;
+        }
+        match (va.is_nan(), vb.is_nan()) {
+            (true, true)   => fmt.canonical_nan(),
+            (true, false)  => b,
+            (false, true)  => a,
+            (false, false) => if fmt.order_key(a).cmp(&fmt.order_key(b)) == pick { a } else { b },
+        }
+    }
+
+    fn compare(&mut self, fmt: Format, a: u64, b: u64, signaling: bool) -> Option<Ordering> {
+        let (va, vb) = (fmt.unpack(a), fmt.unpack(b));
+        if va.is_nan() || vb.is_nan() {
+            if signaling || va.is_signaling() || vb.is_signaling() {
+                self.flags |= NV;
+            }
+            return None;
+        }
+        if matches!((va, vb), (Value::Zero(_), Value::Zero(_))) {
+            return Some(Ordering::Equal);
+        }
+        Some(fmt.order_key(a).cmp(&fmt.order_key(b)))
+    }
+
+    /// Canonical NaN, invalid if any operand is signaling NaN
+    fn nan(&mut self, fmt: Format, values: &[Value]) -> u64 {
+        if values.iter().any(Value::is_signaling) {
+            self.flags |= NV;
+        }
+        fmt.canonical_nan()
+    }
+
+    fn invalid(&mut self, fmt: Format) -> u64 {
+        self.flags |= NV;
+        fmt.canonical_nan()
+    }
+
+    /// Drop `shift` low bits of `sig` by rounding mode. Return the rounded value and if it is inexact
+    fn round_sig(&self, sig: u128, shift: u32, sign: bool) -> (u128, bool) {
+        if shift == 0 {
+            return (sig, false);
+        }
+        let (quotient, half, inexact) = if shift > 128 {
+            (0, Ordering::Less, sig != 0)
+        } else {
+            let rem = sig & (u128::MAX >> (128 - shift));
+            (sig.checked_shr(shift).unwrap_or(0), rem.cmp(&(1 << (shift - 1))), rem != 0)
+        };
+
+        let increase = match self.rm {
+            RoundingMode::Rne => half == Ordering::Greater || (half == Ordering::Equal && quotient & 1 == 1),
+            RoundingMode::Rtz => false,
+            RoundingMode::Rdn => inexact && sign,
+            RoundingMode::Rup => inexact && !sign,
+            RoundingMode::Rmm => half != Ordering::Less,
+        };
+        (quotient + increase as u128, inexact)
+    }
+
+    /// Round `sig * 2^exp` into `fmt`. Low bit of `sig` may be a sticky bit,
+    /// it must be at least 2 bits below the rounding position
+    fn round_pack(&mut self, fmt: Format, sign: bool, exp: i32, sig: u128) -> u64 {
+        let precision = fmt.precision() as i32;
+        let emin = fmt.emin();
+        let top = exp + bit_len(sig) as i32 - 1;
+
+        // Exponent of the result's lowest bit, subnormal has fixed one
+        let mut lsb = top.max(emin) - (precision - 1);
+        let (rounded, inexact) = if lsb > exp {
+            self.round_sig(sig, (lsb - exp) as u32, sign)
+        } else {
+            (sig << (exp - lsb), false)
+        };
+
+        if inexact {
+            self.flags |= NX;
+            // Tininess is detected after rounding with unbounded exponent,
+            // only a value just below the minimum normal can round up to it
+            let unbounded_lsb = top - (precision - 1);
+            let to_normal = top == emin - 1 && unbounded_lsb > exp
+                && self.round_sig(sig, (unbounded_lsb - exp) as u32, sign).0 >> precision != 0;
+            if top < emin && !to_normal {
+                self.flags |= UF;
+            }
+        }
+
+        let mut sig = rounded;
+        if sig >> precision != 0 {
+            sig >>= 1;
+            lsb += 1;
+        }
+
+        if sig >> (precision - 1) == 0 {
+            // Subnormal or zero
+            return fmt.sign(sign) | sig as u64;
+        }
+        let biased = lsb + precision - 1 + fmt.bias();
+        if biased as u64 >= fmt.exp_max() {
+            self.flags |= OF | NX;
+            let to_infinity = match self.rm {
+                RoundingMode::Rne | RoundingMode::Rmm => true,
+                RoundingMode::Rtz => false,
+                RoundingMode::Rdn => sign,
+                RoundingMode::Rup => !sign,
+            };
+            return if to_infinity { fmt.infinity(sign) } else { fmt.max_finite(sign) };
+        }
+        fmt.sign(sign) | (biased as u64) << fmt.frac_bits | (sig as u64 & fmt.frac_mask())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u64 = 0x3f80_0000;
+    const THREE: u64 = 0x4040_0000;
+    const MAX: u64 = 0x7f7f_ffff;
+    const QNAN: u64 = 0x7fc0_0000;
+    const SNAN: u64 = 0x7f80_0001;
+
+    #[test]
+    fn test_rounding_mode() {
+        let div = |rm, a| {
+            let mut fpu = Fpu::new(rm);
+            (fpu.div(SINGLE, a, THREE), fpu.flags)
+        };
+        assert_eq!(div(RoundingMode::Rne, ONE), (0x3eaa_aaab, NX));
+        assert_eq!(div(RoundingMode::Rtz, ONE), (0x3eaa_aaaa, NX));
+        assert_eq!(div(RoundingMode::Rdn, ONE), (0x3eaa_aaaa, NX));
+        assert_eq!(div(RoundingMode::Rup, ONE), (0x3eaa_aaab, NX));
+        assert_eq!(div(RoundingMode::Rdn, ONE | 1 << 31), (0xbeaa_aaab, NX));
+        assert_eq!(div(RoundingMode::Rup, ONE | 1 << 31), (0xbeaa_aaaa, NX));
+        assert_eq!(div(RoundingMode::Rne, THREE), (ONE, 0));
+    }
+
+    #[test]
+    fn test_overflow_underflow() {
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.mul(SINGLE, MAX, THREE), 0x7f80_0000);
+        assert_eq!(fpu.flags, OF | NX);
+
+        let mut fpu = Fpu::new(RoundingMode::Rtz);
+        assert_eq!(fpu.mul(SINGLE, MAX, THREE), MAX, "Round towards zero is saturated");
+
+        // Minimum normal / 3 is subnormal and inexact
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.div(SINGLE, 0x0080_0000, THREE), 0x002a_aaab);
+        assert_eq!(fpu.flags, UF | NX);
+
+        // Exact subnormal result is not underflow
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.mul(SINGLE, 0x0080_0000, 0x3f00_0000), 0x0040_0000);
+        assert_eq!(fpu.flags, 0);
+    }
+
+    #[test]
+    fn test_nan() {
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.add(SINGLE, QNAN | 0x1234, ONE), QNAN, "Result is canonical NaN");
+        assert_eq!(fpu.flags, 0);
+
+        assert_eq!(fpu.add(SINGLE, SNAN, ONE), QNAN);
+        assert_eq!(fpu.flags, NV, "Signaling NaN is invalid");
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.sqrt(SINGLE, ONE | 1 << 31), QNAN);
+        assert_eq!(fpu.flags, NV);
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.mul_add(SINGLE, 0x7f80_0000, 0, QNAN, false, false), QNAN);
+        assert_eq!(fpu.flags, NV, "Infinity times zero is invalid even if addend is NaN");
+    }
+
+    #[test]
+    fn test_min_max_compare() {
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.min(SINGLE, 0, 1 << 31), 1 << 31, "-0 is less than +0");
+        assert_eq!(fpu.max(SINGLE, QNAN, ONE), ONE);
+        assert_eq!(fpu.flags, 0);
+        assert_eq!(fpu.min(SINGLE, SNAN, ONE), ONE);
+        assert_eq!(fpu.flags, NV);
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert!(fpu.feq(SINGLE, 0, 1 << 31));
+        assert!(!fpu.feq(SINGLE, QNAN, QNAN));
+        assert_eq!(fpu.flags, 0, "Equal is quiet compare");
+        assert!(!fpu.flt(SINGLE, QNAN, ONE));
+        assert_eq!(fpu.flags, NV, "Less than is signaling compare");
+    }
+
+    #[test]
+    fn test_convert_int() {
+        let mut fpu = Fpu::new(RoundingMode::Rtz);
+        assert_eq!(fpu.float_to_int(SINGLE, 0xc060_0000, 32, true), -3i32 as u32 as u64);
+        assert_eq!(fpu.flags, NX);
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.float_to_int(SINGLE, 0x4f00_0000, 32, true), i32::MAX as u64, "2^31 is out of range");
+        assert_eq!(fpu.float_to_int(SINGLE, 0xcf00_0000, 32, true), i32::MIN as u32 as u64);
+        assert_eq!(fpu.float_to_int(SINGLE, QNAN, 32, false), u32::MAX as u64);
+        assert_eq!(fpu.float_to_int(SINGLE, ONE | 1 << 31, 32, false), 0);
+        assert_eq!(fpu.flags, NV);
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.int_to_float(SINGLE, u32::MAX as u64, 32, false), 0x4f80_0000);
+        assert_eq!(fpu.flags, NX);
+        assert_eq!(fpu.int_to_float(SINGLE, -3i32 as u32 as u64, 32, true), 0xc040_0000);
+    }
+
+    #[test]
+    fn test_convert_long() {
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        // 2^31 fits in 64 bits
+        assert_eq!(fpu.float_to_int(SINGLE, 0x4f00_0000, 64, true), 1 << 31);
+        assert_eq!(fpu.float_to_int(SINGLE, 0xdf00_0000, 64, true), i64::MIN as u64);
+        assert_eq!(fpu.flags, 0);
+        assert_eq!(fpu.float_to_int(SINGLE, 0x5f00_0000, 64, true), i64::MAX as u64, "2^63 is out of range");
+        assert_eq!(fpu.float_to_int(SINGLE, QNAN, 64, false), u64::MAX);
+        assert_eq!(fpu.flags, NV);
+
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.int_to_float(SINGLE, u64::MAX, 64, false), 0x5f80_0000);
+        assert_eq!(fpu.flags, NX);
+        assert_eq!(fpu.int_to_float(SINGLE, -3i64 as u64, 64, true), 0xc040_0000);
+        assert_eq!(fpu.int_to_float(SINGLE, 0xffff_fffd, 64, true), 0x4f80_0000, "Not sign extended from 32 bits");
+    }
+
+    #[test]
+    fn test_classify_sign_inject() {
+        assert_eq!(Fpu::classify(SINGLE, 0xff80_0000), 1 << 0);
+        assert_eq!(Fpu::classify(SINGLE, 1 << 31), 1 << 3);
+        assert_eq!(Fpu::classify(SINGLE, 0x0000_0001), 1 << 5);
+        assert_eq!(Fpu::classify(SINGLE, SNAN), 1 << 8);
+        assert_eq!(Fpu::classify(SINGLE, QNAN), 1 << 9);
+
+        assert_eq!(Fpu::sign_inject(SINGLE, ONE, 1 << 31, false, false), ONE | 1 << 31);
+        assert_eq!(Fpu::sign_inject(SINGLE, ONE, 1 << 31, true, false), ONE);
+        assert_eq!(Fpu::sign_inject(SINGLE, ONE | 1 << 31, 1 << 31, false, true), ONE);
+    }
+
+    #[cfg(feature = "d")]
+    #[test]
+    fn test_convert_format() {
+        let mut fpu = Fpu::new(RoundingMode::Rne);
+        assert_eq!(fpu.convert(SINGLE, DOUBLE, THREE), 0x4008_0000_0000_0000);
+        assert_eq!(fpu.convert(DOUBLE, SINGLE, 0x3fd5_5555_5555_5555), 0x3eaa_aaab);
+        assert_eq!(fpu.flags, NX);
+        assert_eq!(fpu.convert(SINGLE, DOUBLE, QNAN), DOUBLE.canonical_nan());
+    }
+}
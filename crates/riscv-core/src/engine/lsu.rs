@@ -1,14 +1,18 @@
-use crate::{Exception, Result};
+use crate::{Exception, Result, SXlen, Xlen};
 #[cfg(feature = "zicsr")]
 use crate::core::{CsrFile, PrivilegeMode};
 #[cfg(feature = "s")]
 use crate::core::Mmu;
 use crate::core::access::{Access, AccessType, Physical, Virtual};
+use crate::debug::Debugger;
 use crate::device::bus::SystemBus;
+#[cfg(feature = "d")]
+use crate::device::Device;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Lsu<'a> {
     bus: &'a mut SystemBus,
+    debugger: &'a mut Debugger,
     #[cfg(feature = "s")] mmu: &'a mut Mmu,
     #[cfg(feature = "zicsr")] csrs: &'a CsrFile,
     #[cfg(feature = "zicsr")] mode: PrivilegeMode, 
@@ -17,60 +21,68 @@ pub struct Lsu<'a> {
 impl<'a> Lsu<'a> {
     pub fn new(
         bus: &'a mut SystemBus, 
+        debugger: &'a mut Debugger,
         #[cfg(feature = "s")] mmu: &'a mut Mmu, 
         #[cfg(feature = "zicsr")] csrs: &'a CsrFile, 
         #[cfg(feature = "zicsr")] mode: PrivilegeMode
     ) -> Self {
         Self { 
             bus,
+            debugger,
             #[cfg(feature = "s")] mmu,  
             #[cfg(feature = "zicsr")] csrs, 
             #[cfg(feature = "zicsr")] mode 
         }
     }
 
-    pub fn load(&mut self, src: u32, offset: i32, num: usize) -> Result<u32> {
-        let addr = src.wrapping_add_signed(offset);
+    pub fn load(&mut self, src: Xlen, offset: i32, num: usize) -> Result<Xlen> {
+        let addr = src.wrapping_add_signed(offset as SXlen);
         let va_access = Access::new(addr, AccessType::Load);
         let pa_access = self.pre_work(va_access, num)?;
 
-        self.bus.read_u32_bytes(pa_access, num, false).map_err(|e| match e {
+        let data = self.bus.read_xlen_bytes(pa_access, num, false).map_err(|e| match e {
             Exception::LoadAccessFault(_)  => Exception::LoadAccessFault(addr),
             _ => e,
-        })
+        })?;
+        self.debugger.trace_mem(AccessType::Load, addr, num, data as u64);
+        Ok(data)
     }
 
-    pub fn load_signed(&mut self, src: u32, offset: i32, num: usize) -> Result<u32> {
-        let addr = src.wrapping_add_signed(offset);
+    pub fn load_signed(&mut self, src: Xlen, offset: i32, num: usize) -> Result<Xlen> {
+        let addr = src.wrapping_add_signed(offset as SXlen);
         let va_access = Access::new(addr, AccessType::Load);
         let pa_access = self.pre_work(va_access, num)?;
 
-        self.bus.read_u32_bytes(pa_access, num, true).map_err(|e| match e {
+        let data = self.bus.read_xlen_bytes(pa_access, num, true).map_err(|e| match e {
             Exception::LoadAccessFault(_)  => Exception::LoadAccessFault(addr),
             _ => e,
-        })
+        })?;
+        self.debugger.trace_mem(AccessType::Load, addr, num, data as u64);
+        Ok(data)
     }
 
+    /// `size` is 4 for word, 8 for double word on RV64
     #[cfg(feature = "a")]
-    pub fn atomic_load(&mut self, src: u32) -> Result<(u32, u32)> {
+    pub fn atomic_load(&mut self, src: Xlen, size: usize) -> Result<(Xlen, Xlen)> {
         let addr = src;
-        if addr & 0b11 != 0 {
+        if !(addr as usize).is_multiple_of(size) {
             return Err(Exception::LoadAddressMisaligned);
         }
 
         let va_access = Access::new(addr, AccessType::Load);
-        let pa_access = self.pre_work(va_access, 4)?;
+        let pa_access = self.pre_work(va_access, size)?;
 
-        let res = self.bus.read_u32_bytes(pa_access, 4, false).map_err(|e| match e {
+        let res = self.bus.read_xlen_bytes(pa_access, size, true).map_err(|e| match e {
             Exception::LoadAccessFault(_)  => Exception::LoadAccessFault(addr),
             _ => e,
         })?;
+        self.debugger.trace_mem(AccessType::Load, addr, size, res as u64);
         Ok((res, pa_access.addr))
     }
 
-    pub fn store(&mut self, des: u32, src: u32, offset: i32, num: usize,
-        #[cfg(feature = "a")] reservation: &mut Option<u32>) -> Result<()> {
-        let addr = des.wrapping_add_signed(offset);
+    pub fn store(&mut self, des: Xlen, src: Xlen, offset: i32, num: usize,
+        #[cfg(feature = "a")] reservation: &mut Option<Xlen>) -> Result<()> {
+        let addr = des.wrapping_add_signed(offset as SXlen);
         let va_access = Access::new(addr, AccessType::Store);
         let pa_access = self.pre_work(va_access, num)?;
 
@@ -80,20 +92,60 @@ impl<'a> Lsu<'a> {
         }
 
 
-        self.bus.write_u32_bytes(pa_access, src, num).map_err(|e| match e {
+        self.bus.write_xlen_bytes(pa_access, src, num).map_err(|e| match e {
+            Exception::StoreOrAmoAccessFault(_) => Exception::StoreOrAmoAccessFault(addr),
+            _ => e,
+        })?;
+        self.debugger.trace_mem(AccessType::Store, addr, num, src as u64);
+        Ok(())
+    }
+
+    /// Load 8 bytes for `fld`
+    #[cfg(feature = "d")]
+    pub fn load_double(&mut self, src: Xlen, offset: i32) -> Result<u64> {
+        let addr = src.wrapping_add_signed(offset as SXlen);
+        let va_access = Access::new(addr, AccessType::Load);
+        let pa_access = self.pre_work(va_access, 8)?;
+
+        let mut bytes = [0; 8];
+        self.bus.read_bytes(pa_access, 8, &mut bytes).map_err(|e| match e {
+            Exception::LoadAccessFault(_)  => Exception::LoadAccessFault(addr),
+            _ => e,
+        })?;
+        let data = u64::from_le_bytes(bytes);
+        self.debugger.trace_mem(AccessType::Load, addr, 8, data);
+        Ok(data)
+    }
+
+    /// Store 8 bytes for `fsd`
+    #[cfg(feature = "d")]
+    pub fn store_double(&mut self, des: Xlen, src: u64, offset: i32,
+        #[cfg(feature = "a")] reservation: &mut Option<Xlen>) -> Result<()> {
+        let addr = des.wrapping_add_signed(offset as SXlen);
+        let va_access = Access::new(addr, AccessType::Store);
+        let pa_access = self.pre_work(va_access, 8)?;
+
+        #[cfg(feature = "a")]
+        if let Some(addr) = *reservation && addr == pa_access.addr {
+            *reservation = None;
+        }
+
+        self.bus.write_bytes(pa_access, 8, &src.to_le_bytes()).map_err(|e| match e {
             Exception::StoreOrAmoAccessFault(_) => Exception::StoreOrAmoAccessFault(addr),
             _ => e,
-        })
+        })?;
+        self.debugger.trace_mem(AccessType::Store, addr, 8, src);
+        Ok(())
     }
 
     #[cfg(feature = "a")]
-    pub fn atomic_store(&mut self, des: u32, src: u32, reservation: &mut Option<u32>) -> Result<bool> {
+    pub fn atomic_store(&mut self, des: Xlen, src: Xlen, size: usize, reservation: &mut Option<Xlen>) -> Result<bool> {
         let addr = des;
-        if addr & 0b11 != 0 {
+        if !(addr as usize).is_multiple_of(size) {
             return Err(Exception::LoadAddressMisaligned);
         }
         let va_access = Access::new(addr, AccessType::Store);
-        let pa_access = self.pre_work(va_access, 4)?;
+        let pa_access = self.pre_work(va_access, size)?;
 
         match reservation {
             Some(addr) => if *addr != pa_access.addr {
@@ -104,45 +156,51 @@ impl<'a> Lsu<'a> {
 
         *reservation = None;
 
-        self.bus.write_u32_bytes(pa_access, src, 4).map_err(|e| match e {
+        self.bus.write_xlen_bytes(pa_access, src, size).map_err(|e| match e {
             Exception::StoreOrAmoAccessFault(_) => Exception::StoreOrAmoAccessFault(addr),
             _ => e,
         })?;
+        self.debugger.trace_mem(AccessType::Store, addr, size, src as u64);
         Ok(true)
     }
 
     #[cfg(feature = "a")]
-    pub fn atomic_operate<F>(&mut self, des: u32, data: u32, ope: F, reservation: &mut Option<u32>) -> Result<u32> 
-        where F: Fn(u32, u32) -> u32
+    pub fn atomic_operate<F>(&mut self, des: Xlen, data: Xlen, size: usize, ope: F, reservation: &mut Option<Xlen>) -> Result<Xlen> 
+        where F: Fn(Xlen, Xlen) -> Xlen
     {
         let addr = des;
-        if addr & 0b11 != 0 {
+        if !(addr as usize).is_multiple_of(size) {
             return Err(Exception::LoadAddressMisaligned);
         }
         let va_access = Access::new(addr, AccessType::Amo);
-        let pa_access = self.pre_work(va_access, 4)?;
+        let pa_access = self.pre_work(va_access, size)?;
 
         if let Some(addr) = *reservation && addr == pa_access.addr {
             *reservation = None;
         }
 
-        let tmp = self.bus.read_u32_bytes(pa_access, 4, false).map_err(|e| match e {
+        let tmp = self.bus.read_xlen_bytes(pa_access, size, true).map_err(|e| match e {
             Exception::StoreOrAmoAccessFault(_)  => Exception::StoreOrAmoAccessFault(addr),
             _ => e,
         })?;
 
         let res_data = ope(tmp, data);
 
-        self.bus.write_u32_bytes(pa_access, res_data, 4).map_err(|e| match e {
+        self.bus.write_xlen_bytes(pa_access, res_data, size).map_err(|e| match e {
             Exception::StoreOrAmoAccessFault(_) => Exception::StoreOrAmoAccessFault(addr),
             _ => e,
         })?;
+        self.debugger.trace_mem(AccessType::Load, addr, size, tmp as u64);
+        self.debugger.trace_mem(AccessType::Store, addr, size, res_data as u64);
 
         Ok(tmp)
     }
 
     #[allow(unused_variables)]
     fn pre_work(&mut self, va_access: Access<Virtual>, num: usize) -> Result<Access<Physical>> { 
+        #[cfg(feature = "zicsr")]
+        self.csrs.trigger_check(va_access, num, self.mode)?;
+
         #[cfg(not(feature = "s"))]
         let pa_access = va_access.bypass();
 
@@ -158,6 +216,7 @@ impl<'a> Lsu<'a> {
                 _ => e,
             })?;
         }
+        self.debugger.access(va_access.addr, pa_access.addr, num, va_access.kind);
         Ok(pa_access)      
     }
 }
@@ -165,6 +224,8 @@ impl<'a> Lsu<'a> {
 #[cfg(test)]
 mod tests {
     use super::Lsu;
+    use crate::Xlen;
+    use crate::debug::Debugger;
     use crate::device::bus::{SystemBus, DRAM_BASE_ADDR};
     #[cfg(feature = "zicsr")]
     use crate::core::{CsrFile, PrivilegeMode};
@@ -180,8 +241,10 @@ mod tests {
         let csrs = CsrFile::default();
         #[cfg(feature = "zicsr")]
         let mode = PrivilegeMode::Machine;
+        let mut debugger = Debugger::default();
         let mut lsu = Lsu::new(
             &mut bus,
+            &mut debugger,
             #[cfg(feature = "s")] &mut mmu,
             #[cfg(feature = "zicsr")] &csrs, 
             #[cfg(feature = "zicsr")] mode
@@ -206,8 +269,10 @@ mod tests {
         #[cfg(feature = "zicsr")]
         let mode = PrivilegeMode::Machine;
 
+        let mut debugger = Debugger::default();
         let mut lsu = Lsu::new(
             &mut bus,
+            &mut debugger,
             #[cfg(feature = "s")] &mut mmu,
             #[cfg(feature = "zicsr")] &csrs, 
             #[cfg(feature = "zicsr")] mode
@@ -222,7 +287,7 @@ mod tests {
         let res = lsu.load(base, offset, 4).unwrap();
         assert_eq!(res, val);
         
-        let actual_addr = (base as i32 + offset) as u32;
+        let actual_addr = base.wrapping_add_signed(offset as _);
         let direct_res = lsu.load(actual_addr, 0, 4).unwrap();
         assert_eq!(direct_res, val);
     }
@@ -238,8 +303,10 @@ mod tests {
         #[cfg(feature = "zicsr")]
         let mode = PrivilegeMode::Machine;
 
+        let mut debugger = Debugger::default();
         let mut lsu = Lsu::new(
             &mut bus,
+            &mut debugger,
             #[cfg(feature = "s")] &mut mmu,
             #[cfg(feature = "zicsr")] &csrs, 
             #[cfg(feature = "zicsr")] mode
@@ -252,13 +319,13 @@ mod tests {
         assert_eq!(lbu, 0x000000FF, "Lbu failed: expected zero extension");
 
         let lb = lsu.load_signed(addr, 0, 1).unwrap();
-        assert_eq!(lb, 0xFFFFFFFF, "Lb failed: expected sign extension");
+        assert_eq!(lb, Xlen::MAX, "Lb failed: expected sign extension");
         
         lsu.store(addr + 4, 0xFFAA, 0, 2,
             #[cfg(feature = "a")]&mut None).unwrap();
         
         let lh = lsu.load_signed(addr + 4, 0, 2).unwrap();
-        assert_eq!(lh, 0xFFFF_FFAA, "Lh failed");
+        assert_eq!(lh, -0x56i32 as Xlen, "Lh failed");
         
         let lhu = lsu.load(addr + 4, 0, 2).unwrap();
         assert_eq!(lhu, 0x0000_FFAA, "Lhu failed");
@@ -1,7 +1,13 @@
 mod alu;
 mod branch;
+#[cfg(feature = "f")]
+mod fpu;
 mod lsu;
 
 pub use alu::Alu;
 pub use branch::Branch;
+#[cfg(feature = "f")]
+pub use fpu::{Fpu, RoundingMode, SINGLE};
+#[cfg(feature = "d")]
+pub use fpu::DOUBLE;
 pub use lsu::Lsu;
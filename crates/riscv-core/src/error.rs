@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::Xlen;
+
 #[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RiscVError {
     #[error("Can not load data")]
@@ -8,7 +10,43 @@ pub enum RiscVError {
     #[error("Can not set zero in memory")]
     BssInitFailed,
 
+    #[error("Device at {0:#010x} with size {1:#x} is not a valid range")]
+    InvalidDeviceRange(Xlen, Xlen),
+
+    #[error("Device range {0:#010x}..={1:#010x} overlaps with other device")]
+    DeviceOverlap(Xlen, Xlen),
+
+    #[error("PLIC source {0} is invalid or already used")]
+    InvalidIrq(u32),
+
     #[cfg(not(feature = "zicsr"))]
     #[error("Exception had raised")]
     Exception,
 }
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Can not access snapshot file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a snapshot file")]
+    InvalidMagic,
+
+    #[error("Snapshot version {0} is not supported")]
+    Version(u32),
+
+    #[error("Snapshot is taken by a build with different extensions")]
+    Extensions,
+
+    #[error("Snapshot RAM size {0:#x} does not match machine")]
+    RamSize(u64),
+
+    #[error("Snapshot hart id {0} does not match machine")]
+    HartId(u32),
+
+    #[error("Snapshot reset PC {0:#x} does not match machine")]
+    ResetPc(u64),
+
+    #[error("Snapshot is truncated or corrupted")]
+    Corrupted,
+}
@@ -0,0 +1,67 @@
+use crate::Trap;
+use crate::debug::BreakHit;
+
+/// Reason that machine stop by itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    /// Guest exit with the code
+    Exit(u32),
+}
+
+/// Event happened in a `step`. Normal step has no event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    Trap(Trap),
+    /// Machine is halted. Following `step` do nothing until `reset`
+    Halt(Halt),
+    /// Breakpoint or watchpoint is hit
+    Break(BreakHit),
+}
+
+/// Why `run_until` returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Break(BreakHit),
+    Halt(Halt),
+    /// Trap can not be handled without Zicsr
+    #[cfg(not(feature = "zicsr"))]
+    Trap(Trap),
+    /// Step limit is reached
+    Limit,
+}
+
+impl From<Trap> for StepEvent {
+    fn from(value: Trap) -> Self {
+        StepEvent::Trap(value)
+    }
+}
+
+impl From<Halt> for StepEvent {
+    fn from(value: Halt) -> Self {
+        StepEvent::Halt(value)
+    }
+}
+
+impl From<BreakHit> for StepEvent {
+    fn from(value: BreakHit) -> Self {
+        StepEvent::Break(value)
+    }
+}
+
+impl std::fmt::Display for Halt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Halt::Exit(code) => write!(f, "Exit with code {code}"),
+        }
+    }
+}
+
+impl std::fmt::Display for StepEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepEvent::Trap(trap) => trap.fmt(f),
+            StepEvent::Halt(halt) => halt.fmt(f),
+            StepEvent::Break(hit) => hit.fmt(f),
+        }
+    }
+}
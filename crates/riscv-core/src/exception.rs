@@ -1,21 +1,23 @@
+use crate::Xlen;
+
 use Exception::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Exception {
     InstructionAddressMisaligned,
-    InstructionAccessFault(u32),
+    InstructionAccessFault(Xlen),
     IllegalInstruction(u32),
-    Breakpoint,
+    Breakpoint(Xlen),
     LoadAddressMisaligned,
-    LoadAccessFault(u32),
+    LoadAccessFault(Xlen),
     StoreOrAmoAddressMisaligned,
-    StoreOrAmoAccessFault(u32),
+    StoreOrAmoAccessFault(Xlen),
     EnvironmentCallFromUMode,
     EnvironmentCallFromSMode,
     EnvironmentCallFromMMode,
-    InstructionPageFault(u32),
-    LoadPageFault(u32),
-    StoreOrAmoPageFault(u32),
+    InstructionPageFault(Xlen),
+    LoadPageFault(Xlen),
+    StoreOrAmoPageFault(Xlen),
     
     #[cfg(not(feature = "zicsr"))] Ecall, // Custom
     #[cfg(not(feature = "zicsr"))] Ebreak, // Custom
@@ -27,7 +29,7 @@ impl From<Exception> for u32 {
             InstructionAddressMisaligned => 0,
             InstructionAccessFault(_)    => 1,
             IllegalInstruction(_)        => 2,
-            Breakpoint                   => 3,
+            Breakpoint(_)                => 3,
             LoadAddressMisaligned        => 4,
             LoadAccessFault(_)           => 5,
             StoreOrAmoAddressMisaligned  => 6,
@@ -49,7 +51,6 @@ impl std::fmt::Display for Exception {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             InstructionAddressMisaligned => f.write_str(" 0: Instruction Address Misaligned"),
-            Breakpoint                   => f.write_str(" 3: Breakpoint"),
             LoadAddressMisaligned        => f.write_str(" 4: Load Address Misaligned"),
             StoreOrAmoAddressMisaligned  => f.write_str(" 6: Store/AMO Address Misaligned"),  
             EnvironmentCallFromUMode     => f.write_str(" 8: Environment Call From U-Mode"),
@@ -58,6 +59,7 @@ impl std::fmt::Display for Exception {
 
             InstructionAccessFault(addr) => write!(f, " 1: Instruction Access Fault (From: {:#010x})", addr),
             IllegalInstruction(raw)      => write!(f, " 2: Illegal Instruction (Raw: {:#010x})", raw),
+            Breakpoint(addr)             => write!(f, " 3: Breakpoint (From: {:#010x})", addr),
             LoadAccessFault(addr)        => write!(f, " 5: Load Access Fault (From: {:#010x})", addr),
             StoreOrAmoAccessFault(addr)       => write!(f, " 7: Store/AMO Access Fault (From: {:#010x})", addr),
             InstructionPageFault(addr)   => write!(f, "12: Instruction Page Fault (From: {:#010x})", addr),
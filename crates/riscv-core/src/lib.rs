@@ -1,19 +1,52 @@
+// `Xlen` is `u32` or `u64`, so casts from it are only unnecessary on one of them
+#![allow(clippy::unnecessary_cast)]
+
 mod core;
 mod device;
 mod engine;
 mod error;
+mod event;
 mod exception;
+mod snapshot;
+#[cfg(feature = "zicsr")]
+mod interrupt;
+mod trap;
 
+pub mod config;
 pub mod debug;
 pub mod prelude;
 pub mod constance {
     pub use crate::device::memory::PAGE_SIZE;
-    pub use crate::device::bus::DRAM_BASE_ADDR;
+    pub use crate::device::bus::{CLINT_BASE, CLINT_SIZE, DRAM_BASE_ADDR, PLIC_BASE, PLIC_SIZE, UART_BASE, UART_IRQ, UART_SIZE};
+}
+pub mod mmio {
+    pub use crate::device::Device;
+    pub use crate::core::access::{Access, AccessType, Physical};
 }
 
 pub use core::RiscV;
-pub use error::RiscVError;
+pub use device::uart::UartSink;
+pub use error::{RiscVError, SnapshotError};
+pub use event::{Halt, StepEvent, StopReason};
 pub use exception::Exception;
+#[cfg(feature = "zicsr")]
+pub use interrupt::Interrupt;
+pub use trap::Trap;
+
+/// Width of integer registers, address and CSRs
+#[cfg(not(feature = "rv64"))]
+pub type Xlen = u32;
+/// Width of integer registers, address and CSRs
+#[cfg(feature = "rv64")]
+pub type Xlen = u64;
+/// Signed `Xlen`
+#[cfg(not(feature = "rv64"))]
+pub type SXlen = i32;
+/// Signed `Xlen`
+#[cfg(feature = "rv64")]
+pub type SXlen = i64;
+/// Bits of `Xlen`
+pub const XLEN: u32 = Xlen::BITS;
 
 pub type StdResult<T, E> = std::result::Result<T, E>;
 pub type Result<T> = std::result::Result<T, Exception>;
\ No newline at end of file
@@ -1,5 +1,12 @@
+pub use crate::config::MachineConfig;
 pub use crate::constance;
+pub use crate::mmio;
 pub use crate::core::*;
 pub use crate::debug::*;
+pub use crate::device::uart::UartSink;
 pub use crate::error::*;
+pub use crate::event::*;
 pub use crate::exception::*;
+#[cfg(feature = "zicsr")]
+pub use crate::interrupt::*;
+pub use crate::trap::*;
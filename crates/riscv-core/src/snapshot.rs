@@ -0,0 +1,181 @@
+//! Versioned binary format of machine snapshot. All numbers are little endian.
+//!
+//! | Part | Content |
+//! | --- | --- |
+//! | Header | `MAGIC`, `VERSION` (u32), `extensions()` (u32) |
+//! | Hart | Registers, FP registers, PC, privilege mode, CSRs, MMU/TLB, LR/SC reservation, halt. XLEN wide values are 8 bytes on RV64 |
+//! | Devices | CLINT, PLIC and UART registers |
+//! | RAM | Size (u64), number of populated pages (u32), then (page index (u32), page) |
+
+use crate::{StdResult, Xlen};
+use crate::error::SnapshotError;
+
+pub(crate) const MAGIC: &[u8; 8] = b"RVSNAPSH";
+pub(crate) const VERSION: u32 = 2;
+
+/// Extensions of this build, snapshot can only be loaded by the same build
+pub(crate) const fn extensions() -> u32 {
+    (cfg!(feature = "m") as u32)
+        | (cfg!(feature = "a") as u32) << 1
+        | (cfg!(feature = "c") as u32) << 2
+        | (cfg!(feature = "zicsr") as u32) << 3
+        | (cfg!(feature = "s") as u32) << 4
+        | (cfg!(feature = "f") as u32) << 5
+        | (cfg!(feature = "d") as u32) << 6
+        | (cfg!(feature = "rv64") as u32) << 7
+}
+
+/// State can be saved into and restored from snapshot
+pub(crate) trait Snapshot {
+    fn save(&self, w: &mut Writer);
+
+    fn restore(&mut self, r: &mut Reader) -> StdResult<(), SnapshotError>;
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Writer {
+    pub buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn u8(&mut self, data: u8) {
+        self.buf.push(data);
+    }
+
+    pub fn u16(&mut self, data: u16) {
+        self.buf.extend_from_slice(&data.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, data: u32) {
+        self.buf.extend_from_slice(&data.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, data: u64) {
+        self.buf.extend_from_slice(&data.to_le_bytes());
+    }
+
+    pub fn xlen(&mut self, data: Xlen) {
+        self.buf.extend_from_slice(&data.to_le_bytes());
+    }
+
+    pub fn bytes(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// `0` for `None`, `1` and the value for `Some`
+    pub fn option_u32(&mut self, data: Option<u32>) {
+        match data {
+            Some(data) => {
+                self.u8(1);
+                self.u32(data);
+            },
+            None => self.u8(0),
+        }
+    }
+
+    #[cfg(feature = "a")]
+    pub fn option_xlen(&mut self, data: Option<Xlen>) {
+        match data {
+            Some(data) => {
+                self.u8(1);
+                self.xlen(data);
+            },
+            None => self.u8(0),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Reader { buf }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> StdResult<&'a [u8], SnapshotError> {
+        let (head, tail) = self.buf.split_at_checked(len).ok_or(SnapshotError::Corrupted)?;
+        self.buf = tail;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> StdResult<u8, SnapshotError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> StdResult<u16, SnapshotError> {
+        // Safe: Length is checked by `bytes`
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> StdResult<u32, SnapshotError> {
+        // Safe: Length is checked by `bytes`
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> StdResult<u64, SnapshotError> {
+        // Safe: Length is checked by `bytes`
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    pub fn xlen(&mut self) -> StdResult<Xlen, SnapshotError> {
+        // Safe: Length is checked by `bytes`
+        Ok(Xlen::from_le_bytes(self.bytes(size_of::<Xlen>())?.try_into().unwrap()))
+    }
+
+    pub fn option_u32(&mut self) -> StdResult<Option<u32>, SnapshotError> {
+        Ok(match self.u8()? {
+            0 => None,
+            1 => Some(self.u32()?),
+            _ => return Err(SnapshotError::Corrupted),
+        })
+    }
+
+    #[cfg(feature = "a")]
+    pub fn option_xlen(&mut self) -> StdResult<Option<Xlen>, SnapshotError> {
+        Ok(match self.u8()? {
+            0 => None,
+            1 => Some(self.xlen()?),
+            _ => return Err(SnapshotError::Corrupted),
+        })
+    }
+
+    /// Snapshot must be fully consumed
+    pub fn finish(&self) -> StdResult<(), SnapshotError> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Corrupted)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reader, Writer};
+    use crate::error::SnapshotError;
+
+    #[test]
+    fn test_write_read_back() {
+        let mut w = Writer::default();
+        w.u8(1);
+        w.u16(0x1234);
+        w.u32(0xdead_beef);
+        w.u64(u64::MAX);
+        w.option_u32(Some(7));
+        w.option_u32(None);
+
+        let mut r = Reader::new(&w.buf);
+        assert_eq!(r.u8().unwrap(), 1);
+        assert_eq!(r.u16().unwrap(), 0x1234);
+        assert_eq!(r.u32().unwrap(), 0xdead_beef);
+        assert_eq!(r.u64().unwrap(), u64::MAX);
+        assert_eq!(r.option_u32().unwrap(), Some(7));
+        assert_eq!(r.option_u32().unwrap(), None);
+        assert!(r.finish().is_ok());
+
+        assert!(matches!(r.u8(), Err(SnapshotError::Corrupted)), "Truncated");
+    }
+}
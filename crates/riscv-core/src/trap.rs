@@ -0,0 +1,87 @@
+use crate::{Exception, XLEN, Xlen};
+#[cfg(feature = "zicsr")]
+use crate::Interrupt;
+
+/// Bit of `mcause` / `scause` that mark the trap is an interrupt
+pub const INTERRUPT_MASK: Xlen = 1 << (XLEN - 1);
+
+/// Synchronous exception or asynchronous interrupt that redirect PC to trap handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    Exception(Exception),
+    #[cfg(feature = "zicsr")]
+    Interrupt(Interrupt),
+}
+
+impl Trap {
+    pub fn is_interrupt(&self) -> bool {
+        match self {
+            Trap::Exception(_) => false,
+            #[cfg(feature = "zicsr")]
+            Trap::Interrupt(_) => true,
+        }
+    }
+
+    /// Exception code without interrupt bit
+    pub fn code(&self) -> u32 {
+        match self {
+            Trap::Exception(except) => (*except).into(),
+            #[cfg(feature = "zicsr")]
+            Trap::Interrupt(irq)    => (*irq).into(),
+        }
+    }
+
+    /// Value written into `mtval` / `stval`
+    pub fn tval(&self) -> Xlen {
+        match self {
+            Trap::Exception(except) => match except {
+                Exception::IllegalInstruction(raw) => *raw as Xlen,
+                Exception::Breakpoint(addr) |
+                Exception::LoadAccessFault(addr) |
+                Exception::StoreOrAmoAccessFault(addr) |
+                Exception::InstructionAccessFault(addr) |
+                Exception::LoadPageFault(addr) |
+                Exception::StoreOrAmoPageFault(addr) |
+                Exception::InstructionPageFault(addr)
+                    => *addr,
+                _   => 0,
+            },
+            #[cfg(feature = "zicsr")]
+            Trap::Interrupt(_) => 0,
+        }
+    }
+}
+
+impl From<Exception> for Trap {
+    fn from(value: Exception) -> Self {
+        Trap::Exception(value)
+    }
+}
+
+#[cfg(feature = "zicsr")]
+impl From<Interrupt> for Trap {
+    fn from(value: Interrupt) -> Self {
+        Trap::Interrupt(value)
+    }
+}
+
+/// Value written into `mcause` / `scause`
+impl From<Trap> for Xlen {
+    fn from(value: Trap) -> Self {
+        if value.is_interrupt() {
+            INTERRUPT_MASK | value.code() as Xlen
+        } else {
+            value.code() as Xlen
+        }
+    }
+}
+
+impl std::fmt::Display for Trap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trap::Exception(except) => except.fmt(f),
+            #[cfg(feature = "zicsr")]
+            Trap::Interrupt(irq)    => irq.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs", not(feature = "rv64")))]
+
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::DebugInterface;
+
+#[test]
+fn test_bitmanip() {
+    let code: Vec<u8> = [
+        0x00500593, // addi   a1, zero, 5
+        0x00300613, // addi   a2, zero, 3
+        0x20c5a6b3, // sh1add a3, a1, a2
+        0x60059713, // clz    a4, a1
+        0x40c5f7b3, // andn   a5, a1, a2
+        0x29f01813, // bseti  a6, zero, 31
+        0x0ac598b3, // clmul  a7, a1, a2
+        0x69885293, // rev8   t0, a6
+        0x4825d313, // bexti  t1, a1, 2
+    ].iter().flat_map(|ins: &u32| ins.to_le_bytes()).collect();
+
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+        .build()
+        .unwrap();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+    for _ in 0..9 {
+        machine.step().unwrap();
+    }
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[13], 13);
+    assert_eq!(regs[14], 29);
+    assert_eq!(regs[15], 4);
+    assert_eq!(regs[16], 0x8000_0000);
+    assert_eq!(regs[17], 0b1111, "5 clmul 3 has no carry");
+    assert_eq!(regs[5], 0x80);
+    assert_eq!(regs[6], 1);
+}
@@ -1,8 +1,10 @@
+#![allow(dead_code)]
+
 use std::path::Path;
 
-use riscv_core::RiscV;
-use riscv_core::debug::DebugInterface; 
-use riscv_loader;
+use riscv_core::{Halt, RiscV, StepEvent};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
 
 const MAX_CYCLES: u64 = 1_000_000;
 
@@ -14,32 +16,40 @@ pub fn run_test_file(path: &Path) {
 
     let mut machine = RiscV::default();
 
+    // `tohost` is handled by HTIF
     machine.load_info(&info).expect("Failed to load ELF info");
 
-    let tohost_addr = info.symbols
-        .as_ref()
-        .and_then(|sym| sym.iter().find(|(_, name)| *name == "tohost"))
-        .map(|(addr, _)| *addr)
-        .expect("ELF missing 'tohost' symbol");
-
     for cycle in 0..MAX_CYCLES {
-        machine.step().expect(&format!("CPU Fault at cycle {}", cycle));
+        let event = machine.step().unwrap_or_else(|_| panic!("CPU Fault at cycle {}", cycle));
 
-        // Check tohost
-        let mem_bytes = machine.inspect_bus(tohost_addr, 4);
-        let val = u32::from_le_bytes(mem_bytes.try_into().unwrap());
-
-        if val != 0 {
-            if val == 1 {
+        if let Some(StepEvent::Halt(Halt::Exit(code))) = event {
+            if code == 0 {
                 // PASS
                 println!("\x1b[32mPASS\x1b[0m: {}", filename);
                 return;
             } else {
                 // FAIL
-                panic!("\x1b[31mFAIL\x1b[0m: {} failed with code {} at cycle {}", filename, val, cycle);
+                panic!("\x1b[31mFAIL\x1b[0m: {} failed with test {} at cycle {}", filename, code, cycle);
             }
         }
     }
 
     panic!("TIMEOUT: {} exceeded {} cycles", filename, MAX_CYCLES);
-}
\ No newline at end of file
+}
+
+/// Machine with 1 MiB RAM and `code` loaded at `DRAM_BASE_ADDR`
+pub fn machine(code: &[u32]) -> RiscV {
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+        .build()
+        .unwrap();
+    let code: Vec<u8> = code.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+    machine
+}
+
+pub fn run(machine: &mut RiscV, steps: usize) {
+    for _ in 0..steps {
+        machine.step().unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use riscv_core::{RiscVError, Xlen};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::{DRAM_BASE_ADDR, UART_BASE};
+use riscv_core::debug::DebugInterface;
+
+const RAM_BASE: Xlen = 0x4000_0000;
+const RAM_SIZE: usize = 64 * 1024 * 1024;
+
+#[test]
+fn test_custom_ram() {
+    let mut machine = MachineConfig::default()
+        .with_ram(RAM_BASE, RAM_SIZE)
+        .with_reset_pc(RAM_BASE + 0x100)
+        .build()
+        .unwrap();
+
+    assert_eq!(machine.inspect_pc(), RAM_BASE + 0x100);
+
+    let info = machine.get_info();
+    assert_eq!(info.dram_base, RAM_BASE);
+    assert_eq!(info.dram_size_mb, RAM_SIZE / 1024 / 1024, "Unit is MB");
+
+    assert!(machine.load(RAM_BASE + RAM_SIZE as Xlen - 4, &[1, 2, 3, 4]).is_ok());
+    assert_eq!(machine.load(RAM_BASE + RAM_SIZE as Xlen, &[1]), Err(RiscVError::LoadFailed), "Out of RAM");
+    assert_eq!(machine.load(DRAM_BASE_ADDR, &[1]), Err(RiscVError::LoadFailed), "Default RAM is not mapped");
+
+    machine.set_pc(RAM_BASE);
+    machine.reset();
+    assert_eq!(machine.inspect_pc(), RAM_BASE + 0x100, "Reset to configured PC");
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_hart_id() {
+    let mut machine = MachineConfig::default()
+        .with_hart_id(3)
+        .build()
+        .unwrap();
+
+    // csrr t0, mhartid
+    machine.load(DRAM_BASE_ADDR, &0xf14022f3u32.to_le_bytes()).unwrap();
+    machine.step().unwrap();
+
+    assert_eq!(machine.inspect_regs()[5], 3);
+    assert!(machine.inspect_csrs().contains(&("mhartid".to_string(), 3)));
+}
+
+#[test]
+fn test_optional_device() {
+    let mut machine = MachineConfig::default()
+        .with_uart(None)
+        .build()
+        .unwrap();
+
+    assert_eq!(machine.load(UART_BASE, &[0]), Err(RiscVError::LoadFailed));
+}
+
+#[test]
+fn test_invalid_memory_map() {
+    let overlap = MachineConfig::default()
+        .with_uart(Some(DRAM_BASE_ADDR + 0x1000))
+        .build();
+    assert_eq!(overlap.err(), Some(RiscVError::DeviceOverlap(DRAM_BASE_ADDR + 0x1000, DRAM_BASE_ADDR + 0x10ff)));
+
+    let top = Xlen::MAX - 0x0fff_ffff;
+    let too_large = MachineConfig::default()
+        .with_ram(top, RAM_SIZE * 8)
+        .build();
+    assert_eq!(too_large.err(), Some(RiscVError::InvalidDeviceRange(top, (RAM_SIZE * 8) as Xlen)));
+}
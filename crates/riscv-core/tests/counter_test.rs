@@ -0,0 +1,28 @@
+#![cfg(feature = "zicsr")]
+
+mod common;
+
+use riscv_core::debug::DebugInterface;
+
+use common::machine;
+
+#[test]
+fn test_counter_step() {
+    let mut machine = machine(&[
+        0x00000013, // nop
+        0xc00020f3, // rdcycle   x1
+        0xc0202173, // rdinstret x2
+        0xc01021f3, // rdtime    x3
+        0xffffffff, // illegal
+    ]);
+    for _ in 0..5 {
+        machine.step().unwrap();
+    }
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[1], 1);
+    assert_eq!(regs[2], 2);
+    assert_eq!(regs[3], 4, "time is CLINT's mtime");
+    assert_eq!(machine.inspect_csr(0xb00), Some(5), "Trapped step is a cycle");
+    assert_eq!(machine.inspect_csr(0xb02), Some(4), "Trapped instruction is not retired");
+}
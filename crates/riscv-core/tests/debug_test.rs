@@ -0,0 +1,173 @@
+#![cfg(not(feature = "rv64"))]
+
+mod common;
+
+use riscv_core::StopReason;
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::{BreakHit, Breakpoint, DebugControl, DebugInterface, HitCondition, WatchKind};
+
+use common::machine;
+
+const DATA_ADDR: u32 = DRAM_BASE_ADDR + 0x1000;
+
+/// Store `x1` to `DATA_ADDR` then increase it forever
+const STORE_LOOP: [u32; 5] = [
+    0x00100093, // addi x1, zero, 1
+    0x800011b7, // lui  x3, 0x80001
+    0x0011a023, // sw   x1, 0(x3)
+    0x00108093, // addi x1, x1, 1
+    0xff9ff06f, // jal  zero, -8
+];
+
+#[test]
+fn test_write_reg_and_pc() {
+    let mut machine = machine(&[
+        0x00000013, // nop
+        0x00508093, // addi x1, x1, 5
+    ]);
+
+    machine.write_reg(0, 1);
+    machine.write_reg(1, 10);
+    assert_eq!(machine.inspect_regs()[0], 0, "x0 is hardwired");
+
+    machine.set_pc(DRAM_BASE_ADDR + 4);
+    machine.step().unwrap();
+    assert_eq!(machine.inspect_regs()[1], 15);
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 8);
+}
+
+#[test]
+fn test_write_bus() {
+    let mut machine = machine(&[]);
+
+    assert!(machine.write_bus(DRAM_BASE_ADDR + 0x100, &[1, 2, 3]));
+    assert_eq!(machine.inspect_bus(DRAM_BASE_ADDR + 0x100, 3), [1, 2, 3]);
+    assert!(!machine.write_bus(0, &[1]), "Unmapped address");
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_write_csr_and_privilege() {
+    use riscv_core::{Exception, StepEvent, Trap};
+
+    // csrr a0, mscratch
+    let mut machine = machine(&[0x34002573]);
+
+    assert!(machine.write_csr(0x340, 0x1234));
+    assert_eq!(machine.inspect_csr(0x340), Some(0x1234));
+    assert!(!machine.write_csr(0xf14, 1), "mhartid is read-only");
+    assert_eq!(machine.inspect_csr(0x7ff), None);
+
+    // PMP allows all for U-Mode: pmpaddr0 = top, pmpcfg0 = TOR | X | W | R
+    assert!(machine.write_csr(0x3b0, u32::MAX));
+    assert!(machine.write_csr(0x3a0, 0x0f));
+
+    assert!(!machine.set_privilege(2));
+    assert!(machine.set_privilege(0));
+    assert_eq!(machine.inspect_privilege(), 0);
+    assert_eq!(machine.step().unwrap(),
+        Some(StepEvent::Trap(Trap::Exception(Exception::IllegalInstruction(0x34002573)))));
+    assert_eq!(machine.inspect_privilege(), 3, "Trap into M-Mode");
+}
+
+#[test]
+fn test_run_until_breakpoint() {
+    let mut machine = machine(&STORE_LOOP);
+    let id = machine.add_breakpoint(Breakpoint::exec(DRAM_BASE_ADDR + 12));
+
+    let hit = BreakHit { id, kind: WatchKind::Execute, addr: DRAM_BASE_ADDR + 12 };
+    assert_eq!(machine.run_until(None).unwrap(), StopReason::Break(hit));
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 12, "Stop before the instruction");
+    assert_eq!(machine.inspect_regs()[1], 1);
+
+    // Resume execute the instruction at breakpoint
+    assert_eq!(machine.step().unwrap(), None);
+    assert_eq!(machine.inspect_regs()[1], 2);
+    assert_eq!(machine.run_until(None).unwrap(), StopReason::Break(hit));
+    assert_eq!(machine.breakpoint(id).unwrap().hits, 2);
+
+    assert!(machine.remove_breakpoint(id));
+    assert_eq!(machine.run_until(Some(10)).unwrap(), StopReason::Limit);
+}
+
+#[test]
+fn test_watchpoint_hit_count() {
+    let mut machine = machine(&STORE_LOOP);
+    let id = machine.add_breakpoint(Breakpoint::watch(DATA_ADDR, 4, WatchKind::Write)
+        .with_condition(HitCondition::Equal(3)));
+    machine.add_breakpoint(Breakpoint::watch(DATA_ADDR, 4, WatchKind::Read));
+
+    let hit = BreakHit { id, kind: WatchKind::Write, addr: DATA_ADDR };
+    assert_eq!(machine.run_until(None).unwrap(), StopReason::Break(hit));
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 12, "Stop after the store");
+    assert_eq!(machine.inspect_bus(DATA_ADDR, 1), [3]);
+
+    // Count is not met again
+    assert_eq!(machine.run_until(Some(30)).unwrap(), StopReason::Limit);
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_trigger_breakpoint_exception() {
+    use riscv_core::{Exception, StepEvent, Trap};
+
+    let mut machine = machine(&STORE_LOOP);
+
+    // tdata1 = mcontrol6 with M-Mode store match, enabled by tcontrol.mte
+    assert!(machine.write_csr(0x7a0, 1));
+    assert!(machine.write_csr(0x7a1, (6 << 28) | (1 << 6) | (1 << 1)));
+    assert!(machine.write_csr(0x7a2, DATA_ADDR));
+    assert_eq!(machine.inspect_csr(0x7a1), Some((6 << 28) | (1 << 6) | (1 << 1)));
+
+    machine.run_until(Some(2)).unwrap();
+    assert_eq!(machine.step().unwrap(), None, "M-Mode trigger need tcontrol.mte");
+    machine.set_pc(DRAM_BASE_ADDR);
+    assert!(machine.write_csr(0x7a5, 1 << 3));
+
+    machine.run_until(Some(2)).unwrap();
+    assert_eq!(machine.step().unwrap(),
+        Some(StepEvent::Trap(Trap::Exception(Exception::Breakpoint(DATA_ADDR)))));
+    assert_eq!(machine.inspect_csr(0x341), Some(DRAM_BASE_ADDR + 8), "Fire before the store");
+    assert_eq!(machine.inspect_csr(0x343), Some(DATA_ADDR));
+    assert_eq!(machine.inspect_csr(0x7a5), Some(1 << 7), "mte is saved to mpte");
+    assert_eq!(machine.inspect_bus(DATA_ADDR, 1), [1]);
+}
+
+#[test]
+fn test_step_back_and_forward() {
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+        .with_history(4)
+        .build()
+        .unwrap();
+    let code: Vec<u8> = STORE_LOOP.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+
+    machine.run_until(Some(3)).unwrap();
+    assert_eq!(machine.inspect_bus(DATA_ADDR, 1), [1]);
+    assert_eq!(machine.history_len(), (3, 0));
+
+    // Undo `sw` and `lui`
+    assert!(machine.step_back());
+    assert_eq!(machine.inspect_bus(DATA_ADDR, 1), [0]);
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 8);
+    assert!(machine.step_back());
+    assert_eq!(machine.inspect_regs()[3], 0);
+    assert_eq!(machine.history_len(), (1, 2));
+
+    assert!(machine.step_forward());
+    assert!(machine.step_forward());
+    assert!(!machine.step_forward());
+    assert_eq!(machine.inspect_regs()[3], DATA_ADDR);
+    assert_eq!(machine.inspect_bus(DATA_ADDR, 1), [1]);
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 12);
+
+    // Only the last 4 steps are kept
+    machine.run_until(Some(5)).unwrap();
+    for _ in 0..4 {
+        assert!(machine.step_back());
+    }
+    assert!(!machine.step_back());
+    assert_eq!(machine.inspect_regs()[1], 2);
+}
@@ -0,0 +1,124 @@
+#![cfg(all(feature = "f", not(feature = "rv64")))]
+
+mod common;
+
+use riscv_core::debug::DebugInterface;
+
+use common::{machine, run};
+
+// lui x1, 2; csrs mstatus, x1 (FS = Initial)
+const FP_ENABLE: [u32; 2] = [0x000020b7, 0x3000a073];
+
+#[test]
+fn test_fp_disabled() {
+    let mut machine = machine(&[
+        0x0010f153, // fadd.s  f2, f1, f1
+    ]);
+    run(&mut machine, 1);
+
+    assert_eq!(machine.inspect_csr(0x342), Some(2), "FP instruction is illegal when FS is Off");
+    assert_eq!(machine.inspect_csr(0x003), None, "fcsr is illegal when FS is Off");
+}
+
+#[test]
+fn test_fp_arith() {
+    let mut machine = machine(&[
+        FP_ENABLE[0],
+        FP_ENABLE[1],
+        0x3f800137, // lui      x2, 0x3f800 (1.0)
+        0xf00100d3, // fmv.w.x  f1, x2
+        0x0010f153, // fadd.s   f2, f1, f1
+        0xe00101d3, // fmv.x.w  x3, f2
+        0xf00001d3, // fmv.w.x  f3, x0
+        0x1830f253, // fdiv.s   f4, f1, f3
+        0x00102273, // frflags  x4
+        0xc00272d3, // fcvt.w.s x5, f4
+        0xe00213d3, // fclass.s x7, f4
+    ]);
+    run(&mut machine, 11);
+
+    let regs = machine.inspect_regs();
+    let fregs = machine.inspect_fregs();
+    assert_eq!(fregs[2], 0xffff_ffff_4000_0000, "Single precision value is NaN-boxed");
+    assert_eq!(regs[3], 0x4000_0000);
+    assert_eq!(fregs[4], 0xffff_ffff_7f80_0000);
+    assert_eq!(regs[4], 0x08, "Divide by zero");
+    assert_eq!(regs[5], i32::MAX as u32, "Infinity is saturated");
+    assert_eq!(regs[7], 1 << 7, "Positive infinity");
+    assert_eq!(machine.inspect_csr(0x001), Some(0x18), "Flags are accrued");
+    assert_eq!(machine.inspect_csr(0x300).unwrap() & 0x8000_6000, 0x8000_6000, "FS is Dirty");
+}
+
+#[test]
+fn test_fp_rounding_mode() {
+    let mut machine = machine(&[
+        FP_ENABLE[0],
+        FP_ENABLE[1],
+        0x3f800137, // lui      x2, 0x3f800 (1.0)
+        0xf00100d3, // fmv.w.x  f1, x2
+        0x00300293, // li       x5, 3
+        0xd002f1d3, // fcvt.s.w f3, x5
+        0x0020d073, // fsrmi    1 (RTZ)
+        0x1830f253, // fdiv.s   f4, f1, f3, dyn
+        0x183082d3, // fdiv.s   f5, f1, f3, rne
+        0x0022d073, // fsrmi    5 (reserved)
+        0x20109353, // fsgnjn.s f6, f1, f1
+        0x1830f253, // fdiv.s   f4, f1, f3, dyn
+    ]);
+    run(&mut machine, 11);
+
+    let fregs = machine.inspect_fregs();
+    assert_eq!(fregs[4] as u32, 0x3eaa_aaaa);
+    assert_eq!(fregs[5] as u32, 0x3eaa_aaab);
+    assert_eq!(fregs[6] as u32, 0xbf80_0000, "Sign injection does not use rounding mode");
+    assert_eq!(machine.inspect_csr(0x342), Some(0), "No trap before reserved rounding mode is used");
+
+    run(&mut machine, 1);
+    assert_eq!(machine.inspect_csr(0x342), Some(2), "Reserved dynamic rounding mode is illegal");
+}
+
+#[cfg(feature = "c")]
+#[test]
+fn test_fp_compressed() {
+    let mut machine = machine(&[
+        FP_ENABLE[0],
+        FP_ENABLE[1],
+        0x80001137, // lui      x2, 0x80001
+        0x3f8000b7, // lui      x1, 0x3f800 (1.0)
+        0xf0008453, // fmv.w.x  f8, x1
+        0x6492e222, // c.fswsp  f8, 4(x2); c.flwsp f9, 4(x2)
+        0x6048840a, // c.mv     x8, x2;    c.flw   f10, 4(x8)
+        0x42a2e404, // c.fsw    f9, 8(x8); c.lwsp  x5, 8(x2)
+    ]);
+    run(&mut machine, 11);
+
+    let regs = machine.inspect_regs();
+    let fregs = machine.inspect_fregs();
+    assert_eq!(fregs[9], 0xffff_ffff_3f80_0000);
+    assert_eq!(fregs[10], 0xffff_ffff_3f80_0000);
+    assert_eq!(regs[5], 0x3f80_0000);
+}
+
+#[cfg(feature = "d")]
+#[test]
+fn test_fp_double() {
+    let mut machine = machine(&[
+        FP_ENABLE[0],
+        FP_ENABLE[1],
+        0x00300293, // li       x5, 3
+        0xd2028353, // fcvt.d.w f6, x5
+        0x006373d3, // fadd.s   f7, f6, f6
+        0x40137453, // fcvt.s.d f8, f6
+        0x80001437, // lui      x8, 0x80001
+        0x00643427, // fsd      f6, 8(x8)
+        0x00843587, // fld      f11, 8(x8)
+    ]);
+    run(&mut machine, 9);
+
+    let fregs = machine.inspect_fregs();
+    assert_eq!(fregs[6], 0x4008_0000_0000_0000);
+    assert_eq!(fregs[7], 0xffff_ffff_7fc0_0000, "Not NaN-boxed value is canonical NaN");
+    assert_eq!(fregs[8], 0xffff_ffff_4040_0000);
+    assert_eq!(fregs[11], 0x4008_0000_0000_0000);
+    assert_eq!(machine.inspect_csr(0x001), Some(0), "Quiet NaN operand is not invalid");
+}
@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use riscv_core::{Exception, RiscV, RiscVError, Xlen};
+use riscv_core::constance::{DRAM_BASE_ADDR, UART_BASE, UART_IRQ};
+use riscv_core::debug::DebugInterface;
+use riscv_core::mmio::{Access, Device, Physical};
+
+const DEVICE_BASE: Xlen = 0x2000_0000;
+
+/// Offset 0 store the value, offset 4 return the value plus one
+#[derive(Debug, Default)]
+struct Counter {
+    value: Arc<Mutex<u32>>,
+}
+
+impl Device for Counter {
+    fn read_byte(&self, access: Access<Physical>) -> Result<u8, Exception> {
+        let value = *self.value.lock().unwrap();
+        match access.addr {
+            0..=3 => Ok(value.to_le_bytes()[access.addr as usize]),
+            4..=7 => Ok((value + 1).to_le_bytes()[access.addr as usize - 4]),
+            _     => Err(access.into_access_exception()),
+        }
+    }
+
+    fn write_byte(&mut self, access: Access<Physical>, data: u8) -> Result<(), Exception> {
+        let mut value = self.value.lock().unwrap();
+        match access.addr {
+            0..=3 => {
+                let mut bytes = value.to_le_bytes();
+                bytes[access.addr as usize] = data;
+                *value = u32::from_le_bytes(bytes);
+                Ok(())
+            },
+            _ => Err(access.into_access_exception()),
+        }
+    }
+}
+
+#[test]
+fn test_user_device_access() {
+    let value = Arc::new(Mutex::new(0));
+    let mut machine = RiscV::default();
+    machine.add_device(DEVICE_BASE, 0x1000, None, Counter { value: value.clone() }).unwrap();
+
+    let code: Vec<u8> = [
+        0x200002b7u32, // lui  t0, 0x20000
+        0x02a00313,    // addi t1, zero, 42
+        0x0062a023,    // sw   t1, 0(t0)
+        0x0042a383,    // lw   t2, 4(t0)
+    ].iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(machine.step().unwrap(), None);
+    }
+
+    assert_eq!(*value.lock().unwrap(), 42);
+    assert_eq!(machine.inspect_regs()[7], 43);
+}
+
+#[test]
+fn test_user_device_range_check() {
+    let mut machine = RiscV::default();
+
+    assert_eq!(machine.add_device(DEVICE_BASE, 0, None, Counter::default()),
+        Err(RiscVError::InvalidDeviceRange(DEVICE_BASE, 0)));
+    assert_eq!(machine.add_device(Xlen::MAX, 2, None, Counter::default()),
+        Err(RiscVError::InvalidDeviceRange(Xlen::MAX, 2)));
+    assert_eq!(machine.add_device(UART_BASE - 0x10, 0x20, None, Counter::default()),
+        Err(RiscVError::DeviceOverlap(UART_BASE - 0x10, UART_BASE + 0xf)), "Overlap with UART");
+    assert_eq!(machine.add_device(DRAM_BASE_ADDR, 0x10, None, Counter::default()),
+        Err(RiscVError::DeviceOverlap(DRAM_BASE_ADDR, DRAM_BASE_ADDR + 0xf)), "Overlap with RAM");
+
+    machine.add_device(DEVICE_BASE, 0x1000, Some(1), Counter::default()).unwrap();
+    assert_eq!(machine.add_device(DEVICE_BASE + 0xff0, 0x20, None, Counter::default()),
+        Err(RiscVError::DeviceOverlap(DEVICE_BASE + 0xff0, DEVICE_BASE + 0x100f)), "Overlap with user device");
+
+    assert_eq!(machine.add_device(DEVICE_BASE + 0x1000, 0x10, Some(UART_IRQ), Counter::default()),
+        Err(RiscVError::InvalidIrq(UART_IRQ)));
+    assert_eq!(machine.add_device(DEVICE_BASE + 0x1000, 0x10, Some(1), Counter::default()),
+        Err(RiscVError::InvalidIrq(1)), "Source used by other device");
+    assert_eq!(machine.add_device(DEVICE_BASE + 0x1000, 0x10, Some(2), Counter::default()), Ok(()));
+}
@@ -1,4 +1,4 @@
-#![cfg(feature = "c")]
+#![cfg(all(feature = "c", not(feature = "rv64")))]
 
 mod common;
 
@@ -1,4 +1,4 @@
-#![cfg(all(feature = "zicsr"))]
+#![cfg(all(feature = "zicsr", not(feature = "rv64")))]
 
 mod common;
 
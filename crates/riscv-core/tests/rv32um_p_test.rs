@@ -1,4 +1,4 @@
-#![cfg(all(feature = "m", feature = "zicsr"))]
+#![cfg(all(feature = "m", feature = "zicsr", not(feature = "rv64")))]
 
 mod common;
 
@@ -0,0 +1,133 @@
+#![cfg(feature = "rv64")]
+
+mod common;
+
+#[cfg(feature = "c")]
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::DebugInterface;
+
+use common::{machine, run};
+
+#[test]
+fn test_rv64_base() {
+    let mut machine = machine(&[
+        0x00001297, // auipc t0, 1
+        0xfff00313, // addi  t1, zero, -1
+        0x0062b023, // sd    t1, 0(t0)
+        0x0002a383, // lw    t2, 0(t0)
+        0x0002ee03, // lwu   t3, 0(t0)
+        0x0002be83, // ld    t4, 0(t0)
+        0x001e0f1b, // addiw t5, t3, 1
+        0x00100513, // addi  a0, zero, 1
+        0x02851593, // slli  a1, a0, 40
+        0x0275d613, // srli  a2, a1, 39
+        0x4043569b, // sraiw a3, t1, 4
+        0x00a5873b, // addw  a4, a1, a0
+        0x800007b7, // lui   a5, 0x80000
+    ]);
+    run(&mut machine, 13);
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[7], u64::MAX, "lw is sign-extended");
+    assert_eq!(regs[28], 0xffff_ffff, "lwu is zero-extended");
+    assert_eq!(regs[29], u64::MAX);
+    assert_eq!(regs[30], 0, "addiw wraps at 32 bits");
+    assert_eq!(regs[11], 1 << 40, "Shift amount is 6 bits");
+    assert_eq!(regs[12], 2);
+    assert_eq!(regs[13], u64::MAX);
+    assert_eq!(regs[14], 1, "addw ignores upper bits");
+    assert_eq!(regs[15], 0xffff_ffff_8000_0000, "lui is sign-extended");
+}
+
+#[cfg(feature = "m")]
+#[test]
+fn test_rv64_m() {
+    let mut machine = machine(&[
+        0xfff00313, // addi  t1, zero, -1
+        0x00100513, // addi  a0, zero, 1
+        0x02851593, // slli  a1, a0, 40
+        0x800007b7, // lui   a5, 0x80000
+        0x02a7883b, // mulw  a6, a5, a0
+        0x02b359bb, // divuw s3, t1, a1
+    ]);
+    run(&mut machine, 6);
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[16], 0xffff_ffff_8000_0000);
+    assert_eq!(regs[19], u64::MAX, "Divisor is zero in lower 32 bits");
+}
+
+#[cfg(feature = "a")]
+#[test]
+fn test_rv64_a() {
+    let mut machine = machine(&[
+        0x00001297, // auipc     t0, 1
+        0xfff00313, // addi      t1, zero, -1
+        0x0062b023, // sd        t1, 0(t0)
+        0x00100513, // addi      a0, zero, 1
+        0x02851593, // slli      a1, a0, 40
+        0x00b2b8af, // amoadd.d  a7, a1, (t0)
+        0x0002b903, // ld        s2, 0(t0)
+        0x08a2aa2f, // amoswap.w s4, a0, (t0)
+        0x0002be83, // ld        t4, 0(t0)
+    ]);
+    run(&mut machine, 9);
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[17], u64::MAX);
+    assert_eq!(regs[18], (1 << 40) - 1);
+    assert_eq!(regs[20], u64::MAX, "Word AMO is sign-extended");
+    assert_eq!(regs[29], 0xff_0000_0001, "Word AMO keeps upper word");
+}
+
+#[cfg(feature = "c")]
+#[test]
+fn test_rv64_compressed() {
+    let mut machine = machine(&[
+        0x00001597, // auipc a1, 1
+        0xfff00613, // addi  a2, zero, -1
+        0x6594e590, // c.sd  a2, 8(a1);  c.ld   a3, 8(a1)
+        0x9e352605, // c.addiw a2, 1;    c.addw a2, a3
+        0x00019e91, // c.subw a3, a2;    c.nop
+    ]);
+    run(&mut machine, 8);
+
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[12], u64::MAX);
+    assert_eq!(regs[13], 0);
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 20);
+}
+
+#[cfg(feature = "d")]
+#[test]
+fn test_rv64_fp() {
+    let mut machine = machine(&[
+        0x000020b7, // lui       ra, 2
+        0x3000a073, // csrs      mstatus, ra
+        0xfff00313, // addi      t1, zero, -1
+        0xd2237553, // fcvt.d.l  fa0, t1
+        0xd03375d3, // fcvt.s.lu fa1, t1
+        0xe2050553, // fmv.x.d   a0, fa0
+        0x00100593, // addi      a1, zero, 1
+        0x02859593, // slli      a1, a1, 40
+        0xd235f653, // fcvt.d.lu fa2, a1
+        0xc2267653, // fcvt.l.d  a2, fa2
+        0xc23576d3, // fcvt.lu.d a3, fa0
+        0xc025f753, // fcvt.l.s  a4, fa1
+        0xf20586d3, // fmv.d.x   fa3, a1
+        0x001027f3, // frflags   a5
+    ]);
+    run(&mut machine, 14);
+
+    let regs = machine.inspect_regs();
+    let fregs = machine.inspect_fregs();
+    assert_eq!(fregs[10], (-1.0f64).to_bits());
+    assert_eq!(fregs[11], 0xffff_ffff_5f80_0000, "u64::MAX rounds to 2^64");
+    assert_eq!(regs[10], (-1.0f64).to_bits(), "fmv.x.d moves raw bits");
+    assert_eq!(fregs[12], ((1u64 << 40) as f64).to_bits());
+    assert_eq!(regs[12], 1 << 40);
+    assert_eq!(regs[13], 0, "Negative value is saturated to 0");
+    assert_eq!(regs[14], i64::MAX as u64, "2^64 is out of range");
+    assert_eq!(fregs[13], 1 << 40);
+    assert_eq!(regs[15], 0x11, "Invalid and inexact");
+}
@@ -0,0 +1,125 @@
+#![cfg(not(feature = "rv64"))]
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use riscv_core::{Halt, RiscV, StepEvent, UartSink};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::DebugInterface;
+
+const DATA_ADDR: u32 = DRAM_BASE_ADDR + 0x1000;
+
+/// `slli x0, x0, 0x1f; ebreak; srai x0, x0, 7`
+const SEMIHOST_CALL: [u32; 3] = [0x01f01013, 0x00100073, 0x40705013];
+
+fn semihost_machine(root: PathBuf, code: &[u32], data: &[u8]) -> (RiscV, Arc<Mutex<Vec<u8>>>) {
+    let (sink, output) = UartSink::buffer();
+    let mut machine = MachineConfig::default()
+        .with_uart_sink(sink)
+        .with_semihosting(root)
+        .build()
+        .unwrap();
+
+    let code: Vec<u8> = code.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+    machine.load(DATA_ADDR, data).unwrap();
+    (machine, output)
+}
+
+/// `a0 = op; a1 = DATA_ADDR + offset`, then semihosting call
+fn call(op: u32, offset: u32) -> Vec<u32> {
+    let mut code = vec![
+        (op << 20) | 0x513,              // addi a0, zero, op
+        0x800015b7,                      // lui  a1, 0x80001
+        (offset << 20) | 0x58593,        // addi a1, a1, offset
+    ];
+    code.extend(SEMIHOST_CALL);
+    code
+}
+
+#[test]
+fn test_write0_and_exit() {
+    let mut code = call(0x04, 0);
+    // SYS_EXIT_EXTENDED with { ApplicationExit, 7 }
+    code.extend(call(0x20, 0x10));
+    let mut data = b"hello\0".to_vec();
+    data.resize(0x10, 0);
+    data.extend([0x26, 0x00, 0x02, 0x00, 7, 0, 0, 0]);
+
+    let (mut machine, output) = semihost_machine(std::env::temp_dir(), &code, &data);
+    for _ in 0..6 {
+        assert_eq!(machine.step().unwrap(), None);
+    }
+    assert_eq!(output.lock().unwrap().as_slice(), b"hello");
+
+    assert_eq!(machine.run().unwrap(), Halt::Exit(7));
+    assert_eq!(machine.step().unwrap(), Some(StepEvent::Halt(Halt::Exit(7))));
+}
+
+#[test]
+fn test_file_sandbox() {
+    let root = std::env::temp_dir().join("riscv-semihost-test");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("in.txt"), b"data").unwrap();
+
+    // SYS_OPEN "in.txt" with "r", then "../in.txt"
+    let mut code = call(0x01, 0x20);
+    code.extend(call(0x01, 0x30));
+    let mut data = b"in.txt\0".to_vec();
+    data.resize(0x10, 0);
+    data.extend(b"../in.txt\0");
+    data.resize(0x20, 0);
+    for word in [DATA_ADDR, 0, 6, 0, DATA_ADDR + 0x10, 0, 9] {
+        data.extend(word.to_le_bytes());
+    }
+
+    let (mut machine, _) = semihost_machine(root, &code, &data);
+    for _ in 0..6 {
+        machine.step().unwrap();
+    }
+    assert_eq!(machine.inspect_regs()[10], 3, "First file handle");
+
+    for _ in 0..6 {
+        machine.step().unwrap();
+    }
+    assert_eq!(machine.inspect_regs()[10], u32::MAX, "Can not escape root");
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_plain_ebreak() {
+    use riscv_core::{Exception, Trap};
+
+    // ebreak without entry sequence
+    let (mut machine, _) = semihost_machine(std::env::temp_dir(), &[0x00100073], &[]);
+    assert_eq!(machine.step().unwrap(), Some(StepEvent::Trap(Trap::Exception(Exception::Breakpoint(DRAM_BASE_ADDR)))));
+}
+
+#[test]
+#[cfg(feature = "s")]
+fn test_ebreak_with_paging() {
+    use riscv_core::{Exception, Trap};
+    use riscv_core::debug::DebugControl;
+
+    // Physical sequence at pc - 4 and pc + 4 is not what S-Mode executes
+    let (mut machine, _) = semihost_machine(std::env::temp_dir(), &SEMIHOST_CALL, &[]);
+    // nop; ebreak; nop
+    let code: Vec<u8> = [0x00000013u32, 0x00100073, 0x00000013].iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR + 0x40_0000, &code).unwrap();
+
+    // Sv32 megapage maps DRAM_BASE_ADDR to DRAM_BASE_ADDR + 4MiB, V | R | X | A | D
+    let root = DRAM_BASE_ADDR + 0x2000;
+    let pte = ((DRAM_BASE_ADDR + 0x40_0000) >> 12 << 10) | 0xcb;
+    machine.load(root + (DRAM_BASE_ADDR >> 22) * 4, &pte.to_le_bytes()).unwrap();
+
+    // PMP allows all for S-Mode: pmpaddr0 = top, pmpcfg0 = TOR | X | W | R
+    assert!(machine.write_csr(0x3b0, u32::MAX));
+    assert!(machine.write_csr(0x3a0, 0x0f));
+    assert!(machine.write_csr(0x180, (1 << 31) | (root >> 12)));
+    assert!(machine.set_privilege(1));
+    machine.set_pc(DRAM_BASE_ADDR + 4);
+
+    assert_eq!(machine.step().unwrap(), Some(StepEvent::Trap(Trap::Exception(Exception::Breakpoint(DRAM_BASE_ADDR + 4)))));
+}
@@ -0,0 +1,117 @@
+#![cfg(not(feature = "rv64"))]
+
+use std::path::PathBuf;
+
+use riscv_core::{RiscV, SnapshotError};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::DebugInterface;
+
+const DATA_ADDR: u32 = DRAM_BASE_ADDR + 0x1000;
+
+/// Store `x1` to `DATA_ADDR` then increase it forever
+const STORE_LOOP: [u32; 5] = [
+    0x00100093, // addi x1, zero, 1
+    0x800011b7, // lui  x3, 0x80001
+    0x0011a023, // sw   x1, 0(x3)
+    0x00108093, // addi x1, x1, 1
+    0xff9ff06f, // jal  zero, -8
+];
+
+fn store_loop(ram_size: usize) -> RiscV {
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, ram_size)
+        .build()
+        .unwrap();
+    let code: Vec<u8> = STORE_LOOP.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+    machine
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("riscv-core-{}-{name}.snap", std::process::id()))
+}
+
+#[test]
+fn test_save_and_load_snapshot() {
+    let path = snapshot_path("resume");
+    let mut machine = store_loop(1024 * 1024);
+    machine.run_until(Some(10)).unwrap();
+    machine.save_snapshot(&path).unwrap();
+
+    let regs = machine.inspect_regs();
+    let pc = machine.inspect_pc();
+    let data = machine.inspect_bus(DATA_ADDR, 4);
+    #[cfg(feature = "zicsr")]
+    let csrs = machine.inspect_csrs();
+
+    machine.run_until(Some(10)).unwrap();
+    assert_ne!(machine.inspect_bus(DATA_ADDR, 4), data);
+
+    // Resume in a fresh machine with the same RAM size
+    let mut resumed = store_loop(1024 * 1024);
+    resumed.load_snapshot(&path).unwrap();
+    assert_eq!(resumed.inspect_regs(), regs);
+    assert_eq!(resumed.inspect_pc(), pc);
+    assert_eq!(resumed.inspect_bus(DATA_ADDR, 4), data);
+    #[cfg(feature = "zicsr")]
+    assert_eq!(resumed.inspect_csrs(), csrs);
+
+    // Both continue the same way
+    resumed.run_until(Some(10)).unwrap();
+    assert_eq!(resumed.inspect_regs(), machine.inspect_regs());
+    assert_eq!(resumed.inspect_bus(DATA_ADDR, 4), machine.inspect_bus(DATA_ADDR, 4));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn test_load_invalid_snapshot() {
+    let path = snapshot_path("invalid");
+    let mut machine = store_loop(1024 * 1024);
+    machine.save_snapshot(&path).unwrap();
+
+    let mut small = store_loop(64 * 1024);
+    assert!(matches!(small.load_snapshot(&path), Err(SnapshotError::RamSize(0x10_0000))));
+
+    let mut data = std::fs::read(&path).unwrap();
+    let version = data[8..12].to_vec();
+    // Version 1 has no hardware performance counters
+    data[8..12].copy_from_slice(&1u32.to_le_bytes());
+    std::fs::write(&path, &data).unwrap();
+    assert!(matches!(machine.load_snapshot(&path), Err(SnapshotError::Version(1))));
+
+    data[8..12].copy_from_slice(&version);
+    data.truncate(data.len() - 1);
+    std::fs::write(&path, &data).unwrap();
+    assert!(matches!(machine.load_snapshot(&path), Err(SnapshotError::Corrupted)));
+
+    std::fs::write(&path, b"not a snapshot").unwrap();
+    assert!(matches!(machine.load_snapshot(&path), Err(SnapshotError::InvalidMagic)));
+
+    let _ = std::fs::remove_file(path);
+    assert!(matches!(machine.load_snapshot(snapshot_path("missing")), Err(SnapshotError::Io(_))));
+}
+
+#[test]
+fn test_load_snapshot_of_other_config() {
+    let path = snapshot_path("config");
+    store_loop(1024 * 1024).save_snapshot(&path).unwrap();
+
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+        .with_reset_pc(DRAM_BASE_ADDR + 0x100)
+        .build()
+        .unwrap();
+    assert!(matches!(machine.load_snapshot(&path), Err(SnapshotError::ResetPc(0x8000_0000))));
+
+    #[cfg(feature = "zicsr")] {
+        let mut machine = MachineConfig::default()
+            .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+            .with_hart_id(1)
+            .build()
+            .unwrap();
+        assert!(matches!(machine.load_snapshot(&path), Err(SnapshotError::HartId(0))));
+    }
+    let _ = std::fs::remove_file(path);
+}
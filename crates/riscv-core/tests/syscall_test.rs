@@ -0,0 +1,92 @@
+#![cfg(not(feature = "rv64"))]
+
+use riscv_core::{Halt, StepEvent, UartSink};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::DebugInterface;
+use riscv_loader::LoadInfo;
+
+const RAM_SIZE: usize = 1024 * 1024;
+
+fn user_machine(code: &[u32], data: &[u8]) -> (riscv_core::RiscV, std::sync::Arc<std::sync::Mutex<Vec<u8>>>) {
+    let (sink, output) = UartSink::buffer();
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, RAM_SIZE)
+        .with_uart_sink(sink)
+        .with_user_mode(vec!["prog".to_string(), "arg".to_string()])
+        .build()
+        .unwrap();
+
+    let info = LoadInfo {
+        pc_entry: DRAM_BASE_ADDR,
+        code: vec![(code.iter().flat_map(|ins| ins.to_le_bytes()).collect(), DRAM_BASE_ADDR)],
+        data: Some(vec![(data.to_vec(), DRAM_BASE_ADDR + 0x1000)]),
+        ..Default::default()
+    };
+    machine.load_info(&info).unwrap();
+    (machine, output)
+}
+
+#[test]
+fn test_write_and_exit() {
+    let code = [
+        0x04000893, // addi a7, zero, 64
+        0x00100513, // addi a0, zero, 1
+        0x800015b7, // lui  a1, 0x80001
+        0x00500613, // addi a2, zero, 5
+        0x00000073, // ecall
+        0x05d00893, // addi a7, zero, 93
+        0x00300513, // addi a0, zero, 3
+        0x00000073, // ecall
+    ];
+    let (mut machine, output) = user_machine(&code, b"hello");
+
+    for _ in 0..5 {
+        assert_eq!(machine.step().unwrap(), None, "ecall should not trap");
+    }
+    assert_eq!(machine.inspect_regs()[10], 5, "Return written length");
+    assert_eq!(machine.inspect_pc(), DRAM_BASE_ADDR + 20);
+    assert_eq!(output.lock().unwrap().as_slice(), b"hello");
+
+    assert_eq!(machine.run().unwrap(), Halt::Exit(3));
+    assert_eq!(machine.step().unwrap(), Some(StepEvent::Halt(Halt::Exit(3))));
+}
+
+#[test]
+fn test_initial_stack() {
+    let (machine, _) = user_machine(&[], &[]);
+
+    let sp = machine.inspect_regs()[2];
+    assert_eq!(sp % 16, 0, "Stack is aligned");
+    assert!(sp < DRAM_BASE_ADDR + RAM_SIZE as u32);
+
+    let words: Vec<u32> = machine.inspect_bus(sp, 12)
+        .chunks(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+    assert_eq!(words[0], 2, "argc");
+    assert_eq!(machine.inspect_bus(words[1], 5), b"prog\0");
+    assert_eq!(machine.inspect_bus(words[2], 4), b"arg\0");
+}
+
+#[test]
+fn test_brk() {
+    let code = [
+        0x0d600893, // addi a7, zero, 214
+        0x00000513, // addi a0, zero, 0
+        0x00000073, // ecall
+        0x00050293, // addi t0, a0, 0
+        0x10050513, // addi a0, a0, 256
+        0x00000073, // ecall
+        0x00000513, // addi a0, zero, 0
+        0x00000073, // ecall
+    ];
+    let (mut machine, _) = user_machine(&code, &[1]);
+
+    for _ in 0..code.len() {
+        machine.step().unwrap();
+    }
+    let regs = machine.inspect_regs();
+    assert_eq!(regs[5], DRAM_BASE_ADDR + 0x2000, "Break start at page after data");
+    assert_eq!(regs[10], DRAM_BASE_ADDR + 0x2100, "Break is moved");
+}
@@ -0,0 +1,79 @@
+#![cfg(not(feature = "rv64"))]
+
+use std::sync::{Arc, Mutex};
+
+use riscv_core::{Exception, RiscV, Trap};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_core::debug::{Commit, MemAccess, TraceSink, Tracer};
+use riscv_core::mmio::AccessType;
+
+#[derive(Default)]
+struct Collect(Arc<Mutex<Vec<Commit>>>);
+
+impl Tracer for Collect {
+    fn commit(&mut self, commit: &Commit) {
+        self.0.lock().unwrap().push(commit.clone());
+    }
+}
+
+fn machine(code: &[u32]) -> (RiscV, Arc<Mutex<Vec<Commit>>>) {
+    let tracer = Collect::default();
+    let commits = tracer.0.clone();
+    let mut machine = MachineConfig::default()
+        .with_ram(DRAM_BASE_ADDR, 1024 * 1024)
+        .with_tracer(TraceSink::new(tracer))
+        .build()
+        .unwrap();
+    let code: Vec<u8> = code.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+    machine.load(DRAM_BASE_ADDR, &code).unwrap();
+    (machine, commits)
+}
+
+#[test]
+fn test_trace_commit() {
+    let (mut machine, commits) = machine(&[
+        0x00000093, // addi x1, zero, 0
+        0x800011b7, // lui  x3, 0x80001
+        0x00119023, // sh   x1, 0(x3)
+        0x0001a203, // lw   x4, 0(x3)
+        0xffffffff, // illegal
+    ]);
+    for _ in 0..5 {
+        machine.step().unwrap();
+    }
+
+    let commits = commits.lock().unwrap();
+    assert_eq!(commits.len(), 5);
+
+    assert_eq!(commits[0].pc, DRAM_BASE_ADDR);
+    assert_eq!(commits[0].mode, 3);
+    assert_eq!(commits[0].raw, Some(0x00000093));
+    assert_eq!(commits[0].regs, [(1, 0)], "Same value is still written");
+
+    assert_eq!(commits[2].regs, []);
+    assert_eq!(commits[2].mem, [MemAccess { kind: AccessType::Store, addr: DRAM_BASE_ADDR + 0x1000, len: 2, data: 0 }]);
+    assert_eq!(commits[3].regs, [(4, 0)]);
+    assert_eq!(commits[3].mem, [MemAccess { kind: AccessType::Load, addr: DRAM_BASE_ADDR + 0x1000, len: 4, data: 0 }]);
+
+    assert_eq!(commits[4].raw, Some(0xffffffff));
+    assert_eq!(commits[4].trap, Some(Trap::Exception(Exception::IllegalInstruction(0xffffffff))));
+}
+
+#[test]
+#[cfg(feature = "zicsr")]
+fn test_trace_csr_write() {
+    let (mut machine, commits) = machine(&[
+        0x00500093, // addi  x1, zero, 5
+        0x34009073, // csrw  mscratch, x1
+        0x34002173, // csrr  x2, mscratch
+    ]);
+    for _ in 0..3 {
+        machine.step().unwrap();
+    }
+
+    let commits = commits.lock().unwrap();
+    assert_eq!(commits[1].csrs, [(0x340, 5)]);
+    assert_eq!(commits[2].csrs, [], "Read only is not a write");
+    assert_eq!(commits[2].regs, [(2, 5)]);
+}
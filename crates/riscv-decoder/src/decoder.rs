@@ -35,7 +35,21 @@ pub fn decode(raw: u32) -> Result<Instruction, DecodeError> {
                 let res = Base(op, InstructionData { rd, rs1, rs2, imm });
                 return Ok(res);
             } 
+
+            #[cfg(feature = "rv64")]
+            if let Some(op) = Rv64iOp::decode_itype(itype, funct3, funct7) {
+                let res = Rv64(op, InstructionData { rd, rs1, rs2, imm });
+                return Ok(res);
+            } 
             
+            #[cfg(any(feature = "zbb", feature = "zbs"))]
+            if itype == OpCode::ItypeAr && let Some(op) = BOp::decode_itype(funct3, raw.get_bits(20, 12) as u16) {
+                // `shamt` of shift-like instructions, ignored by unary ones
+                let imm = raw.get_bits(20, 6) as i32;
+                let res = B(op, InstructionData { rd, rs1, rs2, imm });
+                return Ok(res);
+            }
+
             #[cfg(feature = "zifencei")]
             if itype == OpCode::ItypeFence && let Some(op) =  ZifenceiOp::decode(funct3) {
                 let res = Zifencei(op, InstructionData { rd, rs1, rs2, imm });
@@ -57,6 +71,51 @@ pub fn decode(raw: u32) -> Result<Instruction, DecodeError> {
                 return Ok(res);
             } 
 
+            #[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+            if let Some(op) = BOp::decode_rtype(funct3, funct7, rs2) {
+                let res = B(op, InstructionData { rd, rs1, rs2, imm: 0 });
+                return Ok(res);
+            }
+
+            Err(DecodeError::UnknownInstruction(rtype, raw))
+        },
+        #[cfg(feature = "rv64")]
+        itype @ OpCode::ItypeAr32 => {
+            let imm = raw.get_bits_signed(20, 12);
+
+            if let Some(op) = Rv64iOp::decode_itype(itype, funct3, funct7) {
+                let res = Rv64(op, InstructionData { rd, rs1, rs2, imm });
+                return Ok(res);
+            }
+
+            #[cfg(any(feature = "zba", feature = "zbb"))]
+            if let Some(op) = BOp::decode_itype_w(funct3, raw.get_bits(20, 12) as u16) {
+                let imm = raw.get_bits(20, 6) as i32;
+                let res = B(op, InstructionData { rd, rs1, rs2, imm });
+                return Ok(res);
+            }
+
+            Err(DecodeError::UnknownInstruction(itype, raw))
+        },
+        #[cfg(feature = "rv64")]
+        rtype @ OpCode::Rtype32 => {
+            if let Some(op) = Rv64iOp::decode_rtype(funct3, funct7) {
+                let res = Rv64(op, InstructionData { rd, rs1, rs2, imm: 0 });
+                return Ok(res);
+            }
+
+            #[cfg(feature = "m")]
+            if let Some(op) = MOp::decode_w(funct3, funct7) {
+                let res = M(op, InstructionData { rd, rs1, rs2, imm: 0 });
+                return Ok(res);
+            }
+
+            #[cfg(any(feature = "zba", feature = "zbb"))]
+            if let Some(op) = BOp::decode_rtype_w(funct3, funct7, rs2) {
+                let res = B(op, InstructionData { rd, rs1, rs2, imm: 0 });
+                return Ok(res);
+            }
+
             Err(DecodeError::UnknownInstruction(rtype, raw))
         },
         // imm [11:5] | rs2 [4:0] | rs1 [4:0] | funct3 [2:0] | imm [4:0] | opcode [6:0]
@@ -67,6 +126,12 @@ pub fn decode(raw: u32) -> Result<Instruction, DecodeError> {
                 let res = Ok(Base(op, InstructionData { rd, rs1, rs2, imm }));
                 return res;
             } 
+
+            #[cfg(feature = "rv64")]
+            if let Some(op) = Rv64iOp::decode_stype(funct3) {
+                let res = Ok(Rv64(op, InstructionData { rd, rs1, rs2, imm }));
+                return res;
+            } 
             
             Err(DecodeError::UnknownInstruction(stype, raw)) 
         },
@@ -122,6 +187,74 @@ pub fn decode(raw: u32) -> Result<Instruction, DecodeError> {
 
             Err(DecodeError::UnknownInstruction(atomic, raw))
         },
+        // imm [11:0] | rs1 [4:0] | funct3 [2:0] | rd [4:0] | opcode [6:0]
+        #[cfg(feature = "f")]
+        load @ OpCode::LoadFp => {
+            let imm = raw.get_bits_signed(20, 12);
+            let data = FpInsData { rd, rs1, rs2, rs3: 0, rm: funct3, imm };
+
+            if let Some(op) = FOp::decode_load(funct3) {
+                return Ok(F(op, data, raw));
+            }
+
+            #[cfg(feature = "d")]
+            if let Some(op) = DOp::decode_load(funct3) {
+                return Ok(D(op, data, raw));
+            }
+
+            Err(DecodeError::UnknownInstruction(load, raw))
+        },
+        // imm [11:5] | rs2 [4:0] | rs1 [4:0] | funct3 [2:0] | imm [4:0] | opcode [6:0]
+        #[cfg(feature = "f")]
+        store @ OpCode::StoreFp => {
+            let imm = (raw.get_bits_signed(25, 7) << 5) | raw.get_bits(7, 5) as i32;
+            let data = FpInsData { rd, rs1, rs2, rs3: 0, rm: funct3, imm };
+
+            if let Some(op) = FOp::decode_store(funct3) {
+                return Ok(F(op, data, raw));
+            }
+
+            #[cfg(feature = "d")]
+            if let Some(op) = DOp::decode_store(funct3) {
+                return Ok(D(op, data, raw));
+            }
+
+            Err(DecodeError::UnknownInstruction(store, raw))
+        },
+        // rs3 [4:0] | fmt [1:0] | rs2 [4:0] | rs1 [4:0] | rm [2:0] | rd [4:0] | opcode [6:0]
+        #[cfg(feature = "f")]
+        fused @ (OpCode::Madd | OpCode::Msub | OpCode::Nmsub | OpCode::Nmadd) => {
+            let rs3 = raw.get_bits(27, 5) as u8;
+            let fmt = raw.get_bits(25, 2) as u8;
+            let data = FpInsData { rd, rs1, rs2, rs3, rm: funct3, imm: 0 };
+
+            if let Some(op) = FOp::decode_fused(fused, fmt) {
+                return Ok(F(op, data, raw));
+            }
+
+            #[cfg(feature = "d")]
+            if let Some(op) = DOp::decode_fused(fused, fmt) {
+                return Ok(D(op, data, raw));
+            }
+
+            Err(DecodeError::UnknownInstruction(fused, raw))
+        },
+        // funct5 [4:0] | fmt [1:0] | rs2 [4:0] | rs1 [4:0] | rm [2:0] | rd [4:0] | opcode [6:0]
+        #[cfg(feature = "f")]
+        op_fp @ OpCode::OpFp => {
+            let data = FpInsData { rd, rs1, rs2, rs3: 0, rm: funct3, imm: 0 };
+
+            if let Some(op) = FOp::decode_op(funct7, funct3, rs2) {
+                return Ok(F(op, data, raw));
+            }
+
+            #[cfg(feature = "d")]
+            if let Some(op) = DOp::decode_op(funct7, funct3, rs2) {
+                return Ok(D(op, data, raw));
+            }
+
+            Err(DecodeError::UnknownInstruction(op_fp, raw))
+        },
         system @ OpCode::System => {
             let imm = raw.get_bits(20, 12) as i32;
 
@@ -203,6 +336,19 @@ pub fn decompress(raw: u16) -> Result<Instruction, DecodeError> {
                         raw.get_bits(12, 1) << 5 |
                         raw.get_bits(4, 3)  << 2) as i32
                     },
+                    #[cfg(all(feature = "f", not(feature = "rv64")))]
+                    COp::Flwsp => {
+                        rs1 = 2;
+                        (raw.get_bits(2, 2)  << 6 |
+                        raw.get_bits(12, 1) << 5 |
+                        raw.get_bits(4, 3)  << 2) as i32
+                    },
+                    _ if ins.is_double() => {
+                        rs1 = 2;
+                        (raw.get_bits(2, 3)  << 6 |
+                        raw.get_bits(12, 1) << 5 |
+                        raw.get_bits(5, 2)  << 3) as i32
+                    },
                     _ => (if ins.is_uimm() {
                         raw.get_bits(12, 1) as i32
                     } else {
@@ -213,7 +359,11 @@ pub fn decompress(raw: u16) -> Result<Instruction, DecodeError> {
             CFormat::Css => {
                 rd = 0;
                 rs1 = 2;
-                imm = (raw.get_bits(7, 2) << 6 | raw.get_bits(9, 3) << 2) as i32;
+                imm = if ins.is_double() {
+                    (raw.get_bits(7, 3) << 6 | raw.get_bits(10, 3) << 3) as i32
+                } else {
+                    (raw.get_bits(7, 2) << 6 | raw.get_bits(9, 3) << 2) as i32
+                };
             },
             CFormat::Ciw => {
                 rd = raw.get_bits(2, 3) as u8 + 8;
@@ -226,8 +376,12 @@ pub fn decompress(raw: u16) -> Result<Instruction, DecodeError> {
                 rd = raw.get_bits(2, 3) as u8 + 8;
                 rs1 = raw.get_bits(7, 3) as u8 + 8;
                 rs2 = rd;
-                imm = (raw.get_bits(5, 1) << 6  | raw.get_bits(10, 3) << 3 |
-                    raw.get_bits(6, 1) << 2) as i32;
+                imm = if ins.is_double() {
+                    (raw.get_bits(5, 2) << 6 | raw.get_bits(10, 3) << 3) as i32
+                } else {
+                    (raw.get_bits(5, 1) << 6  | raw.get_bits(10, 3) << 3 |
+                        raw.get_bits(6, 1) << 2) as i32
+                };
             },
             CFormat::Ca => {
                 rd = raw.get_bits(7, 3) as u8 + 8;
@@ -260,6 +414,24 @@ pub fn decompress(raw: u16) -> Result<Instruction, DecodeError> {
                 raw.get_bits(2, 1) << 5 | raw.get_bits(11, 1) << 4 | raw.get_bits(3, 3) << 1) as i32;
             }
         }
+        #[cfg(feature = "rv64")]
+        if let Some(op) = ins.into_rv64() {
+            return Ok(Rv64(op, InstructionData { rd, rs1, rs2, imm }));
+        }
+
+        // `rm` holds width as `funct3` of the expanded instruction
+        #[cfg(all(feature = "f", not(feature = "rv64")))]
+        if let Some(op) = ins.into_f() {
+            let data = FpInsData { rd, rs1, rs2, rs3: 0, rm: 0x2, imm };
+            return Ok(F(op, data, raw as u32));
+        }
+
+        #[cfg(feature = "d")]
+        if let Some(op) = ins.into_d() {
+            let data = FpInsData { rd, rs1, rs2, rs3: 0, rm: 0x3, imm };
+            return Ok(D(op, data, raw as u32));
+        }
+
         let res = Instruction::Base(ins.into_base(), InstructionData { rd, rs1, rs2, imm });
         return Ok(res);
     }
@@ -1,5 +1,7 @@
 //! Definition of enum corresponding to opcode
 mod rv32i;
+#[cfg(feature = "rv64")]
+mod rv64i;
 #[cfg(feature = "m")]
 mod m;
 #[cfg(feature = "a")]
@@ -12,8 +14,16 @@ mod zicsr;
 mod zifencei;
 #[cfg(feature = "zicsr")]
 mod privileged;
+#[cfg(feature = "f")]
+mod f;
+#[cfg(feature = "d")]
+mod d;
+#[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+mod b;
 
 pub use rv32i::Rv32iOp;
+#[cfg(feature = "rv64")]
+pub use rv64i::Rv64iOp;
 #[cfg(feature = "m")]
 pub use m::MOp;
 #[cfg(feature = "a")]
@@ -30,6 +40,12 @@ pub use zifencei::ZifenceiOp;
 pub use privileged::PrivilegeOp;
 #[cfg(feature = "a")]
 pub use a::AmoInsData;
+#[cfg(feature = "f")]
+pub use f::{FOp, FpInsData};
+#[cfg(feature = "d")]
+pub use d::DOp;
+#[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+pub use b::BOp;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct InstructionData {
@@ -43,6 +59,8 @@ pub struct InstructionData {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     Base(Rv32iOp, InstructionData),
+    #[cfg(feature = "rv64")]
+    Rv64(Rv64iOp, InstructionData),
     #[cfg(feature = "zicsr")]
     Privileged(PrivilegeOp, InstructionData),
     #[cfg(feature = "m")]
@@ -53,4 +71,10 @@ pub enum Instruction {
     Zicsr(ZicsrOp, InstructionData, u32),
     #[cfg(feature = "zifencei")]
     Zifencei(ZifenceiOp, InstructionData), 
+    #[cfg(feature = "f")]
+    F(FOp, FpInsData, u32),
+    #[cfg(feature = "d")]
+    D(DOp, FpInsData, u32),
+    #[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+    B(BOp, InstructionData),
 }
@@ -0,0 +1,280 @@
+use BOp::*;
+
+/// Bit-manipulation instructions of Zba, Zbb, Zbc and Zbs.
+/// `*i` and `slli.uw` hold `shamt` in `imm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BOp {
+    // Zba
+    #[cfg(feature = "zba")] Sh1add,
+    #[cfg(feature = "zba")] Sh2add,
+    #[cfg(feature = "zba")] Sh3add,
+    #[cfg(all(feature = "zba", feature = "rv64"))] AddUw,
+    #[cfg(all(feature = "zba", feature = "rv64"))] Sh1addUw,
+    #[cfg(all(feature = "zba", feature = "rv64"))] Sh2addUw,
+    #[cfg(all(feature = "zba", feature = "rv64"))] Sh3addUw,
+    #[cfg(all(feature = "zba", feature = "rv64"))] SlliUw,
+
+    // Zbb
+    #[cfg(feature = "zbb")] Andn,
+    #[cfg(feature = "zbb")] Orn,
+    #[cfg(feature = "zbb")] Xnor,
+    #[cfg(feature = "zbb")] Clz,
+    #[cfg(feature = "zbb")] Ctz,
+    #[cfg(feature = "zbb")] Cpop,
+    #[cfg(feature = "zbb")] Max,
+    #[cfg(feature = "zbb")] Maxu,
+    #[cfg(feature = "zbb")] Min,
+    #[cfg(feature = "zbb")] Minu,
+    #[cfg(feature = "zbb")] SextB,
+    #[cfg(feature = "zbb")] SextH,
+    #[cfg(feature = "zbb")] ZextH,
+    #[cfg(feature = "zbb")] Rol,
+    #[cfg(feature = "zbb")] Ror,
+    #[cfg(feature = "zbb")] Rori,
+    #[cfg(feature = "zbb")] OrcB,
+    #[cfg(feature = "zbb")] Rev8,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Clzw,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Ctzw,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Cpopw,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Rolw,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Rorw,
+    #[cfg(all(feature = "zbb", feature = "rv64"))] Roriw,
+
+    // Zbc
+    #[cfg(feature = "zbc")] Clmul,
+    #[cfg(feature = "zbc")] Clmulh,
+    #[cfg(feature = "zbc")] Clmulr,
+
+    // Zbs
+    #[cfg(feature = "zbs")] Bclr,
+    #[cfg(feature = "zbs")] Bclri,
+    #[cfg(feature = "zbs")] Bext,
+    #[cfg(feature = "zbs")] Bexti,
+    #[cfg(feature = "zbs")] Binv,
+    #[cfg(feature = "zbs")] Binvi,
+    #[cfg(feature = "zbs")] Bset,
+    #[cfg(feature = "zbs")] Bseti,
+}
+
+/// `imm[11:0]` of `rev8`, `shamt` field is `XLEN - 8`
+#[cfg(all(feature = "zbb", not(feature = "rv64")))]
+const REV8: u16 = 0x698;
+#[cfg(all(feature = "zbb", feature = "rv64"))]
+const REV8: u16 = 0x6b8;
+
+impl BOp {
+    /// Decode instructions in `OP-IMM`, `imm` is the unsigned `imm[11:0]`
+    #[cfg(any(feature = "zbb", feature = "zbs"))]
+    pub(crate) fn decode_itype(funct3: u8, imm: u16) -> Option<BOp> {
+        let funct7 = (imm >> 5) as u8;
+        // `shamt[5]` is the lowest bit of `funct7`
+        #[cfg(feature = "rv64")]
+        let funct7 = funct7 & !1;
+
+        Some(match (funct3, imm, funct7) {
+            #[cfg(feature = "zbb")]
+            (0x1, 0x600, _) => Clz,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x601, _) => Ctz,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x602, _) => Cpop,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x604, _) => SextB,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x605, _) => SextH,
+            #[cfg(feature = "zbb")]
+            (0x5, 0x287, _) => OrcB,
+            #[cfg(feature = "zbb")]
+            (0x5, REV8, _)  => Rev8,
+            #[cfg(feature = "zbb")]
+            (0x5, _, 0x30)  => Rori,
+            #[cfg(feature = "zbs")]
+            (0x1, _, 0x24)  => Bclri,
+            #[cfg(feature = "zbs")]
+            (0x5, _, 0x24)  => Bexti,
+            #[cfg(feature = "zbs")]
+            (0x1, _, 0x34)  => Binvi,
+            #[cfg(feature = "zbs")]
+            (0x1, _, 0x14)  => Bseti,
+            _ => return None,
+        })
+    }
+
+    /// Decode instructions in `OP`
+    pub(crate) fn decode_rtype(funct3: u8, funct7: u8, rs2: u8) -> Option<BOp> {
+        Some(match (funct7, funct3, rs2) {
+            #[cfg(feature = "zba")]
+            (0x10, 0x2, _) => Sh1add,
+            #[cfg(feature = "zba")]
+            (0x10, 0x4, _) => Sh2add,
+            #[cfg(feature = "zba")]
+            (0x10, 0x6, _) => Sh3add,
+            #[cfg(feature = "zbb")]
+            (0x20, 0x7, _) => Andn,
+            #[cfg(feature = "zbb")]
+            (0x20, 0x6, _) => Orn,
+            #[cfg(feature = "zbb")]
+            (0x20, 0x4, _) => Xnor,
+            #[cfg(feature = "zbb")]
+            (0x05, 0x6, _) => Max,
+            #[cfg(feature = "zbb")]
+            (0x05, 0x7, _) => Maxu,
+            #[cfg(feature = "zbb")]
+            (0x05, 0x4, _) => Min,
+            #[cfg(feature = "zbb")]
+            (0x05, 0x5, _) => Minu,
+            // `zext.h` is in `OP-32` on RV64
+            #[cfg(all(feature = "zbb", not(feature = "rv64")))]
+            (0x04, 0x4, 0) => ZextH,
+            #[cfg(feature = "zbb")]
+            (0x30, 0x1, _) => Rol,
+            #[cfg(feature = "zbb")]
+            (0x30, 0x5, _) => Ror,
+            #[cfg(feature = "zbc")]
+            (0x05, 0x1, _) => Clmul,
+            #[cfg(feature = "zbc")]
+            (0x05, 0x3, _) => Clmulh,
+            #[cfg(feature = "zbc")]
+            (0x05, 0x2, _) => Clmulr,
+            #[cfg(feature = "zbs")]
+            (0x24, 0x1, _) => Bclr,
+            #[cfg(feature = "zbs")]
+            (0x24, 0x5, _) => Bext,
+            #[cfg(feature = "zbs")]
+            (0x34, 0x1, _) => Binv,
+            #[cfg(feature = "zbs")]
+            (0x14, 0x1, _) => Bset,
+            _ => return None,
+        })
+    }
+
+    /// Decode RV64 word instructions in `OP-IMM-32`
+    #[cfg(all(feature = "rv64", any(feature = "zba", feature = "zbb")))]
+    pub(crate) fn decode_itype_w(funct3: u8, imm: u16) -> Option<BOp> {
+        Some(match (funct3, imm, imm >> 5) {
+            #[cfg(feature = "zbb")]
+            (0x1, 0x600, _) => Clzw,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x601, _) => Ctzw,
+            #[cfg(feature = "zbb")]
+            (0x1, 0x602, _) => Cpopw,
+            #[cfg(feature = "zbb")]
+            (0x5, _, 0x30)  => Roriw,
+            // `shamt` of `slli.uw` has 6 bits
+            #[cfg(feature = "zba")]
+            (0x1, _, 0x04 | 0x05) => SlliUw,
+            _ => return None,
+        })
+    }
+
+    /// Decode RV64 word instructions in `OP-32`
+    #[cfg(all(feature = "rv64", any(feature = "zba", feature = "zbb")))]
+    pub(crate) fn decode_rtype_w(funct3: u8, funct7: u8, rs2: u8) -> Option<BOp> {
+        Some(match (funct7, funct3, rs2) {
+            #[cfg(feature = "zba")]
+            (0x04, 0x0, _) => AddUw,
+            #[cfg(feature = "zba")]
+            (0x10, 0x2, _) => Sh1addUw,
+            #[cfg(feature = "zba")]
+            (0x10, 0x4, _) => Sh2addUw,
+            #[cfg(feature = "zba")]
+            (0x10, 0x6, _) => Sh3addUw,
+            #[cfg(feature = "zbb")]
+            (0x04, 0x4, 0) => ZextH,
+            #[cfg(feature = "zbb")]
+            (0x30, 0x1, _) => Rolw,
+            #[cfg(feature = "zbb")]
+            (0x30, 0x5, _) => Rorw,
+            _ => return None,
+        })
+    }
+
+    /// Instruction with `shamt` as second operand
+    pub fn is_itype(&self) -> bool {
+        #[cfg(feature = "zbb")]
+        if *self == Rori {
+            return true;
+        }
+        #[cfg(all(feature = "zbb", feature = "rv64"))]
+        if *self == Roriw {
+            return true;
+        }
+        #[cfg(all(feature = "zba", feature = "rv64"))]
+        if *self == SlliUw {
+            return true;
+        }
+        #[cfg(feature = "zbs")]
+        if matches!(self, Bclri | Bexti | Binvi | Bseti) {
+            return true;
+        }
+        false
+    }
+
+    /// Instruction with only `rs1` as operand
+    pub fn is_unary(&self) -> bool {
+        #[cfg(feature = "zbb")]
+        if matches!(self, Clz | Ctz | Cpop | SextB | SextH | ZextH | OrcB | Rev8) {
+            return true;
+        }
+        #[cfg(all(feature = "zbb", feature = "rv64"))]
+        if matches!(self, Clzw | Ctzw | Cpopw) {
+            return true;
+        }
+        false
+    }
+}
+
+impl std::fmt::Display for BOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op_str = match self {
+            #[cfg(feature = "zba")] Sh1add => "sh1add",
+            #[cfg(feature = "zba")] Sh2add => "sh2add",
+            #[cfg(feature = "zba")] Sh3add => "sh3add",
+            #[cfg(all(feature = "zba", feature = "rv64"))] AddUw    => "add.uw",
+            #[cfg(all(feature = "zba", feature = "rv64"))] Sh1addUw => "sh1add.uw",
+            #[cfg(all(feature = "zba", feature = "rv64"))] Sh2addUw => "sh2add.uw",
+            #[cfg(all(feature = "zba", feature = "rv64"))] Sh3addUw => "sh3add.uw",
+            #[cfg(all(feature = "zba", feature = "rv64"))] SlliUw   => "slli.uw",
+
+            #[cfg(feature = "zbb")] Andn  => "andn",
+            #[cfg(feature = "zbb")] Orn   => "orn",
+            #[cfg(feature = "zbb")] Xnor  => "xnor",
+            #[cfg(feature = "zbb")] Clz   => "clz",
+            #[cfg(feature = "zbb")] Ctz   => "ctz",
+            #[cfg(feature = "zbb")] Cpop  => "cpop",
+            #[cfg(feature = "zbb")] Max   => "max",
+            #[cfg(feature = "zbb")] Maxu  => "maxu",
+            #[cfg(feature = "zbb")] Min   => "min",
+            #[cfg(feature = "zbb")] Minu  => "minu",
+            #[cfg(feature = "zbb")] SextB => "sext.b",
+            #[cfg(feature = "zbb")] SextH => "sext.h",
+            #[cfg(feature = "zbb")] ZextH => "zext.h",
+            #[cfg(feature = "zbb")] Rol   => "rol",
+            #[cfg(feature = "zbb")] Ror   => "ror",
+            #[cfg(feature = "zbb")] Rori  => "rori",
+            #[cfg(feature = "zbb")] OrcB  => "orc.b",
+            #[cfg(feature = "zbb")] Rev8  => "rev8",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Clzw  => "clzw",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Ctzw  => "ctzw",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Cpopw => "cpopw",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Rolw  => "rolw",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Rorw  => "rorw",
+            #[cfg(all(feature = "zbb", feature = "rv64"))] Roriw => "roriw",
+
+            #[cfg(feature = "zbc")] Clmul  => "clmul",
+            #[cfg(feature = "zbc")] Clmulh => "clmulh",
+            #[cfg(feature = "zbc")] Clmulr => "clmulr",
+
+            #[cfg(feature = "zbs")] Bclr  => "bclr",
+            #[cfg(feature = "zbs")] Bclri => "bclri",
+            #[cfg(feature = "zbs")] Bext  => "bext",
+            #[cfg(feature = "zbs")] Bexti => "bexti",
+            #[cfg(feature = "zbs")] Binv  => "binv",
+            #[cfg(feature = "zbs")] Binvi => "binvi",
+            #[cfg(feature = "zbs")] Bset  => "bset",
+            #[cfg(feature = "zbs")] Bseti => "bseti",
+        };
+
+        f.pad(op_str)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::opcode::OpCode;
+
+use DOp::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DOp {
+    Fld, Fsd,
+    FmaddD, FmsubD, FnmsubD, FnmaddD,
+    FaddD, FsubD, FmulD, FdivD, FsqrtD,
+    FsgnjD, FsgnjnD, FsgnjxD,
+    FminD, FmaxD,
+    FcvtSD, FcvtDS,
+    FcvtWD, FcvtWuD, FcvtDW, FcvtDWu,
+    FeqD, FltD, FleD,
+    FclassD,
+    #[cfg(feature = "rv64")]
+    FcvtLD,
+    #[cfg(feature = "rv64")]
+    FcvtLuD,
+    #[cfg(feature = "rv64")]
+    FcvtDL,
+    #[cfg(feature = "rv64")]
+    FcvtDLu,
+    #[cfg(feature = "rv64")]
+    FmvXD,
+    #[cfg(feature = "rv64")]
+    FmvDX,
+}
+
+const FMT_D: u8 = 0b01;
+
+impl DOp {
+    pub(crate) fn decode_load(funct3: u8) -> Option<DOp> {
+        match funct3 {
+            0x3 => Some(Fld),
+            _   => None,
+        }
+    }
+
+    pub(crate) fn decode_store(funct3: u8) -> Option<DOp> {
+        match funct3 {
+            0x3 => Some(Fsd),
+            _   => None,
+        }
+    }
+
+    pub(crate) fn decode_fused(opcode: OpCode, fmt: u8) -> Option<DOp> {
+        if fmt != FMT_D {
+            return None;
+        }
+        Some(match opcode {
+            OpCode::Madd  => FmaddD,
+            OpCode::Msub  => FmsubD,
+            OpCode::Nmsub => FnmsubD,
+            OpCode::Nmadd => FnmaddD,
+            _             => return None,
+        })
+    }
+
+    pub(crate) fn decode_op(funct7: u8, funct3: u8, rs2: u8) -> Option<DOp> {
+        // `fcvt.s.d` is the only one in single format
+        if funct7 == 0x20 && rs2 == 1 {
+            return Some(FcvtSD);
+        }
+        if funct7 & 0b11 != FMT_D {
+            return None;
+        }
+        Some(match funct7 >> 2 {
+            0x00 => FaddD,
+            0x01 => FsubD,
+            0x02 => FmulD,
+            0x03 => FdivD,
+            0x0b if rs2 == 0 => FsqrtD,
+            0x04 => match funct3 {
+                0x0 => FsgnjD,
+                0x1 => FsgnjnD,
+                0x2 => FsgnjxD,
+                _   => return None,
+            },
+            0x05 => match funct3 {
+                0x0 => FminD,
+                0x1 => FmaxD,
+                _   => return None,
+            },
+            0x08 if rs2 == 0 => FcvtDS,
+            0x18 => match rs2 {
+                0x0 => FcvtWD,
+                0x1 => FcvtWuD,
+                #[cfg(feature = "rv64")]
+                0x2 => FcvtLD,
+                #[cfg(feature = "rv64")]
+                0x3 => FcvtLuD,
+                _   => return None,
+            },
+            0x1a => match rs2 {
+                0x0 => FcvtDW,
+                0x1 => FcvtDWu,
+                #[cfg(feature = "rv64")]
+                0x2 => FcvtDL,
+                #[cfg(feature = "rv64")]
+                0x3 => FcvtDLu,
+                _   => return None,
+            },
+            0x1c if rs2 == 0 && funct3 == 1 => FclassD,
+            #[cfg(feature = "rv64")]
+            0x1c if rs2 == 0 && funct3 == 0 => FmvXD,
+            #[cfg(feature = "rv64")]
+            0x1e if rs2 == 0 && funct3 == 0 => FmvDX,
+            0x14 => match funct3 {
+                0x2 => FeqD,
+                0x1 => FltD,
+                0x0 => FleD,
+                _   => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn is_load(&self) -> bool {
+        self == &Fld
+    }
+
+    pub fn is_store(&self) -> bool {
+        self == &Fsd
+    }
+
+    pub fn is_fused(&self) -> bool {
+        matches!(self, FmaddD | FmsubD | FnmsubD | FnmaddD)
+    }
+
+    /// Operation with rounding mode in `rm`
+    pub fn is_rounded(&self) -> bool {
+        #[cfg(feature = "rv64")]
+        if matches!(self, FcvtLD | FcvtLuD | FcvtDL | FcvtDLu) {
+            return true;
+        }
+        self.is_fused() || matches!(self,
+            FaddD | FsubD | FmulD | FdivD | FsqrtD | FcvtSD | FcvtDS | FcvtWD | FcvtWuD | FcvtDW | FcvtDWu)
+    }
+
+    /// Operation with only `rs1` as source
+    pub fn is_unary(&self) -> bool {
+        #[cfg(feature = "rv64")]
+        if matches!(self, FcvtLD | FcvtLuD | FcvtDL | FcvtDLu | FmvXD | FmvDX) {
+            return true;
+        }
+        matches!(self, FsqrtD | FcvtSD | FcvtDS | FcvtWD | FcvtWuD | FcvtDW | FcvtDWu | FclassD)
+    }
+
+    /// Which of (rd, rs1) are integer registers
+    pub fn int_regs(&self) -> (bool, bool) {
+        match self {
+            FcvtWD | FcvtWuD | FeqD | FltD | FleD | FclassD => (true, false),
+            FcvtDW | FcvtDWu => (false, true),
+            #[cfg(feature = "rv64")]
+            FcvtLD | FcvtLuD | FmvXD => (true, false),
+            #[cfg(feature = "rv64")]
+            FcvtDL | FcvtDLu | FmvDX => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+impl std::fmt::Display for DOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(
+            match self {
+                Fld     => "fld",
+                Fsd     => "fsd",
+                FmaddD  => "fmadd.d",
+                FmsubD  => "fmsub.d",
+                FnmsubD => "fnmsub.d",
+                FnmaddD => "fnmadd.d",
+                FaddD   => "fadd.d",
+                FsubD   => "fsub.d",
+                FmulD   => "fmul.d",
+                FdivD   => "fdiv.d",
+                FsqrtD  => "fsqrt.d",
+                FsgnjD  => "fsgnj.d",
+                FsgnjnD => "fsgnjn.d",
+                FsgnjxD => "fsgnjx.d",
+                FminD   => "fmin.d",
+                FmaxD   => "fmax.d",
+                FcvtSD  => "fcvt.s.d",
+                FcvtDS  => "fcvt.d.s",
+                FcvtWD  => "fcvt.w.d",
+                FcvtWuD => "fcvt.wu.d",
+                FcvtDW  => "fcvt.d.w",
+                FcvtDWu => "fcvt.d.wu",
+                FeqD    => "feq.d",
+                FltD    => "flt.d",
+                FleD    => "fle.d",
+                FclassD => "fclass.d",
+                #[cfg(feature = "rv64")]
+                FcvtLD  => "fcvt.l.d",
+                #[cfg(feature = "rv64")]
+                FcvtLuD => "fcvt.lu.d",
+                #[cfg(feature = "rv64")]
+                FcvtDL  => "fcvt.d.l",
+                #[cfg(feature = "rv64")]
+                FcvtDLu => "fcvt.d.lu",
+                #[cfg(feature = "rv64")]
+                FmvXD   => "fmv.x.d",
+                #[cfg(feature = "rv64")]
+                FmvDX   => "fmv.d.x",
+            }
+        )
+    }
+}
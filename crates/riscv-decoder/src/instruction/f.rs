@@ -0,0 +1,208 @@
+use crate::opcode::OpCode;
+
+use FOp::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FpInsData {
+    pub rd: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    pub rs3: u8,
+    /// Rounding mode, `funct3` of the instruction
+    pub rm: u8,
+    pub imm: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FOp {
+    Flw, Fsw,
+    FmaddS, FmsubS, FnmsubS, FnmaddS,
+    FaddS, FsubS, FmulS, FdivS, FsqrtS,
+    FsgnjS, FsgnjnS, FsgnjxS,
+    FminS, FmaxS,
+    FcvtWS, FcvtWuS, FcvtSW, FcvtSWu,
+    FmvXW, FmvWX,
+    FeqS, FltS, FleS,
+    FclassS,
+    #[cfg(feature = "rv64")]
+    FcvtLS,
+    #[cfg(feature = "rv64")]
+    FcvtLuS,
+    #[cfg(feature = "rv64")]
+    FcvtSL,
+    #[cfg(feature = "rv64")]
+    FcvtSLu,
+}
+
+const FMT_S: u8 = 0b00;
+
+impl FOp {
+    pub(crate) fn decode_load(funct3: u8) -> Option<FOp> {
+        match funct3 {
+            0x2 => Some(Flw),
+            _   => None,
+        }
+    }
+
+    pub(crate) fn decode_store(funct3: u8) -> Option<FOp> {
+        match funct3 {
+            0x2 => Some(Fsw),
+            _   => None,
+        }
+    }
+
+    pub(crate) fn decode_fused(opcode: OpCode, fmt: u8) -> Option<FOp> {
+        if fmt != FMT_S {
+            return None;
+        }
+        Some(match opcode {
+            OpCode::Madd  => FmaddS,
+            OpCode::Msub  => FmsubS,
+            OpCode::Nmsub => FnmsubS,
+            OpCode::Nmadd => FnmaddS,
+            _             => return None,
+        })
+    }
+
+    pub(crate) fn decode_op(funct7: u8, funct3: u8, rs2: u8) -> Option<FOp> {
+        if funct7 & 0b11 != FMT_S {
+            return None;
+        }
+        Some(match funct7 >> 2 {
+            0x00 => FaddS,
+            0x01 => FsubS,
+            0x02 => FmulS,
+            0x03 => FdivS,
+            0x0b if rs2 == 0 => FsqrtS,
+            0x04 => match funct3 {
+                0x0 => FsgnjS,
+                0x1 => FsgnjnS,
+                0x2 => FsgnjxS,
+                _   => return None,
+            },
+            0x05 => match funct3 {
+                0x0 => FminS,
+                0x1 => FmaxS,
+                _   => return None,
+            },
+            0x18 => match rs2 {
+                0x0 => FcvtWS,
+                0x1 => FcvtWuS,
+                #[cfg(feature = "rv64")]
+                0x2 => FcvtLS,
+                #[cfg(feature = "rv64")]
+                0x3 => FcvtLuS,
+                _   => return None,
+            },
+            0x1a => match rs2 {
+                0x0 => FcvtSW,
+                0x1 => FcvtSWu,
+                #[cfg(feature = "rv64")]
+                0x2 => FcvtSL,
+                #[cfg(feature = "rv64")]
+                0x3 => FcvtSLu,
+                _   => return None,
+            },
+            0x1c if rs2 == 0 => match funct3 {
+                0x0 => FmvXW,
+                0x1 => FclassS,
+                _   => return None,
+            },
+            0x1e if rs2 == 0 && funct3 == 0 => FmvWX,
+            0x14 => match funct3 {
+                0x2 => FeqS,
+                0x1 => FltS,
+                0x0 => FleS,
+                _   => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn is_load(&self) -> bool {
+        self == &Flw
+    }
+
+    pub fn is_store(&self) -> bool {
+        self == &Fsw
+    }
+
+    pub fn is_fused(&self) -> bool {
+        matches!(self, FmaddS | FmsubS | FnmsubS | FnmaddS)
+    }
+
+    /// Operation with rounding mode in `rm`
+    pub fn is_rounded(&self) -> bool {
+        #[cfg(feature = "rv64")]
+        if matches!(self, FcvtLS | FcvtLuS | FcvtSL | FcvtSLu) {
+            return true;
+        }
+        self.is_fused() || matches!(self,
+            FaddS | FsubS | FmulS | FdivS | FsqrtS | FcvtWS | FcvtWuS | FcvtSW | FcvtSWu)
+    }
+
+    /// Operation with only `rs1` as source
+    pub fn is_unary(&self) -> bool {
+        #[cfg(feature = "rv64")]
+        if matches!(self, FcvtLS | FcvtLuS | FcvtSL | FcvtSLu) {
+            return true;
+        }
+        matches!(self, FsqrtS | FcvtWS | FcvtWuS | FcvtSW | FcvtSWu | FmvXW | FmvWX | FclassS)
+    }
+
+    /// Which of (rd, rs1) are integer registers
+    pub fn int_regs(&self) -> (bool, bool) {
+        match self {
+            FcvtWS | FcvtWuS | FmvXW | FeqS | FltS | FleS | FclassS => (true, false),
+            FcvtSW | FcvtSWu | FmvWX => (false, true),
+            #[cfg(feature = "rv64")]
+            FcvtLS | FcvtLuS => (true, false),
+            #[cfg(feature = "rv64")]
+            FcvtSL | FcvtSLu => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+impl std::fmt::Display for FOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(
+            match self {
+                Flw     => "flw",
+                Fsw     => "fsw",
+                FmaddS  => "fmadd.s",
+                FmsubS  => "fmsub.s",
+                FnmsubS => "fnmsub.s",
+                FnmaddS => "fnmadd.s",
+                FaddS   => "fadd.s",
+                FsubS   => "fsub.s",
+                FmulS   => "fmul.s",
+                FdivS   => "fdiv.s",
+                FsqrtS  => "fsqrt.s",
+                FsgnjS  => "fsgnj.s",
+                FsgnjnS => "fsgnjn.s",
+                FsgnjxS => "fsgnjx.s",
+                FminS   => "fmin.s",
+                FmaxS   => "fmax.s",
+                FcvtWS  => "fcvt.w.s",
+                FcvtWuS => "fcvt.wu.s",
+                FcvtSW  => "fcvt.s.w",
+                FcvtSWu => "fcvt.s.wu",
+                FmvXW   => "fmv.x.w",
+                FmvWX   => "fmv.w.x",
+                FeqS    => "feq.s",
+                FltS    => "flt.s",
+                FleS    => "fle.s",
+                FclassS => "fclass.s",
+                #[cfg(feature = "rv64")]
+                FcvtLS  => "fcvt.l.s",
+                #[cfg(feature = "rv64")]
+                FcvtLuS => "fcvt.lu.s",
+                #[cfg(feature = "rv64")]
+                FcvtSL  => "fcvt.s.l",
+                #[cfg(feature = "rv64")]
+                FcvtSLu => "fcvt.s.lu",
+            }
+        )
+    }
+}
@@ -4,6 +4,16 @@ use MOp::*;
 pub enum MOp {
     Mul, Mulh, Mulhu, Mulhsu,
     Div, Divu, Rem, Remu,
+    #[cfg(feature = "rv64")]
+    Mulw,
+    #[cfg(feature = "rv64")]
+    Divw,
+    #[cfg(feature = "rv64")]
+    Divuw,
+    #[cfg(feature = "rv64")]
+    Remw,
+    #[cfg(feature = "rv64")]
+    Remuw,
 }
 
 impl MOp {
@@ -23,6 +33,22 @@ impl MOp {
             _ => return None,
         })
     }
+
+    /// Decode RV64M word operations in `OP-32`
+    #[cfg(feature = "rv64")]
+    pub(crate) fn decode_w(funct3: u8, funct7: u8) -> Option<MOp> {
+        Some(match funct7 {
+            0x01 => match funct3 {
+                0x0 => Mulw,
+                0x4 => Divw,
+                0x5 => Divuw,
+                0x6 => Remw,
+                0x7 => Remuw,
+                _   => return None
+            }
+            _ => return None,
+        })
+    }
 }
 
 impl std::fmt::Display for MOp {
@@ -37,6 +63,16 @@ impl std::fmt::Display for MOp {
                 Divu   => "divu",
                 Rem    => "rem",
                 Remu   => "remu",
+                #[cfg(feature = "rv64")]
+                Mulw   => "mulw",
+                #[cfg(feature = "rv64")]
+                Divw   => "divw",
+                #[cfg(feature = "rv64")]
+                Divuw  => "divuw",
+                #[cfg(feature = "rv64")]
+                Remw   => "remw",
+                #[cfg(feature = "rv64")]
+                Remuw  => "remuw",
             }
         )
     }
@@ -3,6 +3,7 @@ use PrivilegeOp::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrivilegeOp {
     Mret, 
+    Wfi,
     #[cfg(feature = "s")] Sret,
     #[cfg(feature = "s")] SfenceVma(u32),
 }
@@ -13,6 +14,7 @@ impl PrivilegeOp {
         Some(match raw {
             #[cfg(feature = "s")] 0x10200073 => Sret,
             0x30200073 => Mret,
+            0x10500073 => Wfi,
             _          => match funct3 {
                 0x0 => match funct7 {
                     #[cfg(feature = "s")] 0x09 if rd == 0 => {
@@ -36,6 +38,7 @@ impl std::fmt::Display for PrivilegeOp {
         f.pad( 
             match self {
                 Mret         => "mret",
+                Wfi          => "wfi",
                 #[cfg(feature = "s")] Sret         => "sret",
                 #[cfg(feature = "s")] SfenceVma(_) => "sfence.vma",
             }
@@ -32,10 +32,14 @@ pub enum Rv32iOp {
 
 impl Rv32iOp {
     pub(crate) fn decode_itype(opcode: OpCode, funct3: u8, funct7: u8) -> Option<Rv32iOp> {
+        // `shamt[5]` is the lowest bit of `funct7`
+        #[cfg(feature = "rv64")]
+        let funct7 = funct7 & !1;
+
         Some(match opcode {
             OpCode::ItypeAr => match funct3 {
                 0x0 => Addi,
-                0x1 => Slli,
+                0x1 if funct7 == 0x00 => Slli,
                 0x2 => Slti,
                 0x3 => Sltiu,
                 0x4 => Xori,
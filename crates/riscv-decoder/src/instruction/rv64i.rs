@@ -0,0 +1,97 @@
+use crate::opcode::OpCode;
+
+use Rv64iOp::*;
+
+/// Instructions added by RV64I. `*w` operate on the low 32 bits and sign-extend the result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rv64iOp {
+    // Itype Ar 32
+    Addiw, Slliw, Srliw, Sraiw,
+    // Itype Load
+    Lwu, Ld,
+    // Rtype 32
+    Addw, Subw, Sllw, Srlw, Sraw,
+    // Stype
+    Sd,
+}
+
+impl Rv64iOp {
+    pub(crate) fn decode_itype(opcode: OpCode, funct3: u8, funct7: u8) -> Option<Rv64iOp> {
+        Some(match opcode {
+            OpCode::ItypeAr32 => match funct3 {
+                0x0 => Addiw,
+                0x1 if funct7 == 0x00 => Slliw,
+                0x5 => match funct7 {
+                    0x00 => Srliw,
+                    0x20 => Sraiw,
+                    _    => return None,
+                },
+                _   => return None,
+            },
+            OpCode::ItypeLoad => match funct3 {
+                0x3 => Ld,
+                0x6 => Lwu,
+                _   => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn decode_rtype(funct3: u8, funct7: u8) -> Option<Rv64iOp> {
+        Some(match funct7 {
+            0x00 => match funct3 {
+                0x0 => Addw,
+                0x1 => Sllw,
+                0x5 => Srlw,
+                _   => return None,
+            },
+            0x20 => match funct3 {
+                0x0 => Subw,
+                0x5 => Sraw,
+                _   => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn decode_stype(funct3: u8) -> Option<Rv64iOp> {
+        match funct3 {
+            0x3 => Some(Sd),
+            _   => None,
+        }
+    }
+
+    pub fn is_itype_ar(&self) -> bool {
+        matches!(self, Addiw | Slliw | Srliw | Sraiw)
+    }
+
+    pub fn is_itype_load(&self) -> bool {
+        matches!(self, Lwu | Ld)
+    }
+
+    pub fn is_rtype(&self) -> bool {
+        matches!(self, Addw | Subw | Sllw | Srlw | Sraw)
+    }
+
+    pub fn is_stype(&self) -> bool {
+        self == &Sd
+    }
+}
+
+impl std::fmt::Display for Rv64iOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op_str = match self {
+            Addiw => "addiw", Slliw => "slliw",
+            Srliw => "srliw", Sraiw => "sraiw",
+
+            Lwu => "lwu", Ld => "ld",
+
+            Addw => "addw", Subw => "subw", Sllw => "sllw",
+            Srlw => "srlw", Sraw => "sraw",
+
+            Sd => "sd",
+        };
+
+        f.pad(op_str)
+    }
+}
@@ -18,8 +18,26 @@ pub enum OpCode {
     UtypeLui = 0x37,  
     UtypeAuipc = 0x17,
     System = 0x73,
+    #[cfg(feature = "rv64")]
+    ItypeAr32 = 0x1b,
+    #[cfg(feature = "rv64")]
+    Rtype32 = 0x3b,
     #[cfg(feature = "a")]
     Amo = 0x2f, // Atomic Memory Operation
+    #[cfg(feature = "f")]
+    LoadFp = 0x07,
+    #[cfg(feature = "f")]
+    StoreFp = 0x27,
+    #[cfg(feature = "f")]
+    Madd = 0x43,
+    #[cfg(feature = "f")]
+    Msub = 0x47,
+    #[cfg(feature = "f")]
+    Nmsub = 0x4b,
+    #[cfg(feature = "f")]
+    Nmadd = 0x4f,
+    #[cfg(feature = "f")]
+    OpFp = 0x53,
 }
 
 impl TryFrom<u8> for OpCode {
@@ -40,8 +58,26 @@ impl TryFrom<u8> for OpCode {
             0x37 => UtypeLui,
             0x17 => UtypeAuipc,     
             0x73 => System, 
+            #[cfg(feature = "rv64")]
+            0x1b => ItypeAr32,
+            #[cfg(feature = "rv64")]
+            0x3b => Rtype32,
             #[cfg(feature = "a")]
             0x2f => Amo, 
+            #[cfg(feature = "f")]
+            0x07 => LoadFp,
+            #[cfg(feature = "f")]
+            0x27 => StoreFp,
+            #[cfg(feature = "f")]
+            0x43 => Madd,
+            #[cfg(feature = "f")]
+            0x47 => Msub,
+            #[cfg(feature = "f")]
+            0x4b => Nmsub,
+            #[cfg(feature = "f")]
+            0x4f => Nmadd,
+            #[cfg(feature = "f")]
+            0x53 => OpFp,
             _    => return Err(DecodeError::UnknownOpcode(value)),
         })
     }
@@ -68,8 +104,26 @@ impl std::fmt::Display for OpCode {
             UtypeLui    => "U-type: lui",
             UtypeAuipc  => "U-type: auipc",   
             System      => "System",  
+            #[cfg(feature = "rv64")]
+            ItypeAr32   => "I-type: 32 bits",
+            #[cfg(feature = "rv64")]
+            Rtype32     => "R-type: 32 bits",
             #[cfg(feature = "a")]
             Amo         => "AMO",
+            #[cfg(feature = "f")]
+            LoadFp      => "I-type: load fp",
+            #[cfg(feature = "f")]
+            StoreFp     => "S-type: store fp",
+            #[cfg(feature = "f")]
+            Madd        => "R4-type: madd",
+            #[cfg(feature = "f")]
+            Msub        => "R4-type: msub",
+            #[cfg(feature = "f")]
+            Nmsub       => "R4-type: nmsub",
+            #[cfg(feature = "f")]
+            Nmadd       => "R4-type: nmadd",
+            #[cfg(feature = "f")]
+            OpFp        => "R-type: fp",
         };
         
         f.pad(&format!("{:#02x}({})", opcode, op_str))
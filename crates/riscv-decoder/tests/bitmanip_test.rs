@@ -0,0 +1,145 @@
+#![cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+
+use riscv_decoder::decoder::decode;
+use riscv_decoder::instruction::{BOp, Instruction, InstructionData};
+
+/// `rd` is a0 and `rs1` is a1 for all cases
+fn build_b_data(op: BOp, rs2: u8, imm: i32) -> Instruction {
+    Instruction::B(op, InstructionData { rd: 10, rs1: 11, rs2, imm })
+}
+
+#[test]
+#[cfg(feature = "zba")]
+fn test_zba() {
+    // sh1add a0, a1, a2
+    assert_eq!(decode(0x20c5a533), Ok(build_b_data(BOp::Sh1add, 12, 0)));
+    // sh2add a0, a1, a2
+    assert_eq!(decode(0x20c5c533), Ok(build_b_data(BOp::Sh2add, 12, 0)));
+    // sh3add a0, a1, a2
+    assert_eq!(decode(0x20c5e533), Ok(build_b_data(BOp::Sh3add, 12, 0)));
+}
+
+#[test]
+#[cfg(feature = "zbb")]
+fn test_zbb_rtype() {
+    let build = |op| Ok(build_b_data(op, 12, 0));
+
+    assert_eq!(decode(0x40c5f533), build(BOp::Andn));
+    assert_eq!(decode(0x40c5e533), build(BOp::Orn));
+    assert_eq!(decode(0x40c5c533), build(BOp::Xnor));
+    assert_eq!(decode(0x0ac5e533), build(BOp::Max));
+    assert_eq!(decode(0x0ac5f533), build(BOp::Maxu));
+    assert_eq!(decode(0x0ac5c533), build(BOp::Min));
+    assert_eq!(decode(0x0ac5d533), build(BOp::Minu));
+    assert_eq!(decode(0x60c59533), build(BOp::Rol));
+    assert_eq!(decode(0x60c5d533), build(BOp::Ror));
+}
+
+#[test]
+#[cfg(feature = "zbb")]
+fn test_zbb_unary() {
+    // clz a0, a1
+    assert_eq!(decode(0x60059513), Ok(build_b_data(BOp::Clz, 0, 0)));
+    // ctz a0, a1
+    assert_eq!(decode(0x60159513), Ok(build_b_data(BOp::Ctz, 1, 1)));
+    // cpop a0, a1
+    assert_eq!(decode(0x60259513), Ok(build_b_data(BOp::Cpop, 2, 2)));
+    // sext.b a0, a1
+    assert_eq!(decode(0x60459513), Ok(build_b_data(BOp::SextB, 4, 4)));
+    // sext.h a0, a1
+    assert_eq!(decode(0x60559513), Ok(build_b_data(BOp::SextH, 5, 5)));
+    // orc.b a0, a1
+    assert_eq!(decode(0x2875d513), Ok(build_b_data(BOp::OrcB, 7, 7)));
+    // rori a0, a1, 31
+    assert_eq!(decode(0x61f5d513), Ok(build_b_data(BOp::Rori, 31, 31)));
+    // Not `slli` with non-zero `funct7`
+    assert!(decode(0x60359513).is_err());
+}
+
+#[test]
+#[cfg(all(feature = "zbb", not(feature = "rv64")))]
+fn test_zbb_rv32_only() {
+    // zext.h a0, a1
+    assert_eq!(decode(0x0805c533), Ok(build_b_data(BOp::ZextH, 0, 0)));
+    // rev8 a0, a1
+    assert_eq!(decode(0x6985d513), Ok(build_b_data(BOp::Rev8, 24, 24)));
+    // `shamt[5]` is reserved on RV32
+    assert!(decode(0x63f5d513).is_err());
+}
+
+#[test]
+#[cfg(feature = "zbc")]
+fn test_zbc() {
+    // clmul a0, a1, a2
+    assert_eq!(decode(0x0ac59533), Ok(build_b_data(BOp::Clmul, 12, 0)));
+    // clmulh a0, a1, a2
+    assert_eq!(decode(0x0ac5b533), Ok(build_b_data(BOp::Clmulh, 12, 0)));
+    // clmulr a0, a1, a2
+    assert_eq!(decode(0x0ac5a533), Ok(build_b_data(BOp::Clmulr, 12, 0)));
+}
+
+#[test]
+#[cfg(feature = "zbs")]
+fn test_zbs() {
+    // bclr a0, a1, a2
+    assert_eq!(decode(0x48c59533), Ok(build_b_data(BOp::Bclr, 12, 0)));
+    // bclri a0, a1, 3
+    assert_eq!(decode(0x48359513), Ok(build_b_data(BOp::Bclri, 3, 3)));
+    // bext a0, a1, a2
+    assert_eq!(decode(0x48c5d533), Ok(build_b_data(BOp::Bext, 12, 0)));
+    // bexti a0, a1, 3
+    assert_eq!(decode(0x4835d513), Ok(build_b_data(BOp::Bexti, 3, 3)));
+    // binv a0, a1, a2
+    assert_eq!(decode(0x68c59533), Ok(build_b_data(BOp::Binv, 12, 0)));
+    // binvi a0, a1, 3
+    assert_eq!(decode(0x68359513), Ok(build_b_data(BOp::Binvi, 3, 3)));
+    // bset a0, a1, a2
+    assert_eq!(decode(0x28c59533), Ok(build_b_data(BOp::Bset, 12, 0)));
+    // bseti a0, a1, 31
+    assert_eq!(decode(0x29f59513), Ok(build_b_data(BOp::Bseti, 31, 31)));
+}
+
+#[test]
+#[cfg(all(feature = "zba", feature = "rv64"))]
+fn test_zba_word() {
+    // add.uw a0, a1, a2
+    assert_eq!(decode(0x08c5853b), Ok(build_b_data(BOp::AddUw, 12, 0)));
+    // sh1add.uw a0, a1, a2
+    assert_eq!(decode(0x20c5a53b), Ok(build_b_data(BOp::Sh1addUw, 12, 0)));
+    // sh2add.uw a0, a1, a2
+    assert_eq!(decode(0x20c5c53b), Ok(build_b_data(BOp::Sh2addUw, 12, 0)));
+    // sh3add.uw a0, a1, a2
+    assert_eq!(decode(0x20c5e53b), Ok(build_b_data(BOp::Sh3addUw, 12, 0)));
+    // slli.uw a0, a1, 40
+    assert_eq!(decode(0x0a85951b), Ok(build_b_data(BOp::SlliUw, 8, 40)));
+}
+
+#[test]
+#[cfg(all(feature = "zbb", feature = "rv64"))]
+fn test_zbb_word() {
+    // clzw a0, a1
+    assert_eq!(decode(0x6005951b), Ok(build_b_data(BOp::Clzw, 0, 0)));
+    // ctzw a0, a1
+    assert_eq!(decode(0x6015951b), Ok(build_b_data(BOp::Ctzw, 1, 1)));
+    // cpopw a0, a1
+    assert_eq!(decode(0x6025951b), Ok(build_b_data(BOp::Cpopw, 2, 2)));
+    // rolw a0, a1, a2
+    assert_eq!(decode(0x60c5953b), Ok(build_b_data(BOp::Rolw, 12, 0)));
+    // rorw a0, a1, a2
+    assert_eq!(decode(0x60c5d53b), Ok(build_b_data(BOp::Rorw, 12, 0)));
+    // roriw a0, a1, 31
+    assert_eq!(decode(0x61f5d51b), Ok(build_b_data(BOp::Roriw, 31, 31)));
+    // zext.h a0, a1
+    assert_eq!(decode(0x0805c53b), Ok(build_b_data(BOp::ZextH, 0, 0)));
+    // rev8 a0, a1
+    assert_eq!(decode(0x6b85d513), Ok(build_b_data(BOp::Rev8, 24, 56)));
+    // rori a0, a1, 63
+    assert_eq!(decode(0x63f5d513), Ok(build_b_data(BOp::Rori, 31, 63)));
+}
+
+#[test]
+#[cfg(all(feature = "zbs", feature = "rv64"))]
+fn test_zbs_rv64() {
+    // bseti a0, a1, 63
+    assert_eq!(decode(0x2bf59513), Ok(build_b_data(BOp::Bseti, 31, 63)));
+}
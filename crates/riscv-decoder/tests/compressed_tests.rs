@@ -81,9 +81,56 @@ fn illegal_tests() {
     let ill2 = 0x6001;
     assert!(decompress(ill2).is_err());
 
-    let ill3 = 0xA000;
-    assert!(decompress(ill3).is_err());
+    // c.fsd f8, 0(x8) with D extension
+    #[cfg(not(feature = "d"))]
+    {
+        let ill3 = 0xA000;
+        assert!(decompress(ill3).is_err());
+    }
+
+    let ill4 = 0x8000; // reserved in quadrant 0
+    assert!(decompress(ill4).is_err());
 
     let not_compress = 0x0073;
     assert!(decompress(not_compress).is_err());
-}
\ No newline at end of file
+}
+
+#[cfg(all(feature = "f", not(feature = "rv64")))]
+#[test]
+fn float_tests() {
+    use riscv_decoder::prelude::{FOp, FpInsData};
+
+    let build = |op, rd, rs1, rs2, imm, raw| Instruction::F(op, FpInsData { rd, rs1, rs2, rs3: 0, rm: 0x2, imm }, raw);
+
+    let ins1 = 0x6140; // c.flw f8, 4(x10)
+    assert_eq!(decompress(ins1).unwrap(), build(FOp::Flw, 8, 10, 8, 4, 0x6140)); // rs2 don't care
+
+    let ins2 = 0xfde4; // c.fsw f9, 124(x11)
+    assert_eq!(decompress(ins2).unwrap(), build(FOp::Fsw, 9, 11, 9, 124, 0xfde4)); // rd don't care
+
+    let ins3 = 0x707e; // c.flwsp f0, 252(x2)
+    assert_eq!(decompress(ins3).unwrap(), build(FOp::Flw, 0, 2, 0, 252, 0x707e));
+
+    let ins4 = 0xe27e; // c.fswsp f31, 4(x2)
+    assert_eq!(decompress(ins4).unwrap(), build(FOp::Fsw, 0, 2, 31, 4, 0xe27e));
+}
+
+#[cfg(feature = "d")]
+#[test]
+fn double_tests() {
+    use riscv_decoder::prelude::{DOp, FpInsData};
+
+    let build = |op, rd, rs1, rs2, imm, raw| Instruction::D(op, FpInsData { rd, rs1, rs2, rs3: 0, rm: 0x3, imm }, raw);
+
+    let ins1 = 0x2588; // c.fld f10, 8(x11)
+    assert_eq!(decompress(ins1).unwrap(), build(DOp::Fld, 10, 11, 10, 8, 0x2588));
+
+    let ins2 = 0xbd7c; // c.fsd f15, 248(x10)
+    assert_eq!(decompress(ins2).unwrap(), build(DOp::Fsd, 15, 10, 15, 248, 0xbd7c));
+
+    let ins3 = 0x30fe; // c.fldsp f1, 504(x2)
+    assert_eq!(decompress(ins3).unwrap(), build(DOp::Fld, 1, 2, 0, 504, 0x30fe));
+
+    let ins4 = 0xa67e; // c.fsdsp f31, 264(x2)
+    assert_eq!(decompress(ins4).unwrap(), build(DOp::Fsd, 0, 2, 31, 264, 0xa67e));
+}
@@ -0,0 +1,166 @@
+#![cfg(feature = "rv64")]
+
+use riscv_decoder::decoder::decode;
+use riscv_decoder::instruction::{Instruction, InstructionData, Rv32iOp, Rv64iOp};
+#[cfg(feature = "m")]
+use riscv_decoder::instruction::MOp;
+#[cfg(feature = "a")]
+use riscv_decoder::instruction::{AOp, AmoInsData};
+#[cfg(feature = "f")]
+use riscv_decoder::instruction::{FOp, FpInsData};
+#[cfg(feature = "d")]
+use riscv_decoder::instruction::DOp;
+
+fn build_rv64_data(op: Rv64iOp, rd: u8, rs1: u8, rs2: u8, imm: i32) -> Instruction {
+    let data = InstructionData { rd, rs1, rs2, imm };
+    Instruction::Rv64(op, data)
+}
+
+#[test]
+fn test_itype_word() {
+    // addiw a0, a1, -1
+    assert_eq!(decode(0xfff5851b), Ok(build_rv64_data(Rv64iOp::Addiw, 10, 11, 31, -1)));
+    // slliw t0, t1, 31
+    assert_eq!(decode(0x01f3129b), Ok(build_rv64_data(Rv64iOp::Slliw, 5, 6, 31, 31)));
+    // srliw t0, t1, 3
+    assert_eq!(decode(0x0033529b), Ok(build_rv64_data(Rv64iOp::Srliw, 5, 6, 3, 3)));
+    // sraiw t0, t1, 3
+    assert_eq!(decode(0x4033529b), Ok(build_rv64_data(Rv64iOp::Sraiw, 5, 6, 3, 0x403)));
+    // `shamt[5]` is reserved for word shift
+    assert!(decode(0x0203129b).is_err());
+}
+
+#[test]
+fn test_shift_64() {
+    // slli a0, a1, 63
+    let expect1 = Instruction::Base(Rv32iOp::Slli, InstructionData { rd: 10, rs1: 11, rs2: 31, imm: 63 });
+    assert_eq!(decode(0x03f59513), Ok(expect1));
+    // srai a0, a1, 33
+    let expect2 = Instruction::Base(Rv32iOp::Srai, InstructionData { rd: 10, rs1: 11, rs2: 1, imm: 0x421 });
+    assert_eq!(decode(0x4215d513), Ok(expect2));
+}
+
+#[test]
+fn test_load_store() {
+    // lwu a0, 8(sp)
+    assert_eq!(decode(0x00816503), Ok(build_rv64_data(Rv64iOp::Lwu, 10, 2, 8, 8)));
+    // ld s0, -8(a1)
+    assert_eq!(decode(0xff85b403), Ok(build_rv64_data(Rv64iOp::Ld, 8, 11, 24, -8)));
+    // sd s1, 16(sp)
+    assert_eq!(decode(0x00913823), Ok(build_rv64_data(Rv64iOp::Sd, 16, 2, 9, 16)));
+
+    if let Ok(Instruction::Rv64(op, _)) = decode(0x00913823) {
+        assert!(op.is_stype());
+    } else {
+        unreachable!("Should be Rv64 of Instruction");
+    }
+}
+
+#[test]
+fn test_rtype_word() {
+    // addw a2, a3, a4
+    assert_eq!(decode(0x00e6863b), Ok(build_rv64_data(Rv64iOp::Addw, 12, 13, 14, 0)));
+    // subw a2, a3, a4
+    assert_eq!(decode(0x40e6863b), Ok(build_rv64_data(Rv64iOp::Subw, 12, 13, 14, 0)));
+    // sllw a2, a3, a4
+    assert_eq!(decode(0x00e6963b), Ok(build_rv64_data(Rv64iOp::Sllw, 12, 13, 14, 0)));
+    // srlw a2, a3, a4
+    assert_eq!(decode(0x00e6d63b), Ok(build_rv64_data(Rv64iOp::Srlw, 12, 13, 14, 0)));
+    // sraw a2, a3, a4
+    assert_eq!(decode(0x40e6d63b), Ok(build_rv64_data(Rv64iOp::Sraw, 12, 13, 14, 0)));
+}
+
+#[test]
+#[cfg(feature = "m")]
+fn test_m_word() {
+    let build = |op| Ok(Instruction::M(op, InstructionData { rd: 10, rs1: 11, rs2: 12, imm: 0 }));
+
+    assert_eq!(decode(0x02c5853b), build(MOp::Mulw));
+    assert_eq!(decode(0x02c5c53b), build(MOp::Divw));
+    assert_eq!(decode(0x02c5d53b), build(MOp::Divuw));
+    assert_eq!(decode(0x02c5e53b), build(MOp::Remw));
+    assert_eq!(decode(0x02c5f53b), build(MOp::Remuw));
+}
+
+#[test]
+#[cfg(feature = "a")]
+fn test_a_double() {
+    // lr.d a0, (a1)
+    let expect1 = Instruction::A(AOp::LrD, AmoInsData { rd: 10, rs1: 11, rs2: 0, rl: 0, aq: 0 });
+    assert_eq!(decode(0x1005b52f), Ok(expect1));
+    // sc.d a0, a2, (a1)
+    let expect2 = Instruction::A(AOp::ScD, AmoInsData { rd: 10, rs1: 11, rs2: 12, rl: 0, aq: 0 });
+    assert_eq!(decode(0x18c5b52f), Ok(expect2));
+    // amomaxu.d.aqrl a0, a2, (a1)
+    let expect3 = Instruction::A(AOp::AmoMaxuD, AmoInsData { rd: 10, rs1: 11, rs2: 12, rl: 1, aq: 1 });
+    assert_eq!(decode(0xe6c5b52f), Ok(expect3));
+
+    if let Ok(Instruction::A(op, _)) = decode(0x00c5b52f) {
+        assert_eq!(op, AOp::AmoAddD);
+        assert!(op.is_double());
+    } else {
+        unreachable!("Should be A of Instruction");
+    }
+}
+
+#[test]
+#[cfg(feature = "c")]
+fn test_compressed() {
+    use riscv_decoder::decoder::decompress;
+
+    // c.ld a0, 8(a1)
+    assert_eq!(decompress(0x6588), Ok(build_rv64_data(Rv64iOp::Ld, 10, 11, 10, 8))); // rs2 don't care
+    // c.sd a5, 248(a0)
+    assert_eq!(decompress(0xfd7c), Ok(build_rv64_data(Rv64iOp::Sd, 15, 10, 15, 248))); // rd don't care
+    // c.ldsp ra, 504(sp)
+    assert_eq!(decompress(0x70fe), Ok(build_rv64_data(Rv64iOp::Ld, 1, 2, 0, 504)));
+    // c.sdsp s0, 8(sp)
+    assert_eq!(decompress(0xe422), Ok(build_rv64_data(Rv64iOp::Sd, 0, 2, 8, 8)));
+    // c.addiw a0, -1
+    assert_eq!(decompress(0x357d), Ok(build_rv64_data(Rv64iOp::Addiw, 10, 10, 0, -1)));
+    // c.subw a0, a1
+    assert_eq!(decompress(0x9d0d), Ok(build_rv64_data(Rv64iOp::Subw, 10, 10, 11, 0)));
+    // c.addw s1, a5
+    assert_eq!(decompress(0x9cbd), Ok(build_rv64_data(Rv64iOp::Addw, 9, 9, 15, 0)));
+    // c.slli a0, 63
+    let expect = Instruction::Base(Rv32iOp::Slli, InstructionData { rd: 10, rs1: 10, rs2: 0, imm: 63 });
+    assert_eq!(decompress(0x157e), Ok(expect));
+
+    // c.addiw and c.ldsp with `rd` x0 are reserved
+    assert!(decompress(0x207d).is_err());
+    assert!(decompress(0x607e).is_err());
+}
+
+#[cfg(feature = "f")]
+#[test]
+fn test_fp_long() {
+    let build = |op, rs2, rm, raw| Instruction::F(op, FpInsData { rd: 10, rs1: 10, rs2, rs3: 0, rm, imm: 0 }, raw);
+
+    // fcvt.l.s a0, fa0, rtz
+    assert_eq!(decode(0xc0251553), Ok(build(FOp::FcvtLS, 2, 1, 0xc0251553)));
+    // fcvt.lu.s a0, fa0
+    assert_eq!(decode(0xc0357553), Ok(build(FOp::FcvtLuS, 3, 7, 0xc0357553)));
+    // fcvt.s.l fa0, a0
+    assert_eq!(decode(0xd0257553), Ok(build(FOp::FcvtSL, 2, 7, 0xd0257553)));
+    // fcvt.s.lu fa0, a0
+    assert_eq!(decode(0xd0357553), Ok(build(FOp::FcvtSLu, 3, 7, 0xd0357553)));
+}
+
+#[cfg(feature = "d")]
+#[test]
+fn test_fp_double_long() {
+    let build = |op, rs2, rm, raw| Instruction::D(op, FpInsData { rd: 10, rs1: 10, rs2, rs3: 0, rm, imm: 0 }, raw);
+
+    // fcvt.l.d a0, fa0, rtz
+    assert_eq!(decode(0xc2251553), Ok(build(DOp::FcvtLD, 2, 1, 0xc2251553)));
+    // fcvt.lu.d a0, fa0
+    assert_eq!(decode(0xc2357553), Ok(build(DOp::FcvtLuD, 3, 7, 0xc2357553)));
+    // fcvt.d.l fa0, a0
+    assert_eq!(decode(0xd2257553), Ok(build(DOp::FcvtDL, 2, 7, 0xd2257553)));
+    // fcvt.d.lu fa0, a0
+    assert_eq!(decode(0xd2357553), Ok(build(DOp::FcvtDLu, 3, 7, 0xd2357553)));
+    // fmv.x.d a0, fa0
+    assert_eq!(decode(0xe2050553), Ok(build(DOp::FmvXD, 0, 0, 0xe2050553)));
+    // fmv.d.x fa0, a0
+    assert_eq!(decode(0xf2050553), Ok(build(DOp::FmvDX, 0, 0, 0xf2050553)));
+}
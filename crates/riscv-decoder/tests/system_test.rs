@@ -74,4 +74,8 @@ fn  test_privileged() {
     assert!(matches!(decode(ins1), Ok(Instruction::Privileged(PrivilegeOp::Sret, _))));
     assert!(matches!(decode(ins2), Ok(Instruction::Privileged(PrivilegeOp::Mret, _))));
     assert_eq!(decode(ins3), Ok(expect3));
+
+    // wfi
+    let ins4 = 0x10500073;
+    assert!(matches!(decode(ins4), Ok(Instruction::Privileged(PrivilegeOp::Wfi, _))));
 }
\ No newline at end of file
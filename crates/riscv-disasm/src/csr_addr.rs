@@ -3,6 +3,9 @@ pub use CsrAddr::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CsrAddr {
     Ustatus,
+    Fflags,
+    Frm,
+    Fcsr,
 
     Sstatus,
     Sie,
@@ -13,6 +16,14 @@ pub enum CsrAddr {
     Stval,
     Sip,
     Satp,
+    Scounteren,
+
+    Cycle,
+    Time,
+    Instret,
+    CycleH,
+    TimeH,
+    InstretH,
 
     Mstatus,
     Medeleg,
@@ -24,6 +35,12 @@ pub enum CsrAddr {
     Mcause,
     Mtval,
     Mip,
+    Mcounteren,
+    Mcountinhibit,
+    Mcycle,
+    Minstret,
+    McycleH,
+    MinstretH,
     Pmpcfg0,
     Pmpaddr0,
 
@@ -36,6 +53,9 @@ impl std::fmt::Display for CsrAddr {
         f.pad( 
             match self {
                 Ustatus   => "ustatus",
+                Fflags    => "fflags",
+                Frm       => "frm",
+                Fcsr      => "fcsr",
 
                 Sstatus   => "sstatus",
                 Sie       => "sie",
@@ -46,6 +66,14 @@ impl std::fmt::Display for CsrAddr {
                 Stval     => "stval",
                 Sip       => "sip",
                 Satp      => "satp",
+                Scounteren => "scounteren",
+
+                Cycle     => "cycle",
+                Time      => "time",
+                Instret   => "instret",
+                CycleH    => "cycleh",
+                TimeH     => "timeh",
+                InstretH  => "instreth",
 
                 Mstatus   => "mstatus",
                 Medeleg   => "medeleg",
@@ -57,6 +85,12 @@ impl std::fmt::Display for CsrAddr {
                 Mcause    => "mcause",
                 Mtval     => "mtval",
                 Mip       => "mip",
+                Mcounteren => "mcounteren",
+                Mcountinhibit => "mcountinhibit",
+                Mcycle    => "mcycle",
+                Minstret  => "minstret",
+                McycleH   => "mcycleh",
+                MinstretH => "minstreth",
                 Pmpcfg0   => "pmpcfg0",
                 Pmpaddr0  => "pmpaddr0",
                 Mnstatus  => "mnstatus",
@@ -71,6 +105,9 @@ impl TryFrom<u32> for CsrAddr {
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         Ok(match value {
             0x000 => Ustatus,
+            0x001 => Fflags,
+            0x002 => Frm,
+            0x003 => Fcsr,
 
             0x100 => Sstatus,
             0x104 => Sie,
@@ -81,6 +118,14 @@ impl TryFrom<u32> for CsrAddr {
             0x143 => Stval,
             0x144 => Sip,
             0x180 => Satp,
+            0x106 => Scounteren,
+
+            0xc00 => Cycle,
+            0xc01 => Time,
+            0xc02 => Instret,
+            0xc80 => CycleH,
+            0xc81 => TimeH,
+            0xc82 => InstretH,
 
             0x300 => Mstatus,
             0x302 => Medeleg,
@@ -92,6 +137,12 @@ impl TryFrom<u32> for CsrAddr {
             0x342 => Mcause,
             0x343 => Mtval,
             0x344 => Mip,
+            0x306 => Mcounteren,
+            0x320 => Mcountinhibit,
+            0xb00 => Mcycle,
+            0xb02 => Minstret,
+            0xb80 => McycleH,
+            0xb82 => MinstretH,
             0x3a0 => Pmpcfg0,
             0x3b0 => Pmpaddr0,
 
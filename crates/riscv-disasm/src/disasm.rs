@@ -1,14 +1,40 @@
 use std::collections::HashMap;
 
-use riscv_loader::LoadInfo;
+use riscv_loader::{Addr, LoadInfo};
 use riscv_decoder::decoder::decode;
 #[cfg(feature = "c")]
 use riscv_decoder::decoder::decompress;
 
+#[cfg(feature = "zicsr")]
+use crate::csr_addr::CsrAddr;
 use crate::instructions::ins_to_string;
 
+/// Disassemble one instruction at `addr`. `raw` is compressed if its low 2 bits are not `0b11`
+pub fn disassemble(raw: u32, addr: Addr) -> String {
+    let empty_table = HashMap::new();
+
+    #[cfg(feature = "c")]
+    if raw & 0b11 != 0b11 {
+        return decompress(raw as u16)
+            .map(|ins| format!("(C) {}", ins_to_string(ins, addr, &empty_table)))
+            .unwrap_or_else(|_| format!("(Unknown Compress) {:#06x}", raw));
+    }
+
+    decode(raw)
+        .map(|ins| ins_to_string(ins, addr, &empty_table))
+        .unwrap_or_else(|_| format!("(Unknown) {:010x}", raw))
+}
+
+/// Name of CSR at `addr`, or the address in hex if it is unknown
+#[cfg(feature = "zicsr")]
+pub fn csr_name(addr: u16) -> String {
+    CsrAddr::try_from(addr as u32)
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|addr| format!("{:#x}", addr))
+}
+
 #[cfg(not(feature = "c"))]
-pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
+pub fn disassembler(info: &LoadInfo) -> Vec<(Addr, String)> {
     let empty_table = HashMap::new();
     let sym_table = info.symbols.as_ref().unwrap_or(&empty_table);
 
@@ -16,7 +42,7 @@ pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
         code.chunks_exact(4)
             .enumerate()
             .flat_map(move |(i, chunk)| {
-                let curr_addr = base + i as u32 * 4;
+                let curr_addr = base + i as Addr * 4;
                 let raw = u32::from_le_bytes(chunk.try_into().unwrap());
             
                 let mut lines = Vec::new();
@@ -39,7 +65,7 @@ pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
 }
 
 #[cfg(feature = "c")]
-pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
+pub fn disassembler(info: &LoadInfo) -> Vec<(Addr, String)> {
     let empty_table = HashMap::new();
     let sym_table = info.symbols.as_ref().unwrap_or(&empty_table);
 
@@ -50,7 +76,7 @@ pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
         let len = code_bytes.len();
 
         while offset < len {
-            let curr_addr = base_addr + offset as u32;
+            let curr_addr = base_addr + offset as Addr;
 
             if offset + 2 > len {
                 res.push((curr_addr, format!("{:#010x}: (Incomplete)", curr_addr)));
@@ -106,7 +132,7 @@ pub fn disassembler(info: &LoadInfo) -> Vec<(u32, String)> {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::disasm::disassembler;
+    use crate::disasm::{disassemble, disassembler};
     use riscv_loader::LoadInfo;
 
     #[test]
@@ -159,4 +185,48 @@ mod tests {
         assert!(output[1].1.contains("ecall"));
         assert!(output[2].1.contains("(C) ebreak"));
     }
+
+    #[test]
+    fn test_disassemble_one() {
+        assert!(disassemble(0x00000513, 0x80000000).contains("addi    x10, x0, 0"));
+        assert!(disassemble(0xffffffff, 0x80000000).contains("(Unknown)"));
+        #[cfg(feature = "c")]
+        assert!(disassemble(0x9002, 0x80000000).contains("(C) ebreak"));
+        #[cfg(feature = "f")] {
+            assert!(disassemble(0x003170d3, 0x80000000).contains("fadd.s  f1, f2, f3"));
+            assert!(disassemble(0x00812087, 0x80000000).contains("flw     f1, 8(x2)"));
+            assert!(disassemble(0xc00312d3, 0x80000000).contains("fcvt.w.s x5, f6, rtz"));
+        }
+        #[cfg(feature = "d")] {
+            assert!(disassemble(0x223170c3, 0x80000000).contains("fmadd.d f1, f2, f3, f4"));
+            assert!(disassemble(0xfe813827, 0x80000000).contains("fsd     f8, -16(x2)"));
+        }
+        // `c.flw` and `c.fsw` are `c.ld` and `c.sd` on RV64
+        #[cfg(all(feature = "c", feature = "f", not(feature = "rv64")))] {
+            assert!(disassemble(0x6140, 0x80000000).contains("(C) flw     f8, 4(x10)"));
+            assert!(disassemble(0xe27e, 0x80000000).contains("(C) fsw     f31, 4(x2)"));
+        }
+        #[cfg(all(feature = "c", feature = "d"))] {
+            assert!(disassemble(0x2588, 0x80000000).contains("(C) fld     f10, 8(x11)"));
+            assert!(disassemble(0xa67e, 0x80000000).contains("(C) fsd     f31, 264(x2)"));
+        }
+        #[cfg(feature = "rv64")] {
+            assert!(disassemble(0x001e0f1b, 0x80000000).contains("addiw   x30, x28, 1"));
+            assert!(disassemble(0x0002be83, 0x80000000).contains("ld      x29, 0(x5)"));
+            assert!(disassemble(0x0062b023, 0x80000000).contains("sd      x6, 0(x5)"));
+            assert!(disassemble(0x00a5873b, 0x80000000).contains("addw    x14, x11, x10"));
+        }
+        #[cfg(all(feature = "c", feature = "rv64"))]
+        assert!(disassemble(0x6594, 0x80000000).contains("(C) ld      x13, 8(x11)"));
+        #[cfg(feature = "zba")]
+        assert!(disassemble(0x20c5a6b3, 0x80000000).contains("sh1add  x13, x11, x12"));
+        #[cfg(feature = "zbb")] {
+            assert!(disassemble(0x60059713, 0x80000000).contains("clz     x14, x11"));
+            assert!(disassemble(0x61f5d513, 0x80000000).contains("rori    x10, x11, 31"));
+        }
+        #[cfg(feature = "zbc")]
+        assert!(disassemble(0x0ac598b3, 0x80000000).contains("clmul   x17, x11, x12"));
+        #[cfg(feature = "zbs")]
+        assert!(disassemble(0x29f01813, 0x80000000).contains("bseti   x16, x0, 31"));
+    }
 }
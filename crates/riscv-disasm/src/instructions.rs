@@ -1,18 +1,21 @@
 use std::collections::HashMap;
 
+use riscv_loader::Addr;
 use riscv_decoder::instruction::Instruction::{self, *};
+#[cfg(feature = "f")]
+use riscv_decoder::instruction::FpInsData;
 
 #[cfg(feature = "zicsr")]
 use crate::csr_addr::CsrAddr;
 
-pub fn ins_to_string(ins: Instruction, addr: u32, sym_table: &HashMap<u32, String>) -> String {   
+pub fn ins_to_string(ins: Instruction, addr: Addr, sym_table: &HashMap<Addr, String>) -> String {   
     match ins {
         Base(op, data) => {
             if op.is_itype_ar() {
                 format!("{:<7} x{}, x{}, {}", op, data.rd, data.rs1, data.imm)
             } else if op.is_itype_load() | op.is_itype_jump() {
                 format!("{:<7} x{}, {}(x{})", op, data.rd, 
-                    sym_table.get(&(addr.wrapping_add_signed(data.imm))).unwrap_or(&data.imm.to_string()), 
+                    sym_table.get(&(addr.wrapping_add_signed(data.imm as _))).unwrap_or(&data.imm.to_string()), 
                     data.rs1)
             } else if op.is_itype_fence() {
                 match data.imm & 0xf00 {
@@ -36,7 +39,7 @@ pub fn ins_to_string(ins: Instruction, addr: u32, sym_table: &HashMap<u32, Strin
                 format!("{:<7} x{}, x{}, {}", op, data.rs1, data.rs2, data.imm)
             } else if op.is_jtype() {
                 format!("{:<7} x{}, {}", op, data.rd, 
-                    sym_table.get(&(addr.wrapping_add_signed(data.imm))).unwrap_or(&data.imm.to_string())
+                    sym_table.get(&(addr.wrapping_add_signed(data.imm as _))).unwrap_or(&data.imm.to_string())
                 )
             } else if op.is_utype() {
                 format!("{:<7} x{}, {:#x}", op, data.rd, (data.imm as u32) >> 12)
@@ -44,6 +47,18 @@ pub fn ins_to_string(ins: Instruction, addr: u32, sym_table: &HashMap<u32, Strin
                 format!("{:<7} x{}, x{}, x{}", op, data.rd, data.rs1, data.rs2)
             }
         },
+        #[cfg(feature = "rv64")]
+        Rv64(op, data) => {
+            if op.is_itype_ar() {
+                format!("{:<7} x{}, x{}, {}", op, data.rd, data.rs1, data.imm)
+            } else if op.is_itype_load() {
+                format!("{:<7} x{}, {}(x{})", op, data.rd, data.imm, data.rs1)
+            } else if op.is_stype() {
+                format!("{:<7} x{}, {}(x{})", op, data.rs2, data.imm, data.rs1)
+            } else { // rtype
+                format!("{:<7} x{}, x{}, x{}", op, data.rd, data.rs1, data.rs2)
+            }
+        },
         #[cfg(feature = "zicsr")]
         #[allow(unused)]
         Privileged(op, data) => {
@@ -84,7 +99,72 @@ pub fn ins_to_string(ins: Instruction, addr: u32, sym_table: &HashMap<u32, Strin
         Zifencei(op, _)=> {
             format!("{:<7}", op)
         },
+        #[cfg(feature = "f")]
+        F(op, data, _) => {
+            let kind = FpKind { load: op.is_load(), store: op.is_store(), fused: op.is_fused(),
+                unary: op.is_unary(), rounded: op.is_rounded(), int_regs: op.int_regs() };
+            fp_to_string(op, data, kind)
+        },
+        #[cfg(feature = "d")]
+        D(op, data, _) => {
+            let kind = FpKind { load: op.is_load(), store: op.is_store(), fused: op.is_fused(),
+                unary: op.is_unary(), rounded: op.is_rounded(), int_regs: op.int_regs() };
+            fp_to_string(op, data, kind)
+        },
+        #[cfg(any(feature = "zba", feature = "zbb", feature = "zbc", feature = "zbs"))]
+        B(op, data) => {
+            if op.is_itype() {
+                format!("{:<7} x{}, x{}, {}", op, data.rd, data.rs1, data.imm)
+            } else if op.is_unary() {
+                format!("{:<7} x{}, x{}", op, data.rd, data.rs1)
+            } else {
+                format!("{:<7} x{}, x{}, x{}", op, data.rd, data.rs1, data.rs2)
+            }
+        },
+    }
+}
+
+/// Operand layout of F or D instruction
+#[cfg(feature = "f")]
+struct FpKind {
+    load: bool,
+    store: bool,
+    fused: bool,
+    unary: bool,
+    rounded: bool,
+    int_regs: (bool, bool),
+}
+
+#[cfg(feature = "f")]
+fn fp_to_string(op: impl std::fmt::Display, data: FpInsData, kind: FpKind) -> String {
+    if kind.load {
+        return format!("{:<7} f{}, {}(x{})", op, data.rd, data.imm, data.rs1);
+    }
+    if kind.store {
+        return format!("{:<7} f{}, {}(x{})", op, data.rs2, data.imm, data.rs1);
     }
+
+    let reg = |is_int: bool, id: u8| if is_int { format!("x{id}") } else { format!("f{id}") };
+    let (rd_is_int, rs1_is_int) = kind.int_regs;
+    let mut output = format!("{:<7} {}, {}", op, reg(rd_is_int, data.rd), reg(rs1_is_int, data.rs1));
+    if !kind.unary {
+        output += &format!(", f{}", data.rs2);
+    }
+    if kind.fused {
+        output += &format!(", f{}", data.rs3);
+    }
+    // Dynamic rounding mode is omitted
+    if kind.rounded {
+        match data.rm {
+            0b000 => output += ", rne",
+            0b001 => output += ", rtz",
+            0b010 => output += ", rdn",
+            0b011 => output += ", rup",
+            0b100 => output += ", rmm",
+            _     => {},
+        }
+    }
+    output
 }
 
 fn check_fence(data: i32) -> String {
@@ -0,0 +1,55 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::GdbError;
+
+/// Byte stream to GDB. Non-blocking mode is used to poll `Ctrl-C` while target is running
+pub trait Connection: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+impl<C: Connection + ?Sized> Connection for Box<C> {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        (**self).set_nonblocking(nonblocking)
+    }
+}
+
+/// Wait one GDB connection on `host:port` or `unix:path`
+/// ## Example
+/// ```no_run
+/// let conn = riscv_gdbstub::listen("127.0.0.1:1234").unwrap();
+/// ```
+pub fn listen(addr: &str) -> Result<Box<dyn Connection>, GdbError> {
+    if let Some(path) = addr.strip_prefix("unix:") {
+        #[cfg(unix)]
+        {
+            let (stream, _) = UnixListener::bind(path)?.accept()?;
+            return Ok(Box::new(stream));
+        }
+        #[cfg(not(unix))]
+        return Err(GdbError::InvalidAddress(addr.to_string()));
+    }
+
+    if !addr.contains(':') {
+        return Err(GdbError::InvalidAddress(addr.to_string()));
+    }
+    let (stream, _) = TcpListener::bind(addr)?.accept()?;
+    // Packets are small, do not wait to merge them
+    stream.set_nodelay(true)?;
+    Ok(Box::new(stream))
+}
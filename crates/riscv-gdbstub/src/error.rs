@@ -0,0 +1,17 @@
+use std::io;
+
+use thiserror::Error;
+
+use riscv_core::RiscVError;
+
+#[derive(Error, Debug)]
+pub enum GdbError {
+    #[error("GDB connection failed: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Address `{0}` is not `host:port` or `unix:path`")]
+    InvalidAddress(String),
+
+    #[error(transparent)]
+    Machine(#[from] RiscVError),
+}
@@ -0,0 +1,9 @@
+mod connection;
+mod error;
+mod packet;
+mod stub;
+mod xml;
+
+pub use connection::{Connection, listen};
+pub use error::GdbError;
+pub use stub::{Disconnect, GdbStub};
@@ -0,0 +1,172 @@
+use std::io::{self, ErrorKind};
+
+use crate::{Connection, GdbError};
+
+const INTERRUPT: u8 = 0x03;
+
+/// Data from GDB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Incoming {
+    Packet(Vec<u8>),
+    /// `Ctrl-C` out of packet
+    Interrupt,
+    /// Connection is closed
+    Closed,
+}
+
+pub(crate) fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// `$`, `#`, `}` and `*` are escaped by `}` with XOR 0x20
+pub(crate) fn escape(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for &byte in data {
+        match byte {
+            b'$' | b'#' | b'}' | b'*' => out.extend([b'}', byte ^ 0x20]),
+            _                         => out.push(byte),
+        }
+    }
+    out
+}
+
+/// `$data#checksum` framing with `+` / `-` acknowledgement
+pub(crate) struct PacketStream<C: Connection> {
+    conn: C,
+    buf: Vec<u8>,
+    /// Skip acknowledgement after `QStartNoAckMode`
+    no_ack: bool,
+}
+
+impl<C: Connection> PacketStream<C> {
+    pub fn new(conn: C) -> Self {
+        PacketStream { conn, buf: Vec::new(), no_ack: false }
+    }
+
+    pub fn set_no_ack(&mut self) {
+        self.no_ack = true;
+    }
+
+    /// Read more bytes into buffer. Return `false` if connection is closed
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 1024];
+        loop {
+            match self.conn.read(&mut chunk) {
+                Ok(0)  => return Ok(false),
+                Ok(n)  => {
+                    self.buf.extend_from_slice(&chunk[..n]);
+                    return Ok(true);
+                },
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Block until a packet or interrupt. Bad checksum is answered with `-`
+    pub fn recv(&mut self) -> Result<Incoming, GdbError> {
+        loop {
+            // Drop acks and noise before packet
+            while let Some(&byte) = self.buf.first() {
+                match byte {
+                    b'$'      => break,
+                    INTERRUPT => {
+                        self.buf.remove(0);
+                        return Ok(Incoming::Interrupt);
+                    },
+                    _         => { self.buf.remove(0); },
+                }
+            }
+
+            // `$data#xx`
+            if let Some(end) = self.buf.iter().position(|&b| b == b'#')
+                && self.buf.len() >= end + 3 {
+                let frame: Vec<u8> = self.buf.drain(..end + 3).collect();
+                let data = &frame[1..end];
+                let sum = std::str::from_utf8(&frame[end + 1..])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                if self.no_ack {
+                    return Ok(Incoming::Packet(data.to_vec()));
+                }
+                if sum == Some(checksum(data)) {
+                    self.conn.write_all(b"+")?;
+                    return Ok(Incoming::Packet(data.to_vec()));
+                }
+                self.conn.write_all(b"-")?;
+                continue;
+            }
+
+            if !self.fill()? {
+                return Ok(Incoming::Closed);
+            }
+        }
+    }
+
+    /// Send packet, and resend until GDB acknowledge it
+    pub fn send(&mut self, data: &[u8]) -> Result<(), GdbError> {
+        let body = escape(data);
+        let mut frame = Vec::with_capacity(body.len() + 4);
+        frame.push(b'$');
+        frame.extend_from_slice(&body);
+        frame.extend(format!("#{:02x}", checksum(&body)).bytes());
+
+        loop {
+            self.conn.write_all(&frame)?;
+            self.conn.flush()?;
+            if self.no_ack {
+                return Ok(());
+            }
+
+            loop {
+                if self.buf.is_empty() && !self.fill()? {
+                    return Ok(());
+                }
+                match self.buf[0] {
+                    // Next packet means last one is received
+                    b'$' => return Ok(()),
+                    b'+' => { self.buf.remove(0); return Ok(()); },
+                    b'-' => { self.buf.remove(0); break; },
+                    _    => { self.buf.remove(0); },
+                }
+            }
+        }
+    }
+
+    /// Check `Ctrl-C` without blocking, other bytes are kept
+    pub fn interrupted(&mut self) -> Result<bool, GdbError> {
+        self.conn.set_nonblocking(true)?;
+        let filled = self.fill();
+        self.conn.set_nonblocking(false)?;
+
+        match filled {
+            Ok(_) => {},
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {},
+            Err(e) => return Err(e.into()),
+        }
+        match self.buf.iter().position(|&b| b == INTERRUPT) {
+            Some(pos) => {
+                self.buf.remove(pos);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(b"OK"), 0x9a);
+        assert_eq!(checksum(b""), 0);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(b"a$b#c}d*"), b"a}\x04b}\x03c}]d}\x0a");
+    }
+}
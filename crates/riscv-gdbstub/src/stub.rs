@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+
+use riscv_core::{Halt, RiscV, StepEvent, StopReason, Xlen};
+use riscv_core::debug::{BreakHit, Breakpoint, BreakpointId, DebugControl, DebugInterface, WatchKind};
+
+use crate::packet::{Incoming, PacketStream};
+use crate::xml::{self, PC_REGNUM};
+#[cfg(feature = "zicsr")]
+use crate::xml::{CSR_REGNUM, PRIV_REGNUM};
+#[cfg(feature = "f")]
+use crate::xml::{FLEN, FPR_REGNUM};
+use crate::{Connection, GdbError};
+
+/// Max packet size told in `qSupported`
+const PACKET_SIZE: usize = 0x4000;
+/// Steps between `Ctrl-C` polling when continue
+const POLL_INTERVAL: u64 = 1024;
+
+/// Hex digits of one register
+const REG_HEX: usize = size_of::<Xlen>() * 2;
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+const ERR_ARGUMENT: &str = "E01";
+const ERR_FAULT: &str = "E14";
+
+/// How the debug session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disconnect {
+    /// `D`, machine can keep running without GDB
+    Detach,
+    /// `k`
+    Kill,
+    /// Connection closed by GDB
+    Closed,
+}
+
+/// Why target stopped, sent as stop reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    Step,
+    SwBreak,
+    HwBreak,
+    Watch(Xlen),
+    ReadWatch(Xlen),
+    AccessWatch(Xlen),
+    Interrupt,
+    Halt(Halt),
+    /// Trap can not be handled without Zicsr
+    #[cfg(not(feature = "zicsr"))]
+    Trap,
+}
+
+impl Stop {
+    fn reply(&self) -> String {
+        match self {
+            Stop::Step        => format!("S{SIGTRAP:02x}"),
+            Stop::SwBreak     => format!("T{SIGTRAP:02x}swbreak:;"),
+            Stop::HwBreak     => format!("T{SIGTRAP:02x}hwbreak:;"),
+            Stop::Watch(addr) => format!("T{SIGTRAP:02x}watch:{addr:x};"),
+            Stop::ReadWatch(addr)   => format!("T{SIGTRAP:02x}rwatch:{addr:x};"),
+            Stop::AccessWatch(addr) => format!("T{SIGTRAP:02x}awatch:{addr:x};"),
+            Stop::Interrupt   => format!("S{SIGINT:02x}"),
+            Stop::Halt(Halt::Exit(code)) => format!("W{:02x}", code & 0xff),
+            #[cfg(not(feature = "zicsr"))]
+            Stop::Trap        => format!("S{SIGTRAP:02x}"),
+        }
+    }
+}
+
+/// GDB remote serial protocol server for one connection.
+/// Breakpoints are kept by machine, so guest memory is never patched
+pub struct GdbStub<C: Connection> {
+    io: PacketStream<C>,
+    /// `Z` type and address to machine's breakpoint
+    points: BTreeMap<(u8, Xlen), BreakpointId>,
+    last_stop: Stop,
+}
+
+fn parse_hex(hex: &str) -> Option<Xlen> {
+    Xlen::from_str_radix(hex, 16).ok()
+}
+
+/// Register number, which does not follow XLEN
+fn parse_regnum(hex: &str) -> Option<u32> {
+    u32::from_str_radix(hex, 16).ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Register in target byte order
+fn decode_reg(hex: &str) -> Option<Xlen> {
+    let bytes = decode_hex(hex)?;
+    Some(Xlen::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// FP register in target byte order, single precision value is NaN-boxed
+#[cfg(feature = "f")]
+fn decode_freg(hex: &str) -> Option<u64> {
+    let data = decode_hex(hex)?;
+    if data.len() != FLEN / 8 {
+        return None;
+    }
+    let mut bytes = [0xff; 8];
+    bytes[..data.len()].copy_from_slice(&data);
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// `addr,len`
+fn parse_range(args: &str) -> Option<(Xlen, Xlen)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((parse_hex(addr)?, parse_hex(len)?))
+}
+
+impl<C: Connection> GdbStub<C> {
+    pub fn new(conn: C) -> Self {
+        GdbStub {
+            io: PacketStream::new(conn),
+            points: BTreeMap::new(),
+            last_stop: Stop::Step,
+        }
+    }
+
+    /// Serve GDB until it detach, kill or close the connection. Breakpoints are removed after that
+    pub fn serve(&mut self, mach: &mut RiscV) -> Result<Disconnect, GdbError> {
+        let res = self.session(mach);
+        for (_, id) in std::mem::take(&mut self.points) {
+            mach.remove_breakpoint(id);
+        }
+        res
+    }
+
+    fn session(&mut self, mach: &mut RiscV) -> Result<Disconnect, GdbError> {
+        loop {
+            let packet = match self.io.recv()? {
+                Incoming::Packet(packet) => String::from_utf8_lossy(&packet).into_owned(),
+                // Target is already stopped
+                Incoming::Interrupt => continue,
+                Incoming::Closed    => return Ok(Disconnect::Closed),
+            };
+            let (cmd, args) = packet.split_at(packet.len().min(1));
+
+            let reply = match cmd {
+                "?"       => self.last_stop.reply(),
+                "g"       => self.read_regs(mach),
+                "G"       => self.write_regs(mach, args),
+                "p"       => self.read_reg(mach, args),
+                "P"       => self.write_reg(mach, args),
+                "m"       => self.read_mem(mach, args),
+                "M"       => self.write_mem(mach, args),
+                "s" | "c" => {
+                    if let Some(addr) = parse_hex(args) {
+                        mach.set_pc(addr);
+                    }
+                    self.last_stop = self.resume(mach, cmd == "s")?;
+                    self.last_stop.reply()
+                },
+                "Z" | "z" => self.breakpoint(mach, cmd == "Z", args),
+                // Only one thread
+                "H" | "T" => "OK".to_string(),
+                "q"       => self.query(args),
+                "Q" if args == "StartNoAckMode" => {
+                    self.io.send(b"OK")?;
+                    self.io.set_no_ack();
+                    continue;
+                },
+                "D"       => {
+                    self.io.send(b"OK")?;
+                    return Ok(Disconnect::Detach);
+                },
+                "k"       => return Ok(Disconnect::Kill),
+                // Empty reply means not supported
+                _         => String::new(),
+            };
+            self.io.send(reply.as_bytes())?;
+        }
+    }
+
+    /// Step until breakpoint, watchpoint, halt or `Ctrl-C`. Only one step if `single`
+    fn resume(&mut self, mach: &mut RiscV, single: bool) -> Result<Stop, GdbError> {
+        if single {
+            return Ok(match mach.step()? {
+                Some(StepEvent::Break(hit)) => self.break_stop(hit),
+                Some(StepEvent::Halt(halt)) => Stop::Halt(halt),
+                #[cfg(not(feature = "zicsr"))]
+                Some(StepEvent::Trap(_))    => Stop::Trap,
+                _ => Stop::Step,
+            });
+        }
+
+        loop {
+            match mach.run_until(Some(POLL_INTERVAL))? {
+                StopReason::Break(hit)  => return Ok(self.break_stop(hit)),
+                StopReason::Halt(halt)  => return Ok(Stop::Halt(halt)),
+                #[cfg(not(feature = "zicsr"))]
+                StopReason::Trap(_)     => return Ok(Stop::Trap),
+                StopReason::Limit       => if self.io.interrupted()? {
+                    return Ok(Stop::Interrupt);
+                },
+            }
+        }
+    }
+
+    /// Report by `Z` type that inserted the breakpoint
+    fn break_stop(&self, hit: BreakHit) -> Stop {
+        let kind = self.points.iter()
+            .find(|(_, id)| **id == hit.id)
+            .map(|((kind, _), _)| *kind);
+
+        match kind {
+            Some(1) => Stop::HwBreak,
+            Some(2) => Stop::Watch(hit.addr),
+            Some(3) => Stop::ReadWatch(hit.addr),
+            Some(4) => Stop::AccessWatch(hit.addr),
+            _       => Stop::SwBreak,
+        }
+    }
+
+    /// `type,addr,kind`. Type 0 and 1 are software and hardware breakpoint, kind is instruction length.
+    /// Type 2, 3 and 4 are write, read and access watchpoint, kind is watched length
+    fn breakpoint(&mut self, mach: &mut RiscV, insert: bool, args: &str) -> String {
+        let mut parts = args.splitn(3, ',');
+        let (Some(kind), Some(addr), Some(len)) = (
+            parts.next().and_then(|kind| kind.parse::<u8>().ok()),
+            parts.next().and_then(parse_hex),
+            parts.next().and_then(parse_hex),
+        ) else {
+            return ERR_ARGUMENT.to_string();
+        };
+
+        let point = match kind {
+            0 | 1 => Breakpoint::exec(addr),
+            2     => Breakpoint::watch(addr, len, WatchKind::Write),
+            3     => Breakpoint::watch(addr, len, WatchKind::Read),
+            4     => Breakpoint::watch(addr, len, WatchKind::Access),
+            _     => return String::new(),
+        };
+
+        if insert {
+            if let Some(id) = self.points.insert((kind, addr), mach.add_breakpoint(point)) {
+                mach.remove_breakpoint(id);
+            }
+        } else if let Some(id) = self.points.remove(&(kind, addr)) {
+            mach.remove_breakpoint(id);
+        }
+        "OK".to_string()
+    }
+
+    fn query(&mut self, args: &str) -> String {
+        let (name, params) = args.split_once(':').unwrap_or((args, ""));
+        match name {
+            "Supported"   => format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+;swbreak+;hwbreak+;QStartNoAckMode+"),
+            "Xfer"        => self.read_features(params),
+            "Attached"    => "1".to_string(),
+            "C"           => "QC1".to_string(),
+            "fThreadInfo" => "m1".to_string(),
+            "sThreadInfo" => "l".to_string(),
+            _             => String::new(),
+        }
+    }
+
+    /// `features:read:target.xml:offset,length`
+    fn read_features(&self, params: &str) -> String {
+        let Some(range) = params.strip_prefix("features:read:target.xml:") else {
+            return String::new();
+        };
+        let Some((offset, len)) = parse_range(range) else {
+            return ERR_ARGUMENT.to_string();
+        };
+
+        let xml = xml::target_xml();
+        let start = (offset as usize).min(xml.len());
+        let end = start.saturating_add(len as usize).min(xml.len());
+        let mark = if end == xml.len() { 'l' } else { 'm' };
+        format!("{mark}{}", &xml[start..end])
+    }
+
+    /// `x0..x31` and `pc`
+    fn read_regs(&self, mach: &RiscV) -> String {
+        mach.inspect_regs()
+            .iter()
+            .chain([mach.inspect_pc()].iter())
+            .map(|reg| encode_hex(&reg.to_le_bytes()))
+            .collect()
+    }
+
+    fn write_regs(&self, mach: &mut RiscV, args: &str) -> String {
+        let regs: Option<Vec<Xlen>> = (0..=PC_REGNUM as usize)
+            .map(|i| decode_reg(args.get(i * REG_HEX..(i + 1) * REG_HEX)?))
+            .collect();
+        let Some(regs) = regs else {
+            return ERR_ARGUMENT.to_string();
+        };
+
+        for (id, data) in regs.iter().enumerate().take(32) {
+            mach.write_reg(id as u8, *data);
+        }
+        mach.set_pc(regs[PC_REGNUM as usize]);
+        "OK".to_string()
+    }
+
+    fn read_reg(&self, mach: &RiscV, args: &str) -> String {
+        let value = match parse_regnum(args) {
+            Some(id @ 0..32)  => Some(mach.inspect_regs()[id as usize]),
+            Some(PC_REGNUM)   => Some(mach.inspect_pc()),
+            #[cfg(feature = "f")]
+            Some(id) if (FPR_REGNUM..FPR_REGNUM + 32).contains(&id) => {
+                let freg = mach.inspect_fregs()[(id - FPR_REGNUM) as usize];
+                return encode_hex(&freg.to_le_bytes()[..FLEN / 8]);
+            },
+            #[cfg(feature = "zicsr")]
+            Some(PRIV_REGNUM) => Some(mach.inspect_privilege() as Xlen),
+            #[cfg(feature = "zicsr")]
+            Some(id) if (CSR_REGNUM..CSR_REGNUM + 0x1000).contains(&id) =>
+                mach.inspect_csr((id - CSR_REGNUM) as u16),
+            _ => None,
+        };
+        match value {
+            Some(value) => encode_hex(&value.to_le_bytes()),
+            None        => ERR_ARGUMENT.to_string(),
+        }
+    }
+
+    /// `n=value`
+    fn write_reg(&self, mach: &mut RiscV, args: &str) -> String {
+        let Some((id, value)) = args.split_once('=')
+            .and_then(|(id, value)| Some((parse_regnum(id)?, value))) else {
+            return ERR_ARGUMENT.to_string();
+        };
+        #[cfg(feature = "f")]
+        if (FPR_REGNUM..FPR_REGNUM + 32).contains(&id) {
+            return match decode_freg(value) {
+                Some(freg) => { mach.write_freg((id - FPR_REGNUM) as u8, freg); "OK" },
+                None       => ERR_ARGUMENT,
+            }.to_string();
+        }
+        let Some(value) = decode_reg(value) else {
+            return ERR_ARGUMENT.to_string();
+        };
+
+        let done = match id {
+            0..32     => { mach.write_reg(id as u8, value); true },
+            PC_REGNUM => { mach.set_pc(value); true },
+            #[cfg(feature = "zicsr")]
+            PRIV_REGNUM => u8::try_from(value).is_ok_and(|mode| mach.set_privilege(mode)),
+            #[cfg(feature = "zicsr")]
+            _ if (CSR_REGNUM..CSR_REGNUM + 0x1000).contains(&id) =>
+                mach.write_csr((id - CSR_REGNUM) as u16, value),
+            _ => false,
+        };
+        if done { "OK" } else { ERR_ARGUMENT }.to_string()
+    }
+
+    /// `addr,len`. Unmapped memory is an error
+    fn read_mem(&self, mach: &RiscV, args: &str) -> String {
+        let Some((addr, len)) = parse_range(args) else {
+            return ERR_ARGUMENT.to_string();
+        };
+        match mach.peek_bus(addr, (len as usize).min(PACKET_SIZE / 2)) {
+            Some(bytes) => encode_hex(&bytes),
+            None        => ERR_FAULT.to_string(),
+        }
+    }
+
+    /// `addr,len:data`
+    fn write_mem(&self, mach: &mut RiscV, args: &str) -> String {
+        let Some((range, data)) = args.split_once(':') else {
+            return ERR_ARGUMENT.to_string();
+        };
+        let (Some((addr, len)), Some(data)) = (parse_range(range), decode_hex(data)) else {
+            return ERR_ARGUMENT.to_string();
+        };
+        if data.len() != len as usize {
+            return ERR_ARGUMENT.to_string();
+        }
+
+        if mach.write_bus(addr, &data) { "OK" } else { ERR_FAULT }.to_string()
+    }
+}
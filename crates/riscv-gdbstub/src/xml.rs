@@ -0,0 +1,155 @@
+use std::fmt::Write;
+
+use riscv_core::XLEN;
+#[cfg(feature = "zicsr")]
+use riscv_core::debug::csr_implemented;
+
+/// GDB numbers CSR as `CSR_REGNUM + address`
+#[cfg(feature = "zicsr")]
+pub(crate) const CSR_REGNUM: u32 = 65;
+/// Virtual register `priv` is after all CSRs
+#[cfg(feature = "zicsr")]
+pub(crate) const PRIV_REGNUM: u32 = CSR_REGNUM + 0x1000;
+pub(crate) const PC_REGNUM: u32 = 32;
+/// GDB numbers FP registers after `pc`
+#[cfg(feature = "f")]
+pub(crate) const FPR_REGNUM: u32 = 33;
+/// FP register width
+#[cfg(all(feature = "f", not(feature = "d")))]
+pub(crate) const FLEN: usize = 32;
+/// FP register width
+#[cfg(feature = "d")]
+pub(crate) const FLEN: usize = 64;
+
+const REG_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2",
+    "fp", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7",
+    "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
+#[cfg(feature = "f")]
+const FREG_NAMES: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7",
+    "fs0", "fs1", "fa0", "fa1", "fa2", "fa3", "fa4", "fa5",
+    "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7",
+    "fs8", "fs9", "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+#[cfg(feature = "zicsr")]
+fn csr_name(addr: u16) -> String {
+    let name = match addr {
+        0x000 => "ustatus",
+        0x001 => "fflags",
+        0x002 => "frm",
+        0x003 => "fcsr",
+        0x100 => "sstatus",
+        0x104 => "sie",
+        0x105 => "stvec",
+        0x140 => "sscratch",
+        0x141 => "sepc",
+        0x142 => "scause",
+        0x143 => "stval",
+        0x144 => "sip",
+        0x106 => "scounteren",
+        0x180 => "satp",
+        0x300 => "mstatus",
+        0x301 => "misa",
+        0x302 => "medeleg",
+        0x303 => "mideleg",
+        0x304 => "mie",
+        0x305 => "mtvec",
+        0x306 => "mcounteren",
+        0x320 => "mcountinhibit",
+        0x323..=0x33f => return format!("mhpmevent{}", addr - 0x320),
+        0x340 => "mscratch",
+        0x341 => "mepc",
+        0x342 => "mcause",
+        0x343 => "mtval",
+        0x344 => "mip",
+        0x3a0..=0x3af => return format!("pmpcfg{}", addr - 0x3a0),
+        0x3b0..=0x3ef => return format!("pmpaddr{}", addr - 0x3b0),
+        0x744 => "mnstatus",
+        0x7a0 => "tselect",
+        0x7a1 => "tdata1",
+        0x7a2 => "tdata2",
+        0x7a3 => "tdata3",
+        0x7a4 => "tinfo",
+        0x7a5 => "tcontrol",
+        0xb00 => "mcycle",
+        0xb02 => "minstret",
+        0xb03..=0xb1f => return format!("mhpmcounter{}", addr - 0xb00),
+        0xb80 => "mcycleh",
+        0xb82 => "minstreth",
+        0xb83..=0xb9f => return format!("mhpmcounter{}h", addr - 0xb80),
+        0xc00 => "cycle",
+        0xc01 => "time",
+        0xc02 => "instret",
+        0xc03..=0xc1f => return format!("hpmcounter{}", addr - 0xc00),
+        0xc80 => "cycleh",
+        0xc81 => "timeh",
+        0xc82 => "instreth",
+        0xc83..=0xc9f => return format!("hpmcounter{}h", addr - 0xc80),
+        0xf11 => "mvendorid",
+        0xf12 => "marchid",
+        0xf13 => "mimpid",
+        0xf14 => "mhartid",
+        _     => return format!("csr{addr:#x}"),
+    };
+    name.to_string()
+}
+
+fn reg_type(id: usize) -> &'static str {
+    match id {
+        1     => "code_ptr",
+        2..=4 => "data_ptr",
+        _     => "int",
+    }
+}
+
+/// `target.xml` with general registers, FP registers and CSRs of this build
+pub(crate) fn target_xml() -> String {
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\"?>\n",
+        "<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n",
+        "<target version=\"1.0\">\n",
+    ));
+
+    // Safe: Write to String never fail
+    writeln!(xml, "<architecture>riscv:rv{XLEN}</architecture>").unwrap();
+    xml.push_str("<feature name=\"org.gnu.gdb.riscv.cpu\">\n");
+    for (id, name) in REG_NAMES.iter().enumerate() {
+        writeln!(xml, "<reg name=\"{name}\" bitsize=\"{XLEN}\" type=\"{}\" regnum=\"{id}\"/>", reg_type(id)).unwrap();
+    }
+    writeln!(xml, "<reg name=\"pc\" bitsize=\"{XLEN}\" type=\"code_ptr\" regnum=\"{PC_REGNUM}\"/>").unwrap();
+    xml.push_str("</feature>\n");
+
+    #[cfg(feature = "f")]
+    {
+        let ty = if FLEN == 64 { "ieee_double" } else { "ieee_single" };
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.fpu\">\n");
+        for (id, name) in FREG_NAMES.iter().enumerate() {
+            writeln!(xml, "<reg name=\"{name}\" bitsize=\"{FLEN}\" type=\"{ty}\" regnum=\"{}\"/>",
+                FPR_REGNUM + id as u32).unwrap();
+        }
+        xml.push_str("</feature>\n");
+    }
+
+    // CSRs like `fcsr` are listed even if they can not be accessed now
+    #[cfg(feature = "zicsr")]
+    {
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.csr\">\n");
+        for addr in (0..0x1000).filter(|addr| csr_implemented(*addr)) {
+            writeln!(xml, "<reg name=\"{}\" bitsize=\"{XLEN}\" regnum=\"{}\" group=\"csr\"/>",
+                csr_name(addr), CSR_REGNUM + addr as u32).unwrap();
+        }
+        xml.push_str("</feature>\n");
+
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.virtual\">\n");
+        writeln!(xml, "<reg name=\"priv\" bitsize=\"{XLEN}\" regnum=\"{PRIV_REGNUM}\" group=\"system\"/>").unwrap();
+        xml.push_str("</feature>\n");
+    }
+
+    xml.push_str("</target>\n");
+    xml
+}
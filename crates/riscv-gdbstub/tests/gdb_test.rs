@@ -0,0 +1,183 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+
+use riscv_core::{XLEN, Xlen};
+use riscv_core::config::MachineConfig;
+use riscv_core::constance::DRAM_BASE_ADDR;
+use riscv_gdbstub::{Disconnect, GdbStub};
+
+const CODE: [u32; 5] = [
+    0x00100093, // addi x1, zero, 1
+    0x00200113, // addi x2, zero, 2
+    0x00001197, // auipc x3, 1
+    0xfe11ac23, // sw   x1, -8(x3)
+    0x0000006f, // jal  zero, 0
+];
+
+/// Start stub with `CODE` loaded on loopback, return connected client
+fn connect() -> (Client, JoinHandle<Disconnect>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut mach = MachineConfig::default().with_ram(DRAM_BASE_ADDR, 1 << 20).build().unwrap();
+        let code: Vec<u8> = CODE.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+        mach.load(DRAM_BASE_ADDR, &code).unwrap();
+        GdbStub::new(stream).serve(&mut mach).unwrap()
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    stream.set_nodelay(true).unwrap();
+    (Client(stream), server)
+}
+
+struct Client(TcpStream);
+
+impl Client {
+    fn byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.0.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    /// Send packet and return reply, acks are checked
+    fn request(&mut self, data: &str) -> String {
+        let sum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        write!(self.0, "${data}#{sum:02x}").unwrap();
+        assert_eq!(self.byte(), b'+');
+        if data == "k" {
+            return String::new();
+        }
+
+        assert_eq!(self.byte(), b'$');
+        let mut reply = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                byte => reply.push(byte),
+            }
+        }
+        self.byte();
+        self.byte();
+        self.0.write_all(b"+").unwrap();
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+/// Hex digits of one register
+const REG_HEX: usize = size_of::<Xlen>() * 2;
+
+fn hex(value: Xlen) -> String {
+    value.to_le_bytes().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn test_registers_and_memory() {
+    let (mut client, server) = connect();
+
+    assert!(client.request("qSupported:swbreak+").contains("qXfer:features:read+"));
+    assert_eq!(client.request("?"), "S05");
+    assert_eq!(client.request("s"), "S05");
+
+    let regs = client.request("g");
+    assert_eq!(regs.len(), 33 * REG_HEX);
+    assert_eq!(&regs[REG_HEX..2 * REG_HEX], hex(1));
+    assert_eq!(&regs[32 * REG_HEX..], hex(DRAM_BASE_ADDR + 4));
+    assert_eq!(client.request("p20"), hex(DRAM_BASE_ADDR + 4));
+
+    assert_eq!(client.request(&format!("P5={}", hex(0x1234))), "OK");
+    assert_eq!(client.request("p5"), hex(0x1234));
+
+    #[cfg(feature = "zicsr")]
+    {
+        // mscratch and priv
+        assert_eq!(client.request(&format!("P381={}", hex(0xabcd))), "OK");
+        assert_eq!(client.request("p381"), hex(0xabcd));
+        assert_eq!(client.request("p1041"), hex(3));
+        assert_eq!(client.request(&format!("P1041={}", hex(2))), "E01");
+    }
+
+    #[cfg(feature = "d")]
+    {
+        // fa0 holds 1.0
+        assert_eq!(client.request("P2b=000000000000f03f"), "OK");
+        assert_eq!(client.request("p2b"), "000000000000f03f");
+        assert_eq!(client.request("P2b=0000803f"), "E01");
+    }
+    #[cfg(all(feature = "f", not(feature = "d")))]
+    {
+        assert_eq!(client.request("P2b=0000803f"), "OK");
+        assert_eq!(client.request("p2b"), "0000803f");
+        assert_eq!(client.request("P2b=000000000000f03f"), "E01");
+    }
+
+    assert_eq!(client.request(&format!("m{DRAM_BASE_ADDR:x},4")), &hex(Xlen::from(CODE[0]))[..8]);
+    assert_eq!(client.request(&format!("M{:x},2:abcd", DRAM_BASE_ADDR + 0x100)), "OK");
+    assert_eq!(client.request(&format!("m{:x},2", DRAM_BASE_ADDR + 0x100)), "abcd");
+    assert_eq!(client.request("M0,1:00"), "E14");
+    assert_eq!(client.request("m0,4"), "E14", "Unmapped memory is not read as zero");
+
+    client.request("k");
+    assert_eq!(server.join().unwrap(), Disconnect::Kill);
+}
+
+#[test]
+fn test_breakpoint_and_watchpoint() {
+    let (mut client, server) = connect();
+
+    assert_eq!(client.request(&format!("Z0,{:x},4", DRAM_BASE_ADDR + 8)), "OK");
+    assert_eq!(client.request("c"), "T05swbreak:;");
+    assert_eq!(client.request("p20"), hex(DRAM_BASE_ADDR + 8));
+    assert_eq!(client.request(&format!("z0,{:x},4", DRAM_BASE_ADDR + 8)), "OK");
+
+    assert_eq!(client.request(&format!("Z2,{:x},4", DRAM_BASE_ADDR + 0x1000)), "OK");
+    assert_eq!(client.request("c"), format!("T05watch:{:x};", DRAM_BASE_ADDR + 0x1000));
+    assert_eq!(client.request("p20"), hex(DRAM_BASE_ADDR + 16));
+
+    // Range pass the top of address space
+    assert_eq!(client.request(&format!("Z0,{:x},2", Xlen::MAX)), "OK");
+    assert_eq!(client.request(&format!("Z2,{:x},4", Xlen::MAX)), "OK");
+    assert_eq!(client.request("s"), "S05");
+
+    assert_eq!(client.request("D"), "OK");
+    assert_eq!(server.join().unwrap(), Disconnect::Detach);
+}
+
+#[test]
+fn test_target_xml() {
+    let (mut client, server) = connect();
+
+    // Read in chunks like GDB does
+    let mut xml = String::new();
+    loop {
+        let reply = client.request(&format!("qXfer:features:read:target.xml:{:x},ffb", xml.len()));
+        xml.push_str(&reply[1..]);
+        if reply.starts_with('l') {
+            break;
+        }
+        assert!(reply.starts_with('m'));
+    }
+    assert!(xml.contains(&format!("<architecture>riscv:rv{XLEN}</architecture>")));
+    assert!(xml.contains("name=\"pc\""));
+    #[cfg(feature = "zicsr")]
+    assert!(xml.contains(&format!("<reg name=\"mstatus\" bitsize=\"{XLEN}\" regnum=\"833\" group=\"csr\"/>")));
+    #[cfg(feature = "f")]
+    {
+        assert!(xml.contains("<feature name=\"org.gnu.gdb.riscv.fpu\">"));
+        assert!(xml.contains("name=\"ft0\""));
+        assert!(xml.contains("name=\"ft11\""));
+        // Listed although mstatus.FS is Off after reset
+        assert!(xml.contains(&format!("<reg name=\"fcsr\" bitsize=\"{XLEN}\" regnum=\"68\" group=\"csr\"/>")));
+    }
+    #[cfg(feature = "zicsr")]
+    assert!(xml.contains(&format!("<reg name=\"priv\" bitsize=\"{XLEN}\" regnum=\"4161\" group=\"system\"/>")));
+
+    let part = client.request("qXfer:features:read:target.xml:0,10");
+    assert_eq!(part, format!("m{}", &xml[..0x10]));
+
+    drop(client);
+    assert_eq!(server.join().unwrap(), Disconnect::Closed);
+}
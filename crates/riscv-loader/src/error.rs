@@ -11,7 +11,7 @@ pub enum LoadError {
     #[error("Can not read raw binary: {0}")]
     ReadRawBinaryFailed(String),
 
-    #[error("Only support 32-bit now")]
+    #[error("ELF class does not match target XLEN")]
     NotSupportClass,
 
     #[error("Can not read .elf's program headers")]
@@ -4,4 +4,11 @@ mod load_info;
 
 pub use error::LoadError;
 pub use loader::load;
-pub use load_info::LoadInfo;
\ No newline at end of file
+pub use load_info::LoadInfo;
+
+/// Target address width, follows the ELF class to load
+#[cfg(not(feature = "rv64"))]
+pub type Addr = u32;
+/// Target address width, follows the ELF class to load
+#[cfg(feature = "rv64")]
+pub type Addr = u64;
\ No newline at end of file